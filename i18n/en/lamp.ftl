@@ -51,6 +51,11 @@ projects-stuck = Stuck — needs a next action
 
 # Waiting
 waiting-empty = Not waiting on anything.
+waiting-overdue = Follow-ups due
+waiting-compose = Compose follow-up
+waiting-unassigned = No one recorded
+waiting-draft-title = Follow-up for { $name }
+waiting-draft-dismiss = Dismiss
 
 # Someday
 someday-empty = No someday/maybe items.
@@ -137,6 +142,28 @@ review-step-calendar = Review upcoming calendar (14 days)
 review-step-capture = Capture any new ideas or projects
 review-step-horizons = Review goals and larger horizons
 review-complete = Review complete!
+review-ai-hint = The assistant can suggest next actions, clearer titles and someday items to start. Nothing changes until you approve it.
+review-ai-ask = Ask the assistant
+review-ai-running = Asking the assistant…
+review-ai-error = The assistant failed: { $error }
+review-ai-next-action = Suggested next action for { $project }
+review-ai-rewrite = Clearer title for "{ $title }"
+review-ai-activate = Worth activating now
+review-ai-approve = Approve
+review-ai-dismiss = Dismiss
+
+breakdown-title = Break down
+breakdown-replaces = Replaces "{ $title }"
+breakdown-project = Project name
+breakdown-outcome = Outcome
+breakdown-steps = Steps ({ $esc } spoons)
+breakdown-step = Step
+breakdown-add-step = Add step
+breakdown-create = Create project
+breakdown-cancel = Cancel
+breakdown-no-name = Give the project a name.
+breakdown-name-taken = There is already a project called "{ $name }".
+breakdown-no-steps = Add at least one step.
 
 # ESC
 esc-column = ESC
@@ -159,6 +186,23 @@ sync-test-connection = Test Connection
 sync-calendar-purpose-tasks = Tasks
 sync-calendar-purpose-events = Events
 sync-calendar-purpose-disabled = Disabled
+sync-service-name = Account name
+sync-add-account = Add Account
+sync-subscriptions = Subscriptions
+sync-subscriptions-hint = Read-only calendar feeds (holidays, fixtures, rotas), refreshed with every sync. Accepts http(s)://, webcal:// and file:// URLs.
+sync-subscription-url = Feed URL
+sync-add-subscription = Add Subscription
+sync-refresh-subscriptions = Refresh
+sync-task-routing = Task Routing
+sync-task-routing-hint = Send tasks in a project, or with an @context, to a specific task list. Unmatched tasks use the first task list.
+sync-task-route-key = Project or @context
+sync-project-mode = Projects in Other Apps
+sync-project-mode-property = Lamp only
+sync-project-mode-collections = Separate task lists
+sync-project-mode-related = Parent tasks
+sync-project-mode-property-hint = Projects are stored in a Lamp-specific field that other apps ignore.
+sync-project-mode-collections-hint = Each project gets its own task list on the server, named after the project.
+sync-project-mode-related-hint = Each project becomes a parent task, with its tasks as subtasks.
 
 # Conflicts
 conflicts-empty = No sync conflicts
@@ -169,6 +213,12 @@ conflicts-accept-remote = Accept Remote
 conflicts-accept-local = Accept Local
 conflicts-import = Import
 conflicts-delete = Delete
+conflicts-outbox = Waiting to sync ({ $count })
+conflicts-outbox-retry = Retry Now
+conflicts-outbox-discard = Discard
+conflicts-outbox-queued = Queued { $time }
+conflicts-outbox-attempts = { $count } failed attempts
+conflicts-outbox-next = next try { $time }
 
 # Agenda / Calendar
 agenda-empty = No upcoming events or tasks
@@ -187,6 +237,162 @@ agenda-event-save = Save
 agenda-event-cancel = Cancel
 agenda-event-delete = Delete
 agenda-event-edit = Edit
+agenda-event-scope-this = This occurrence
+agenda-event-scope-following = This and following
+agenda-event-scope-all = All occurrences
+agenda-event-delete-recurring = Delete recurring event "{ $title }"
+agenda-event-series-only = This event repeats by a rule Lamp can't follow exactly, so it can only be changed as a whole.
+agenda-event-esc = Spoons
+agenda-event-esc-auto = Auto
+agenda-event-alarms = Reminders
+agenda-event-add-alarm = Add reminder
+alarm-at-start = At start
+alarm-at-end = At end
+alarm-before = { $time } before
+alarm-after = { $time } after start
+alarm-before-end = { $time } before end
+alarm-after-end = { $time } after end
+alarm-at = At { $time }
+alarm-email = { $alarm } by email
+alarm-hours = { $hours } h
+alarm-days = { $days } d
+agenda-layout-list = List
+agenda-layout-day = Day
+agenda-layout-week = Week
+agenda-layout-free-busy = Free/busy
+free-busy-calendars = Calendars
+free-busy-refresh = Refresh
+free-busy-loading = Loading…
+free-busy-local-only = Local events only
+free-busy-local = Local events
+free-busy-find = Find a time
+free-busy-duration = Length (minutes)
+free-busy-no-slots = No free time in working hours these days
+free-busy-free-day = Free
+timeline-tasks = Tasks to block
+timeline-no-tasks = Nothing left to schedule
+timeline-pick = Pick
+timeline-picked = Picked
+timeline-auto = Auto
+timeline-minutes = { $minutes } min
+agenda-repeat = Repeat
+agenda-repeat-never = Never
+agenda-repeat-daily = Daily
+agenda-repeat-weekly = Weekly
+agenda-repeat-monthly = Monthly
+agenda-repeat-yearly = Yearly
+agenda-repeat-every = Every
+agenda-repeat-days = days
+agenda-repeat-weeks = weeks
+agenda-repeat-months = months
+agenda-repeat-years = years
+agenda-repeat-same-date = Same date
+agenda-repeat-same-weekday = Same weekday
+agenda-repeat-forever = Forever
+agenda-repeat-until = Until
+agenda-repeat-count = For
+agenda-repeat-times = times
+
+# Repeat summaries, e.g. "Every other Tue & Thu until Jun 30"
+rrule-daily = { $interval ->
+    [1] Daily
+    [2] Every other day
+   *[other] Every { $interval } days
+}
+rrule-daily-on = { $interval ->
+    [1] Daily on { $days }
+    [2] Every other day on { $days }
+   *[other] Every { $interval } days on { $days }
+}
+rrule-weekly = { $interval ->
+    [1] Weekly on { $days }
+    [2] Every other { $days }
+   *[other] Every { $interval } weeks on { $days }
+}
+rrule-monthly = { $interval ->
+    [1] Monthly { $on }
+    [2] Every other month { $on }
+   *[other] Every { $interval } months { $on }
+}
+rrule-yearly = { $interval ->
+    [1] Yearly { $on }
+    [2] Every other year { $on }
+   *[other] Every { $interval } years { $on }
+}
+rrule-on = on { $days }
+rrule-on-date = on { $date }
+rrule-on-day = on day { $day }
+rrule-of-months = { $on } of { $months }
+rrule-nth-weekdays = the { $days }
+rrule-every-weekday = every { $days }
+rrule-month-days = day { $days }
+rrule-month-day-from-end = { $nth } day
+rrule-nth-weekday = { $nth } { $weekday }
+rrule-both = { $first } that is { $second }
+rrule-unsupported = { $summary } ({ $parts })
+rrule-count = { $summary }, { $count } times
+rrule-until = { $summary } until { $date }
+rrule-list = { $rest }, { $item }
+rrule-list-last = { $rest } & { $last }
+rrule-ordinal = { $n ->
+    [1] 1st
+    [2] 2nd
+    [3] 3rd
+    [21] 21st
+    [22] 22nd
+    [23] 23rd
+    [31] 31st
+    [32] 32nd
+    [33] 33rd
+    [41] 41st
+    [42] 42nd
+    [43] 43rd
+    [51] 51st
+    [52] 52nd
+    [53] 53rd
+   *[other] { $n }th
+}
+rrule-ordinal-from-end = { $n ->
+    [1] last
+    [2] second to last
+    [3] third to last
+    [4] fourth to last
+    [5] fifth to last
+   *[other] { $nth } to last
+}
+rrule-weekday-short = { $day ->
+    [MO] Mon
+    [TU] Tue
+    [WE] Wed
+    [TH] Thu
+    [FR] Fri
+    [SA] Sat
+   *[SU] Sun
+}
+rrule-weekday = { $day ->
+    [MO] Monday
+    [TU] Tuesday
+    [WE] Wednesday
+    [TH] Thursday
+    [FR] Friday
+    [SA] Saturday
+   *[SU] Sunday
+}
+agenda-event-organizer = Organized by { $name }
+agenda-event-attendees = Attendees
+
+# Invitations
+invitations = Invitations
+invitation-from = { $when } · from { $from }
+invitation-accept = Accept
+invitation-tentative = Maybe
+invitation-decline = Decline
+invitation-replies-waiting = Replies to mail invitations waiting to be sent: { $count }
+partstat-needs-action = No answer yet
+partstat-accepted = Accepted
+partstat-tentative = Maybe
+partstat-declined = Declined
+partstat-delegated = Delegated
 
 # Contacts
 nav-contacts = Contacts
@@ -222,10 +428,18 @@ notes-source = Source
 sync-imap = Email Inbox (IMAP)
 sync-imap-host = IMAP Host
 sync-imap-folder = Folder
+sync-imap-capture-hint = Mail in the capture folder, or sent to the capture address, becomes an inbox task and is archived. Use @context, #project and !A in the subject.
+sync-imap-capture-folder = Capture folder (e.g. to-lamp)
+sync-imap-capture-address = Capture address (e.g. me+lamp@example.com)
+sync-smtp = Outgoing Mail (SMTP)
+sync-smtp-hint = Used to email delegated tasks, follow-ups and meeting replies. A local test server such as MailHog (localhost:1025) needs no login.
+sync-smtp-host = SMTP Host (port 587 unless given)
+sync-smtp-from = From (e.g. Sam Lee <sam@example.com>)
 email-archive = Archive
 email-suggest-tasks = Suggest Tasks
 email-analyzing = Analyzing emails...
 email-suggested-task = Suggested task
+email-suggested-by-rule = Suggested by rule "{ $rule }"
 email-approve = Approve
 email-dismiss = Dismiss
 email-no-action = No action needed
@@ -235,8 +449,66 @@ inbox-emails = Emails
 settings-title = Settings
 settings-debug-logging = Debug logging
 settings-browser = Browser Command
+settings-sync-interval = Background Sync
+settings-sync-interval-off = Off
+settings-sync-interval-minutes = { $minutes } min
+settings-meeting-spoons = Meeting Spoons per Hour
+settings-meeting-spoons-hint = Events cost this many spoons per hour in daily planning unless they have their own cost.
+settings-calendar-spoons-default = Default rate
+settings-calendar-spoons = { $spoons } per hour
+ics-title = Import / Export (.ics)
+ics-import-path = Path to an .ics file
+ics-import = Import
+ics-export-path = Export to
+ics-export = Export
+ics-all-projects = All projects
+ics-all-calendars = All calendars
+ics-from = From (YYYY-MM-DD)
+ics-to = To (YYYY-MM-DD)
+ics-tasks = Tasks
+ics-events = Events
+ics-imported = Imported { $tasks } tasks and { $events } events ({ $duplicates } duplicates skipped)
+ics-exported = Exported { $tasks } tasks and { $events } events
+triage-rules = Email rules
+triage-rules-hint = Rules are tried in order on each email before any model is asked. Leave a condition empty to ignore it.
+triage-add-rule = Add rule
+triage-ai-fallback = Ask the model about email no rule matches
+triage-name = Rule name
+triage-from = From contains
+triage-subject = Subject pattern (regex)
+triage-list-id = List-Id contains
+triage-attachment-any = Any attachments
+triage-attachment-with = With attachments
+triage-attachment-without = Without attachments
+triage-title = Task title ({"{"}subject{"}"}, {"{"}from{"}"})
+triage-none = —
+triage-esc = Spoons
+triage-create-task = Create a task
+triage-archive = Archive without asking
 settings-ai = AI Task Extraction
-settings-ai-api-key = Anthropic API Key
+settings-ai-api-key = API Key
+settings-ai-backend = Model provider
+settings-ai-endpoint = Endpoint
+settings-ai-model = Model
+settings-ai-anthropic = Anthropic
+settings-ai-openai = OpenAI-compatible
+settings-ai-ollama = Ollama (local)
+settings-ai-mock = Offline test (mock)
+
+secrets = Passwords and keys
+secrets-hint = Kept in the Secret Service (GNOME Keyring, KWallet) when one is running, otherwise in an encrypted vault at { $path }.
+secrets-backend = Store secrets in
+secrets-auto = Automatic
+secrets-service = Secret Service
+secrets-vault = Encrypted vault
+secrets-passphrase = Vault passphrase
+secrets-passphrase-repeat = Repeat the passphrase for the new vault
+secrets-passphrase-mismatch = The passphrases don't match
+secrets-unlock = Unlock
+secrets-move-to-vault = Move secrets to the vault
+secrets-move-to-service = Move secrets to the Secret Service
+secrets-moved = Moved { $count } secrets
+secrets-env-hint = For scripts and CI: LAMP_VAULT_PASSPHRASE unlocks the vault, LAMP_SECRET_BACKEND picks secret-service or vault, and LAMP_SECRET_<NAME> (e.g. LAMP_SECRET_IMAP_MAIL_EXAMPLE_COM=user:password) overrides one secret.
 
 # Search
 search-placeholder = Search...
@@ -1,35 +1,55 @@
 use std::collections::{HashMap, HashSet};
 
-use chrono::Timelike;
+use chrono::{Datelike, Timelike};
 
 use cosmic::app::{Core, Task as CosmicTask, context_drawer};
-use cosmic::iced::Length;
-use cosmic::widget::{button, column, container, flex_row, icon, nav_bar, row, scrollable, text, text_editor, text_input};
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{button, column, container, dropdown, flex_row, icon, nav_bar, row, scrollable, text, text_editor, text_input};
 use cosmic::{Application, Element, executor};
 
-use crate::config::LampConfig;
+use crate::config::{CalendarPurpose, IcsSubscription, LampConfig, ProjectSyncMode, ServiceConfig, TriageRule};
 use crate::core::account::Account;
-use crate::core::day_plan::DayPlan;
-use crate::core::event::{self, CalendarEvent};
+use crate::core::alarm::Alarm;
+use crate::core::day_plan::{self, DayLoad, DayPlan};
+use crate::core::delegation::{self, FollowUpDraft};
+use crate::core::email_link::EmailLink;
+use crate::core::event::{self, CalendarEvent, EditScope, PartStat};
+use crate::core::rrule::{self, Frequency, RecurrenceRule, RuleEnd};
 use crate::core::habit::Habit;
 use crate::core::link::LinkTarget;
 use crate::core::list_item::ListItem;
 use crate::core::note::Note;
 use crate::core::project::Project;
 use crate::core::task::{Priority, Task, TaskState};
-use crate::message::{AccountField, ActiveView, AppMode, ContactField, ListKind, Message, NoteField, ServiceKind, SortColumn, WhatPage};
+use crate::core::timeblock;
+use crate::message::{AccountField, ActiveView, AgendaLayout, AppMode, ContactField, ListKind, Message, NoteField, ServiceKind, SortColumn, TriageRuleField, WhatPage};
 use crate::org::convert;
 use crate::org::writer::OrgWriter;
 use crate::pages;
 use crate::components::month_calendar::MonthCalendarState;
 use crate::sync::caldav::{CalDavClient, CalendarInfo};
 use crate::sync::carddav::Contact;
-use crate::sync::imap::ImapEmail;
+use crate::sync::ics::{self, ExportFilter};
+use crate::sync::itip::{self, Invitation, InvitationSource, ItipReply};
+use crate::sync::imap::{CapturedEmail, ImapEmail, MailCache};
+use crate::sync::outbox::{Outbox, OutboxEntry, OutboxOp};
+use crate::sync::projects::ProjectAction;
+use crate::sync::smtp::OutgoingEmail;
 use crate::sync::{SyncConflict, SyncStatus};
 
+/// How often the background sync timer fires.
+const SYNC_TICK_SECS: u64 = 15;
+/// Quiet period after a local edit before a sync pushes it.
+const EDIT_DEBOUNCE_SECS: i64 = 20;
+/// How often sync-collection tokens are polled for remote changes between full syncs.
+const POLL_INTERVAL_SECS: i64 = 120;
+/// How long one IMAP IDLE wait lasts; servers may drop it after 30 minutes (RFC 2177).
+const IMAP_IDLE_MINUTES: u64 = 29;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContextDrawerState {
     NewTask,
+    Breakdown,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -79,23 +99,228 @@ pub struct EventForm {
     pub location: String,
     pub description: String,
     pub calendar_href: String,
+    /// Spoon cost; empty means priced by duration.
+    pub esc: String,
+    /// Original start of the occurrence being edited, for recurring events.
+    pub occurrence: Option<chrono::NaiveDateTime>,
+    pub scope: EditScope,
+    /// The series repeats by a rule Lamp can't expand exactly, so only `All` applies.
+    pub series_only: bool,
+    pub repeat: RepeatChoice,
+    /// Organizer and attendees as shown, named from contacts where possible.
+    pub organizer: Option<String>,
+    pub attendees: Vec<(String, PartStat)>,
+    /// Our own answer, when we're among the attendees.
+    pub rsvp: Option<PartStat>,
+    pub alarms: Vec<Alarm>,
+}
+
+/// Repeat rule choices in the event form.
+#[derive(Clone)]
+pub struct RepeatChoice {
+    pub freq: Option<Frequency>,
+    pub interval: String,
+    pub weekdays: Vec<chrono::Weekday>,
+    /// Monthly on the same weekday ("2nd Tuesday") rather than the same date.
+    pub nth_weekday: bool,
+    pub end: RuleEnd,
+    pub until: String,
+    pub count: String,
+    /// Whether the user touched these; rules the form can't express are kept otherwise.
+    pub changed: bool,
+}
+
+impl Default for RepeatChoice {
+    fn default() -> Self {
+        Self {
+            freq: None,
+            interval: "1".to_string(),
+            weekdays: Vec::new(),
+            nth_weekday: false,
+            end: RuleEnd::Never,
+            until: String::new(),
+            count: "10".to_string(),
+            changed: false,
+        }
+    }
+}
+
+impl RepeatChoice {
+    fn from_rule(rule: &RecurrenceRule) -> Self {
+        Self {
+            freq: Some(rule.freq),
+            interval: rule.interval.to_string(),
+            weekdays: rule.by_day.clone(),
+            nth_weekday: !rule.nth_weekdays.is_empty(),
+            end: match (rule.count, rule.until) {
+                (Some(_), _) => RuleEnd::Count,
+                (None, Some(_)) => RuleEnd::Until,
+                (None, None) => RuleEnd::Never,
+            },
+            until: rule
+                .until
+                .map(|u| u.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            count: rule.count.unwrap_or(10).to_string(),
+            changed: false,
+        }
+    }
 }
 
 impl EventForm {
+    /// The repeat rule the form describes, if the event repeats.
+    pub fn recurrence_rule(&self) -> Option<RecurrenceRule> {
+        let freq = self.repeat.freq?;
+        let start = chrono::NaiveDate::parse_from_str(self.start_date.trim(), "%Y-%m-%d").ok()?;
+        let count = match self.repeat.end {
+            RuleEnd::Count => self.repeat.count.trim().parse().ok().filter(|&c| c > 0),
+            _ => None,
+        };
+        let until = match self.repeat.end {
+            RuleEnd::Until => chrono::NaiveDate::parse_from_str(self.repeat.until.trim(), "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(23, 59, 59)),
+            _ => None,
+        };
+        Some(RecurrenceRule {
+            freq,
+            interval: self.repeat.interval.trim().parse().ok().filter(|&i| i > 0).unwrap_or(1),
+            count,
+            until,
+            by_day: if freq == Frequency::Weekly { self.repeat.weekdays.clone() } else { Vec::new() },
+            nth_weekdays: (freq == Frequency::Monthly && self.repeat.nth_weekday)
+                .then(|| rrule::weekday_position(start))
+                .into_iter()
+                .collect(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            unsupported: Vec::new(),
+        })
+    }
+
+    /// Readable summary of the repeat rule, e.g. "Every other Tue & Thu until Jun 30".
+    pub fn repeat_summary(&self) -> Option<String> {
+        let start = chrono::NaiveDate::parse_from_str(self.start_date.trim(), "%Y-%m-%d").ok()?;
+        self.recurrence_rule().map(|rule| rule.summary(start))
+    }
+
     fn from_event(event: &CalendarEvent) -> Self {
         Self {
             editing: Some(event.id),
             title: event.title.clone(),
-            start_date: event.start.format("%Y-%m-%d").to_string(),
-            start_time: event.start.format("%H:%M").to_string(),
-            end_date: event.end.format("%Y-%m-%d").to_string(),
-            end_time: event.end.format("%H:%M").to_string(),
+            start_date: event.local_start().format("%Y-%m-%d").to_string(),
+            start_time: event.local_start().format("%H:%M").to_string(),
+            end_date: event.local_end().format("%Y-%m-%d").to_string(),
+            end_time: event.local_end().format("%H:%M").to_string(),
             all_day: event.all_day,
             location: event.location.clone(),
             description: event.description.clone(),
             calendar_href: event.calendar_href.clone(),
+            esc: event.esc.map(|e| e.to_string()).unwrap_or_default(),
+            occurrence: event.recurrence_id,
+            scope: if event.occurrences_editable() { EditScope::This } else { EditScope::All },
+            series_only: !event.occurrences_editable(),
+            repeat: event
+                .recurrence_rule()
+                .map(|rule| RepeatChoice::from_rule(&rule))
+                .unwrap_or_default(),
+            organizer: None,
+            attendees: Vec::new(),
+            rsvp: None,
+            alarms: event.alarms.clone(),
+        }
+    }
+}
+
+/// Free/busy layout: which calendars count and what the servers last said.
+#[derive(Clone)]
+pub struct FreeBusyForm {
+    /// Meeting length to find slots for, in minutes as typed.
+    pub minutes: String,
+    /// Calendars left out of the busy time, by href.
+    pub hidden: HashSet<String>,
+    /// Busy local times per calendar href, as the server reported them.
+    pub busy: HashMap<String, Vec<(chrono::NaiveDateTime, chrono::NaiveDateTime)>>,
+    /// Calendars whose server refused the query; local events stand in for them.
+    pub errors: HashMap<String, String>,
+    pub loading: bool,
+}
+
+impl Default for FreeBusyForm {
+    fn default() -> Self {
+        Self {
+            minutes: "60".to_string(),
+            hidden: HashSet::new(),
+            busy: HashMap::new(),
+            errors: HashMap::new(),
+            loading: false,
+        }
+    }
+}
+
+/// Days the free/busy layout covers.
+const FREE_BUSY_DAYS: i64 = 7;
+/// How many free slots to propose.
+const FREE_SLOT_SUGGESTIONS: usize = 5;
+
+/// Paths and filter for importing and exporting `.ics` files in settings.
+#[derive(Clone)]
+pub struct IcsForm {
+    pub import_path: String,
+    pub export_path: String,
+    pub project: Option<String>,
+    pub calendar_href: Option<String>,
+    /// Export range as typed, YYYY-MM-DD; empty is open-ended.
+    pub from: String,
+    pub to: String,
+    pub tasks: bool,
+    pub events: bool,
+    /// Outcome of the last import or export.
+    pub status: Option<Result<String, String>>,
+}
+
+impl IcsForm {
+    fn new(org_directory: &std::path::Path) -> Self {
+        Self {
+            import_path: String::new(),
+            export_path: org_directory.join("lamp-export.ics").display().to_string(),
+            project: None,
+            calendar_href: None,
+            from: String::new(),
+            to: String::new(),
+            tasks: true,
+            events: true,
+            status: None,
         }
     }
+
+    fn filter(&self) -> Result<ExportFilter, String> {
+        let date = |s: &str| -> Result<Option<chrono::NaiveDate>, String> {
+            let s = s.trim();
+            if s.is_empty() {
+                return Ok(None);
+            }
+            chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .map(Some)
+                .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", s))
+        };
+        Ok(ExportFilter {
+            project: self.project.clone(),
+            calendar_href: self.calendar_href.clone(),
+            from: date(&self.from)?,
+            to: date(&self.to)?,
+            tasks: self.tasks,
+            events: self.events,
+        })
+    }
+}
+
+/// Expand a leading `~/` in a path typed by the user.
+fn expand_home(path: &str) -> std::path::PathBuf {
+    match (path.trim().strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => std::path::PathBuf::from(path.trim()),
+    }
 }
 
 /// Buffered edit state for note fields — committed on Done, avoids re-renders while typing.
@@ -108,11 +333,21 @@ pub struct NoteEditBuffer {
 
 #[derive(Debug, Clone)]
 pub enum EmailSuggestionState {
-    Suggested(crate::sync::anthropic::BatchEmailSuggestion),
+    Suggested(crate::sync::llm::BatchEmailSuggestion),
     NoAction,
     Dismissed,
 }
 
+impl EmailSuggestionState {
+    fn from_suggestion(suggestion: crate::sync::llm::BatchEmailSuggestion) -> Self {
+        if !suggestion.action_needed || suggestion.is_duplicate == Some(true) {
+            EmailSuggestionState::NoAction
+        } else {
+            EmailSuggestionState::Suggested(suggestion)
+        }
+    }
+}
+
 pub struct Lamp {
     core: Core,
     nav_model: nav_bar::Model,
@@ -162,13 +397,42 @@ pub struct Lamp {
     flipped_list_items: HashSet<uuid::Uuid>,
     pending_delete_list_item: Option<(ListKind, uuid::Uuid)>,
     waiting_for_inputs: HashMap<uuid::Uuid, String>,
+    /// Follow-up composed for a contact without an email address (shown for copying).
+    follow_up_draft: Option<FollowUpDraft>,
 
     // Review checklist (ephemeral — resets on nav away)
     review_checked: HashSet<usize>,
+    /// Assistant suggestions awaiting approval; kept across navigation
+    review_suggestions: Vec<crate::sync::assistant::ReviewSuggestion>,
+    review_ai_running: bool,
+    review_ai_error: Option<String>,
+
+    /// Task the assistant is breaking down, with the error if that failed
+    breakdown_status: Option<(uuid::Uuid, Option<String>)>,
+    /// Breakdown being edited in the context drawer, and the task it replaces
+    breakdown: Option<(uuid::Uuid, crate::sync::assistant::TaskBreakdown)>,
 
     // Events
     events: Vec<CalendarEvent>,
+    /// Events of the read-only ICS subscriptions, kept apart so they're never synced.
+    subscription_events: Vec<CalendarEvent>,
+    subscription_status: HashMap<String, SyncStatus>,
+    /// Meeting requests from the CalDAV inbox and mail, not yet in a calendar.
+    pending_invitations: Vec<Invitation>,
+    /// Invitations answered this session, so mail still in the folder doesn't re-ask.
+    answered_invitations: HashSet<uuid::Uuid>,
+    /// Replies to mail invitations waiting to be sent to their organizers.
+    pending_replies: Vec<ItipReply>,
     event_form: Option<EventForm>,
+    /// Occurrence of a recurring event awaiting a delete scope choice.
+    event_delete_prompt: Option<(uuid::Uuid, chrono::NaiveDateTime)>,
+    agenda_layout: AgendaLayout,
+    ics_form: IcsForm,
+    free_busy: FreeBusyForm,
+    /// First day the timeline shows.
+    timeline_day: chrono::NaiveDate,
+    /// Task picked up to be dropped into a timeline slot.
+    picked_block_task: Option<uuid::Uuid>,
 
     // Contacts
     contacts: Vec<Contact>,
@@ -197,33 +461,62 @@ pub struct Lamp {
 
     // Sync
     sync_status: SyncStatus,
-    /// Discovered calendars from CalDAV test connection
-    discovered_calendars: Vec<CalendarInfo>,
-    /// Password inputs for [Calendars, Contacts, Notes, Imap]
-    service_passwords: [String; 4],
-    /// Test connection results for [Calendars, Contacts, Notes, Imap]
-    service_test_status: [Option<Result<String, String>>; 4],
+    /// Per calendar account status, keyed by account id
+    account_sync_status: HashMap<String, SyncStatus>,
+    /// Discovered calendars from CalDAV test connection, keyed by account id
+    discovered_calendars: HashMap<String, Vec<CalendarInfo>>,
+    /// Password inputs, keyed by service kind and account index
+    service_passwords: HashMap<(ServiceKind, usize), String>,
+    /// Test connection results, keyed by service kind and account index
+    service_test_status: HashMap<(ServiceKind, usize), Result<String, String>>,
+    /// New task route being entered in settings: key and index into the task calendars
+    task_route_input: String,
+    task_route_calendar: usize,
 
     // IMAP emails
     imap_emails: Vec<ImapEmail>,
+    /// The folder as last listed, persisted so refreshes only list new mail
+    mail_cache: MailCache,
+    /// Whether an IMAP IDLE wait is running
+    imap_idling: bool,
+    /// Whether the capture folder is being read
+    imap_capturing: bool,
+    /// Whether queued meeting replies are being sent
+    sending_replies: bool,
+    /// Outcome of the last delegation email per task, shown in its row
+    delegation_status: HashMap<uuid::Uuid, Result<String, String>>,
 
     // AI batch email suggestions
-    anthropic_api_key_input: String,
-    anthropic_test_status: Option<Result<String, String>>,
+    llm_api_key_input: String,
+    llm_test_status: Option<Result<String, String>>,
+    /// Vault passphrase as typed, until it's used to unlock
+    vault_passphrase_input: String,
+    /// The passphrase typed again; a new vault needs both to match
+    vault_passphrase_repeat: String,
+    secret_store_status: Option<Result<String, String>>,
     email_suggestions: HashMap<u32, EmailSuggestionState>,
     ai_batch_processing: bool,
+    /// Suggestions by Message-ID, persisted so re-fetched mail isn't sent again
+    suggestion_cache: crate::sync::llm::SuggestionCache,
     /// UIDs of emails archived this session — filtered out on re-fetch
     archived_email_uids: HashSet<u32>,
 
-    // Pending sync completions: (sync_href, vcalendar_body) to push on next sync
-    pending_completions: Vec<(String, String)>,
+    // CalDAV writes waiting to reach the server, persisted to outbox.json
+    outbox: Outbox,
+    // Whether a delayed sync is already scheduled to retry the outbox
+    outbox_retry_scheduled: bool,
+
+    // Background sync bookkeeping (wall-clock, so time asleep counts)
+    last_sync_at: Option<chrono::DateTime<chrono::Utc>>,
+    last_sync_tick: chrono::DateTime<chrono::Utc>,
+    last_poll_at: Option<chrono::DateTime<chrono::Utc>>,
+    sync_poll_in_flight: bool,
+    /// Set by local saves; a sync runs once edits have been quiet for `EDIT_DEBOUNCE_SECS`
+    local_edit_at: Option<chrono::DateTime<chrono::Utc>>,
 
     // Sync conflicts awaiting user resolution
     sync_conflicts: Vec<SyncConflict>,
 
-    // Pending remote deletions: hrefs to delete on next sync
-    pending_deletions: Vec<String>,
-
     // Month calendar
     month_calendar: MonthCalendarState,
 }
@@ -250,9 +543,10 @@ impl Application for Lamp {
     }
 
     fn init(mut core: Core, flags: Self::Flags) -> (Self, CosmicTask<Self::Message>) {
-        let config = flags.config;
+        let mut config = flags.config;
         let cosmic_config = flags.cosmic_config;
         let launch_mode = flags.launch_mode;
+        let migrated = config.migrate_accounts();
 
         // Ensure org files exist
         if let Err(e) = config.ensure_files() {
@@ -290,8 +584,13 @@ impl Application for Lamp {
         // Load cached contacts, events, and accounts
         let contacts = crate::sync::carddav::load_contacts(&config.contacts_path());
         let events = event::load_events(&config.events_cache_path());
+        let subscription_events = event::load_events(&config.subscriptions_cache_path());
+        let pending_replies = itip::load_replies(&config.replies_path());
+        let mail_cache = MailCache::load(&config.mail_cache_path());
+        let suggestion_cache = crate::sync::llm::load_suggestion_cache(&config.suggestions_path());
         let accounts = load_accounts(&config.accounts_path());
         let notes = load_notes_dir(&config.notes_dir(), &config.notes_path());
+        let outbox = Outbox::load(&config.outbox_path());
 
         // Set initial state based on launch mode
         let (app_mode, context_drawer_state) = match launch_mode {
@@ -303,6 +602,8 @@ impl Application for Lamp {
             LaunchMode::Normal => (AppMode::Plan, None),
         };
 
+        let ics_form = IcsForm::new(&config.org_directory);
+
         let mut app = Self {
             core,
             nav_model,
@@ -338,6 +639,7 @@ impl Application for Lamp {
             flipped_list_items: HashSet::new(),
             pending_delete_list_item: None,
             waiting_for_inputs: HashMap::new(),
+            follow_up_draft: None,
             contacts,
             contact_input: String::new(),
             flipped_contacts: HashSet::new(),
@@ -357,26 +659,70 @@ impl Application for Lamp {
             note_link_search: String::new(),
             note_edit_buffer: None,
             events,
+            subscription_events,
+            subscription_status: HashMap::new(),
+            pending_invitations: Vec::new(),
+            answered_invitations: HashSet::new(),
+            pending_replies,
             event_form: None,
+            event_delete_prompt: None,
+            agenda_layout: AgendaLayout::default(),
+            ics_form,
+            free_busy: FreeBusyForm::default(),
+            timeline_day: chrono::Local::now().date_naive(),
+            picked_block_task: None,
             review_checked: HashSet::new(),
+            review_suggestions: Vec::new(),
+            review_ai_running: false,
+            review_ai_error: None,
+            breakdown_status: None,
+            breakdown: None,
             sync_status: SyncStatus::default(),
-            discovered_calendars: Vec::new(),
-            service_passwords: [String::new(), String::new(), String::new(), String::new()],
-            service_test_status: [None, None, None, None],
+            account_sync_status: HashMap::new(),
+            discovered_calendars: HashMap::new(),
+            service_passwords: HashMap::new(),
+            service_test_status: HashMap::new(),
+            task_route_input: String::new(),
+            task_route_calendar: 0,
             imap_emails: Vec::new(),
-            anthropic_api_key_input: String::new(),
-            anthropic_test_status: None,
+            mail_cache,
+            imap_idling: false,
+            imap_capturing: false,
+            sending_replies: false,
+            delegation_status: HashMap::new(),
+            llm_api_key_input: String::new(),
+            llm_test_status: None,
+            vault_passphrase_input: String::new(),
+            vault_passphrase_repeat: String::new(),
+            secret_store_status: None,
             email_suggestions: HashMap::new(),
             ai_batch_processing: false,
+            suggestion_cache,
             archived_email_uids: HashSet::new(),
-            pending_completions: Vec::new(),
+            outbox,
+            outbox_retry_scheduled: false,
+            last_sync_at: None,
+            last_sync_tick: chrono::Utc::now(),
+            last_poll_at: None,
+            sync_poll_in_flight: false,
+            local_edit_at: None,
             sync_conflicts: Vec::new(),
-            pending_deletions: Vec::new(),
             month_calendar: MonthCalendarState::default(),
         };
         app.rebuild_cache();
+        if migrated {
+            log::info!("Migrated sync settings to named accounts");
+            app.save_config();
+        }
 
-        (app, CosmicTask::none())
+        // Sync on startup when background sync is on
+        let startup = if app.background_sync_enabled() {
+            CosmicTask::perform(async {}, |_| cosmic::Action::App(Message::SyncNow))
+        } else {
+            CosmicTask::none()
+        };
+
+        (app, startup)
     }
 
     fn nav_model(&self) -> Option<&nav_bar::Model> {
@@ -509,28 +855,142 @@ impl Application for Lamp {
                     value.clone()
                 };
                 let wf = if effective.trim().is_empty() { None } else { Some(effective.trim().to_string()) };
+                let contact_id = wf
+                    .as_deref()
+                    .and_then(|name| delegation::contact_by_name(name, &self.contacts))
+                    .map(|c| c.id);
                 self.modify_task(id, |task| {
                     task.waiting_for = wf;
+                    task.waiting_for_contact = contact_id;
                 });
                 self.waiting_for_inputs.remove(&id);
             }
 
+            Message::SetWaitingForContact(id, contact_id) => {
+                if let Some(contact) = self.contacts.iter().find(|c| c.id == contact_id) {
+                    let name = contact.name.clone();
+                    self.modify_task(id, |task| {
+                        task.waiting_for = Some(name);
+                        task.waiting_for_contact = Some(contact_id);
+                    });
+                }
+                self.waiting_for_inputs.remove(&id);
+            }
+
+            Message::ComposeFollowUp(ref party) => {
+                let today = chrono::Local::now().date_naive();
+                let groups = delegation::group_waiting(self.all_tasks_cache.iter(), &self.contacts);
+                if let Some(group) = groups.iter().find(|g| g.party.as_ref() == Some(party)) {
+                    let draft = delegation::compose_follow_up(group, today);
+                    // Sent straight away when outgoing mail is set up
+                    let to = draft.email.clone().filter(|e| !e.is_empty() && self.config.smtp.is_ready());
+                    if let Some(to) = to {
+                        let mut email = OutgoingEmail::new(&to, &draft.subject, &draft.body);
+                        email.to_name = Some(draft.recipient.clone());
+                        // A single item is followed up in its own thread
+                        if let [only] = group.tasks.as_slice() {
+                            email.in_reply_to = only.email.as_ref().map(|l| l.message_id.clone());
+                        }
+                        let ids: Vec<uuid::Uuid> = group.tasks.iter().map(|t| t.id).collect();
+                        self.follow_up_draft = None;
+                        return self.send_email(email, move |result| Message::FollowUpSent(ids, draft, result));
+                    }
+                    match draft.mailto_url() {
+                        Some(url) => {
+                            if let Err(e) = std::process::Command::new(&self.config.browser_command)
+                                .arg(&url)
+                                .spawn()
+                            {
+                                log::error!("Failed to open mail client: {}", e);
+                                self.follow_up_draft = Some(draft);
+                            } else {
+                                self.follow_up_draft = None;
+                            }
+                        }
+                        None => self.follow_up_draft = Some(draft),
+                    }
+                }
+            }
+
+            Message::DismissFollowUpDraft => {
+                self.follow_up_draft = None;
+            }
+
+            Message::FollowUpSent(ids, draft, result) => match result {
+                Ok(_) => {
+                    let next = chrono::Local::now().date_naive()
+                        + chrono::Duration::days(delegation::DEFAULT_FOLLOW_UP_DAYS);
+                    for id in ids {
+                        self.modify_task(id, |task| task.follow_up = Some(next));
+                    }
+                }
+                Err(e) => {
+                    log::error!("Failed to send follow-up to {}: {}", draft.recipient, e);
+                    self.follow_up_draft = Some(draft);
+                }
+            },
+
+            Message::SendDelegationEmail(id) => {
+                let Some(task) = self.all_tasks_cache.iter().find(|t| t.id == id) else {
+                    return CosmicTask::none();
+                };
+                let Some(contact) = delegation::resolve_contact(task, &self.contacts) else {
+                    return CosmicTask::none();
+                };
+                let draft = delegation::compose_delegation(task, contact);
+                let Some(to) = draft.email.clone().filter(|e| !e.is_empty()) else {
+                    self.delegation_status
+                        .insert(id, Err(format!("No email address for {}", draft.recipient)));
+                    return CosmicTask::none();
+                };
+                if !self.config.smtp.is_ready() {
+                    self.delegation_status
+                        .insert(id, Err("Set up outgoing mail (SMTP) in Settings".to_string()));
+                    return CosmicTask::none();
+                }
+                self.delegation_status.remove(&id);
+                let mut email = OutgoingEmail::new(&to, &draft.subject, &draft.body);
+                email.to_name = Some(draft.recipient);
+                return self.send_email(email, move |result| Message::DelegationSent(id, result));
+            }
+
+            Message::DelegationSent(id, result) => match result {
+                Ok(message_id) => {
+                    let today = chrono::Local::now().date_naive();
+                    self.modify_task(id, |task| {
+                        task.delegated = Some(today);
+                        task.follow_up = Some(today + chrono::Duration::days(delegation::DEFAULT_FOLLOW_UP_DAYS));
+                        // Replies to the request are what the task now waits on
+                        task.delegation_id = Some(message_id);
+                    });
+                    self.delegation_status.insert(id, Ok("Sent".to_string()));
+                }
+                Err(e) => {
+                    log::error!("Failed to send delegation email: {}", e);
+                    self.delegation_status.insert(id, Err(e));
+                }
+            },
+
             Message::DeleteTask(id) => {
                 // Queue server-side deletion if task has a sync_href
                 if let Some(task) = self.remove_task(id) {
                     if let Some(href) = task.sync_href {
-                        self.pending_deletions.push(href);
+                        self.queue_sync_write(OutboxOp::Delete { href });
                     }
                 }
                 // Also remove from projects
+                let mut project_hrefs = Vec::new();
                 for project in &mut self.projects {
                     if let Some(pos) = project.tasks.iter().position(|t| t.id == id) {
                         if let Some(href) = project.tasks[pos].sync_href.clone() {
-                            self.pending_deletions.push(href);
+                            project_hrefs.push(href);
                         }
                         project.tasks.remove(pos);
                     }
                 }
+                for href in project_hrefs {
+                    self.queue_sync_write(OutboxOp::Delete { href });
+                }
                 self.save_all();
             }
 
@@ -615,6 +1075,70 @@ impl Application for Lamp {
                 self.save_config();
             }
 
+            Message::SetIcsImportPath(path) => {
+                self.ics_form.import_path = path;
+            }
+
+            Message::ImportIcs => {
+                let path = expand_home(&self.ics_form.import_path);
+                self.ics_form.status = Some(self.import_ics(&path));
+            }
+
+            Message::SetIcsExportPath(path) => {
+                self.ics_form.export_path = path;
+            }
+
+            Message::SetIcsExportProject(project) => {
+                self.ics_form.project = project;
+            }
+
+            Message::SetIcsExportCalendar(href) => {
+                self.ics_form.calendar_href = href;
+            }
+
+            Message::SetIcsExportFrom(date) => {
+                self.ics_form.from = date;
+            }
+
+            Message::SetIcsExportTo(date) => {
+                self.ics_form.to = date;
+            }
+
+            Message::ToggleIcsExportTasks => {
+                self.ics_form.tasks = !self.ics_form.tasks;
+            }
+
+            Message::ToggleIcsExportEvents => {
+                self.ics_form.events = !self.ics_form.events;
+            }
+
+            Message::ExportIcs => {
+                let path = expand_home(&self.ics_form.export_path);
+                self.ics_form.status = Some(self.export_ics(&path));
+            }
+
+            Message::SetMeetingSpoonRate(spoons) => {
+                self.config.meeting_spoons_per_hour = spoons;
+                self.save_config();
+            }
+
+            Message::SetCalendarSpoonRate(ref account, ref href, spoons) => {
+                if let Some(assignment) = self
+                    .config
+                    .calendar_assignments
+                    .iter_mut()
+                    .find(|a| a.account == *account && a.calendar_href == *href)
+                {
+                    assignment.spoons_per_hour = spoons;
+                    self.save_config();
+                }
+            }
+
+            Message::SetSyncInterval(minutes) => {
+                self.config.sync_interval_minutes = minutes;
+                self.save_config();
+            }
+
             Message::ProjectInputChanged(value) => {
                 self.project_input = value;
             }
@@ -636,6 +1160,10 @@ impl Application for Lamp {
             }
 
             Message::DeleteProject(name) => {
+                if let Some(id) = self.projects.iter().find(|p| p.name == name).map(|p| p.id) {
+                    self.unlink_project(id, false);
+                    self.save_config();
+                }
                 self.projects.retain(|p| p.name != name);
                 self.save_projects();
                 self.rebuild_cache();
@@ -706,6 +1234,166 @@ impl Application for Lamp {
                 }
             }
 
+            Message::SuggestReview => {
+                if self.review_ai_running {
+                    return CosmicTask::none();
+                }
+                self.review_ai_running = true;
+                self.review_ai_error = None;
+                let llm = self.config.llm.clone();
+                let projects = self.projects.clone();
+                let tasks = self.all_tasks_cache.clone();
+                let contexts = self.config.contexts.clone();
+                let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+                return CosmicTask::perform(
+                    async move {
+                        let provider = crate::sync::llm::Provider::load(&llm).await?;
+                        crate::sync::assistant::suggest_review(&provider, &projects, &tasks, &contexts, &today)
+                            .await
+                    },
+                    |result| cosmic::Action::App(Message::ReviewSuggestionsReady(result)),
+                );
+            }
+
+            Message::ReviewSuggestionsReady(result) => {
+                self.review_ai_running = false;
+                match result {
+                    Ok(suggestions) => self.review_suggestions = suggestions,
+                    Err(e) => {
+                        log::error!("Review suggestions failed: {}", e);
+                        self.review_ai_error = Some(e);
+                    }
+                }
+            }
+
+            Message::ApproveReviewSuggestion(idx) => {
+                use crate::sync::assistant::ReviewSuggestion;
+
+                if idx >= self.review_suggestions.len() {
+                    return CosmicTask::none();
+                }
+                match self.review_suggestions.remove(idx) {
+                    ReviewSuggestion::NextAction { project, title, contexts } => {
+                        let mut task = Task::new(title);
+                        task.contexts = contexts;
+                        task.project = Some(project);
+                        self.file_new_task(task);
+                    }
+                    ReviewSuggestion::Rewrite { task, title, .. } => {
+                        self.modify_task(task, |t| t.title = title);
+                    }
+                    ReviewSuggestion::Activate { task, .. } => {
+                        self.set_task_state(task, TaskState::Next);
+                    }
+                }
+            }
+
+            Message::DismissReviewSuggestion(idx) => {
+                if idx < self.review_suggestions.len() {
+                    self.review_suggestions.remove(idx);
+                }
+            }
+
+            Message::BreakDownTask(id) => {
+                // One at a time; a failed attempt can be retried
+                if self.breakdown_status.as_ref().is_some_and(|(_, error)| error.is_none()) {
+                    return CosmicTask::none();
+                }
+                let Some(task) = self.all_tasks_cache.iter().find(|t| t.id == id).cloned() else {
+                    return CosmicTask::none();
+                };
+                self.breakdown_status = Some((id, None));
+                let llm = self.config.llm.clone();
+                let contexts = self.config.contexts.clone();
+                let project_names: Vec<String> = self.projects.iter().map(|p| p.name.clone()).collect();
+                let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+                return CosmicTask::perform(
+                    async move {
+                        let provider = crate::sync::llm::Provider::load(&llm).await?;
+                        crate::sync::assistant::break_down_task(&provider, &task, &contexts, &project_names, &today)
+                            .await
+                    },
+                    move |result| cosmic::Action::App(Message::BreakdownReady(id, result)),
+                );
+            }
+
+            Message::BreakdownReady(id, result) => match result {
+                Ok(breakdown) => {
+                    self.breakdown_status = None;
+                    self.breakdown = Some((id, breakdown));
+                    self.context_drawer_state = Some(ContextDrawerState::Breakdown);
+                    self.core.window.show_context = true;
+                }
+                Err(e) => {
+                    log::error!("Task breakdown failed: {}", e);
+                    self.breakdown_status = Some((id, Some(e)));
+                }
+            },
+
+            Message::SetBreakdownProject(name) => {
+                if let Some((_, ref mut breakdown)) = self.breakdown {
+                    breakdown.project = name;
+                }
+            }
+
+            Message::SetBreakdownOutcome(outcome) => {
+                if let Some((_, ref mut breakdown)) = self.breakdown {
+                    breakdown.outcome = outcome;
+                }
+            }
+
+            Message::SetBreakdownStepTitle(idx, title) => {
+                if let Some(step) = self.breakdown.as_mut().and_then(|(_, b)| b.steps.get_mut(idx)) {
+                    step.title = title;
+                }
+            }
+
+            Message::SetBreakdownStepEsc(idx, esc) => {
+                if let Some(step) = self.breakdown.as_mut().and_then(|(_, b)| b.steps.get_mut(idx)) {
+                    step.esc = esc;
+                }
+            }
+
+            Message::SetBreakdownStepContext(idx, context) => {
+                if let Some(step) = self.breakdown.as_mut().and_then(|(_, b)| b.steps.get_mut(idx)) {
+                    step.context = context;
+                }
+            }
+
+            Message::AddBreakdownStep => {
+                if let Some((_, ref mut breakdown)) = self.breakdown {
+                    if breakdown.steps.len() < *crate::sync::assistant::BREAKDOWN_STEPS.end() {
+                        breakdown.steps.push(Default::default());
+                    }
+                }
+            }
+
+            Message::RemoveBreakdownStep(idx) => {
+                if let Some((_, ref mut breakdown)) = self.breakdown {
+                    if idx < breakdown.steps.len() {
+                        breakdown.steps.remove(idx);
+                    }
+                }
+            }
+
+            Message::ApplyBreakdown => {
+                if let Some((id, breakdown)) = self.breakdown.clone() {
+                    match self.apply_breakdown(id, breakdown) {
+                        Ok(()) => return self.update(Message::CloseBreakdown),
+                        Err(e) => self.breakdown_status = Some((id, Some(e))),
+                    }
+                }
+            }
+
+            Message::CloseBreakdown => {
+                self.breakdown = None;
+                self.breakdown_status = None;
+                if self.context_drawer_state == Some(ContextDrawerState::Breakdown) {
+                    self.context_drawer_state = None;
+                    self.core.window.show_context = false;
+                }
+            }
+
             Message::CompleteHabit(id) => {
                 let today = chrono::Local::now().date_naive();
                 if let Some(habit) = self.habits.iter_mut().find(|h| h.task.id == id) {
@@ -924,12 +1612,18 @@ impl Application for Lamp {
 
                     // Delete from CardDAV server if we have a sync_href
                     if let Some(href) = removed.sync_href {
-                        let contacts_url = self.config.contacts.url.trim().to_string();
+                        let account = removed
+                            .sync_account
+                            .as_deref()
+                            .and_then(|id| self.config.contact_accounts.iter().find(|a| a.id == id))
+                            .or(self.config.contact_accounts.first());
+                        let contacts_url = account.map(|a| a.url.trim().to_string()).unwrap_or_default();
+                        let username = account.map(|a| a.username.clone()).unwrap_or_default();
                         if !contacts_url.is_empty() {
                             return CosmicTask::perform(
                                 async move {
-                                    let (username, pw) = match crate::sync::keyring::load_credentials(&contacts_url).await {
-                                        Ok(Some(creds)) => creds,
+                                    let pw = match crate::sync::keyring::load_account_credentials(&contacts_url, &username).await {
+                                        Ok(Some((_, pw))) => pw,
                                         _ => return Err("No CardDAV credentials".to_string()),
                                     };
                                     let client = crate::sync::carddav::CardDavClient::new(
@@ -1461,42 +2155,30 @@ impl Application for Lamp {
                     return CosmicTask::none();
                 }
                 self.sync_status = SyncStatus::Syncing;
+                self.last_sync_at = Some(chrono::Utc::now());
+                self.prepare_project_sync();
+                // This sync picks up every edit made so far
+                self.local_edit_at = None;
 
                 let mut batch: Vec<CosmicTask<Message>> = Vec::new();
 
-                // CalDAV task/event sync (only if configured)
+                // CalDAV task/event sync, one account after another so engines never overlap
                 if self.config.sync_ready() {
-                    let tasks: Vec<Task> = self.all_active_tasks();
-                    let events = self.events.clone();
-                    let caldav_url = self.config.calendars.url.clone();
-                    let task_cals = self.config.task_calendar_hrefs();
-                    let event_cals = self.config.event_calendar_hrefs();
-                    let sync_tokens = self.config.sync_tokens.clone();
-                    let completions = std::mem::take(&mut self.pending_completions);
-                    let deletions = std::mem::take(&mut self.pending_deletions);
+                    let jobs = self.calendar_sync_jobs();
+                    for job in &jobs {
+                        self.account_sync_status.insert(job.account.clone(), SyncStatus::Syncing);
+                    }
 
                     batch.push(CosmicTask::perform(
                         async move {
-                            let (username, password) = match crate::sync::keyring::load_credentials(&caldav_url).await {
-                                Ok(Some(creds)) => creds,
-                                Ok(None) => return Err("No CalDAV credentials stored".to_string()),
-                                Err(e) => return Err(format!("Keyring error: {}", e)),
-                            };
-                            crate::sync::sync_all(
-                                &caldav_url,
-                                &username,
-                                &password,
-                                &tasks,
-                                &events,
-                                &task_cals,
-                                &event_cals,
-                                &sync_tokens,
-                                &completions,
-                                &deletions,
-                            )
-                            .await
+                            let mut results = Vec::new();
+                            for job in jobs {
+                                let result = job.run().await;
+                                results.push((job.account, result));
+                            }
+                            results
                         },
-                        |result| cosmic::Action::App(Message::SyncCompleted(result)),
+                        |results| cosmic::Action::App(Message::SyncCompleted(results)),
                     ));
                 }
 
@@ -1522,12 +2204,17 @@ impl Application for Lamp {
                 }
 
                 // CardDAV contacts sync
-                let contacts_url = self.config.contacts.url.trim().to_string();
-                if !contacts_url.is_empty() {
+                for account in &self.config.contact_accounts {
+                    let contacts_url = account.url.trim().to_string();
+                    if contacts_url.is_empty() {
+                        continue;
+                    }
+                    let account_id = account.id.clone();
+                    let username = account.username.clone();
                     batch.push(CosmicTask::perform(
                         async move {
-                            let (username, pw) = match crate::sync::keyring::load_credentials(&contacts_url).await {
-                                Ok(Some((u, pw))) => (u, pw),
+                            let pw = match crate::sync::keyring::load_account_credentials(&contacts_url, &username).await {
+                                Ok(Some((_, pw))) => pw,
                                 _ => return Err("No CardDAV credentials stored".to_string()),
                             };
                             let client = crate::sync::carddav::CardDavClient::new(
@@ -1535,29 +2222,17 @@ impl Application for Lamp {
                             )?;
                             client.fetch_contacts().await
                         },
-                        |result| cosmic::Action::App(Message::ContactsFetched(result)),
+                        move |result| cosmic::Action::App(Message::ContactsFetched(account_id.clone(), result)),
                     ));
                 }
 
                 // IMAP email fetch
-                let imap_host = self.config.imap.host.trim().to_string();
-                if !imap_host.is_empty() {
-                    let folder = if self.config.imap.folder.is_empty() {
-                        "flup".to_string()
-                    } else {
-                        self.config.imap.folder.clone()
-                    };
-                    batch.push(CosmicTask::perform(
-                        async move {
-                            let keyring_key = format!("imap://{}", imap_host);
-                            let (username, pw) = match crate::sync::keyring::load_credentials(&keyring_key).await {
-                                Ok(Some(creds)) => creds,
-                                _ => return Err("No IMAP credentials stored".to_string()),
-                            };
-                            crate::sync::imap::fetch_emails(&imap_host, &username, &pw, &folder).await
-                        },
-                        |result| cosmic::Action::App(Message::ImapFetched(result)),
-                    ));
+                if let Some(fetch) = self.fetch_imap() {
+                    batch.push(fetch);
+                }
+
+                if let Some(refresh) = self.refresh_subscriptions() {
+                    batch.push(refresh);
                 }
 
                 if batch.is_empty() {
@@ -1567,164 +2242,369 @@ impl Application for Lamp {
                 return CosmicTask::batch(batch);
             }
 
-            Message::SyncCompleted(result) => {
-                match result {
-                    Ok(sync_result) => {
-                        // Update sync tokens
-                        for (href, token) in &sync_result.new_sync_tokens {
-                            self.config.set_sync_token(href, token);
-                        }
-
-                        // Remove deleted tasks
-                        for id in &sync_result.deleted_local {
-                            self.inbox_tasks.retain(|t| t.id != *id);
-                            self.next_tasks.retain(|t| t.id != *id);
-                            self.waiting_tasks.retain(|t| t.id != *id);
-                            self.someday_tasks.retain(|t| t.id != *id);
-                            for project in &mut self.projects {
-                                project.tasks.retain(|t| t.id != *id);
+            Message::SyncCompleted(results) => {
+                // Applying pulled tasks isn't a local edit; keep the marker as it was
+                let local_edit_at = self.local_edit_at;
+                let utc_now = chrono::Utc::now();
+                let now = chrono::Local::now().format("%H:%M").to_string();
+                let mut last_error = None;
+                let mut any_ok = false;
+                self.sync_conflicts.clear();
+                // Each sync lists the inboxes afresh
+                self.pending_invitations
+                    .retain(|i| !matches!(i.source, InvitationSource::CalDavInbox { .. }));
+
+                for (account, result) in results {
+                    match result {
+                        Ok(sync_result) => {
+                            any_ok = true;
+                            if !sync_result.errors.is_empty() {
+                                log::warn!("Sync completed with errors: {:?}", sync_result.errors);
                             }
+                            self.apply_sync_result(&account, sync_result);
+                            self.account_sync_status
+                                .insert(account, SyncStatus::LastSynced(now.clone()));
                         }
-
-                        // Apply pulled tasks (new + updated)
-                        for pulled in &sync_result.pulled {
-                            let _existing = self.remove_task(pulled.id);
-                            if let Some(ref project_name) = pulled.project {
-                                let project_name = project_name.clone();
-                                if let Some(project) = self.projects.iter_mut().find(|p| p.name == project_name) {
-                                    project.tasks.push(pulled.clone());
-                                    continue;
-                                }
+                        Err(e) => {
+                            log::error!("Sync failed for account {}: {}", account, e);
+                            // Nothing reached the server; back off this account's due writes
+                            let due: Vec<uuid::Uuid> = self
+                                .outbox_entries_for(&account)
+                                .into_iter()
+                                .filter(|entry| entry.is_due(utc_now))
+                                .map(|entry| entry.id)
+                                .collect();
+                            for id in due {
+                                self.outbox.record_failure(id, &e, utc_now);
                             }
-                            self.route_task_by_state(pulled.clone());
+                            self.account_sync_status
+                                .insert(account, SyncStatus::Error(e.clone()));
+                            last_error = Some(e);
                         }
+                    }
+                }
 
-                        // Remove deleted events
-                        for id in &sync_result.deleted_events {
-                            self.events.retain(|e| e.id != *id);
-                        }
+                self.save_events();
+                self.save_all();
+                self.save_config();
+                self.save_outbox();
+                self.local_edit_at = local_edit_at;
 
-                        // Apply pulled events
-                        for pulled_event in &sync_result.pulled_events {
-                            self.events.retain(|e| e.id != pulled_event.id);
-                            self.events.push(pulled_event.clone());
-                        }
+                self.sync_status = match last_error {
+                    Some(e) if !any_ok => SyncStatus::Error(e),
+                    _ => SyncStatus::LastSynced(now),
+                };
+                return self.schedule_outbox_retry();
+            }
 
-                        self.save_events();
-                        self.save_all();
-                        self.save_config();
+            Message::RetryOutbox => {
+                self.outbox_retry_scheduled = false;
+                if !self.outbox.is_empty() {
+                    return self.update(Message::SyncNow);
+                }
+            }
 
-                        self.sync_conflicts = sync_result.conflicts;
+            Message::DiscardOutboxEntry(id) => {
+                self.outbox.remove(id);
+                self.save_outbox();
+            }
 
-                        let now = chrono::Local::now().format("%H:%M").to_string();
-                        self.sync_status = SyncStatus::LastSynced(now);
+            Message::RetryOutboxNow => {
+                self.outbox.retry_now();
+                self.save_outbox();
+                return self.update(Message::SyncNow);
+            }
 
-                        if !sync_result.errors.is_empty() {
-                            log::warn!("Sync completed with errors: {:?}", sync_result.errors);
-                        }
+            Message::SyncTick => {
+                let now = chrono::Utc::now();
+                let gap = now - self.last_sync_tick;
+                self.last_sync_tick = now;
+
+                if !self.background_sync_enabled() || self.sync_status == SyncStatus::Syncing {
+                    return CosmicTask::none();
+                }
+
+                // A tick arriving long after the previous one means we were suspended
+                let resumed = gap > chrono::Duration::seconds(SYNC_TICK_SECS as i64 * 4);
+                let interval = chrono::Duration::minutes(self.config.sync_interval_minutes as i64);
+                let interval_due = self.last_sync_at.is_none_or(|t| now - t >= interval);
+                let edits_due = self.config.sync_ready()
+                    && self
+                        .local_edit_at
+                        .is_some_and(|t| now - t >= chrono::Duration::seconds(EDIT_DEBOUNCE_SECS));
+
+                if resumed || interval_due || edits_due {
+                    log::info!(
+                        "Background sync (resumed={}, interval={}, edits={})",
+                        resumed, interval_due, edits_due
+                    );
+                    return self.update(Message::SyncNow);
+                }
+
+                // Between full syncs, poll sync tokens so remote changes arrive promptly
+                let poll_due = self
+                    .last_poll_at
+                    .is_none_or(|t| now - t >= chrono::Duration::seconds(POLL_INTERVAL_SECS));
+                if self.config.sync_ready() && !self.sync_poll_in_flight && poll_due {
+                    self.sync_poll_in_flight = true;
+                    self.last_poll_at = Some(now);
+                    let polls: Vec<(String, String, Vec<String>, Vec<(String, String)>)> = self
+                        .config
+                        .ready_calendar_accounts()
+                        .into_iter()
+                        .map(|account| {
+                            let mut cals = self.config.task_calendar_hrefs(&account.id);
+                            cals.extend(self.config.event_calendar_hrefs(&account.id));
+                            (
+                                account.url.trim().to_string(),
+                                account.username.clone(),
+                                cals,
+                                self.config.sync_tokens_for(&account.id),
+                            )
+                        })
+                        .collect();
+                    return CosmicTask::perform(
+                        async move {
+                            for (url, username, cals, sync_tokens) in polls {
+                                let password = match crate::sync::keyring::load_account_credentials(&url, &username).await {
+                                    Ok(Some((_, pw))) => pw,
+                                    Ok(None) => return Err("No CalDAV credentials stored".to_string()),
+                                    Err(e) => return Err(format!("Keyring error: {}", e)),
+                                };
+                                if crate::sync::poll_changes(&url, &username, &password, &cals, &sync_tokens).await? {
+                                    return Ok(true);
+                                }
+                            }
+                            Ok(false)
+                        },
+                        |result| cosmic::Action::App(Message::SyncPollCompleted(result)),
+                    );
+                }
+            }
+
+            Message::SyncPollCompleted(result) => {
+                self.sync_poll_in_flight = false;
+                match result {
+                    Ok(true) => {
+                        log::info!("Remote changes detected, syncing");
+                        return self.update(Message::SyncNow);
                     }
-                    Err(e) => {
-                        log::error!("Sync failed: {}", e);
-                        self.sync_status = SyncStatus::Error(e);
+                    Ok(false) => {}
+                    Err(e) => log::debug!("Sync poll failed: {}", e),
+                }
+            }
+
+            Message::AddSubscription => {
+                self.config.subscriptions.push(IcsSubscription::new());
+                self.save_config();
+            }
+
+            Message::RemoveSubscription(idx) => {
+                if idx < self.config.subscriptions.len() {
+                    let removed = self.config.subscriptions.remove(idx);
+                    let href = removed.calendar_href();
+                    self.subscription_events.retain(|e| e.calendar_href != href);
+                    self.subscription_status.remove(&removed.id);
+                    self.save_subscription_events();
+                    self.save_config();
+                }
+            }
+
+            Message::SetSubscriptionName(idx, name) => {
+                if let Some(sub) = self.config.subscriptions.get_mut(idx) {
+                    let href = sub.calendar_href();
+                    for event in self.subscription_events.iter_mut().filter(|e| e.calendar_href == href) {
+                        event.calendar_name = name.clone();
                     }
+                    sub.name = name;
+                    self.save_subscription_events();
+                    self.save_config();
                 }
             }
 
-            Message::SetServiceUrl(kind, url) => {
-                match kind {
-                    ServiceKind::Calendars => self.config.calendars.url = url,
-                    ServiceKind::Contacts => self.config.contacts.url = url,
-                    ServiceKind::Notes => self.config.notes_sync.url = url,
-                    ServiceKind::Imap => self.config.imap.host = url,
+            Message::SetSubscriptionUrl(idx, url) => {
+                if let Some(sub) = self.config.subscriptions.get_mut(idx) {
+                    sub.url = url;
+                    // Validators belong to the old URL
+                    sub.etag = None;
+                    sub.last_modified = None;
+                    self.save_config();
+                }
+            }
+
+            Message::RefreshSubscriptions => {
+                if let Some(refresh) = self.refresh_subscriptions() {
+                    return refresh;
                 }
+            }
+
+            Message::SubscriptionsRefreshed(results) => {
+                let now = chrono::Local::now().format("%H:%M").to_string();
+                for (id, result) in results {
+                    let Some(sub) = self.config.subscriptions.iter_mut().find(|s| s.id == id) else {
+                        continue;
+                    };
+                    match result {
+                        Ok(crate::sync::subscription::FeedFetch::NotModified) => {}
+                        Ok(crate::sync::subscription::FeedFetch::Updated { events, etag, last_modified }) => {
+                            let href = sub.calendar_href();
+                            log::info!("Subscription {} refreshed: {} events", sub.url, events.len());
+                            self.subscription_events.retain(|e| e.calendar_href != href);
+                            self.subscription_events.extend(events);
+                            sub.etag = etag;
+                            sub.last_modified = last_modified;
+                        }
+                        Err(e) => {
+                            log::warn!("Subscription {} failed: {}", sub.url, e);
+                            self.subscription_status.insert(id, SyncStatus::Error(e));
+                            continue;
+                        }
+                    }
+                    self.subscription_status.insert(id, SyncStatus::LastSynced(now.clone()));
+                }
+                self.save_subscription_events();
                 self.save_config();
             }
 
-            Message::SetServiceUsername(kind, username) => {
+            Message::AddServiceAccount(kind) => {
                 match kind {
-                    ServiceKind::Calendars => self.config.calendars.username = username,
-                    ServiceKind::Contacts => self.config.contacts.username = username,
-                    ServiceKind::Notes => self.config.notes_sync.username = username,
-                    ServiceKind::Imap => self.config.imap.username = username,
+                    ServiceKind::Calendars => self.config.calendar_accounts.push(ServiceConfig::new()),
+                    ServiceKind::Contacts => self.config.contact_accounts.push(ServiceConfig::new()),
+                    ServiceKind::Notes | ServiceKind::Imap | ServiceKind::Smtp => {}
                 }
                 self.save_config();
             }
 
-            Message::SetServicePassword(kind, password) => {
-                let idx = kind as usize;
-                self.service_passwords[idx] = password;
+            Message::RemoveServiceAccount(kind, idx) => {
+                let removed = match kind {
+                    ServiceKind::Calendars if idx < self.config.calendar_accounts.len() => {
+                        Some(self.config.calendar_accounts.remove(idx))
+                    }
+                    ServiceKind::Contacts if idx < self.config.contact_accounts.len() => {
+                        Some(self.config.contact_accounts.remove(idx))
+                    }
+                    _ => None,
+                };
+                if let Some(account) = removed {
+                    if kind == ServiceKind::Calendars {
+                        self.config.calendar_assignments.retain(|a| a.account != account.id);
+                        self.config.task_routes.retain(|r| r.account != account.id);
+                        self.discovered_calendars.remove(&account.id);
+                        self.account_sync_status.remove(&account.id);
+                    }
+                    // Inputs are keyed by index, so they no longer line up
+                    self.service_passwords.retain(|(k, _), _| *k != kind);
+                    self.service_test_status.retain(|(k, _), _| *k != kind);
+                    self.save_config();
+                }
+            }
+
+            Message::SetServiceName(kind, idx, name) => {
+                if let Some(account) = self.service_account_mut(kind, idx) {
+                    account.name = name;
+                    self.save_config();
+                }
             }
 
-            Message::TestServiceConnection(kind) => {
-                let idx = kind as usize;
+            Message::SetServiceUrl(kind, idx, url) => {
+                if kind == ServiceKind::Imap {
+                    self.config.imap.host = url;
+                } else if kind == ServiceKind::Smtp {
+                    self.config.smtp.host = url;
+                } else if let Some(account) = self.service_account_mut(kind, idx) {
+                    account.url = url;
+                }
+                self.save_config();
+            }
 
+            Message::SetServiceUsername(kind, idx, username) => {
                 if kind == ServiceKind::Imap {
-                    // IMAP uses host, not URL
-                    let host = self.config.imap.host.trim().to_string();
+                    self.config.imap.username = username;
+                } else if kind == ServiceKind::Smtp {
+                    self.config.smtp.username = username;
+                } else if let Some(account) = self.service_account_mut(kind, idx) {
+                    account.username = username;
+                }
+                self.save_config();
+            }
+
+            Message::SetServicePassword(kind, idx, password) => {
+                self.service_passwords.insert((kind, idx), password);
+            }
+
+            Message::TestServiceConnection(kind, idx) => {
+                let key = (kind, idx);
+
+                if kind == ServiceKind::Imap || kind == ServiceKind::Smtp {
+                    // Mail servers use host, not URL
+                    let (scheme, host, username) = if kind == ServiceKind::Imap {
+                        ("imap", self.config.imap.host.trim().to_string(), self.config.imap.username.clone())
+                    } else {
+                        ("smtp", self.config.smtp.host.trim().to_string(), self.config.smtp.username.clone())
+                    };
                     if host.is_empty() {
-                        self.service_test_status[idx] = Some(Err("IMAP host is required".to_string()));
+                        self.service_test_status.insert(key, Err("Host is required".to_string()));
                         return CosmicTask::none();
                     }
-                    let username = self.config.imap.username.clone();
-                    if username.is_empty() {
-                        self.service_test_status[idx] = Some(Err("Username is required".to_string()));
+                    // Local SMTP sinks take mail without logging in
+                    if username.is_empty() && kind == ServiceKind::Imap {
+                        self.service_test_status.insert(key, Err("Username is required".to_string()));
                         return CosmicTask::none();
                     }
-                    let password = self.service_passwords[idx].clone();
-                    self.service_test_status[idx] = None;
+                    let password = self.service_passwords.get(&key).cloned().unwrap_or_default();
+                    self.service_test_status.remove(&key);
 
                     return CosmicTask::perform(
                         async move {
-                            let keyring_key = format!("imap://{}", host);
+                            let keyring_key = format!("{}://{}", scheme, host);
                             if !password.is_empty() {
                                 let _ = crate::sync::keyring::store_credentials(
                                     &keyring_key, &username, &password,
                                 ).await;
                             }
-                            let pw = if !password.is_empty() {
+                            let pw = if !password.is_empty() || username.is_empty() {
                                 password
                             } else {
                                 match crate::sync::keyring::load_credentials(&keyring_key).await {
                                     Ok(Some((_, pw))) => pw,
-                                    _ => return Err((kind, "No password available — enter an app password".to_string())),
+                                    _ => return Err("No password available — enter an app password".to_string()),
                                 }
                             };
-                            let msg = crate::sync::imap::test_connection(&host, &username, &pw)
-                                .await
-                                .map_err(|e| (kind, e))?;
-                            Ok((kind, msg, Vec::new()))
+                            let msg = if kind == ServiceKind::Imap {
+                                crate::sync::imap::test_connection(&host, &username, &pw).await?
+                            } else {
+                                crate::sync::smtp::test_connection(&host, &username, &pw).await?
+                            };
+                            Ok((msg, Vec::new()))
                         },
-                        |result| {
+                        move |result| {
                             match result {
-                                Ok((kind, msg, cals)) => {
-                                    cosmic::Action::App(Message::ServiceConnectionTested(kind, Ok(msg), cals))
+                                Ok((msg, cals)) => {
+                                    cosmic::Action::App(Message::ServiceConnectionTested(kind, idx, Ok(msg), cals))
                                 }
-                                Err((kind, e)) => {
-                                    cosmic::Action::App(Message::ServiceConnectionTested(kind, Err(e), Vec::new()))
+                                Err(e) => {
+                                    cosmic::Action::App(Message::ServiceConnectionTested(kind, idx, Err(e), Vec::new()))
                                 }
                             }
                         },
                     );
                 }
 
-                let svc = match kind {
-                    ServiceKind::Calendars => &self.config.calendars,
-                    ServiceKind::Contacts => &self.config.contacts,
-                    ServiceKind::Notes => &self.config.notes_sync,
-                    ServiceKind::Imap => unreachable!(),
+                let Some(svc) = self.service_account(kind, idx) else {
+                    return CosmicTask::none();
                 };
                 let url = svc.url.trim().to_string();
                 if url.is_empty() || !url.starts_with("https://") {
-                    self.service_test_status[idx] = Some(Err("URL must start with https://".to_string()));
+                    self.service_test_status.insert(key, Err("URL must start with https://".to_string()));
                     return CosmicTask::none();
                 }
                 let username = svc.username.clone();
                 if username.is_empty() {
-                    self.service_test_status[idx] = Some(Err("Username is required".to_string()));
+                    self.service_test_status.insert(key, Err("Username is required".to_string()));
                     return CosmicTask::none();
                 }
-                let password = self.service_passwords[idx].clone();
-                self.service_test_status[idx] = None;
+                let password = self.service_passwords.get(&key).cloned().unwrap_or_default();
+                self.service_test_status.remove(&key);
 
                 return CosmicTask::perform(
                     async move {
@@ -1737,70 +2617,129 @@ impl Application for Lamp {
                         let pw = if !password.is_empty() {
                             password
                         } else {
-                            match crate::sync::keyring::load_credentials(&url).await {
+                            match crate::sync::keyring::load_account_credentials(&url, &username).await {
                                 Ok(Some((_, pw))) => pw,
-                                _ => return Err((kind, "No password available — enter an app password".to_string())),
+                                _ => return Err("No password available — enter an app password".to_string()),
                             }
                         };
 
                         match kind {
                             ServiceKind::Calendars => {
-                                let client = CalDavClient::new(&url, &username, &pw).map_err(|e| (kind, e))?;
-                                let cals = client.discover_calendars().await.map_err(|e| (kind, e))?;
+                                let client = CalDavClient::new(&url, &username, &pw)?;
+                                let cals = client.discover_calendars().await?;
                                 let msg = format!("Found {} calendars", cals.len());
-                                Ok((kind, msg, cals))
+                                Ok((msg, cals))
                             }
                             ServiceKind::Contacts => {
-                                let client = crate::sync::carddav::CardDavClient::new(&url, &username, &pw).map_err(|e| (kind, e))?;
-                                let contacts = client.fetch_contacts().await.map_err(|e| (kind, e))?;
-                                Ok((kind, format!("Connected ({} contacts)", contacts.len()), Vec::new()))
+                                let client = crate::sync::carddav::CardDavClient::new(&url, &username, &pw)?;
+                                let contacts = client.fetch_contacts().await?;
+                                Ok((format!("Connected ({} contacts)", contacts.len()), Vec::new()))
                             }
                             ServiceKind::Notes => {
-                                let client = crate::sync::webdav::WebDavClient::new(&url, &username, &pw).map_err(|e| (kind, e))?;
-                                client.ensure_collection().await.map_err(|e| (kind, format!("Collection error: {}", e)))?;
-                                let files = client.list_files().await.map_err(|e| (kind, e))?;
-                                Ok((kind, format!("Connected ({} files)", files.len()), Vec::new()))
+                                let client = crate::sync::webdav::WebDavClient::new(&url, &username, &pw)?;
+                                client.ensure_collection().await.map_err(|e| format!("Collection error: {}", e))?;
+                                let files = client.list_files().await?;
+                                Ok((format!("Connected ({} files)", files.len()), Vec::new()))
                             }
-                            ServiceKind::Imap => unreachable!(),
+                            ServiceKind::Imap | ServiceKind::Smtp => unreachable!(),
                         }
                     },
-                    |result| {
+                    move |result| {
                         match result {
-                            Ok((kind, msg, cals)) => {
-                                cosmic::Action::App(Message::ServiceConnectionTested(kind, Ok(msg), cals))
+                            Ok((msg, cals)) => {
+                                cosmic::Action::App(Message::ServiceConnectionTested(kind, idx, Ok(msg), cals))
                             }
-                            Err((kind, e)) => {
-                                cosmic::Action::App(Message::ServiceConnectionTested(kind, Err(e), Vec::new()))
+                            Err(e) => {
+                                cosmic::Action::App(Message::ServiceConnectionTested(kind, idx, Err(e), Vec::new()))
                             }
                         }
                     },
                 );
             }
 
-            Message::ServiceConnectionTested(kind, ref result, ref cals) => {
-                let idx = kind as usize;
-                self.service_test_status[idx] = Some(result.clone());
+            Message::ServiceConnectionTested(kind, idx, ref result, ref cals) => {
+                self.service_test_status.insert((kind, idx), result.clone());
 
                 if kind == ServiceKind::Calendars {
-                    self.discovered_calendars = cals.clone();
+                    if let Some(account) = self.config.calendar_accounts.get(idx) {
+                        self.discovered_calendars.insert(account.id.clone(), cals.clone());
+                    }
                 }
             }
 
-            Message::SetCalendarPurpose(ref href, ref purpose) => {
-                // Remove existing assignment for this href
+            Message::SetCalendarPurpose(ref account, ref href, ref purpose) => {
+                // Keep the sync token and spoon rate if the calendar was already assigned
+                let sync_token = self
+                    .config
+                    .get_sync_token(account, href)
+                    .map(str::to_string);
+                let spoons_per_hour = self
+                    .config
+                    .calendar_assignments
+                    .iter()
+                    .find(|a| a.account == *account && a.calendar_href == *href)
+                    .and_then(|a| a.spoons_per_hour);
+                // Remove existing assignment for this calendar
                 self.config
                     .calendar_assignments
-                    .retain(|a| a.calendar_href != *href);
+                    .retain(|a| !(a.account == *account && a.calendar_href == *href));
                 // Add new assignment
                 self.config
                     .calendar_assignments
                     .push(crate::config::CalendarAssignment {
+                        account: account.clone(),
                         calendar_href: href.clone(),
                         purpose: purpose.clone(),
+                        sync_token,
+                        spoons_per_hour,
                     });
                 self.save_config();
             }
 
+            Message::TaskRouteInput(value) => {
+                self.task_route_input = value;
+            }
+
+            Message::SetTaskRouteCalendar(idx) => {
+                self.task_route_calendar = idx;
+            }
+
+            Message::AddTaskRoute => {
+                let key = self.task_route_input.trim().to_string();
+                let target = self
+                    .task_calendar_choices()
+                    .into_iter()
+                    .nth(self.task_route_calendar);
+                if let (false, Some((account, href, _))) = (key.is_empty(), target) {
+                    self.config.task_routes.push(crate::config::TaskRoute {
+                        key,
+                        account,
+                        calendar_href: href,
+                    });
+                    self.task_route_input.clear();
+                    self.save_config();
+                }
+            }
+
+            Message::SetProjectSyncMode(mode) => {
+                if mode != self.config.project_sync_mode {
+                    let projects: Vec<uuid::Uuid> =
+                        self.config.project_links.iter().map(|l| l.project).collect();
+                    for project in projects {
+                        self.unlink_project(project, true);
+                    }
+                    self.config.project_sync_mode = mode;
+                    self.save_config();
+                }
+            }
+
+            Message::RemoveTaskRoute(idx) => {
+                if idx < self.config.task_routes.len() {
+                    self.config.task_routes.remove(idx);
+                    self.save_config();
+                }
+            }
+
             Message::SyncNotesCompleted(result) => {
                 match result {
                     Ok(sync_result) => {
@@ -1829,9 +2768,12 @@ impl Application for Lamp {
                 }
             }
 
-            Message::ContactsFetched(result) => {
+            Message::ContactsFetched(account, result) => {
                 match result {
-                    Ok(remote) => {
+                    Ok(mut remote) => {
+                        for contact in &mut remote {
+                            contact.sync_account = Some(account.clone());
+                        }
                         crate::sync::carddav::merge_contacts(&mut self.contacts, remote);
                         let _ = crate::sync::carddav::save_contacts(
                             &self.config.contacts_path(),
@@ -1852,21 +2794,54 @@ impl Application for Lamp {
 
             // --- IMAP email integration ---
             Message::ImapFetched(result) => {
+                let mut tasks = Vec::new();
                 match result {
-                    Ok(emails) => {
-                        log::info!("IMAP: fetched {} emails", emails.len());
-                        if self.archived_email_uids.is_empty() {
-                            self.imap_emails = emails;
+                    Ok(cache) => {
+                        log::info!("IMAP: {} emails in {}", cache.emails.len(), cache.folder);
+                        // A new folder or UIDVALIDITY reuses UIDs for other messages
+                        let renumbered = self.mail_cache.folder != cache.folder
+                            || self.mail_cache.uid_validity != cache.uid_validity;
+                        self.mail_cache = cache;
+                        self.apply_mail_cache();
+                        // Suggestions, dismissed ones included, stay for mail still in the folder
+                        if renumbered {
+                            self.email_suggestions.clear();
                         } else {
-                            self.imap_emails = emails
-                                .into_iter()
-                                .filter(|e| !self.archived_email_uids.contains(&e.uid))
-                                .collect();
+                            let uids: HashSet<u32> = self.imap_emails.iter().map(|e| e.uid).collect();
+                            self.email_suggestions.retain(|uid, _| uids.contains(uid));
+                        }
+                        let message_ids: HashSet<&str> = self
+                            .imap_emails
+                            .iter()
+                            .filter_map(|e| e.message_id.as_deref())
+                            .collect();
+                        let cached = self.suggestion_cache.len();
+                        self.suggestion_cache.retain(|id, _| message_ids.contains(id.as_str()));
+                        if self.suggestion_cache.len() != cached {
+                            self.save_suggestion_cache();
+                        }
+                        // New mail was only listed; load the newest bodies to look
+                        // for invitations and tasks
+                        let unloaded =
+                            crate::sync::imap::newest_unloaded(&self.imap_emails, crate::sync::imap::BODY_WINDOW);
+                        if unloaded.is_empty() {
+                            if !self.imap_emails.is_empty() {
+                                tasks.push(self.update(Message::SuggestEmailTasks));
+                            }
+                        } else if let Some(task) = self.fetch_email_bodies(unloaded) {
+                            tasks.push(task);
                         }
-                        // Clear old suggestions and auto-trigger batch analysis
-                        self.email_suggestions.clear();
-                        if !self.imap_emails.is_empty() {
-                            return self.update(Message::SuggestEmailTasks);
+                        if !self.imap_idling {
+                            tasks.push(self.imap_idle());
+                        }
+                        if let Some(check) = self.check_email_replies() {
+                            tasks.push(check);
+                        }
+                        if let Some(capture) = self.fetch_captures() {
+                            tasks.push(capture);
+                        }
+                        if let Some(send) = self.send_pending_replies() {
+                            tasks.push(send);
                         }
                     }
                     Err(e) => {
@@ -1878,16 +2853,122 @@ impl Application for Lamp {
                     let now = chrono::Local::now().format("%H:%M").to_string();
                     self.sync_status = SyncStatus::LastSynced(now);
                 }
+                return CosmicTask::batch(tasks);
+            }
+
+            Message::ImapBodiesFetched(result) => match result {
+                Ok(emails) => {
+                    self.mail_cache.merge(emails);
+                    self.apply_mail_cache();
+                    return self.update(Message::SuggestEmailTasks);
+                }
+                Err(e) => log::error!("Failed to load email bodies: {}", e),
+            },
+
+            Message::EmailRepliesFound(result) => match result {
+                Ok(message_ids) => {
+                    // A reply in the thread means the wait is over
+                    let answered: Vec<uuid::Uuid> = self
+                        .all_tasks_cache
+                        .iter()
+                        .filter(|t| t.state == TaskState::Waiting)
+                        .filter(|t| {
+                            t.email.as_ref().is_some_and(|e| message_ids.contains(&e.message_id))
+                                || t.delegation_id.as_ref().is_some_and(|id| message_ids.contains(id))
+                        })
+                        .map(|t| t.id)
+                        .collect();
+                    for id in answered {
+                        log::info!("Reply arrived for waiting task {}", id);
+                        self.set_task_state(id, TaskState::Next);
+                    }
+                }
+                Err(e) => log::error!("Failed to look for email replies: {}", e),
+            },
+
+            Message::OpenTaskEmail(id) => {
+                let url = self
+                    .all_tasks_cache
+                    .iter()
+                    .find(|t| t.id == id)
+                    .and_then(|t| t.email.as_ref())
+                    .map(EmailLink::mid_url);
+                if let Some(url) = url {
+                    if let Err(e) = std::process::Command::new(&self.config.browser_command)
+                        .arg(&url)
+                        .spawn()
+                    {
+                        log::error!("Failed to open email: {}", e);
+                    }
+                }
+            }
+
+            Message::ImapIdleEnded(result) => {
+                self.imap_idling = false;
+                match result {
+                    // Something changed in the folder
+                    Ok(true) => {
+                        if let Some(fetch) = self.fetch_imap() {
+                            return fetch;
+                        }
+                    }
+                    // Servers drop idle connections after 30 minutes; start over
+                    Ok(false) => return self.imap_idle(),
+                    // Picked up again by the next successful fetch
+                    Err(e) => log::warn!("IMAP IDLE stopped: {}", e),
+                }
             }
 
             Message::SuggestEmailTasks => {
-                if self.imap_emails.is_empty() || self.ai_batch_processing {
+                if self.imap_emails.is_empty() {
                     return CosmicTask::none();
                 }
 
+                // Rules come first; archiving rules handle the email outright
+                let mut tasks = Vec::new();
+                let matched: Vec<(u32, bool, crate::sync::llm::BatchEmailSuggestion)> = self
+                    .imap_emails
+                    .iter()
+                    .filter(|e| e.body_loaded && !self.email_suggestions.contains_key(&e.uid))
+                    .filter_map(|e| {
+                        crate::sync::imap::triage(&self.config.triage_rules, e)
+                            .map(|(rule, suggestion)| (e.uid, rule.archive, suggestion))
+                    })
+                    .collect();
+                for (uid, auto_archive, suggestion) in matched {
+                    if auto_archive {
+                        if suggestion.action_needed {
+                            self.add_task_from_suggestion(uid, suggestion);
+                        }
+                        // Hidden right away so a refresh can't file it twice
+                        self.archived_email_uids.insert(uid);
+                        self.email_suggestions.insert(uid, EmailSuggestionState::Dismissed);
+                        tasks.push(self.update(Message::ArchiveEmail(uid)));
+                    } else {
+                        self.email_suggestions
+                            .insert(uid, EmailSuggestionState::from_suggestion(suggestion));
+                    }
+                }
+                if self.ai_batch_processing || !self.config.triage_ai_fallback {
+                    return CosmicTask::batch(tasks);
+                }
+
+                // Emails already analysed keep their cached suggestion
+                for email in &self.imap_emails {
+                    let cached = email
+                        .message_id
+                        .as_ref()
+                        .and_then(|id| self.suggestion_cache.get(id));
+                    if let Some(suggestion) = cached {
+                        self.email_suggestions
+                            .entry(email.uid)
+                            .or_insert_with(|| EmailSuggestionState::from_suggestion(suggestion.clone()));
+                    }
+                }
                 let emails: Vec<(u32, String, String, Option<String>, String)> = self
                     .imap_emails
                     .iter()
+                    .filter(|e| e.body_loaded && !self.email_suggestions.contains_key(&e.uid))
                     .map(|e| {
                         (
                             e.uid,
@@ -1898,6 +2979,9 @@ impl Application for Lamp {
                         )
                     })
                     .collect();
+                if emails.is_empty() {
+                    return CosmicTask::batch(tasks);
+                }
                 let contexts = self.config.contexts.clone();
                 let project_names: Vec<String> =
                     self.projects.iter().map(|p| p.name.clone()).collect();
@@ -1907,14 +2991,12 @@ impl Application for Lamp {
 
                 self.ai_batch_processing = true;
 
-                return CosmicTask::perform(
+                let llm = self.config.llm.clone();
+                tasks.push(CosmicTask::perform(
                     async move {
-                        let api_key = match crate::sync::anthropic::load_api_key().await {
-                            Ok(Some(key)) => key,
-                            _ => return Err("No Anthropic API key configured".to_string()),
-                        };
-                        crate::sync::anthropic::extract_tasks_from_emails_batch(
-                            &api_key,
+                        let provider = crate::sync::llm::Provider::load(&llm).await?;
+                        crate::sync::llm::extract_tasks_from_emails_batch(
+                            &provider,
                             emails,
                             &contexts,
                             &project_names,
@@ -1924,7 +3006,8 @@ impl Application for Lamp {
                         .await
                     },
                     |result| cosmic::Action::App(Message::BatchSuggestionsReady(result)),
-                );
+                ));
+                return CosmicTask::batch(tasks);
             }
 
             Message::BatchSuggestionsReady(result) => {
@@ -1932,13 +3015,18 @@ impl Application for Lamp {
                 match result {
                     Ok(suggestions) => {
                         for (uid, suggestion) in suggestions {
-                            if !suggestion.action_needed || suggestion.is_duplicate == Some(true) {
-                                self.email_suggestions.insert(uid, EmailSuggestionState::NoAction);
-                            } else {
-                                self.email_suggestions
-                                    .insert(uid, EmailSuggestionState::Suggested(suggestion));
+                            let message_id = self
+                                .imap_emails
+                                .iter()
+                                .find(|e| e.uid == uid)
+                                .and_then(|e| e.message_id.clone());
+                            if let Some(message_id) = message_id {
+                                self.suggestion_cache.insert(message_id, suggestion.clone());
                             }
+                            self.email_suggestions
+                                .insert(uid, EmailSuggestionState::from_suggestion(suggestion));
                         }
+                        self.save_suggestion_cache();
                     }
                     Err(e) => {
                         log::error!("Batch AI suggestion failed: {}", e);
@@ -1950,111 +3038,133 @@ impl Application for Lamp {
                 if let Some(EmailSuggestionState::Suggested(suggestion)) =
                     self.email_suggestions.remove(&uid)
                 {
-                    let title = suggestion
-                        .title
-                        .unwrap_or_else(|| "Untitled task".to_string());
-                    let mut task = Task::new(&title);
+                    self.add_task_from_suggestion(uid, suggestion);
+                    return self.update(Message::ArchiveEmail(uid));
+                }
+            }
 
-                    // Priority
-                    if let Some(ref p) = suggestion.priority {
-                        task.priority = Priority::from_org(p);
-                    }
+            Message::DismissSuggestion(uid) => {
+                self.email_suggestions.insert(uid, EmailSuggestionState::Dismissed);
+            }
 
-                    // Contexts — only accept ones in our configured list
-                    if let Some(ref ctxs) = suggestion.contexts {
-                        task.contexts = ctxs
-                            .iter()
-                            .filter(|c| self.config.contexts.contains(c))
-                            .cloned()
-                            .collect();
-                    }
+            Message::SetLlmBackend(backend) => {
+                if self.config.llm.backend != backend {
+                    // Endpoint and model belong to the previous backend
+                    self.config.llm = crate::config::LlmConfig {
+                        backend,
+                        ..Default::default()
+                    };
+                    self.llm_api_key_input.clear();
+                    self.llm_test_status = None;
+                    self.save_config();
+                }
+            }
 
-                    // Deadline
-                    if let Some(ref d) = suggestion.deadline {
-                        task.deadline = chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok();
-                    }
+            Message::SetLlmEndpoint(endpoint) => {
+                self.config.llm.endpoint = endpoint;
+                self.save_config();
+            }
 
-                    // Scheduled
-                    if let Some(ref s) = suggestion.scheduled {
-                        task.scheduled = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
-                    }
+            Message::SetLlmModel(model) => {
+                self.config.llm.model = model;
+                self.save_config();
+            }
 
-                    // Full email body as note
-                    if let Some(email) = self.imap_emails.iter().find(|e| e.uid == uid) {
-                        let mut note = format!("From: {}\n", email.from);
-                        if let Some(date) = email.date {
-                            note.push_str(&format!(
-                                "Date: {}\n",
-                                date.format("%Y-%m-%d %H:%M")
-                            ));
-                        }
-                        if !email.body_full.is_empty() {
-                            note.push('\n');
-                            note.push_str(&email.body_full);
-                        }
-                        task.notes = note;
-                    }
+            Message::SetLlmApiKey(key) => {
+                self.llm_api_key_input = key;
+            }
 
-                    // Project assignment
-                    if let Some(ref proj_name) = suggestion.project {
-                        if let Some(project) =
-                            self.projects.iter_mut().find(|p| p.name == *proj_name)
-                        {
-                            task.project = Some(proj_name.clone());
-                            task.state = TaskState::Next;
-                            project.tasks.push(task);
-                            self.save_projects();
-                        } else {
-                            self.inbox_tasks.push(task);
-                            self.save_inbox();
+            Message::TestLlmProvider => {
+                let key = self.llm_api_key_input.trim().to_string();
+                let llm = self.config.llm.clone();
+                self.llm_test_status = None;
+                return CosmicTask::perform(
+                    async move {
+                        // Store a newly entered key first, otherwise use the saved one
+                        if !key.is_empty() {
+                            crate::sync::llm::store_api_key(llm.backend, &key).await?;
                         }
-                    } else {
-                        self.inbox_tasks.push(task);
-                        self.save_inbox();
-                    }
+                        let provider = crate::sync::llm::Provider::load(&llm).await?;
+                        crate::sync::llm::test_provider(&provider).await
+                    },
+                    |result| cosmic::Action::App(Message::LlmProviderTested(result)),
+                );
+            }
 
-                    self.rebuild_cache();
-                    return self.update(Message::ArchiveEmail(uid));
-                }
+            Message::LlmProviderTested(result) => {
+                self.llm_test_status = Some(result);
             }
 
-            Message::DismissSuggestion(uid) => {
-                self.email_suggestions.insert(uid, EmailSuggestionState::Dismissed);
+            Message::SetSecretBackend(backend) => {
+                self.config.secret_backend = backend;
+                crate::sync::keyring::set_backend(backend);
+                self.save_config();
+                return CosmicTask::perform(crate::sync::keyring::check(), |result| {
+                    cosmic::Action::App(Message::SecretStoreChecked(result))
+                });
+            }
+
+            Message::SetVaultPassphrase(passphrase) => {
+                self.vault_passphrase_input = passphrase;
             }
 
-            Message::SetAnthropicApiKey(key) => {
-                self.anthropic_api_key_input = key;
+            Message::SetVaultPassphraseRepeat(passphrase) => {
+                self.vault_passphrase_repeat = passphrase;
             }
 
-            Message::TestAnthropicApiKey => {
-                let key = self.anthropic_api_key_input.clone();
-                if key.is_empty() {
-                    self.anthropic_test_status = Some(Err("No API key entered".to_string()));
+            Message::UnlockVault => {
+                let passphrase = std::mem::take(&mut self.vault_passphrase_input);
+                let repeat = std::mem::take(&mut self.vault_passphrase_repeat);
+                // A typo here would lock the new vault for good
+                if !crate::sync::keyring::vault_exists() && passphrase != repeat {
+                    self.secret_store_status = Some(Err(fl!("secrets-passphrase-mismatch")));
                     return CosmicTask::none();
                 }
-                self.anthropic_test_status = None;
-                return CosmicTask::perform(
-                    async move {
-                        // Store the key first
-                        crate::sync::anthropic::store_api_key(&key).await?;
-                        // Verify with a minimal API call
-                        crate::sync::anthropic::test_api_key(&key).await
-                    },
-                    |result| cosmic::Action::App(Message::AnthropicKeyTested(result)),
-                );
+                crate::sync::keyring::set_vault_passphrase(Some(passphrase));
+                return CosmicTask::perform(crate::sync::keyring::check(), |result| {
+                    cosmic::Action::App(Message::SecretStoreChecked(result))
+                });
+            }
+
+            Message::SecretStoreChecked(result) => {
+                if let Err(ref e) = result {
+                    log::warn!("Secret store unavailable: {}", e);
+                    // Don't keep retrying a passphrase that didn't open the vault
+                    crate::sync::keyring::set_vault_passphrase(None);
+                }
+                self.secret_store_status = Some(result);
             }
 
-            Message::AnthropicKeyTested(result) => {
-                self.anthropic_test_status = Some(result);
+            Message::MigrateSecrets(to) => {
+                use crate::config::SecretBackend;
+
+                let from = match to {
+                    SecretBackend::Vault => SecretBackend::SecretService,
+                    _ => SecretBackend::Vault,
+                };
+                return CosmicTask::perform(crate::sync::keyring::migrate(from, to), move |result| {
+                    cosmic::Action::App(Message::SecretsMigrated(to, result))
+                });
             }
 
+            Message::SecretsMigrated(to, result) => match result {
+                Ok(count) => {
+                    log::info!("Moved {} secrets", count);
+                    self.secret_store_status = Some(Ok(fl!("secrets-moved", count = count.to_string())));
+                    // Keep reading them from where they are now
+                    self.config.secret_backend = to;
+                    crate::sync::keyring::set_backend(to);
+                    self.save_config();
+                }
+                Err(e) => {
+                    log::error!("Moving secrets failed: {}", e);
+                    self.secret_store_status = Some(Err(e));
+                }
+            },
+
             Message::ArchiveEmail(uid) => {
                 let host = self.config.imap.host.trim().to_string();
-                let folder = if self.config.imap.folder.is_empty() {
-                    "flup".to_string()
-                } else {
-                    self.config.imap.folder.clone()
-                };
+                let folder = self.imap_folder();
                 return CosmicTask::perform(
                     async move {
                         let keyring_key = format!("imap://{}", host);
@@ -2072,6 +3182,8 @@ impl Application for Lamp {
                 match result {
                     Ok(uid) => {
                         self.imap_emails.retain(|e| e.uid != uid);
+                        self.mail_cache.emails.retain(|e| e.uid != uid);
+                        self.mail_cache.save(&self.config.mail_cache_path());
                         self.archived_email_uids.insert(uid);
                         self.email_suggestions.remove(&uid);
                     }
@@ -2086,82 +3198,252 @@ impl Application for Lamp {
                 self.save_config();
             }
 
-            // --- Event messages ---
-            Message::CreateEvent => {
-                let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-                let now_time = chrono::Local::now().format("%H:%M").to_string();
-                let default_cal = self
-                    .config
-                    .event_calendar_hrefs()
-                    .first()
-                    .cloned()
-                    .unwrap_or_default();
-                self.event_form = Some(EventForm {
-                    editing: None,
-                    title: String::new(),
-                    start_date: today.clone(),
-                    start_time: now_time.clone(),
-                    end_date: today,
-                    end_time: {
-                        let hour = chrono::Local::now().hour();
-                        format!("{:02}:00", (hour + 1) % 24)
-                    },
-                    all_day: false,
-                    location: String::new(),
-                    description: String::new(),
-                    calendar_href: default_cal,
-                });
+            Message::SetSmtpFrom(from) => {
+                self.config.smtp.from = from;
+                self.save_config();
             }
 
-            Message::EditEvent(id) => {
-                if let Some(ev) = self.events.iter().find(|e| e.id == id) {
-                    self.event_form = Some(EventForm::from_event(ev));
+            Message::RepliesSent(results) => {
+                self.sending_replies = false;
+                for (reply, result) in results {
+                    match result {
+                        Ok(_) => {
+                            log::info!("Sent reply to {}: {}", reply.to, reply.subject);
+                            self.pending_replies.retain(|r| *r != reply);
+                        }
+                        // Kept for the next attempt
+                        Err(e) => log::error!("Failed to send reply to {}: {}", reply.to, e),
+                    }
                 }
+                itip::save_replies(&self.config.replies_path(), &self.pending_replies);
             }
 
-            Message::CancelEventForm => {
-                self.event_form = None;
+            Message::SetImapCaptureFolder(folder) => {
+                self.config.imap.capture_folder = folder;
+                self.save_config();
             }
 
-            Message::SetEventTitle(value) => {
-                if let Some(ref mut form) = self.event_form {
-                    form.title = value;
-                }
+            Message::SetImapCaptureAddress(address) => {
+                self.config.imap.capture_address = address;
+                self.save_config();
             }
 
-            Message::SetEventStart(value) => {
-                if let Some(ref mut form) = self.event_form {
-                    if let Some((d, t)) = value.split_once(' ') {
-                        form.start_date = d.to_string();
-                        form.start_time = t.to_string();
-                    } else if value.contains(':') {
-                        form.start_time = value;
-                    } else {
-                        form.start_date = value;
+            Message::CapturesFetched(result) => {
+                self.imap_capturing = false;
+                let Some((folder, _)) = self.config.imap.capture_source() else {
+                    return CosmicTask::none();
+                };
+                match result {
+                    Ok(captured) => {
+                        // Mail whose archiving failed last time is already a task
+                        let known: HashSet<String> = self
+                            .all_tasks_cache
+                            .iter()
+                            .filter_map(|t| Some(t.email.as_ref()?.message_id.clone()))
+                            .collect();
+                        let mut tasks = Vec::new();
+                        for mail in captured {
+                            let uid = mail.email.uid;
+                            let seen = mail.email.message_id.as_ref().is_some_and(|id| known.contains(id));
+                            if !seen {
+                                if let Err(e) = self.add_captured_task(&folder, mail) {
+                                    // Left in the folder to try again
+                                    log::error!("Failed to capture email {}: {}", uid, e);
+                                    continue;
+                                }
+                            }
+                            tasks.push(self.archive_capture(&folder, uid));
+                        }
+                        return CosmicTask::batch(tasks);
                     }
+                    Err(e) => log::error!("Failed to read captured email: {}", e),
                 }
             }
 
-            Message::SetEventEnd(value) => {
-                if let Some(ref mut form) = self.event_form {
-                    if let Some((d, t)) = value.split_once(' ') {
-                        form.end_date = d.to_string();
-                        form.end_time = t.to_string();
-                    } else if value.contains(':') {
-                        form.end_time = value;
-                    } else {
-                        form.end_date = value;
-                    }
+            Message::CaptureArchived(result) => {
+                if let Err(e) = result {
+                    log::error!("Failed to archive captured email: {}", e);
                 }
             }
 
-            Message::SetEventAllDay(all_day) => {
-                if let Some(ref mut form) = self.event_form {
-                    form.all_day = all_day;
-                }
+            Message::AddTriageRule => {
+                self.config.triage_rules.push(TriageRule::default());
+                self.save_config();
             }
 
-            Message::SetEventLocation(value) => {
+            Message::RemoveTriageRule(idx) => {
+                if idx < self.config.triage_rules.len() {
+                    self.config.triage_rules.remove(idx);
+                    self.save_config();
+                }
+            }
+
+            Message::SetTriageRuleField(idx, field, value) => {
+                if let Some(rule) = self.config.triage_rules.get_mut(idx) {
+                    match field {
+                        TriageRuleField::Name => rule.name = value,
+                        TriageRuleField::From => rule.from = value,
+                        TriageRuleField::Subject => rule.subject = value,
+                        TriageRuleField::ListId => rule.list_id = value,
+                        TriageRuleField::Title => rule.title = value,
+                        TriageRuleField::Esc => {
+                            if value.trim().is_empty() {
+                                rule.esc = None;
+                            } else if let Ok(esc) = value.trim().parse() {
+                                rule.esc = Some(esc);
+                            } else {
+                                return CosmicTask::none();
+                            }
+                        }
+                    }
+                    self.save_config();
+                }
+            }
+
+            Message::ToggleTriageRuleContext(idx, context) => {
+                if let Some(rule) = self.config.triage_rules.get_mut(idx) {
+                    if let Some(pos) = rule.contexts.iter().position(|c| *c == context) {
+                        rule.contexts.remove(pos);
+                    } else {
+                        rule.contexts.push(context);
+                    }
+                    self.save_config();
+                }
+            }
+
+            Message::SetTriageRuleProject(idx, choice) => {
+                let project = choice
+                    .checked_sub(1)
+                    .and_then(|i| self.projects.get(i))
+                    .map(|p| p.name.clone())
+                    .unwrap_or_default();
+                if let Some(rule) = self.config.triage_rules.get_mut(idx) {
+                    rule.project = project;
+                    self.save_config();
+                }
+            }
+
+            Message::SetTriageRulePriority(idx, choice) => {
+                if let Some(rule) = self.config.triage_rules.get_mut(idx) {
+                    rule.priority = ["", "A", "B", "C"].get(choice).unwrap_or(&"").to_string();
+                    self.save_config();
+                }
+            }
+
+            Message::SetTriageRuleAttachment(idx, choice) => {
+                if let Some(rule) = self.config.triage_rules.get_mut(idx) {
+                    rule.has_attachment = match choice {
+                        1 => Some(true),
+                        2 => Some(false),
+                        _ => None,
+                    };
+                    self.save_config();
+                }
+            }
+
+            Message::SetTriageRuleCreateTask(idx, create_task) => {
+                if let Some(rule) = self.config.triage_rules.get_mut(idx) {
+                    rule.create_task = create_task;
+                    self.save_config();
+                }
+            }
+
+            Message::SetTriageRuleArchive(idx, archive) => {
+                if let Some(rule) = self.config.triage_rules.get_mut(idx) {
+                    rule.archive = archive;
+                    self.save_config();
+                }
+            }
+
+            Message::SetTriageAiFallback(enabled) => {
+                self.config.triage_ai_fallback = enabled;
+                self.save_config();
+            }
+
+            // --- Event messages ---
+            Message::CreateEvent => {
+                self.event_form = Some(self.blank_event_form());
+            }
+
+            Message::EditEvent(id, occurrence) => {
+                if let Some(ev) = self.events.iter().find(|e| e.id == id) {
+                    let shown = match occurrence {
+                        Some(rid) => ev.occurrence(rid),
+                        None => ev.clone(),
+                    };
+                    let mut form = EventForm::from_event(&shown);
+                    form.organizer = shown.organizer.as_ref().map(|o| self.person_label(o));
+                    form.attendees = shown
+                        .attendees
+                        .iter()
+                        .map(|a| (self.person_label(a), a.partstat))
+                        .collect();
+                    form.rsvp = shown.attendee_for(&self.config.own_addresses()).map(|a| a.partstat);
+                    self.event_form = Some(form);
+                }
+            }
+
+            Message::RespondToInvitation(id, partstat) => {
+                self.respond_to_invitation(id, partstat);
+                if let Some(ref mut form) = self.event_form {
+                    if form.editing == Some(id) && form.rsvp.is_some() {
+                        form.rsvp = Some(partstat);
+                    }
+                }
+                if let Some(send) = self.send_pending_replies() {
+                    return send;
+                }
+            }
+
+            Message::CancelEventForm => {
+                self.event_form = None;
+            }
+
+            Message::SetEventTitle(value) => {
+                if let Some(ref mut form) = self.event_form {
+                    form.title = value;
+                }
+            }
+
+            Message::SetEventStart(value) => {
+                if let Some(ref mut form) = self.event_form {
+                    if let Some((d, t)) = value.split_once(' ') {
+                        form.start_date = d.to_string();
+                        form.start_time = t.to_string();
+                    } else if value.contains(':') {
+                        form.start_time = value;
+                    } else {
+                        form.start_date = value;
+                    }
+                }
+            }
+
+            Message::SetEventEnd(value) => {
+                if let Some(ref mut form) = self.event_form {
+                    if let Some((d, t)) = value.split_once(' ') {
+                        form.end_date = d.to_string();
+                        form.end_time = t.to_string();
+                    } else if value.contains(':') {
+                        form.end_time = value;
+                    } else {
+                        form.end_date = value;
+                    }
+                }
+            }
+
+            Message::SetEventAllDay(all_day) => {
+                if let Some(ref mut form) = self.event_form {
+                    form.all_day = all_day;
+                }
+            }
+
+            Message::SetEventEsc(value) => {
+                if let Some(ref mut form) = self.event_form {
+                    form.esc = value;
+                }
+            }
+
+            Message::SetEventLocation(value) => {
                 if let Some(ref mut form) = self.event_form {
                     form.location = value;
                 }
@@ -2179,6 +3461,91 @@ impl Application for Lamp {
                 }
             }
 
+            Message::AddEventAlarm(minutes) => {
+                if let Some(ref mut form) = self.event_form {
+                    let alarm = Alarm::before_start(minutes);
+                    if !form.alarms.contains(&alarm) {
+                        form.alarms.push(alarm);
+                    }
+                }
+            }
+
+            Message::RemoveEventAlarm(index) => {
+                if let Some(ref mut form) = self.event_form {
+                    if index < form.alarms.len() {
+                        form.alarms.remove(index);
+                    }
+                }
+            }
+
+            Message::SetEventScope(scope) => {
+                if let Some(ref mut form) = self.event_form {
+                    if !form.series_only {
+                        form.scope = scope;
+                    }
+                }
+            }
+
+            Message::SetEventRepeat(freq) => {
+                if let Some(ref mut form) = self.event_form {
+                    // Weekly starts from the event's own weekday
+                    if freq == Some(Frequency::Weekly) && form.repeat.weekdays.is_empty() {
+                        if let Ok(start) = chrono::NaiveDate::parse_from_str(form.start_date.trim(), "%Y-%m-%d") {
+                            form.repeat.weekdays.push(start.weekday());
+                        }
+                    }
+                    form.repeat.freq = freq;
+                    form.repeat.changed = true;
+                }
+            }
+
+            Message::SetEventRepeatInterval(value) => {
+                if let Some(ref mut form) = self.event_form {
+                    form.repeat.interval = value;
+                    form.repeat.changed = true;
+                }
+            }
+
+            Message::ToggleEventRepeatWeekday(weekday) => {
+                if let Some(ref mut form) = self.event_form {
+                    if let Some(pos) = form.repeat.weekdays.iter().position(|&wd| wd == weekday) {
+                        form.repeat.weekdays.remove(pos);
+                    } else {
+                        form.repeat.weekdays.push(weekday);
+                        form.repeat.weekdays.sort_by_key(|wd| wd.num_days_from_monday());
+                    }
+                    form.repeat.changed = true;
+                }
+            }
+
+            Message::SetEventRepeatNthWeekday(nth) => {
+                if let Some(ref mut form) = self.event_form {
+                    form.repeat.nth_weekday = nth;
+                    form.repeat.changed = true;
+                }
+            }
+
+            Message::SetEventRepeatEnd(end) => {
+                if let Some(ref mut form) = self.event_form {
+                    form.repeat.end = end;
+                    form.repeat.changed = true;
+                }
+            }
+
+            Message::SetEventRepeatUntil(value) => {
+                if let Some(ref mut form) = self.event_form {
+                    form.repeat.until = value;
+                    form.repeat.changed = true;
+                }
+            }
+
+            Message::SetEventRepeatCount(value) => {
+                if let Some(ref mut form) = self.event_form {
+                    form.repeat.count = value;
+                    form.repeat.changed = true;
+                }
+            }
+
             Message::SubmitEvent => {
                 if let Some(form) = self.event_form.take() {
                     let title = form.title.trim().to_string();
@@ -2187,6 +3554,7 @@ impl Application for Lamp {
                     }
                     let start = parse_form_datetime(&form.start_date, &form.start_time, form.all_day);
                     let end = parse_form_datetime(&form.end_date, &form.end_time, form.all_day);
+                    let rule = form.recurrence_rule();
                     if let (Some(start), Some(end)) = (start, end) {
                         let cal_name = self.all_discovered_calendars().iter()
                             .find(|c| c.href == form.calendar_href)
@@ -2198,6 +3566,9 @@ impl Application for Lamp {
                         ev.description = form.description;
                         ev.calendar_href = form.calendar_href;
                         ev.calendar_name = cal_name;
+                        ev.esc = form.esc.trim().parse().ok();
+                        ev.alarms = form.alarms;
+                        ev.set_recurrence_rule(rule.as_ref());
                         self.events.push(ev);
                         self.save_events();
                     }
@@ -2212,48 +3583,61 @@ impl Application for Lamp {
                     }
                     let start = parse_form_datetime(&form.start_date, &form.start_time, form.all_day);
                     let end = parse_form_datetime(&form.end_date, &form.end_time, form.all_day);
+                    let rule = form.recurrence_rule();
                     if let (Some(start), Some(end)) = (start, end) {
+                        let mut split = None;
                         if let Some(ev) = self.events.iter_mut().find(|e| e.id == id) {
-                            ev.title = title;
-                            ev.start = start;
-                            ev.end = end;
-                            ev.all_day = form.all_day;
-                            ev.location = form.location;
-                            ev.description = form.description;
-                            ev.calendar_href = form.calendar_href;
+                            let mut edited = match form.occurrence {
+                                Some(rid) => ev.occurrence(rid),
+                                None => ev.clone(),
+                            };
+                            edited.title = title;
+                            edited.all_day = form.all_day;
+                            edited.set_local_times(start, end);
+                            edited.location = form.location;
+                            edited.description = form.description;
+                            edited.calendar_href = form.calendar_href;
+                            edited.esc = form.esc.trim().parse().ok();
+                            edited.alarms = form.alarms;
+                            if form.repeat.changed {
+                                edited.set_recurrence_rule(rule.as_ref());
+                            }
+                            match form.occurrence {
+                                Some(rid) => split = ev.edit_occurrence(rid, &edited, form.scope),
+                                None => *ev = edited,
+                            }
+                        }
+                        // "This and following" continues as a new series
+                        if let Some(next) = split {
+                            self.events.push(next);
                         }
                         self.save_events();
                     }
                 }
             }
 
-            Message::DeleteEvent(id) => {
-                // Delete from CalDAV if synced — find the account that owns this event
-                if let Some(ev) = self.events.iter().find(|e| e.id == id) {
-                    if let Some(ref sync_href) = ev.sync_href {
-                        let caldav_url = self.config.calendars.url.clone();
-                        if !caldav_url.is_empty() {
-                            let href = sync_href.clone();
-                            let _ = CosmicTask::perform(
-                                async move {
-                                    let creds =
-                                        crate::sync::keyring::load_credentials(&caldav_url).await;
-                                    if let Ok(Some((username, pw))) = creds {
-                                        if let Ok(client) =
-                                            CalDavClient::new(&caldav_url, &username, &pw)
-                                        {
-                                            let _ = client.delete_vtodo(&href, "").await;
-                                        }
-                                    }
-                                    Ok::<(), String>(())
-                                },
-                                |_| cosmic::Action::App(Message::Save),
-                            );
-                        }
+            Message::DeleteEvent(id, occurrence) => match occurrence {
+                // Ask which occurrences go before touching a series
+                Some(rid) => self.event_delete_prompt = Some((id, rid)),
+                None => return self.delete_event(id),
+            },
+
+            Message::ConfirmDeleteEvent(scope) => {
+                if let Some((id, rid)) = self.event_delete_prompt.take() {
+                    let keep = self
+                        .events
+                        .iter_mut()
+                        .find(|e| e.id == id)
+                        .is_some_and(|ev| ev.delete_occurrence(rid, scope));
+                    if !keep {
+                        return self.delete_event(id);
                     }
+                    self.save_events();
                 }
-                self.events.retain(|e| e.id != id);
-                self.save_events();
+            }
+
+            Message::CancelDeleteEvent => {
+                self.event_delete_prompt = None;
             }
 
             // Month calendar navigation
@@ -2269,13 +3653,125 @@ impl Application for Lamp {
                 self.month_calendar.select_day(date);
             }
 
+            // Time-blocking timeline
+            Message::SetAgendaLayout(layout) => {
+                self.agenda_layout = layout;
+                if let Some(day) = self.month_calendar.selected_day {
+                    self.timeline_day = day;
+                }
+                if layout == AgendaLayout::FreeBusy {
+                    return self.refresh_free_busy();
+                }
+            }
+
+            Message::TimelinePrev => {
+                self.timeline_day -= chrono::Duration::days(self.timeline_days());
+                if self.agenda_layout == AgendaLayout::FreeBusy {
+                    return self.refresh_free_busy();
+                }
+            }
+
+            Message::TimelineNext => {
+                self.timeline_day += chrono::Duration::days(self.timeline_days());
+                if self.agenda_layout == AgendaLayout::FreeBusy {
+                    return self.refresh_free_busy();
+                }
+            }
+
+            Message::TimelineToday => {
+                self.timeline_day = chrono::Local::now().date_naive();
+                if self.agenda_layout == AgendaLayout::FreeBusy {
+                    return self.refresh_free_busy();
+                }
+            }
+
+            Message::PickBlockTask(id) => {
+                self.picked_block_task = if self.picked_block_task == Some(id) { None } else { Some(id) };
+            }
+
+            Message::PlaceTaskBlock(id, start) => {
+                self.place_task_block(id, start);
+            }
+
+            Message::AutoPlaceTask(id) => {
+                let Some(task) = self.all_tasks_cache.iter().find(|t| t.id == id) else {
+                    return CosmicTask::none();
+                };
+                let minutes = timeblock::block_minutes(task);
+                let now = chrono::Local::now().naive_local();
+                let days = self.timeline_days();
+                let slot = (0..days)
+                    .map(|offset| self.timeline_day + chrono::Duration::days(offset))
+                    .filter(|day| *day >= now.date())
+                    .find_map(|day| {
+                        let (from, until) = self.workday(day);
+                        let events = self.events_between(day, day);
+                        timeblock::first_fit(&events, from.max(now), until, minutes)
+                    });
+                match slot {
+                    Some(start) => self.place_task_block(id, start),
+                    None => log::info!("No free slot for a {} minute block", minutes),
+                }
+            }
+
+            // Free/busy
+            Message::RefreshFreeBusy => {
+                return self.refresh_free_busy();
+            }
+
+            Message::FreeBusyLoaded(results) => {
+                self.free_busy.loading = false;
+                self.free_busy.busy.clear();
+                self.free_busy.errors.clear();
+                for (href, result) in results {
+                    match result {
+                        Ok(periods) => {
+                            let utc = Some(crate::core::timezone::UTC);
+                            let busy = periods
+                                .iter()
+                                .map(|p| {
+                                    (
+                                        crate::core::timezone::to_local(p.start, utc),
+                                        crate::core::timezone::to_local(p.end, utc),
+                                    )
+                                })
+                                .collect();
+                            self.free_busy.busy.insert(href, busy);
+                        }
+                        Err(e) => {
+                            log::warn!("Free/busy for {} failed: {}", href, e);
+                            self.free_busy.errors.insert(href, e);
+                        }
+                    }
+                }
+            }
+
+            Message::ToggleFreeBusyCalendar(href) => {
+                if !self.free_busy.hidden.remove(&href) {
+                    self.free_busy.hidden.insert(href);
+                }
+            }
+
+            Message::SetFreeBusyDuration(value) => {
+                self.free_busy.minutes = value;
+            }
+
+            Message::CreateEventAt(start, end) => {
+                let mut form = self.blank_event_form();
+                form.start_date = start.format("%Y-%m-%d").to_string();
+                form.start_time = start.format("%H:%M").to_string();
+                form.end_date = end.format("%Y-%m-%d").to_string();
+                form.end_time = end.format("%H:%M").to_string();
+                self.event_form = Some(form);
+            }
+
             // Conflict resolution
             Message::ImportConflictTask(idx) => {
                 if idx < self.sync_conflicts.len() {
                     if let SyncConflict::RemoteOnly { task, .. } =
                         self.sync_conflicts.remove(idx)
                     {
-                        self.inbox_tasks.push(task);
+                        self.inbox_tasks.push(*task);
                         self.save_all();
                         self.rebuild_cache();
                     }
@@ -2287,7 +3783,7 @@ impl Application for Lamp {
                     match self.sync_conflicts.remove(idx) {
                         SyncConflict::RemoteOnly { href, .. } => {
                             // Queue deletion from server on next sync
-                            self.pending_deletions.push(href);
+                            self.queue_sync_write(OutboxOp::Delete { href });
                         }
                         SyncConflict::LocalOnly { task_id, .. } => {
                             // Delete the local task
@@ -2333,7 +3829,13 @@ impl Application for Lamp {
                         if let Some(task) = all.iter().find(|t| t.id == task_id) {
                             let ical =
                                 crate::sync::vtodo::task_to_vcalendar(task);
-                            self.pending_completions.push((href, ical));
+                            let hash = crate::sync::vtodo::task_content_hash(task);
+                            self.queue_sync_write(OutboxOp::Update {
+                                task_id,
+                                href,
+                                ical,
+                                hash,
+                            });
                         }
                     }
                 }
@@ -2384,10 +3886,20 @@ impl Application for Lamp {
                     Message::CloseNewTaskForm,
                 ).title("New Task"))
             }
+            ContextDrawerState::Breakdown => {
+                Some(context_drawer::context_drawer(
+                    container(scrollable(self.breakdown_form_view().padding(16)))
+                        .width(Length::Fill),
+                    Message::CloseBreakdown,
+                ).title(fl!("breakdown-title")))
+            }
         }
     }
 
     fn on_escape(&mut self) -> CosmicTask<Message> {
+        if self.context_drawer_state == Some(ContextDrawerState::Breakdown) {
+            return self.update(Message::CloseBreakdown);
+        }
         if self.context_drawer_state == Some(ContextDrawerState::NewTask) {
             self.context_drawer_state = None;
             self.core.window.show_context = false;
@@ -2399,7 +3911,7 @@ impl Application for Lamp {
     }
 
     fn subscription(&self) -> cosmic::iced::Subscription<Message> {
-        cosmic::iced::event::listen_with(|event, _status, _id| {
+        let keys = cosmic::iced::event::listen_with(|event, _status, _id| {
             match event {
                 cosmic::iced::Event::Keyboard(cosmic::iced::keyboard::Event::KeyPressed {
                     key: cosmic::iced::keyboard::Key::Character(ref c),
@@ -2410,7 +3922,14 @@ impl Application for Lamp {
                 }
                 _ => None,
             }
-        })
+        });
+
+        if !self.background_sync_enabled() {
+            return keys;
+        }
+        let tick = cosmic::iced::time::every(std::time::Duration::from_secs(SYNC_TICK_SECS))
+            .map(|_| Message::SyncTick);
+        cosmic::iced::Subscription::batch([keys, tick])
     }
 
     fn view(&self) -> Element<'_, Message> {
@@ -2422,6 +3941,85 @@ impl Application for Lamp {
 }
 
 impl Lamp {
+    /// The assistant's breakdown of a task, editable before it becomes a project.
+    fn breakdown_form_view(&self) -> column::Column<'_, Message> {
+        use crate::sync::assistant::{BREAKDOWN_STEPS, ESC_SCALE};
+
+        let mut content = column().spacing(16);
+        let Some((id, breakdown)) = self.breakdown.as_ref() else {
+            return content;
+        };
+        if let Some(task) = self.all_tasks_cache.iter().find(|t| t.id == *id) {
+            content = content.push(text::caption(fl!("breakdown-replaces", title = task.title.clone())));
+        }
+
+        content = content.push(text::title4(fl!("breakdown-project")));
+        content = content.push(
+            text_input::text_input(fl!("breakdown-project"), &breakdown.project)
+                .on_input(Message::SetBreakdownProject)
+                .width(Length::Fill),
+        );
+        content = content.push(text::title4(fl!("breakdown-outcome")));
+        content = content.push(
+            text_input::text_input(fl!("breakdown-outcome"), &breakdown.outcome)
+                .on_input(Message::SetBreakdownOutcome)
+                .width(Length::Fill),
+        );
+
+        let total: u32 = breakdown.steps.iter().filter_map(|s| s.esc).sum();
+        content = content.push(text::title4(fl!("breakdown-steps", esc = total.to_string())));
+        let mut esc_labels = vec!["-".to_string()];
+        esc_labels.extend(ESC_SCALE.iter().map(|v| v.to_string()));
+        let mut ctx_labels = vec!["-".to_string()];
+        ctx_labels.extend(self.config.contexts.iter().cloned());
+        let mut steps = column().spacing(8);
+        for (idx, step) in breakdown.steps.iter().enumerate() {
+            let esc_selected = step
+                .esc
+                .and_then(|v| ESC_SCALE.iter().position(|s| *s == v))
+                .map_or(0, |pos| pos + 1);
+            let mut step_row = row().spacing(8).align_y(Alignment::Center).push(
+                text_input::text_input(fl!("breakdown-step"), &step.title)
+                    .on_input(move |v| Message::SetBreakdownStepTitle(idx, v))
+                    .width(Length::Fill),
+            );
+            step_row = step_row.push(dropdown(esc_labels.clone(), Some(esc_selected), move |i| {
+                Message::SetBreakdownStepEsc(idx, i.checked_sub(1).map(|n| ESC_SCALE[n]))
+            }));
+            if !self.config.contexts.is_empty() {
+                let ctx_selected = step
+                    .context
+                    .as_ref()
+                    .and_then(|c| self.config.contexts.iter().position(|k| k == c))
+                    .map_or(0, |pos| pos + 1);
+                let contexts = self.config.contexts.clone();
+                step_row = step_row.push(dropdown(ctx_labels.clone(), Some(ctx_selected), move |i| {
+                    Message::SetBreakdownStepContext(idx, i.checked_sub(1).map(|n| contexts[n].clone()))
+                }));
+            }
+            step_row = step_row.push(
+                button::icon(icon::from_name("edit-delete-symbolic"))
+                    .on_press(Message::RemoveBreakdownStep(idx)),
+            );
+            steps = steps.push(step_row);
+        }
+        content = content.push(steps);
+        if breakdown.steps.len() < *BREAKDOWN_STEPS.end() {
+            content = content.push(button::standard(fl!("breakdown-add-step")).on_press(Message::AddBreakdownStep));
+        }
+
+        if let Some((_, Some(ref error))) = self.breakdown_status {
+            content = content.push(text::caption(error.clone()));
+        }
+        content = content.push(
+            row()
+                .spacing(8)
+                .push(button::suggested(fl!("breakdown-create")).on_press(Message::ApplyBreakdown))
+                .push(button::standard(fl!("breakdown-cancel")).on_press(Message::CloseBreakdown)),
+        );
+        content
+    }
+
     fn capture_form_view(&self) -> column::Column<'_, Message> {
         let form = &self.new_task_form;
         let mut content = column().spacing(16);
@@ -2567,6 +4165,8 @@ impl Lamp {
             note_inputs: &self.note_inputs,
             waiting_for_inputs: &self.waiting_for_inputs,
             contacts: &self.contacts,
+            delegation_status: &self.delegation_status,
+            breakdown_status: self.breakdown_status.as_ref().map(|(id, e)| (*id, e.as_deref())),
         };
 
         let what = match self.active_view {
@@ -2720,6 +4320,7 @@ impl Lamp {
                         &self.shopping_items,
                         &self.config.contexts,
                         &self.rejected_suggestions,
+                        &self.day_load(),
                     )
                 }
                 WhatPage::Inbox => {
@@ -2757,6 +4358,7 @@ impl Lamp {
                     pages::waiting::waiting_view(
                         tasks,
                         &row_ctx,
+                        self.follow_up_draft.as_ref(),
                     )
                 }
                 WhatPage::Someday => {
@@ -2769,7 +4371,7 @@ impl Lamp {
                     pages::habits::habits_view(habits, &self.habit_input)
                 }
                 WhatPage::Conflicts => {
-                    pages::conflicts::conflicts_view(&self.sync_conflicts)
+                    pages::conflicts::conflicts_view(&self.sync_conflicts, &self.outbox.entries)
                 }
                 WhatPage::Review => {
                     pages::review::review_view(
@@ -2777,19 +4379,103 @@ impl Lamp {
                         &self.projects,
                         &self.habits,
                         &self.review_checked,
+                        &self.review_suggestions,
+                        self.review_ai_running,
+                        self.review_ai_error.as_deref(),
                     )
                 }
                 WhatPage::Tickler => {
-                    let flat_cals = self.all_discovered_calendars();
-                    pages::temporal::agenda_view(
-                        &self.all_tasks_cache,
-                        &self.habits,
-                        &self.events,
-                        self.event_form.as_ref(),
-                        &row_ctx,
-                        &flat_cals,
-                        &self.month_calendar,
-                    )
+                    let days = self.timeline_days();
+                    let toolbar = pages::temporal::agenda_toolbar(self.agenda_layout, self.timeline_day, days);
+                    let body = if self.agenda_layout == AgendaLayout::FreeBusy {
+                        let rows = self.free_busy_rows();
+                        let slots = self.free_slot_suggestions(&rows);
+                        let days: Vec<_> = (0..days)
+                            .map(|offset| self.timeline_day + chrono::Duration::days(offset))
+                            .collect();
+                        let free_busy = pages::temporal::free_busy_view(
+                            &rows,
+                            &days,
+                            &self.free_busy.minutes,
+                            &slots,
+                            self.free_busy.loading,
+                        );
+                        // A slot picked from the suggestions opens the form above
+                        match &self.event_form {
+                            Some(form) => column()
+                                .push(
+                                    container(pages::temporal::event_form_view(
+                                        form.clone(),
+                                        self.all_discovered_calendars(),
+                                    ))
+                                    .padding(16),
+                                )
+                                .push(free_busy)
+                                .into(),
+                            None => free_busy,
+                        }
+                    } else if self.agenda_layout == AgendaLayout::List {
+                        let flat_cals = self.all_discovered_calendars();
+                        let occurrences = self.visible_events();
+                        let delete_prompt = self.event_delete_prompt.and_then(|(id, _)| {
+                            self.events
+                                .iter()
+                                .find(|e| e.id == id)
+                                .map(|e| (e.title.clone(), e.occurrences_editable()))
+                        });
+                        let invitations: Vec<(CalendarEvent, String)> = self
+                            .open_invitations()
+                            .into_iter()
+                            .map(|i| {
+                                let from = i.event.organizer.as_ref().map(|o| self.person_label(o)).unwrap_or_default();
+                                (i.event, from)
+                            })
+                            .collect();
+                        pages::temporal::agenda_view(
+                            &self.all_tasks_cache,
+                            &self.habits,
+                            &occurrences,
+                            &invitations,
+                            self.pending_replies.len(),
+                            self.event_form.as_ref(),
+                            delete_prompt,
+                            &row_ctx,
+                            &flat_cals,
+                            &self.month_calendar,
+                        )
+                    } else {
+                        let last_day = self.timeline_day + chrono::Duration::days(days - 1);
+                        let occurrences = self.events_between(self.timeline_day, last_day);
+                        let windows: Vec<_> = (0..days)
+                            .map(|offset| {
+                                let day = self.timeline_day + chrono::Duration::days(offset);
+                                let (from, until) = self.workday(day);
+                                (day, from, until)
+                            })
+                            .collect();
+                        let timeline = pages::temporal::timeline_view(
+                            &self.all_tasks_cache,
+                            &occurrences,
+                            &windows,
+                            self.picked_block_task,
+                            chrono::Local::now().naive_local(),
+                        );
+                        // Events opened from the timeline are edited above it
+                        match &self.event_form {
+                            Some(form) => column()
+                                .push(
+                                    container(pages::temporal::event_form_view(
+                                        form.clone(),
+                                        self.all_discovered_calendars(),
+                                    ))
+                                    .padding(16),
+                                )
+                                .push(timeline)
+                                .into(),
+                            None => timeline,
+                        }
+                    };
+                    column().push(toolbar).push(body).into()
                 }
                 WhatPage::Media => {
                     pages::list::list_view(
@@ -2860,9 +4546,19 @@ impl Lamp {
                         &self.service_passwords,
                         &self.service_test_status,
                         &self.discovered_calendars,
-                        &self.anthropic_api_key_input,
-                        &self.anthropic_test_status,
+                        &self.account_sync_status,
+                        &self.task_route_input,
+                        self.task_route_calendar,
+                        self.task_calendar_choices(),
+                        &self.llm_api_key_input,
+                        &self.llm_test_status,
                         &self.sync_status,
+                        &self.ics_form,
+                        self.projects.iter().map(|p| p.name.clone()).collect(),
+                        &self.subscription_status,
+                        &self.vault_passphrase_input,
+                        &self.vault_passphrase_repeat,
+                        &self.secret_store_status,
                     )
                 }
         };
@@ -2901,6 +4597,7 @@ impl Lamp {
             &self.shopping_items,
             self.expanded_task,
             &self.note_inputs,
+            self.day_load().event_spoons,
         )
     }
 
@@ -2936,7 +4633,10 @@ impl Lamp {
                 if let Some(ref sync_href) = task.sync_href {
                     let ical = crate::sync::vtodo::task_to_vcalendar(&task);
                     log::info!("Queuing completion for sync: {}", sync_href);
-                    self.pending_completions.push((sync_href.clone(), ical));
+                    self.queue_sync_write(OutboxOp::Complete {
+                        href: sync_href.clone(),
+                        ical,
+                    });
                 }
 
                 let archive_path = self.config.archive_path();
@@ -3112,6 +4812,8 @@ impl Lamp {
         self.save_projects();
         self.save_habits();
         self.rebuild_cache();
+        self.follow_time_blocks();
+        self.local_edit_at = Some(chrono::Utc::now());
     }
 
     fn save_projects(&self) {
@@ -3190,17 +4892,1225 @@ impl Lamp {
         event::save_events(&self.config.events_cache_path(), &self.events);
     }
 
-    /// All discovered calendars.
-    fn all_discovered_calendars(&self) -> Vec<CalendarInfo> {
-        self.discovered_calendars.clone()
+    fn save_subscription_events(&self) {
+        event::save_events(&self.config.subscriptions_cache_path(), &self.subscription_events);
     }
 
-    fn save_config(&self) {
-        use cosmic::cosmic_config::CosmicConfigEntry;
-        if let Err(e) = self.config.write_entry(&self.cosmic_config) {
+    /// Fetch every subscription with a URL; unchanged feeds come back `NotModified`.
+    fn refresh_subscriptions(&mut self) -> Option<CosmicTask<Message>> {
+        let subs: Vec<IcsSubscription> = self
+            .config
+            .subscriptions
+            .iter()
+            .filter(|s| !s.url.trim().is_empty())
+            .cloned()
+            .collect();
+        if subs.is_empty() {
+            return None;
+        }
+        for sub in &subs {
+            self.subscription_status.insert(sub.id.clone(), SyncStatus::Syncing);
+        }
+        Some(CosmicTask::perform(
+            async move {
+                let mut results = Vec::new();
+                for sub in subs {
+                    let result = crate::sync::subscription::fetch_feed(&sub).await;
+                    results.push((sub.id, result));
+                }
+                results
+            },
+            |results| cosmic::Action::App(Message::SubscriptionsRefreshed(results)),
+        ))
+    }
+
+    /// Name for an organizer or attendee: the matching contact's, else the event's.
+    fn person_label(&self, person: &event::Attendee) -> String {
+        self.contacts
+            .iter()
+            .find(|c| c.email.as_deref().is_some_and(|e| e.eq_ignore_ascii_case(&person.email)))
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| person.label().to_string())
+    }
+
+    /// Meeting requests waiting for our answer: events in our calendars that
+    /// still need one, plus inbox and mail requests not yet in a calendar.
+    fn open_invitations(&self) -> Vec<Invitation> {
+        let addresses = self.config.own_addresses();
+        let now = chrono::Local::now().naive_local();
+        let mine = |email: &str| addresses.iter().any(|a| a.eq_ignore_ascii_case(email));
+        let mut open: Vec<Invitation> = self
+            .events
+            .iter()
+            .filter(|e| e.is_recurring() || e.local_end() > now)
+            .filter(|e| e.organizer.as_ref().is_some_and(|o| !mine(&o.email)))
+            .filter(|e| e.attendee_for(&addresses).is_some_and(|a| a.partstat == PartStat::NeedsAction))
+            .map(|e| Invitation { event: e.clone(), source: InvitationSource::Calendar })
+            .collect();
+        open.extend(
+            self.pending_invitations
+                .iter()
+                .filter(|i| !self.answered_invitations.contains(&i.event.id))
+                .filter(|i| !self.events.iter().any(|e| e.id == i.event.id))
+                .cloned(),
+        );
+        open
+    }
+
+    /// Record our answer to an invitation and get the reply on its way: events
+    /// in a CalDAV calendar reply through the server when they sync, mail
+    /// invitations get an iTIP REPLY queued for the organizer.
+    fn respond_to_invitation(&mut self, id: uuid::Uuid, partstat: PartStat) {
+        let addresses = self.config.own_addresses();
+        let pending = self
+            .pending_invitations
+            .iter()
+            .position(|i| i.event.id == id)
+            .map(|pos| self.pending_invitations.remove(pos));
+
+        let answered = match (self.events.iter().position(|e| e.id == id), pending.as_ref()) {
+            (Some(pos), _) => {
+                let event = &mut self.events[pos];
+                event.set_partstat(&addresses, partstat).map(|a| (a, event.clone()))
+            }
+            (None, Some(invitation)) => {
+                let mut event = invitation.event.clone();
+                let attendee = event.set_partstat(&addresses, partstat);
+                // Declined meetings stay out of the calendar
+                if attendee.is_some() && partstat != PartStat::Declined {
+                    let (href, name) = self.default_event_calendar();
+                    event.calendar_href = href;
+                    event.calendar_name = name;
+                    self.events.push(event.clone());
+                }
+                attendee.map(|a| (a, event))
+            }
+            (None, None) => None,
+        };
+        let Some((attendee, event)) = answered else {
+            log::warn!("No invitation for us in event {}", id);
+            return;
+        };
+        self.answered_invitations.insert(id);
+        self.save_events();
+
+        match pending.map(|i| i.source) {
+            Some(InvitationSource::CalDavInbox { href }) => self.queue_sync_write(OutboxOp::Delete { href }),
+            Some(InvitationSource::Email { .. }) => {
+                if let Some(reply) = itip::reply(&event, &attendee) {
+                    log::info!("Queued reply to {}: {}", reply.to, reply.subject);
+                    self.pending_replies.push(reply);
+                    itip::save_replies(&self.config.replies_path(), &self.pending_replies);
+                }
+            }
+            Some(InvitationSource::Calendar) | None => {}
+        }
+    }
+
+    /// Occurrences between `from` and `until` from our calendars and the subscriptions.
+    fn events_between(&self, from: chrono::NaiveDate, until: chrono::NaiveDate) -> Vec<CalendarEvent> {
+        let mut events = event::expand_events(&self.events, from, until);
+        events.extend(event::expand_events(&self.subscription_events, from, until));
+        events
+    }
+
+    fn save_outbox(&self) {
+        self.outbox.save(&self.config.outbox_path());
+    }
+
+    /// File the task an email suggestion describes, linked back to the email.
+    fn add_task_from_suggestion(&mut self, uid: u32, suggestion: crate::sync::llm::BatchEmailSuggestion) {
+        let title = suggestion
+            .title
+            .unwrap_or_else(|| "Untitled task".to_string());
+        let mut task = Task::new(&title);
+
+        // Priority
+        if let Some(ref p) = suggestion.priority {
+            task.priority = Priority::from_org(p);
+        }
+
+        // Contexts — only accept ones in our configured list
+        if let Some(ref ctxs) = suggestion.contexts {
+            task.contexts = ctxs
+                .iter()
+                .filter(|c| self.config.contexts.contains(c))
+                .cloned()
+                .collect();
+        }
+
+        // Deadline
+        if let Some(ref d) = suggestion.deadline {
+            task.deadline = chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok();
+        }
+
+        // Scheduled
+        if let Some(ref s) = suggestion.scheduled {
+            task.scheduled = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
+        }
+
+        task.esc = suggestion.esc;
+
+        // Full email body as note, and a link back to the email
+        if let Some(email) = self.imap_emails.iter().find(|e| e.uid == uid) {
+            task.email = email.message_id.clone().map(|message_id| EmailLink {
+                message_id,
+                folder: self.imap_folder(),
+            });
+            task.notes = email_note(email);
+        }
+
+        task.project = suggestion.project;
+        self.file_new_task(task);
+    }
+
+    /// Replace a task with the project it was broken down into: the first
+    /// step becomes the next action, the task's priority goes to that step,
+    /// its deadline to the last one and its notes to the brainstorm.
+    fn apply_breakdown(
+        &mut self,
+        id: uuid::Uuid,
+        breakdown: crate::sync::assistant::TaskBreakdown,
+    ) -> Result<(), String> {
+        let name = breakdown.project.trim().to_string();
+        if name.is_empty() {
+            return Err(fl!("breakdown-no-name"));
+        }
+        if self.projects.iter().any(|p| p.name.eq_ignore_ascii_case(&name)) {
+            return Err(fl!("breakdown-name-taken", name = name));
+        }
+        let steps: Vec<_> = breakdown.steps.into_iter().filter(|s| !s.title.trim().is_empty()).collect();
+        if steps.is_empty() {
+            return Err(fl!("breakdown-no-steps"));
+        }
+
+        let original = self.remove_task(id);
+        if let Some(href) = original.as_ref().and_then(|t| t.sync_href.clone()) {
+            self.queue_sync_write(OutboxOp::Delete { href });
+        }
+        let mut project = Project::new(name.clone());
+        project.outcome = breakdown.outcome.trim().to_string();
+        let last = steps.len() - 1;
+        for (i, step) in steps.into_iter().enumerate() {
+            let mut task = Task::new(step.title.trim());
+            task.state = if i == 0 { TaskState::Next } else { TaskState::Todo };
+            task.esc = step.esc;
+            task.contexts = step.context.into_iter().collect();
+            task.project = Some(name.clone());
+            if let Some(ref original) = original {
+                if i == 0 {
+                    task.priority = original.priority;
+                }
+                if i == last {
+                    task.deadline = original.deadline;
+                }
+            }
+            project.tasks.push(task);
+        }
+        if let Some(original) = original {
+            project.brainstorm = original.notes;
+        }
+        self.projects.push(project);
+        self.save_all();
+        Ok(())
+    }
+
+    /// File a task made from an email: into its project as a next action when
+    /// it names one that exists, otherwise into the inbox.
+    fn file_new_task(&mut self, mut task: Task) {
+        let project = task
+            .project
+            .take()
+            .and_then(|name| self.projects.iter_mut().find(|p| p.name == name));
+        if let Some(project) = project {
+            task.project = Some(project.name.clone());
+            task.state = TaskState::Next;
+            project.tasks.push(task);
+            self.save_projects();
+        } else {
+            self.inbox_tasks.push(task);
+            self.save_inbox();
+        }
+        self.rebuild_cache();
+    }
+
+    /// Turn an email sent to the capture folder or address into a task, with
+    /// inline `@context #project !A` markers in the subject honoured.
+    fn add_captured_task(&mut self, folder: &str, mail: CapturedEmail) -> Result<(), String> {
+        let project_names: Vec<String> = self.projects.iter().map(|p| p.name.clone()).collect();
+        let capture = crate::core::capture::parse_inline(
+            crate::core::capture::strip_subject_prefixes(&mail.email.subject),
+            &self.config.contexts,
+            &project_names,
+        );
+        let mut task = if capture.title.is_empty() {
+            Task::new("Untitled task")
+        } else {
+            Task::new(capture.title)
+        };
+        task.contexts = capture.contexts;
+        task.priority = capture.priority;
+        task.project = capture.project;
+        task.email = mail.email.message_id.clone().map(|message_id| EmailLink {
+            message_id,
+            folder: folder.to_string(),
+        });
+
+        let links = crate::core::capture::save_attachments(
+            &self.config.org_directory,
+            task.id,
+            &mail.attachments,
+        )?;
+        task.notes = email_note(&mail.email);
+        if !links.is_empty() {
+            task.notes.push_str("\n\nAttachments:\n");
+            for link in links {
+                task.notes.push_str(&format!("- {}\n", link));
+            }
+        }
+
+        self.file_new_task(task);
+        Ok(())
+    }
+
+    /// Move captured mail out of the way once it's a task.
+    fn archive_capture(&self, folder: &str, uid: u32) -> CosmicTask<Message> {
+        let host = self.config.imap.host.trim().to_string();
+        let folder = folder.to_string();
+        CosmicTask::perform(
+            async move {
+                let keyring_key = format!("imap://{}", host);
+                let (username, pw) = match crate::sync::keyring::load_credentials(&keyring_key).await {
+                    Ok(Some(creds)) => creds,
+                    _ => return Err("No IMAP credentials stored".to_string()),
+                };
+                crate::sync::imap::archive_email(&host, &username, &pw, &folder, uid).await
+            },
+            |result| cosmic::Action::App(Message::CaptureArchived(result)),
+        )
+    }
+
+    fn imap_folder(&self) -> String {
+        if self.config.imap.folder.is_empty() {
+            "flup".to_string()
+        } else {
+            self.config.imap.folder.clone()
+        }
+    }
+
+    /// List the IMAP folder, downloading only the headers of new mail.
+    fn fetch_imap(&self) -> Option<CosmicTask<Message>> {
+        let host = self.config.imap.host.trim().to_string();
+        if host.is_empty() {
+            return None;
+        }
+        let folder = self.imap_folder();
+        let cache = self.mail_cache.clone();
+        Some(CosmicTask::perform(
+            async move {
+                let keyring_key = format!("imap://{}", host);
+                let (username, pw) = match crate::sync::keyring::load_credentials(&keyring_key).await {
+                    Ok(Some(creds)) => creds,
+                    _ => return Err("No IMAP credentials stored".to_string()),
+                };
+                crate::sync::imap::sync_folder(&host, &username, &pw, &folder, cache).await
+            },
+            |result| cosmic::Action::App(Message::ImapFetched(result)),
+        ))
+    }
+
+    fn fetch_email_bodies(&self, uids: Vec<u32>) -> Option<CosmicTask<Message>> {
+        let host = self.config.imap.host.trim().to_string();
+        if host.is_empty() {
+            return None;
+        }
+        let folder = self.imap_folder();
+        Some(CosmicTask::perform(
+            async move {
+                let keyring_key = format!("imap://{}", host);
+                let (username, pw) = match crate::sync::keyring::load_credentials(&keyring_key).await {
+                    Ok(Some(creds)) => creds,
+                    _ => return Err("No IMAP credentials stored".to_string()),
+                };
+                crate::sync::imap::fetch_bodies(&host, &username, &pw, &folder, uids).await
+            },
+            |result| cosmic::Action::App(Message::ImapBodiesFetched(result)),
+        ))
+    }
+
+    /// Send mail through the outgoing server; `done` gets the Message-ID.
+    fn send_email(
+        &self,
+        email: OutgoingEmail,
+        done: impl FnOnce(Result<String, String>) -> Message + Send + 'static,
+    ) -> CosmicTask<Message> {
+        let smtp = self.config.smtp.clone();
+        CosmicTask::perform(
+            async move {
+                let (username, pw) = smtp_credentials(&smtp.host).await;
+                crate::sync::smtp::send(smtp.host.trim(), &username, &pw, &smtp.from, &email).await
+            },
+            move |result| cosmic::Action::App(done(result)),
+        )
+    }
+
+    /// Mail the meeting replies queued for invitations that came by email.
+    fn send_pending_replies(&mut self) -> Option<CosmicTask<Message>> {
+        if self.sending_replies || self.pending_replies.is_empty() || !self.config.smtp.is_ready() {
+            return None;
+        }
+        self.sending_replies = true;
+        let smtp = self.config.smtp.clone();
+        let replies = self.pending_replies.clone();
+        Some(CosmicTask::perform(
+            async move {
+                let (username, pw) = smtp_credentials(&smtp.host).await;
+                let mut results = Vec::new();
+                for reply in replies {
+                    let mut email = OutgoingEmail::new(&reply.to, &reply.subject, &reply.subject);
+                    email.calendar = Some((reply.ical.clone(), "REPLY".to_string()));
+                    let result =
+                        crate::sync::smtp::send(smtp.host.trim(), &username, &pw, &smtp.from, &email).await;
+                    results.push((reply, result));
+                }
+                results
+            },
+            |results| cosmic::Action::App(Message::RepliesSent(results)),
+        ))
+    }
+
+    /// Read the capture folder (or the mail sent to the capture address).
+    fn fetch_captures(&mut self) -> Option<CosmicTask<Message>> {
+        let host = self.config.imap.host.trim().to_string();
+        let (folder, address) = self.config.imap.capture_source()?;
+        if host.is_empty() || self.imap_capturing {
+            return None;
+        }
+        self.imap_capturing = true;
+        Some(CosmicTask::perform(
+            async move {
+                let keyring_key = format!("imap://{}", host);
+                let (username, pw) = match crate::sync::keyring::load_credentials(&keyring_key).await {
+                    Ok(Some(creds)) => creds,
+                    _ => return Err("No IMAP credentials stored".to_string()),
+                };
+                crate::sync::imap::fetch_captures(&host, &username, &pw, &folder, &address).await
+            },
+            |result| cosmic::Action::App(Message::CapturesFetched(result)),
+        ))
+    }
+
+    /// Look in the inbox for replies to the emails that waiting tasks came from.
+    fn check_email_replies(&self) -> Option<CosmicTask<Message>> {
+        let host = self.config.imap.host.trim().to_string();
+        let threads: Vec<(String, chrono::NaiveDate)> = self
+            .all_tasks_cache
+            .iter()
+            .filter(|t| t.state == TaskState::Waiting)
+            .flat_map(|t| {
+                let since = t.delegated.unwrap_or(t.created.date());
+                t.email
+                    .iter()
+                    .map(|e| e.message_id.clone())
+                    .chain(t.delegation_id.clone())
+                    .map(move |id| (id, since))
+            })
+            .collect();
+        if host.is_empty() || threads.is_empty() {
+            return None;
+        }
+        Some(CosmicTask::perform(
+            async move {
+                let keyring_key = format!("imap://{}", host);
+                let (username, pw) = match crate::sync::keyring::load_credentials(&keyring_key).await {
+                    Ok(Some(creds)) => creds,
+                    _ => return Err("No IMAP credentials stored".to_string()),
+                };
+                // Replies land in the inbox, not the follow-up folder
+                crate::sync::imap::find_replies(&host, &username, &pw, "INBOX", threads).await
+            },
+            |result| cosmic::Action::App(Message::EmailRepliesFound(result)),
+        ))
+    }
+
+    /// Wait for new mail with IMAP IDLE; the folder is fetched when it arrives.
+    fn imap_idle(&mut self) -> CosmicTask<Message> {
+        let host = self.config.imap.host.trim().to_string();
+        if host.is_empty() {
+            return CosmicTask::none();
+        }
+        let folder = self.imap_folder();
+        self.imap_idling = true;
+        CosmicTask::perform(
+            async move {
+                let keyring_key = format!("imap://{}", host);
+                let (username, pw) = match crate::sync::keyring::load_credentials(&keyring_key).await {
+                    Ok(Some(creds)) => creds,
+                    _ => return Err("No IMAP credentials stored".to_string()),
+                };
+                let timeout = std::time::Duration::from_secs(IMAP_IDLE_MINUTES * 60);
+                crate::sync::imap::idle(&host, &username, &pw, &folder, timeout).await
+            },
+            |result| cosmic::Action::App(Message::ImapIdleEnded(result)),
+        )
+    }
+
+    /// Show the cached folder in the inbox, save it, and pick up the meeting
+    /// invitations among the mail.
+    fn apply_mail_cache(&mut self) {
+        self.mail_cache.save(&self.config.mail_cache_path());
+        self.imap_emails = self
+            .mail_cache
+            .emails
+            .iter()
+            .filter(|e| !self.archived_email_uids.contains(&e.uid))
+            .cloned()
+            .collect();
+
+        let addresses = self.config.own_addresses();
+        self.pending_invitations
+            .retain(|i| !matches!(i.source, InvitationSource::Email { .. }));
+        for email in &self.imap_emails {
+            let Some(ref ical) = email.calendar else {
+                continue;
+            };
+            for event in itip::parse_request(ical, &addresses) {
+                self.pending_invitations.push(Invitation {
+                    event,
+                    source: InvitationSource::Email { uid: email.uid },
+                });
+            }
+        }
+    }
+
+    fn save_suggestion_cache(&self) {
+        crate::sync::llm::save_suggestion_cache(&self.config.suggestions_path(), &self.suggestion_cache);
+    }
+
+    /// Whether the background timer should sync: an interval is set and something is configured.
+    fn background_sync_enabled(&self) -> bool {
+        self.config.sync_interval_minutes > 0
+            && self.launch_mode != LaunchMode::Capture
+            && (self.config.sync_ready()
+                || !self.config.notes_sync.url.trim().is_empty()
+                || self.config.contact_accounts.iter().any(|a| !a.url.trim().is_empty())
+                || !self.config.imap.host.trim().is_empty())
+    }
+
+    /// Queue a CalDAV write in the outbox; it goes out on the next sync.
+    fn queue_sync_write(&mut self, op: OutboxOp) {
+        self.outbox.push(op, chrono::Utc::now());
+        self.save_outbox();
+    }
+
+    /// Event occurrences for the agenda: its 30-day horizon plus the month on display.
+    fn visible_events(&self) -> Vec<CalendarEvent> {
+        let today = chrono::Local::now().date_naive();
+        let month = self.month_calendar.displayed_month;
+        let from = today.min(month - chrono::Duration::days(7));
+        let to = (today + chrono::Duration::days(30)).max(month + chrono::Duration::days(42));
+        self.events_between(from, to)
+    }
+
+    /// Calendar new events go in by default: the first one assigned to Events.
+    fn default_event_calendar(&self) -> (String, String) {
+        let href = self
+            .config
+            .calendar_assignments
+            .iter()
+            .find(|a| a.purpose == CalendarPurpose::Events)
+            .map(|a| a.calendar_href.clone())
+            .unwrap_or_default();
+        let name = self
+            .all_discovered_calendars()
+            .iter()
+            .find(|c| c.href == href)
+            .map(|c| c.display_name.clone())
+            .unwrap_or_default();
+        (href, name)
+    }
+
+    /// Days the timeline shows at once.
+    fn timeline_days(&self) -> i64 {
+        match self.agenda_layout {
+            AgendaLayout::Week => 7,
+            AgendaLayout::FreeBusy => FREE_BUSY_DAYS,
+            _ => 1,
+        }
+    }
+
+    /// A new event from now to the top of the next hour, in the default calendar.
+    fn blank_event_form(&self) -> EventForm {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let now_time = chrono::Local::now().format("%H:%M").to_string();
+        let (default_cal, _) = self.default_event_calendar();
+        EventForm {
+            editing: None,
+            title: String::new(),
+            start_date: today.clone(),
+            start_time: now_time,
+            end_date: today,
+            end_time: {
+                let hour = chrono::Local::now().hour();
+                format!("{:02}:00", (hour + 1) % 24)
+            },
+            all_day: false,
+            location: String::new(),
+            description: String::new(),
+            calendar_href: default_cal,
+            esc: String::new(),
+            occurrence: None,
+            scope: EditScope::All,
+            series_only: false,
+            repeat: RepeatChoice::default(),
+            organizer: None,
+            attendees: Vec::new(),
+            rsvp: None,
+            alarms: Vec::new(),
+        }
+    }
+
+    /// Ask each account's server for busy time in the days on display, for
+    /// every events calendar it lists: our own and those shared with us.
+    fn refresh_free_busy(&mut self) -> CosmicTask<Message> {
+        let midnight = |day: chrono::NaiveDate| day.and_time(chrono::NaiveTime::MIN);
+        let from = crate::core::timezone::to_utc(midnight(self.timeline_day), None);
+        let until = crate::core::timezone::to_utc(
+            midnight(self.timeline_day + chrono::Duration::days(FREE_BUSY_DAYS)),
+            None,
+        );
+        let jobs: Vec<(String, String, Vec<String>)> = self
+            .discovered_calendars
+            .iter()
+            .filter_map(|(account_id, cals)| {
+                let account = self.config.calendar_account(account_id)?;
+                let hrefs: Vec<String> = cals
+                    .iter()
+                    .filter(|c| c.supports_vevent)
+                    .map(|c| c.href.clone())
+                    .collect();
+                (!account.url.trim().is_empty() && !hrefs.is_empty())
+                    .then(|| (account.url.trim().to_string(), account.username.clone(), hrefs))
+            })
+            .collect();
+        if jobs.is_empty() {
+            return CosmicTask::none();
+        }
+        self.free_busy.loading = true;
+        CosmicTask::perform(
+            async move {
+                let mut results = Vec::new();
+                for (url, username, hrefs) in jobs {
+                    let client = match crate::sync::keyring::load_account_credentials(&url, &username).await {
+                        Ok(Some((username, pw))) => CalDavClient::new(&url, &username, &pw),
+                        Ok(None) => Err(format!("No password stored for {}", username)),
+                        Err(e) => Err(e),
+                    };
+                    for href in hrefs {
+                        let result = match client {
+                            Ok(ref client) => client.free_busy(&href, from, until).await,
+                            Err(ref e) => Err(e.clone()),
+                        };
+                        results.push((href, result));
+                    }
+                }
+                results
+            },
+            |results| cosmic::Action::App(Message::FreeBusyLoaded(results)),
+        )
+    }
+
+    /// One row per events calendar and subscription for the days on display:
+    /// busy time from the server where it answered, else from local events.
+    fn free_busy_rows(&self) -> Vec<pages::temporal::FreeBusyRow> {
+        let last_day = self.timeline_day + chrono::Duration::days(FREE_BUSY_DAYS - 1);
+        let events = self.events_between(self.timeline_day, last_day);
+        let local_busy = |href: &str| -> Vec<(chrono::NaiveDateTime, chrono::NaiveDateTime)> {
+            events
+                .iter()
+                .filter(|e| e.calendar_href == href && !e.all_day && e.status != event::EventStatus::Cancelled)
+                .map(|e| (e.local_start(), e.local_end()))
+                .collect()
+        };
+
+        let mut calendars: Vec<(String, String)> = self
+            .all_discovered_calendars()
+            .into_iter()
+            .filter(|c| c.supports_vevent)
+            .map(|c| (c.href, c.display_name))
+            .collect();
+        calendars.extend(self.config.subscriptions.iter().map(|s| (s.calendar_href(), s.name.clone())));
+        // Events kept only here, without a calendar
+        if events.iter().any(|e| e.calendar_href.is_empty()) {
+            calendars.push((String::new(), crate::fl!("free-busy-local")));
+        }
+
+        calendars
+            .into_iter()
+            .map(|(href, name)| pages::temporal::FreeBusyRow {
+                shown: !self.free_busy.hidden.contains(&href),
+                busy: self.free_busy.busy.get(&href).cloned().unwrap_or_else(|| local_busy(&href)),
+                error: self.free_busy.errors.get(&href).cloned(),
+                href,
+                name,
+            })
+            .collect()
+    }
+
+    /// The next free slots of the requested length in working hours,
+    /// around busy time in every calendar that's shown.
+    fn free_slot_suggestions(&self, rows: &[pages::temporal::FreeBusyRow]) -> Vec<(chrono::NaiveDateTime, chrono::NaiveDateTime)> {
+        let Some(minutes) = self.free_busy.minutes.trim().parse::<u32>().ok().filter(|&m| m > 0) else {
+            return Vec::new();
+        };
+        let busy: Vec<_> = rows
+            .iter()
+            .filter(|r| r.shown)
+            .flat_map(|r| r.busy.iter().copied())
+            .collect();
+        let now = chrono::Local::now().naive_local();
+        let windows: Vec<_> = (0..FREE_BUSY_DAYS)
+            .map(|offset| {
+                let (from, until) = self.workday(self.timeline_day + chrono::Duration::days(offset));
+                (from.max(now), until)
+            })
+            .filter(|(from, until)| from < until)
+            .collect();
+        timeblock::suggest_slots(&busy, &windows, minutes, FREE_SLOT_SUGGESTIONS)
+    }
+
+    /// Local start and end of the working day the timeline covers.
+    fn workday(&self, day: chrono::NaiveDate) -> (chrono::NaiveDateTime, chrono::NaiveDateTime) {
+        let start_hour = self.config.workday_start_hour.min(23);
+        let end_hour = self.config.workday_end_hour.clamp(start_hour + 1, 24);
+        let midnight = day.and_time(chrono::NaiveTime::MIN);
+        (
+            midnight + chrono::Duration::hours(i64::from(start_hour)),
+            midnight + chrono::Duration::hours(i64::from(end_hour)),
+        )
+    }
+
+    /// Block time for a task at local `start`. A task already blocked later
+    /// today or on a coming day has that block moved instead of a second one added.
+    fn place_task_block(&mut self, id: uuid::Uuid, start: chrono::NaiveDateTime) {
+        let Some(task) = self.all_tasks_cache.iter().find(|t| t.id == id).cloned() else {
+            return;
+        };
+        self.picked_block_task = None;
+        let now = chrono::Local::now().naive_local();
+        let existing = self
+            .events
+            .iter()
+            .position(|e| e.task_id == Some(id) && !e.is_recurring() && e.local_end() > now);
+        match existing {
+            Some(pos) => {
+                let block = &mut self.events[pos];
+                let length = (block.local_end() - block.local_start()).max(chrono::Duration::minutes(1));
+                block.set_local_times(start, start + length);
+            }
+            None => {
+                let mut block = timeblock::block_for_task(&task, start);
+                let (href, name) = self.default_event_calendar();
+                block.calendar_href = href;
+                block.calendar_name = name;
+                self.events.push(block);
+            }
+        }
+        self.save_events();
+        // The task is scheduled for the day it's blocked on
+        if task.scheduled != Some(start.date()) {
+            self.modify_task(id, |task| task.scheduled = Some(start.date()));
+        }
+    }
+
+    /// Add the tasks and events of an `.ics` file, skipping UIDs we already have.
+    /// Events stay local until moved to a calendar; tasks sync like new ones.
+    fn import_ics(&mut self, path: &std::path::Path) -> Result<String, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let known: HashSet<String> = self
+            .all_tasks_cache
+            .iter()
+            .map(ics::task_uid)
+            .chain(self.events.iter().map(ics::event_uid))
+            .collect();
+        let import = ics::parse_ics(&content, &known);
+        let (task_count, event_count) = (import.tasks.len(), import.events.len());
+
+        for task in import.tasks {
+            let project = task
+                .project
+                .as_ref()
+                .and_then(|name| self.projects.iter_mut().find(|p| p.name == *name));
+            match project {
+                Some(project) => project.tasks.push(task),
+                None => self.route_task_by_state(task),
+            }
+        }
+        if task_count > 0 {
+            self.save_all();
+        }
+        if event_count > 0 {
+            self.events.extend(import.events);
+            self.save_events();
+        }
+        log::info!(
+            "Imported {} tasks and {} events from {} ({} duplicates)",
+            task_count,
+            event_count,
+            path.display(),
+            import.duplicates
+        );
+        Ok(crate::fl!(
+            "ics-imported",
+            tasks = task_count,
+            events = event_count,
+            duplicates = import.duplicates
+        ))
+    }
+
+    /// Write the tasks and events matching the export filter to one `.ics` file.
+    fn export_ics(&self, path: &std::path::Path) -> Result<String, String> {
+        let filter = self.ics_form.filter()?;
+        let tasks: Vec<&Task> = self.all_tasks_cache.iter().filter(|t| filter.matches_task(t)).collect();
+        let events: Vec<&CalendarEvent> = self.events.iter().filter(|e| filter.matches_event(e)).collect();
+        let content = ics::export_ics(tasks.iter().copied(), events.iter().copied());
+        std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        log::info!("Exported {} tasks and {} events to {}", tasks.len(), events.len(), path.display());
+        Ok(crate::fl!("ics-exported", tasks = tasks.len(), events = events.len()))
+    }
+
+    /// Today's events priced in spoons, and the free time left in the working day.
+    fn day_load(&self) -> DayLoad {
+        let now = chrono::Local::now().naive_local();
+        let today = now.date();
+        let (from, until) = self.workday(today);
+        let events = self.events_between(today, today);
+        DayLoad::build(&events, from.max(now), until, |e| {
+            day_plan::event_spoons(e, self.config.spoons_per_hour(&e.calendar_href))
+        })
+    }
+
+    /// Move or drop time blocks whose tasks were completed, deleted or rescheduled.
+    fn follow_time_blocks(&mut self) {
+        let now = chrono::Local::now().naive_local();
+        let update = timeblock::follow_tasks(&mut self.events, &self.all_tasks_cache, now);
+        if !update.changed {
+            return;
+        }
+        for block in update.removed {
+            if let Some(href) = block.sync_href {
+                self.queue_sync_write(OutboxOp::Delete { href });
+            }
+        }
+        self.save_events();
+    }
+
+    /// Remove an event (a whole series if recurring), deleting it from CalDAV if synced.
+    fn delete_event(&mut self, id: uuid::Uuid) -> CosmicTask<Message> {
+        // Find the account that owns this event
+        let mut delete = CosmicTask::none();
+        if let Some(ev) = self.events.iter().find(|e| e.id == id) {
+            let account = ev
+                .sync_href
+                .as_deref()
+                .and_then(|href| self.config.account_for_href(href))
+                .and_then(|account_id| self.config.calendar_account(account_id));
+            if let (Some(sync_href), Some(account)) = (&ev.sync_href, account) {
+                let caldav_url = account.url.trim().to_string();
+                let username = account.username.clone();
+                if !caldav_url.is_empty() {
+                    let href = sync_href.clone();
+                    delete = CosmicTask::perform(
+                        async move {
+                            let creds =
+                                crate::sync::keyring::load_account_credentials(&caldav_url, &username).await;
+                            if let Ok(Some((username, pw))) = creds {
+                                if let Ok(client) =
+                                    CalDavClient::new(&caldav_url, &username, &pw)
+                                {
+                                    let _ = client.delete_vtodo(&href, "").await;
+                                }
+                            }
+                            Ok::<(), String>(())
+                        },
+                        |_| cosmic::Action::App(Message::Save),
+                    );
+                }
+            }
+        }
+        self.events.retain(|e| e.id != id);
+        self.save_events();
+        delete
+    }
+
+    /// Schedule a sync for when the next backed-off outbox entry comes due.
+    fn schedule_outbox_retry(&mut self) -> CosmicTask<Message> {
+        if self.outbox_retry_scheduled || !self.config.sync_ready() {
+            return CosmicTask::none();
+        }
+        let Some(next) = self.outbox.next_retry() else {
+            return CosmicTask::none();
+        };
+        let delay = (next - chrono::Utc::now()).to_std().unwrap_or_default();
+        self.outbox_retry_scheduled = true;
+        CosmicTask::perform(tokio::time::sleep(delay), |_| {
+            cosmic::Action::App(Message::RetryOutbox)
+        })
+    }
+
+    /// Before a sync: follow project renames, point project tasks at their
+    /// parent VTODO, and move tasks whose project now lives in another list.
+    fn prepare_project_sync(&mut self) {
+        let mut config_changed = self.config.refresh_project_links(&self.projects);
+        let stale: Vec<uuid::Uuid> = self
+            .config
+            .project_links
+            .iter()
+            .filter(|l| !self.projects.iter().any(|p| p.id == l.project))
+            .map(|l| l.project)
+            .collect();
+        for project in stale {
+            self.unlink_project(project, false);
+            config_changed = true;
+        }
+        if config_changed {
+            self.save_config();
+        }
+
+        let related_to = self.config.project_sync_mode == ProjectSyncMode::RelatedTo;
+        let mut tasks_changed = false;
+        for task in self.all_active_tasks() {
+            let parent = if related_to {
+                task.project
+                    .as_deref()
+                    .and_then(|name| self.projects.iter().find(|p| p.name == name))
+                    .map(|p| p.id.to_string())
+            } else {
+                None
+            };
+            let misplaced = task.sync_href.as_deref().is_some_and(|href| {
+                match task.project.as_deref().and_then(|p| self.config.project_link(p)) {
+                    Some(link) => !href.starts_with(&link.calendar_href),
+                    None => self
+                        .config
+                        .project_links
+                        .iter()
+                        .any(|l| l.parent_href.is_none() && href.starts_with(&l.calendar_href)),
+                }
+            });
+            if task.sync_parent == parent && !misplaced {
+                continue;
+            }
+            if misplaced {
+                // Recreated in the right list on this sync
+                if let Some(href) = task.sync_href.clone() {
+                    self.queue_sync_write(OutboxOp::Delete { href });
+                }
+            }
+            self.modify_task(task.id, |t| {
+                t.sync_parent = parent;
+                if misplaced {
+                    t.sync_href = None;
+                    t.sync_hash = None;
+                    t.sync_uid = None;
+                }
+            });
+            tasks_changed = true;
+        }
+        if tasks_changed {
+            self.save_all();
+            self.rebuild_cache();
+        }
+    }
+
+    /// Forget where a project lives on the server. A parent VTODO is deleted.
+    /// A project's own list is left on the server; it stays in sync as an
+    /// ordinary task list if `keep_list`, otherwise it's disabled.
+    fn unlink_project(&mut self, project: uuid::Uuid, keep_list: bool) {
+        let Some(pos) = self.config.project_links.iter().position(|l| l.project == project) else {
+            return;
+        };
+        let link = self.config.project_links.remove(pos);
+        match link.parent_href {
+            Some(href) => self.queue_sync_write(OutboxOp::Delete { href }),
+            None if keep_list => {}
+            None => {
+                for a in &mut self.config.calendar_assignments {
+                    if a.account == link.account && a.calendar_href == link.calendar_href {
+                        a.purpose = CalendarPurpose::Disabled;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The account at `idx` for a service kind. Notes has a single account at index 0.
+    fn service_account(&self, kind: ServiceKind, idx: usize) -> Option<&ServiceConfig> {
+        match kind {
+            ServiceKind::Calendars => self.config.calendar_accounts.get(idx),
+            ServiceKind::Contacts => self.config.contact_accounts.get(idx),
+            ServiceKind::Notes => Some(&self.config.notes_sync),
+            ServiceKind::Imap | ServiceKind::Smtp => None,
+        }
+    }
+
+    fn service_account_mut(&mut self, kind: ServiceKind, idx: usize) -> Option<&mut ServiceConfig> {
+        match kind {
+            ServiceKind::Calendars => self.config.calendar_accounts.get_mut(idx),
+            ServiceKind::Contacts => self.config.contact_accounts.get_mut(idx),
+            ServiceKind::Notes => Some(&mut self.config.notes_sync),
+            ServiceKind::Imap | ServiceKind::Smtp => None,
+        }
+    }
+
+    /// Calendars assigned to Tasks across all accounts: (account id, href, label).
+    fn task_calendar_choices(&self) -> Vec<(String, String, String)> {
+        self.config
+            .calendar_assignments
+            .iter()
+            .filter(|a| a.purpose == CalendarPurpose::Tasks)
+            .map(|a| {
+                let account_label = self
+                    .config
+                    .calendar_account(&a.account)
+                    .map(|acc| acc.label())
+                    .unwrap_or_default();
+                let cal_name = self
+                    .discovered_calendars
+                    .get(&a.account)
+                    .and_then(|cals| cals.iter().find(|c| c.href == a.calendar_href))
+                    .map(|c| c.display_name.clone())
+                    .unwrap_or_else(|| a.calendar_href.clone());
+                (
+                    a.account.clone(),
+                    a.calendar_href.clone(),
+                    format!("{} — {}", account_label, cal_name),
+                )
+            })
+            .collect()
+    }
+
+    /// All discovered calendars.
+    fn all_discovered_calendars(&self) -> Vec<CalendarInfo> {
+        self.discovered_calendars.values().flatten().cloned().collect()
+    }
+
+    fn save_config(&self) {
+        use cosmic::cosmic_config::CosmicConfigEntry;
+        if let Err(e) = self.config.write_entry(&self.cosmic_config) {
             log::error!("Failed to save config: {:?}", e);
         }
     }
+
+    /// One sync job per ready calendar account, each carrying the tasks
+    /// routed to that account and the outbox writes aimed at it.
+    fn calendar_sync_jobs(&self) -> Vec<CalendarSyncJob> {
+        let tasks = self.all_active_tasks();
+        self.config
+            .ready_calendar_accounts()
+            .into_iter()
+            .map(|account| {
+                let mut account_tasks = Vec::new();
+                let mut task_targets = HashMap::new();
+                for task in &tasks {
+                    if let Some((target_account, href)) = self.config.task_target(task) {
+                        if target_account == account.id {
+                            task_targets.insert(task.id, href.to_string());
+                            account_tasks.push(task.clone());
+                        }
+                    }
+                }
+                CalendarSyncJob {
+                    account: account.id.clone(),
+                    url: account.url.trim().to_string(),
+                    username: account.username.clone(),
+                    tasks: account_tasks,
+                    task_targets,
+                    events: self.events.clone(),
+                    task_cals: self.config.task_calendar_hrefs(&account.id),
+                    event_cals: self.config.event_calendar_hrefs(&account.id),
+                    sync_tokens: self.config.sync_tokens_for(&account.id),
+                    project_actions: crate::sync::projects::plan_project_actions(
+                        &self.config,
+                        &account.id,
+                        &self.projects,
+                    ),
+                    outbox: self
+                        .outbox_entries_for(&account.id)
+                        .into_iter()
+                        .cloned()
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Outbox entries that go to an account. Writes whose calendar is no
+    /// longer assigned fall to the first account rather than being stranded.
+    fn outbox_entries_for(&self, account: &str) -> Vec<&OutboxEntry> {
+        let fallback = self
+            .config
+            .ready_calendar_accounts()
+            .first()
+            .map(|a| a.id.as_str());
+        self.outbox
+            .entries
+            .iter()
+            .filter(|e| {
+                self.config
+                    .account_for_href(e.op.href())
+                    .or(fallback)
+                    .is_some_and(|a| a == account)
+            })
+            .collect()
+    }
+
+    /// Apply one account's sync result to local state (saving is left to the caller).
+    fn apply_sync_result(&mut self, account: &str, sync_result: crate::sync::SyncResult) {
+        // Update sync tokens
+        for (href, token) in &sync_result.new_sync_tokens {
+            self.config.set_sync_token(account, href, token);
+        }
+
+        // Remove deleted tasks
+        for id in &sync_result.deleted_local {
+            self.inbox_tasks.retain(|t| t.id != *id);
+            self.next_tasks.retain(|t| t.id != *id);
+            self.waiting_tasks.retain(|t| t.id != *id);
+            self.someday_tasks.retain(|t| t.id != *id);
+            for project in &mut self.projects {
+                project.tasks.retain(|t| t.id != *id);
+            }
+        }
+
+        // Lists created and renames pushed for projects
+        for link in sync_result.project_links.iter().cloned() {
+            self.config.set_project_link(link);
+        }
+
+        // Apply pulled tasks (new + updated)
+        for pulled in &sync_result.pulled {
+            let _existing = self.remove_task(pulled.id);
+            // Tasks added in other clients carry their project as a list or parent task
+            let mut pulled = pulled.clone();
+            if pulled.project.is_none() {
+                pulled.project = pulled.sync_href.as_deref().and_then(|href| {
+                    self.config
+                        .project_for_remote(href, pulled.sync_parent.as_deref())
+                        .map(str::to_string)
+                });
+            }
+            if let Some(ref project_name) = pulled.project {
+                let project_name = project_name.clone();
+                if let Some(project) = self.projects.iter_mut().find(|p| p.name == project_name) {
+                    project.tasks.push(pulled.clone());
+                    continue;
+                }
+            }
+            self.route_task_by_state(pulled.clone());
+        }
+
+        // Remove deleted events
+        for id in &sync_result.deleted_events {
+            self.events.retain(|e| e.id != *id);
+        }
+
+        // Apply pulled events
+        for pulled_event in &sync_result.pulled_events {
+            self.events.retain(|e| e.id != pulled_event.id);
+            self.events.push(pulled_event.clone());
+        }
+
+        self.sync_conflicts.extend(sync_result.conflicts);
+
+        // Meeting requests in the scheduling inbox
+        let addresses = self.config.own_addresses();
+        for (href, ical) in &sync_result.inbox {
+            for event in itip::parse_request(ical, &addresses) {
+                self.pending_invitations.push(Invitation {
+                    event,
+                    source: InvitationSource::CalDavInbox { href: href.clone() },
+                });
+            }
+        }
+
+        // Settle the outbox: drop what was sent, back off what failed,
+        // and queue anything the sync itself couldn't push
+        let utc_now = chrono::Utc::now();
+        for id in &sync_result.outbox_sent {
+            self.outbox.remove(*id);
+        }
+        for (id, e) in &sync_result.outbox_failed {
+            self.outbox.record_failure(*id, e, utc_now);
+        }
+        for (op, e) in sync_result.queued {
+            let id = self.outbox.push(op, utc_now);
+            self.outbox.record_failure(id, &e, utc_now);
+        }
+    }
+}
+
+/// Everything needed to sync one CalDAV account off the UI thread.
+struct CalendarSyncJob {
+    account: String,
+    url: String,
+    username: String,
+    tasks: Vec<Task>,
+    task_targets: HashMap<uuid::Uuid, String>,
+    events: Vec<CalendarEvent>,
+    task_cals: Vec<String>,
+    event_cals: Vec<String>,
+    sync_tokens: Vec<(String, String)>,
+    outbox: Vec<OutboxEntry>,
+    project_actions: Vec<ProjectAction>,
+}
+
+impl CalendarSyncJob {
+    async fn run(&self) -> Result<crate::sync::SyncResult, String> {
+        let password = match crate::sync::keyring::load_account_credentials(&self.url, &self.username).await {
+            Ok(Some((_, pw))) => pw,
+            Ok(None) => return Err("No CalDAV credentials stored".to_string()),
+            Err(e) => return Err(format!("Keyring error: {}", e)),
+        };
+
+        // Make projects visible first, so new project lists sync in this same run
+        let mut task_cals = self.task_cals.clone();
+        let mut task_targets = self.task_targets.clone();
+        let (project_links, project_errors) = if self.project_actions.is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            let client = CalDavClient::new(&self.url, &self.username, &password)?;
+            crate::sync::projects::apply_project_actions(&client, &self.account, &self.project_actions).await
+        };
+        for link in project_links.iter().filter(|l| l.parent_href.is_none()) {
+            if !task_cals.contains(&link.calendar_href) {
+                task_cals.push(link.calendar_href.clone());
+            }
+            for task in &self.tasks {
+                if task.sync_href.is_none() && task.project.as_deref() == Some(link.name.as_str()) {
+                    task_targets.insert(task.id, link.calendar_href.clone());
+                }
+            }
+        }
+
+        let mut result = crate::sync::sync_all(
+            &self.url,
+            &self.username,
+            &password,
+            &self.tasks,
+            &self.events,
+            &task_cals,
+            &self.event_cals,
+            &self.sync_tokens,
+            &task_targets,
+            &self.outbox,
+        )
+        .await?;
+        result.project_links = project_links;
+        result.errors.extend(project_errors);
+        Ok(result)
+    }
 }
 
 fn parse_form_datetime(
@@ -3218,6 +6128,27 @@ fn parse_form_datetime(
 }
 
 /// Sentence-case: first letter uppercase, rest lowercase.
+/// The outgoing server's login from the keyring; none for local test sinks.
+async fn smtp_credentials(host: &str) -> (String, String) {
+    match crate::sync::keyring::load_credentials(&format!("smtp://{}", host.trim())).await {
+        Ok(Some(creds)) => creds,
+        _ => (String::new(), String::new()),
+    }
+}
+
+/// Task notes for an email: who sent it and when, then the body.
+fn email_note(email: &ImapEmail) -> String {
+    let mut note = format!("From: {}\n", email.from);
+    if let Some(date) = email.date {
+        note.push_str(&format!("Date: {}\n", date.format("%Y-%m-%d %H:%M")));
+    }
+    if !email.body_full.is_empty() {
+        note.push('\n');
+        note.push_str(&email.body_full);
+    }
+    note
+}
+
 fn sentence_case(s: &str) -> String {
     let s = s.trim();
     if s.is_empty() {
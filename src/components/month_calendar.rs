@@ -211,7 +211,7 @@ fn day_detail<'a>(
     // Collect events for this date
     let day_events: Vec<&CalendarEvent> = events
         .iter()
-        .filter(|e| e.start.date() == date)
+        .filter(|e| e.local_start().date() == date)
         .collect();
 
     // Collect tasks scheduled/due this date
@@ -233,7 +233,7 @@ fn day_detail<'a>(
         let time_str = if event.all_day {
             "All day".to_string()
         } else {
-            format!("{} – {}", event.start.format("%H:%M"), event.end.format("%H:%M"))
+            format!("{} – {}", event.local_start().format("%H:%M"), event.local_end().format("%H:%M"))
         };
 
         let mut r = row()
@@ -245,6 +245,9 @@ fn day_detail<'a>(
         if !event.location.is_empty() {
             r = r.push(text::caption(event.location.clone()));
         }
+        if event.is_subscribed() {
+            r = r.push(text::caption(event.calendar_name.clone()));
+        }
 
         items.push(r.into());
     }
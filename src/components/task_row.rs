@@ -7,6 +7,8 @@ use cosmic::iced::{Alignment, Length};
 use cosmic::widget::{button, checkbox, column, container, dropdown, icon, row, text, text_input};
 use cosmic::{Element, theme};
 
+use crate::core::delegation;
+use crate::core::email_link::excerpt;
 use crate::core::task::{Priority, Task, TaskState};
 use crate::message::{Message, SortColumn};
 
@@ -36,6 +38,9 @@ const COL_DATE: f32 = 96.0;
 const COL_ESC: f32 = 48.0;
 const COL_DELETE: f32 = 40.0;
 
+/// Length of the quote shown under tasks made from an email.
+const EXCERPT_CHARS: usize = 120;
+
 use crate::sync::carddav::Contact;
 
 /// Context passed to task grid.
@@ -46,6 +51,10 @@ pub struct TaskRowCtx<'a> {
     pub note_inputs: &'a HashMap<Uuid, String>,
     pub waiting_for_inputs: &'a HashMap<Uuid, String>,
     pub contacts: &'a [Contact],
+    /// Outcome of the last delegation email per task.
+    pub delegation_status: &'a HashMap<Uuid, Result<String, String>>,
+    /// Task the assistant is breaking down, with the error if that failed.
+    pub breakdown_status: Option<(Uuid, Option<&'a str>)>,
 }
 
 // --- Date picker presets ---
@@ -286,24 +295,31 @@ fn task_row(
             .on_press(Message::SetTaskPriority(id, next_priority)),
     );
 
-    // 4. Title (clickable to expand/collapse notes) + waiting_for label
+    // 4. Title (clickable to expand/collapse notes) + waiting_for label,
+    // and for tasks made from mail an open-email button and a quote of it
     let title: Element<'static, Message> = {
         let title_btn: Element<'static, Message> = button::custom(text::body(task.title.clone()))
             .padding([0, 0])
             .class(theme::Button::Text)
             .on_press(Message::ToggleTaskExpand(id))
             .into();
+        let mut title_row = row().spacing(6).align_y(Alignment::Center).push(title_btn);
         if let Some(ref wf) = task.waiting_for {
             let label = format!("\u{2190} @{}", wf);
-            col_fill(
-                row()
-                    .spacing(6)
-                    .align_y(Alignment::Center)
-                    .push(title_btn)
-                    .push(text::caption(label).size(11.0)),
-            )
+            title_row = title_row.push(text::caption(label).size(11.0));
+        }
+        if task.email.is_none() {
+            col_fill(title_row)
         } else {
-            col_fill(title_btn)
+            title_row = title_row.push(
+                button::icon(icon::from_name("mail-read-symbolic"))
+                    .on_press(Message::OpenTaskEmail(id)),
+            );
+            let mut title_col = column().spacing(2).push(title_row);
+            if let Some(quote) = excerpt(&task.notes, EXCERPT_CHARS) {
+                title_col = title_col.push(text::caption(format!("\u{201C}{}\u{201D}", quote)).size(11.0));
+            }
+            col_fill(title_col)
         }
     };
 
@@ -444,12 +460,12 @@ fn task_row(
                     if !suggestions.is_empty() {
                         let mut suggestion_row = row().spacing(4);
                         for contact in suggestions {
-                            let name = contact.name.clone();
+                            let contact_id = contact.id;
                             suggestion_row = suggestion_row.push(
-                                button::custom(text::caption(name.clone()).size(11.0))
+                                button::custom(text::caption(contact.name.clone()).size(11.0))
                                     .padding([2, 8])
                                     .class(theme::Button::Text)
-                                    .on_press(Message::SetWaitingFor(id, name)),
+                                    .on_press(Message::SetWaitingForContact(id, contact_id)),
                             );
                         }
                         notes_col = notes_col.push(suggestion_row);
@@ -457,6 +473,22 @@ fn task_row(
                 }
             }
 
+            // Email the request to a contact with an address
+            let contact_email = delegation::resolve_contact(task, ctx.contacts)
+                .is_some_and(|c| c.email.as_deref().is_some_and(|e| !e.is_empty()));
+            if contact_email {
+                let mut send_row = row().spacing(8).align_y(Alignment::Center).push(
+                    button::standard("Send delegation email")
+                        .on_press(Message::SendDelegationEmail(id)),
+                );
+                match ctx.delegation_status.get(&id) {
+                    Some(Ok(msg)) => send_row = send_row.push(text::caption(format!("✓ {}", msg))),
+                    Some(Err(e)) => send_row = send_row.push(text::caption(format!("✗ {}", e))),
+                    None => {}
+                }
+                notes_col = notes_col.push(send_row);
+            }
+
             // Show delegated date if set
             if let Some(delegated) = task.delegated {
                 notes_col = notes_col.push(
@@ -472,6 +504,26 @@ fn task_row(
             .width(Length::Fill);
         notes_col = notes_col.push(title_input);
 
+        // Too big to start: let the assistant propose a project instead
+        if task.state.is_active() {
+            let breakdown_row = match ctx.breakdown_status {
+                Some((busy, None)) if busy == id => row().push(text::caption("Breaking down…")),
+                status => {
+                    let mut breakdown_row = row().spacing(8).align_y(Alignment::Center).push(
+                        button::standard("Break down into a project")
+                            .on_press(Message::BreakDownTask(id)),
+                    );
+                    if let Some((failed, Some(e))) = status {
+                        if failed == id {
+                            breakdown_row = breakdown_row.push(text::caption(format!("✗ {}", e)));
+                        }
+                    }
+                    breakdown_row
+                }
+            };
+            notes_col = notes_col.push(breakdown_row);
+        }
+
         if !notes_text.is_empty() {
             notes_col = notes_col.push(
                 container(text::body(notes_text))
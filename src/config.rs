@@ -2,6 +2,9 @@ use cosmic::cosmic_config::{self, CosmicConfigEntry, cosmic_config_derive::Cosmi
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::core::project::Project;
+use crate::core::task::Task;
+
 pub const CONFIG_VERSION: u64 = 2;
 
 fn default_org_dir() -> PathBuf {
@@ -14,6 +17,22 @@ fn default_browser_command() -> String {
     "xdg-open".to_string()
 }
 
+fn default_sync_interval() -> u32 {
+    15
+}
+
+fn default_workday_start() -> u32 {
+    8
+}
+
+fn default_workday_end() -> u32 {
+    18
+}
+
+fn default_meeting_spoons() -> u32 {
+    10
+}
+
 fn default_contexts() -> Vec<String> {
     vec![
         "@home".into(),
@@ -36,12 +55,43 @@ pub enum CalendarPurpose {
 /// Per-service sync configuration (URL + username; password lives in keyring).
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ServiceConfig {
+    /// Stable identifier that calendar assignments and routes refer to.
+    #[serde(default)]
+    pub id: String,
+    /// User-chosen display name, e.g. "Personal" or "Work".
+    #[serde(default)]
+    pub name: String,
     #[serde(default)]
     pub url: String,
     #[serde(default)]
     pub username: String,
 }
 
+impl ServiceConfig {
+    pub fn new() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// The name, falling back to the URL's host.
+    pub fn label(&self) -> String {
+        if !self.name.trim().is_empty() {
+            return self.name.trim().to_string();
+        }
+        let host = self
+            .url
+            .split("://")
+            .nth(1)
+            .unwrap_or(&self.url)
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        host.to_string()
+    }
+}
+
 /// IMAP inbox configuration (host + username + folder; password lives in keyring).
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ImapConfig {
@@ -51,34 +101,363 @@ pub struct ImapConfig {
     pub username: String,
     #[serde(default, rename = "imap_folder")]
     pub folder: String,
+    /// Folder whose mail is turned into inbox tasks; INBOX when only an
+    /// address is set.
+    #[serde(default)]
+    pub capture_folder: String,
+    /// Address (e.g. `me+lamp@example.com`) whose mail is turned into inbox tasks.
+    #[serde(default)]
+    pub capture_address: String,
+}
+
+impl ImapConfig {
+    /// The folder to capture tasks from, if capture is set up.
+    pub fn capture_source(&self) -> Option<(String, String)> {
+        let folder = self.capture_folder.trim();
+        let address = self.capture_address.trim();
+        match (folder.is_empty(), address.is_empty()) {
+            (true, true) => None,
+            (true, false) => Some(("INBOX".to_string(), address.to_string())),
+            _ => Some((folder.to_string(), address.to_string())),
+        }
+    }
+}
+
+/// Outgoing mail server (host + username + sender; password lives in keyring).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    /// `host[:port]`; port 587 when left out.
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub username: String,
+    /// Sender address, optionally as `Name <address>`.
+    #[serde(default)]
+    pub from: String,
+}
+
+impl SmtpConfig {
+    pub fn is_ready(&self) -> bool {
+        !self.host.trim().is_empty() && !self.from.trim().is_empty()
+    }
 }
 
 /// Maps a discovered calendar to a purpose (Tasks, Events, or Disabled).
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CalendarAssignment {
+    /// Id of the calendar account the calendar belongs to.
+    #[serde(default)]
+    pub account: String,
     pub calendar_href: String,
     pub purpose: CalendarPurpose,
+    /// Sync token from the last sync-collection REPORT.
+    #[serde(default)]
+    pub sync_token: Option<String>,
+    /// Spoons per hour an event on this calendar costs, instead of
+    /// `LampConfig::meeting_spoons_per_hour`.
+    #[serde(default)]
+    pub spoons_per_hour: Option<u32>,
+}
+
+/// A read-only iCalendar feed (holidays, fixtures, a team rota) shown with
+/// the CalDAV calendars but never written back.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IcsSubscription {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    /// `http(s)://`, `webcal://` or `file://` URL of the feed.
+    #[serde(default)]
+    pub url: String,
+    /// Validators from the last fetch, sent back so unchanged feeds aren't re-read.
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+}
+
+impl IcsSubscription {
+    pub fn new() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// `calendar_href` given to the feed's events.
+    pub fn calendar_href(&self) -> String {
+        format!("{}{}", crate::core::event::SUBSCRIPTION_HREF_PREFIX, self.id)
+    }
+}
+
+/// How Lamp projects appear to other CalDAV clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProjectSyncMode {
+    /// Only as the `X-LAMP-PROJECT` property, which other clients ignore.
+    #[default]
+    Property,
+    /// Each project gets its own task list, created with MKCALENDAR.
+    Collections,
+    /// Each project becomes a parent VTODO its tasks point at with `RELATED-TO`.
+    RelatedTo,
+}
+
+/// Where passwords and API keys are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SecretBackend {
+    /// The Secret Service when one is running, otherwise the vault.
+    #[default]
+    Auto,
+    /// GNOME Keyring, KWallet or another Secret Service.
+    SecretService,
+    /// An encrypted file in the config directory, unlocked with a passphrase.
+    Vault,
+}
+
+impl SecretBackend {
+    pub const ALL: [SecretBackend; 3] = [
+        SecretBackend::Auto,
+        SecretBackend::SecretService,
+        SecretBackend::Vault,
+    ];
+}
+
+/// Which kind of model server suggests tasks from email.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LlmBackend {
+    /// Anthropic's Messages API.
+    #[default]
+    Anthropic,
+    /// Any OpenAI-compatible chat completions API, including llama.cpp's server.
+    OpenAi,
+    /// A local Ollama server; email content stays on this machine.
+    Ollama,
+    /// Canned answers without any network, for trying the pipeline offline.
+    Mock,
+}
+
+impl LlmBackend {
+    pub const ALL: [LlmBackend; 4] = [
+        LlmBackend::Anthropic,
+        LlmBackend::OpenAi,
+        LlmBackend::Ollama,
+        LlmBackend::Mock,
+    ];
+
+    pub fn default_endpoint(self) -> &'static str {
+        match self {
+            LlmBackend::Anthropic => "https://api.anthropic.com/v1/messages",
+            LlmBackend::OpenAi => "https://api.openai.com/v1/chat/completions",
+            LlmBackend::Ollama => "http://localhost:11434/api/chat",
+            LlmBackend::Mock => "",
+        }
+    }
+
+    pub fn default_model(self) -> &'static str {
+        match self {
+            LlmBackend::Anthropic => "claude-haiku-4-5-20251001",
+            LlmBackend::OpenAi => "gpt-4o-mini",
+            LlmBackend::Ollama => "llama3.2",
+            LlmBackend::Mock => "mock",
+        }
+    }
+
+    /// Whether requests need an API key from the keyring.
+    pub fn needs_api_key(self) -> bool {
+        matches!(self, LlmBackend::Anthropic | LlmBackend::OpenAi)
+    }
+}
+
+/// Model used for email task suggestions. Empty fields use the backend's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmConfig {
+    #[serde(default)]
+    pub backend: LlmBackend,
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default)]
+    pub model: String,
+}
+
+impl LlmConfig {
+    pub fn endpoint(&self) -> &str {
+        match self.endpoint.trim() {
+            "" => self.backend.default_endpoint(),
+            endpoint => endpoint,
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        match self.model.trim() {
+            "" => self.backend.default_model(),
+            model => model,
+        }
+    }
+}
+
+/// Where a project lives on a CalDAV server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectLink {
+    pub project: uuid::Uuid,
+    pub account: String,
+    /// The project's own task list, or the list holding its parent VTODO.
+    pub calendar_href: String,
+    /// Href of the parent VTODO; `None` when the project has its own list.
+    #[serde(default)]
+    pub parent_href: Option<String>,
+    /// Current project name, used to match tasks to the link.
+    pub name: String,
+    /// Name last written to the server; differs from `name` after a rename.
+    #[serde(default)]
+    pub synced_name: String,
+}
+
+/// Sends tasks with a given context or project to a specific task calendar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskRoute {
+    /// A context (`@work`) or a project name.
+    pub key: String,
+    pub account: String,
+    pub calendar_href: String,
+}
+
+impl TaskRoute {
+    pub fn matches(&self, task: &Task) -> bool {
+        if self.key.starts_with('@') {
+            task.contexts.iter().any(|c| c.eq_ignore_ascii_case(&self.key))
+        } else {
+            task.project.as_deref().is_some_and(|p| p.eq_ignore_ascii_case(&self.key))
+        }
+    }
+}
+
+/// Makes a task from matching email without asking a model. Every condition
+/// that is set must hold; a rule without conditions matches nothing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriageRule {
+    #[serde(default)]
+    pub name: String,
+    /// Case-insensitive part of the sender's name or address.
+    #[serde(default)]
+    pub from: String,
+    /// Case-insensitive regular expression on the subject.
+    #[serde(default)]
+    pub subject: String,
+    /// Case-insensitive part of the `List-Id` header.
+    #[serde(default)]
+    pub list_id: String,
+    /// Require attachments (`Some(true)`) or their absence (`Some(false)`).
+    #[serde(default)]
+    pub has_attachment: Option<bool>,
+    /// Task title with `{subject}` and `{from}` filled in; the subject when empty.
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub contexts: Vec<String>,
+    #[serde(default)]
+    pub project: String,
+    /// `A`, `B`, `C` or empty.
+    #[serde(default)]
+    pub priority: String,
+    #[serde(default)]
+    pub esc: Option<u32>,
+    /// Off for mail that only needs filing away.
+    #[serde(default = "default_true")]
+    pub create_task: bool,
+    /// Handle the email without asking: create the task and archive it.
+    #[serde(default)]
+    pub archive: bool,
+}
+
+impl Default for TriageRule {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            from: String::new(),
+            subject: String::new(),
+            list_id: String::new(),
+            has_attachment: None,
+            title: String::new(),
+            contexts: Vec::new(),
+            project: String::new(),
+            priority: String::new(),
+            esc: None,
+            create_task: true,
+            archive: false,
+        }
+    }
+}
+
+impl TriageRule {
+    pub fn has_conditions(&self) -> bool {
+        !self.from.trim().is_empty()
+            || !self.subject.trim().is_empty()
+            || !self.list_id.trim().is_empty()
+            || self.has_attachment.is_some()
+    }
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, CosmicConfigEntry)]
 pub struct LampConfig {
     pub org_directory: PathBuf,
     pub contexts: Vec<String>,
+    /// Legacy single CalDAV account; moved into `calendar_accounts` by `migrate_accounts`.
     #[serde(default)]
     pub calendars: ServiceConfig,
+    /// Legacy single CardDAV account; moved into `contact_accounts` by `migrate_accounts`.
     #[serde(default)]
     pub contacts: ServiceConfig,
     #[serde(default)]
+    pub calendar_accounts: Vec<ServiceConfig>,
+    #[serde(default)]
+    pub contact_accounts: Vec<ServiceConfig>,
+    #[serde(default)]
     pub notes_sync: ServiceConfig,
     #[serde(default)]
     pub imap: ImapConfig,
+    #[serde(default)]
+    pub smtp: SmtpConfig,
     pub calendar_assignments: Vec<CalendarAssignment>,
-    /// Sync tokens: (calendar_href, token)
+    /// Legacy sync tokens: (calendar_href, token). Now kept on each `CalendarAssignment`.
     pub sync_tokens: Vec<(String, String)>,
+    #[serde(default)]
+    pub task_routes: Vec<TaskRoute>,
+    #[serde(default)]
+    pub project_sync_mode: ProjectSyncMode,
+    #[serde(default)]
+    pub project_links: Vec<ProjectLink>,
     #[serde(default = "default_browser_command")]
     pub browser_command: String,
     #[serde(default)]
     pub debug_logging: bool,
+    /// Minutes between background syncs; 0 turns background sync off.
+    #[serde(default = "default_sync_interval")]
+    pub sync_interval_minutes: u32,
+    /// Hours of the day the time-blocking timeline covers.
+    #[serde(default = "default_workday_start")]
+    pub workday_start_hour: u32,
+    #[serde(default = "default_workday_end")]
+    pub workday_end_hour: u32,
+    /// Default spoon cost of an hour of events, taken off the day's budget.
+    #[serde(default = "default_meeting_spoons")]
+    pub meeting_spoons_per_hour: u32,
+    #[serde(default)]
+    pub subscriptions: Vec<IcsSubscription>,
+    #[serde(default)]
+    pub llm: LlmConfig,
+    /// Email rules, tried in order before any model is asked.
+    #[serde(default)]
+    pub triage_rules: Vec<TriageRule>,
+    /// Ask the model about email no rule matched.
+    #[serde(default = "default_true")]
+    pub triage_ai_fallback: bool,
+    #[serde(default)]
+    pub secret_backend: SecretBackend,
 }
 
 impl Default for LampConfig {
@@ -88,12 +467,27 @@ impl Default for LampConfig {
             contexts: default_contexts(),
             calendars: ServiceConfig::default(),
             contacts: ServiceConfig::default(),
+            calendar_accounts: Vec::new(),
+            contact_accounts: Vec::new(),
             notes_sync: ServiceConfig::default(),
             imap: ImapConfig::default(),
+            smtp: SmtpConfig::default(),
             calendar_assignments: Vec::new(),
             sync_tokens: Vec::new(),
+            task_routes: Vec::new(),
+            project_sync_mode: ProjectSyncMode::default(),
+            project_links: Vec::new(),
             browser_command: default_browser_command(),
             debug_logging: false,
+            sync_interval_minutes: default_sync_interval(),
+            workday_start_hour: default_workday_start(),
+            workday_end_hour: default_workday_end(),
+            meeting_spoons_per_hour: default_meeting_spoons(),
+            subscriptions: Vec::new(),
+            llm: LlmConfig::default(),
+            triage_rules: Vec::new(),
+            triage_ai_fallback: true,
+            secret_backend: SecretBackend::default(),
         }
     }
 }
@@ -171,53 +565,289 @@ impl LampConfig {
         self.org_directory.join("events.json")
     }
 
-    /// Calendar hrefs assigned to Tasks purpose.
-    pub fn task_calendar_hrefs(&self) -> Vec<String> {
+    /// Last fetched events of the ICS subscriptions.
+    pub fn subscriptions_cache_path(&self) -> PathBuf {
+        self.org_directory.join("subscriptions.json")
+    }
+
+    /// iTIP replies to mail-borne invitations, waiting to be sent.
+    pub fn replies_path(&self) -> PathBuf {
+        self.org_directory.join("itip-replies.json")
+    }
+
+    /// Headers and loaded bodies of the IMAP folder's mail.
+    pub fn mail_cache_path(&self) -> PathBuf {
+        self.org_directory.join("mail-cache.json")
+    }
+
+    /// AI task suggestions by email Message-ID, so they aren't requested twice.
+    pub fn suggestions_path(&self) -> PathBuf {
+        self.org_directory.join("email-suggestions.json")
+    }
+
+    pub fn outbox_path(&self) -> PathBuf {
+        self.org_directory.join("outbox.json")
+    }
+
+    /// Move the legacy single-account fields into the account lists.
+    /// Returns true if anything changed and the config should be saved.
+    pub fn migrate_accounts(&mut self) -> bool {
+        let mut changed = false;
+
+        if self.calendar_accounts.is_empty() && !self.calendars.url.trim().is_empty() {
+            let mut account = std::mem::take(&mut self.calendars);
+            account.id = uuid::Uuid::new_v4().to_string();
+            for assignment in &mut self.calendar_assignments {
+                if assignment.account.is_empty() {
+                    assignment.account = account.id.clone();
+                }
+                if assignment.sync_token.is_none() {
+                    assignment.sync_token = self
+                        .sync_tokens
+                        .iter()
+                        .find(|(h, _)| *h == assignment.calendar_href)
+                        .map(|(_, t)| t.clone());
+                }
+            }
+            self.sync_tokens.clear();
+            self.calendar_accounts.push(account);
+            changed = true;
+        }
+
+        if self.contact_accounts.is_empty() && !self.contacts.url.trim().is_empty() {
+            let mut account = std::mem::take(&mut self.contacts);
+            account.id = uuid::Uuid::new_v4().to_string();
+            self.contact_accounts.push(account);
+            changed = true;
+        }
+
+        changed
+    }
+
+    pub fn calendar_account(&self, id: &str) -> Option<&ServiceConfig> {
+        self.calendar_accounts.iter().find(|a| a.id == id)
+    }
+
+    /// Calendar accounts with a URL and at least one calendar in use.
+    pub fn ready_calendar_accounts(&self) -> Vec<&ServiceConfig> {
+        self.calendar_accounts
+            .iter()
+            .filter(|acc| {
+                !acc.url.trim().is_empty()
+                    && self
+                        .calendar_assignments
+                        .iter()
+                        .any(|a| a.account == acc.id && a.purpose != CalendarPurpose::Disabled)
+            })
+            .collect()
+    }
+
+    /// Calendar hrefs of an account assigned to Tasks purpose.
+    pub fn task_calendar_hrefs(&self, account: &str) -> Vec<String> {
         self.calendar_assignments
             .iter()
-            .filter(|a| a.purpose == CalendarPurpose::Tasks)
+            .filter(|a| a.account == account && a.purpose == CalendarPurpose::Tasks)
             .map(|a| a.calendar_href.clone())
             .collect()
     }
 
-    /// Calendar hrefs assigned to Events purpose.
-    pub fn event_calendar_hrefs(&self) -> Vec<String> {
+    /// Calendar hrefs of an account assigned to Events purpose.
+    pub fn event_calendar_hrefs(&self, account: &str) -> Vec<String> {
         self.calendar_assignments
             .iter()
-            .filter(|a| a.purpose == CalendarPurpose::Events)
+            .filter(|a| a.account == account && a.purpose == CalendarPurpose::Events)
             .map(|a| a.calendar_href.clone())
             .collect()
     }
 
+    /// Sync tokens of an account's calendars: (calendar_href, token).
+    pub fn sync_tokens_for(&self, account: &str) -> Vec<(String, String)> {
+        self.calendar_assignments
+            .iter()
+            .filter(|a| a.account == account)
+            .filter_map(|a| Some((a.calendar_href.clone(), a.sync_token.clone()?)))
+            .collect()
+    }
+
     /// Get sync token for a calendar.
-    pub fn get_sync_token(&self, href: &str) -> Option<&str> {
-        self.sync_tokens
+    pub fn get_sync_token(&self, account: &str, href: &str) -> Option<&str> {
+        self.calendar_assignments
             .iter()
-            .find(|(h, _)| h == href)
-            .map(|(_, t)| t.as_str())
+            .find(|a| a.account == account && a.calendar_href == href)
+            .and_then(|a| a.sync_token.as_deref())
     }
 
     /// Set sync token for a calendar.
-    pub fn set_sync_token(&mut self, href: &str, token: &str) {
+    pub fn set_sync_token(&mut self, account: &str, href: &str, token: &str) {
         if let Some(existing) = self
-            .sync_tokens
+            .calendar_assignments
             .iter_mut()
-            .find(|(h, _)| h == href)
+            .find(|a| a.account == account && a.calendar_href == href)
         {
-            existing.1 = token.to_string();
-        } else {
-            self.sync_tokens
-                .push((href.to_string(), token.to_string()));
+            existing.sync_token = Some(token.to_string());
         }
     }
 
-    /// Whether CalDAV is configured with at least one calendar assigned.
-    pub fn sync_ready(&self) -> bool {
-        !self.calendars.url.is_empty()
-            && self
+    /// The account whose calendars contain a resource href.
+    pub fn account_for_href(&self, href: &str) -> Option<&str> {
+        self.calendar_assignments
+            .iter()
+            .filter(|a| a.purpose != CalendarPurpose::Disabled)
+            .find(|a| self.calendar_contains(a, href))
+            .map(|a| a.account.as_str())
+    }
+
+    /// Whether a resource href lies inside an assigned calendar. Both hrefs
+    /// are resolved against the account's URL, so calendars at the same path
+    /// on different servers stay apart.
+    fn calendar_contains(&self, assignment: &CalendarAssignment, href: &str) -> bool {
+        let base = self
+            .calendar_account(&assignment.account)
+            .map(|acc| acc.url.trim())
+            .unwrap_or_default();
+        let (cal_origin, cal_path) = split_href(&assignment.calendar_href, base);
+        let (origin, path) = split_href(href, base);
+        origin == cal_origin && path.starts_with(cal_path)
+    }
+
+    /// Our own mail addresses, to find ourselves among an event's attendees:
+    /// the calendar and IMAP usernames that look like addresses.
+    pub fn own_addresses(&self) -> Vec<String> {
+        let mut addresses: Vec<String> = self
+            .calendar_accounts
+            .iter()
+            .map(|a| a.username.trim())
+            .chain(std::iter::once(self.imap.username.trim()))
+            .filter(|u| u.contains('@'))
+            .map(str::to_string)
+            .collect();
+        addresses.sort_by_key(|a| a.to_lowercase());
+        addresses.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        addresses
+    }
+
+    /// Spoons per hour for events on a calendar: its own rate, else the default.
+    pub fn spoons_per_hour(&self, calendar_href: &str) -> u32 {
+        self.calendar_assignments
+            .iter()
+            .find(|a| a.calendar_href == calendar_href)
+            .and_then(|a| a.spoons_per_hour)
+            .unwrap_or(self.meeting_spoons_per_hour)
+    }
+
+    /// The task calendar a task syncs to, as (account id, calendar href).
+    /// Tasks of a linked project go to the project's list. Otherwise a task
+    /// already on the server stays where it is, then the first matching
+    /// route wins, then the first task calendar.
+    pub fn task_target(&self, task: &Task) -> Option<(&str, &str)> {
+        if let Some(link) = task.project.as_deref().and_then(|p| self.project_link(p)) {
+            return Some((&link.account, &link.calendar_href));
+        }
+
+        if let Some(ref href) = task.sync_href {
+            if let Some(a) = self.shared_task_calendars().find(|a| self.calendar_contains(a, href)) {
+                return Some((&a.account, &a.calendar_href));
+            }
+        }
+
+        for route in self.task_routes.iter().filter(|r| r.matches(task)) {
+            if let Some(a) = self.shared_task_calendars().find(|a| {
+                a.account == route.account && a.calendar_href == route.calendar_href
+            }) {
+                return Some((&a.account, &a.calendar_href));
+            }
+        }
+
+        self.shared_task_calendars()
+            .next()
+            .map(|a| (a.account.as_str(), a.calendar_href.as_str()))
+    }
+
+    /// Task calendars that aren't a single project's own list.
+    fn shared_task_calendars(&self) -> impl Iterator<Item = &CalendarAssignment> {
+        self.calendar_assignments
+            .iter()
+            .filter(|a| a.purpose == CalendarPurpose::Tasks && !self.is_project_list(&a.calendar_href))
+    }
+
+    /// Whether a calendar is a project's own task list.
+    pub fn is_project_list(&self, calendar_href: &str) -> bool {
+        self.project_links
+            .iter()
+            .any(|l| l.parent_href.is_none() && l.calendar_href == calendar_href)
+    }
+
+    pub fn project_link(&self, name: &str) -> Option<&ProjectLink> {
+        self.project_links.iter().find(|l| l.name == name)
+    }
+
+    /// Where a project without a link should be created: a route naming the
+    /// project, else the first task calendar. Returns (account id, calendar href).
+    pub fn project_home(&self, name: &str) -> Option<(&str, &str)> {
+        let routed = self
+            .task_routes
+            .iter()
+            .filter(|r| !r.key.starts_with('@') && r.key.eq_ignore_ascii_case(name))
+            .find_map(|r| {
+                self.shared_task_calendars()
+                    .find(|a| a.account == r.account && a.calendar_href == r.calendar_href)
+            });
+        routed
+            .or_else(|| self.shared_task_calendars().next())
+            .map(|a| (a.account.as_str(), a.calendar_href.as_str()))
+    }
+
+    /// The project a pulled task belongs to, judged by the list it lives in
+    /// or the parent VTODO it points at.
+    pub fn project_for_remote(&self, href: &str, parent: Option<&str>) -> Option<&str> {
+        self.project_links
+            .iter()
+            .find(|l| match l.parent_href {
+                None => href.starts_with(&l.calendar_href),
+                Some(_) => parent.is_some_and(|p| p == l.project.to_string()),
+            })
+            .map(|l| l.name.as_str())
+    }
+
+    /// Bring link names in line with the projects, so renames get pushed and
+    /// tasks keep matching. Links of deleted projects are left for the caller.
+    pub fn refresh_project_links(&mut self, projects: &[Project]) -> bool {
+        let mut changed = false;
+        for link in &mut self.project_links {
+            if let Some(project) = projects.iter().find(|p| p.id == link.project) {
+                if link.name != project.name {
+                    link.name = project.name.clone();
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Record a link created or renamed on the server. New project lists are
+    /// assigned as task calendars so they sync like any other.
+    pub fn set_project_link(&mut self, link: ProjectLink) {
+        if link.parent_href.is_none()
+            && !self
                 .calendar_assignments
                 .iter()
-                .any(|a| a.purpose != CalendarPurpose::Disabled)
+                .any(|a| a.account == link.account && a.calendar_href == link.calendar_href)
+        {
+            self.calendar_assignments.push(CalendarAssignment {
+                account: link.account.clone(),
+                calendar_href: link.calendar_href.clone(),
+                purpose: CalendarPurpose::Tasks,
+                sync_token: None,
+                spoons_per_hour: None,
+            });
+        }
+        self.project_links.retain(|l| l.project != link.project);
+        self.project_links.push(link);
+    }
+
+    /// Whether CalDAV is configured with at least one calendar assigned.
+    pub fn sync_ready(&self) -> bool {
+        !self.ready_calendar_accounts().is_empty()
     }
 
     /// Ensure the org directory and files exist.
@@ -265,3 +895,115 @@ impl LampConfig {
         Ok(())
     }
 }
+
+/// Scheme, lowercased host and port of an absolute URL.
+type Origin = (String, String, u16);
+
+/// Split an href into its origin and path. A bare path takes the origin of
+/// `base`; ports default by scheme.
+fn split_href<'a>(href: &'a str, base: &str) -> (Option<Origin>, &'a str) {
+    fn origin(url: &str) -> Option<(Origin, usize)> {
+        let scheme_end = url.find("://")?;
+        let scheme = url[..scheme_end].to_ascii_lowercase();
+        let rest = &url[scheme_end + 3..];
+        let authority_len = rest.find('/').unwrap_or(rest.len());
+        let authority = &rest[..authority_len];
+        let host_port = authority.rsplit('@').next().unwrap_or(authority);
+        let default_port = if scheme == "https" { 443 } else { 80 };
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, port.parse().ok()?),
+            _ => (host_port, default_port),
+        };
+        Some(((scheme, host.to_ascii_lowercase(), port), scheme_end + 3 + authority_len))
+    }
+
+    match origin(href) {
+        Some((o, path_start)) => (Some(o), &href[path_start..]),
+        None => (origin(base).map(|(o, _)| o), href),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignment(account: &str, href: &str, purpose: CalendarPurpose) -> CalendarAssignment {
+        CalendarAssignment {
+            account: account.to_string(),
+            calendar_href: href.to_string(),
+            purpose,
+            sync_token: None,
+            spoons_per_hour: None,
+        }
+    }
+
+    #[test]
+    fn migrate_moves_legacy_account_and_tokens() {
+        let mut config = LampConfig::default();
+        config.calendars.url = "https://dav.example.com/".to_string();
+        config.calendars.username = "me".to_string();
+        config.calendar_assignments = vec![assignment("", "/cal/tasks/", CalendarPurpose::Tasks)];
+        config.sync_tokens = vec![("/cal/tasks/".to_string(), "tok-1".to_string())];
+
+        assert!(config.migrate_accounts());
+        assert_eq!(config.calendar_accounts.len(), 1);
+        let id = config.calendar_accounts[0].id.clone();
+        assert!(!id.is_empty());
+        assert_eq!(config.calendar_accounts[0].username, "me");
+        assert!(config.calendars.url.is_empty());
+        assert!(config.sync_tokens.is_empty());
+        assert_eq!(config.get_sync_token(&id, "/cal/tasks/"), Some("tok-1"));
+
+        // Already migrated
+        assert!(!config.migrate_accounts());
+    }
+
+    #[test]
+    fn task_target_prefers_existing_href_then_routes() {
+        let mut config = LampConfig::default();
+        config.calendar_assignments = vec![
+            assignment("home", "/home/tasks/", CalendarPurpose::Tasks),
+            assignment("work", "/work/tasks/", CalendarPurpose::Tasks),
+            assignment("work", "/work/events/", CalendarPurpose::Events),
+        ];
+        config.task_routes = vec![TaskRoute {
+            key: "@work".to_string(),
+            account: "work".to_string(),
+            calendar_href: "/work/tasks/".to_string(),
+        }];
+
+        let mut task = Task::new("Write report");
+        assert_eq!(config.task_target(&task), Some(("home", "/home/tasks/")));
+
+        task.contexts.push("@Work".to_string());
+        assert_eq!(config.task_target(&task), Some(("work", "/work/tasks/")));
+
+        task.sync_href = Some("/home/tasks/abc.ics".to_string());
+        assert_eq!(config.task_target(&task), Some(("home", "/home/tasks/")));
+    }
+
+    #[test]
+    fn hrefs_match_on_server_as_well_as_path() {
+        let mut config = LampConfig::default();
+        for (id, url) in [("a", "https://dav.one.example/"), ("b", "https://DAV.two.example:8443/dav")] {
+            config.calendar_accounts.push(ServiceConfig {
+                id: id.to_string(),
+                url: url.to_string(),
+                ..ServiceConfig::default()
+            });
+        }
+        config.calendar_assignments = vec![
+            assignment("a", "/cal/tasks/", CalendarPurpose::Tasks),
+            assignment("b", "/cal/tasks/", CalendarPurpose::Tasks),
+        ];
+
+        assert_eq!(config.account_for_href("https://dav.one.example:443/cal/tasks/x.ics"), Some("a"));
+        assert_eq!(config.account_for_href("https://dav.two.example:8443/cal/tasks/x.ics"), Some("b"));
+        assert_eq!(config.account_for_href("https://dav.two.example/cal/tasks/x.ics"), None);
+        assert_eq!(config.account_for_href("/cal/other/x.ics"), None);
+
+        let mut task = Task::new("Call back");
+        task.sync_href = Some("https://dav.two.example:8443/cal/tasks/y.ics".to_string());
+        assert_eq!(config.task_target(&task), Some(("b", "/cal/tasks/")));
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+/// When an alarm goes off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlarmTrigger {
+    /// Minutes from the start (or end), negative for before.
+    Relative { minutes: i64, from_end: bool },
+    /// A fixed time, in UTC.
+    Absolute(NaiveDateTime),
+}
+
+/// What an alarm does. Other clients' actions (AUDIO, PROCEDURE) are kept by name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlarmAction {
+    Display,
+    Email,
+    Other(String),
+}
+
+impl AlarmAction {
+    pub fn as_ical(&self) -> &str {
+        match self {
+            AlarmAction::Display => "DISPLAY",
+            AlarmAction::Email => "EMAIL",
+            AlarmAction::Other(name) => name,
+        }
+    }
+
+    pub fn from_ical(value: &str) -> Self {
+        match value.trim().to_uppercase().as_str() {
+            "DISPLAY" => AlarmAction::Display,
+            "EMAIL" => AlarmAction::Email,
+            other => AlarmAction::Other(other.to_string()),
+        }
+    }
+}
+
+/// A reminder on an event or task (a VALARM).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alarm {
+    pub trigger: AlarmTrigger,
+    pub action: AlarmAction,
+    #[serde(default)]
+    pub description: String,
+    /// VALARM properties Lamp doesn't interpret (REPEAT, DURATION, ATTENDEE,
+    /// SUMMARY...), as unfolded lines written back unchanged.
+    #[serde(default)]
+    pub extra: Vec<String>,
+}
+
+impl Alarm {
+    /// A notification `minutes` before the start.
+    pub fn before_start(minutes: i64) -> Self {
+        Self {
+            trigger: AlarmTrigger::Relative { minutes: -minutes, from_end: false },
+            action: AlarmAction::Display,
+            description: String::new(),
+            extra: Vec::new(),
+        }
+    }
+
+    /// When the alarm fires for something running from `start` to `end` (both UTC).
+    pub fn fires_at(&self, start: NaiveDateTime, end: NaiveDateTime) -> NaiveDateTime {
+        match self.trigger {
+            AlarmTrigger::Relative { minutes, from_end } => {
+                (if from_end { end } else { start }) + Duration::minutes(minutes)
+            }
+            AlarmTrigger::Absolute(at) => at,
+        }
+    }
+
+    /// Compact form for org properties: `<trigger> <action>`, e.g. `-PT15M DISPLAY`,
+    /// `END-PT5M EMAIL` or `20260301T090000Z DISPLAY`.
+    pub fn to_org(&self) -> String {
+        let trigger = match self.trigger {
+            AlarmTrigger::Relative { minutes, from_end } => {
+                format!("{}{}", if from_end { "END" } else { "" }, format_duration(minutes))
+            }
+            AlarmTrigger::Absolute(at) => at.format("%Y%m%dT%H%M%SZ").to_string(),
+        };
+        format!("{} {}", trigger, self.action.as_ical())
+    }
+
+    /// Parse the form written by `to_org`.
+    pub fn parse_org(s: &str) -> Option<Self> {
+        let (trigger, action) = s.trim().split_once(' ')?;
+        let trigger = match trigger.strip_prefix("END") {
+            Some(rest) => AlarmTrigger::Relative { minutes: parse_duration(rest)?, from_end: true },
+            None if trigger.starts_with(['P', '-', '+']) => {
+                AlarmTrigger::Relative { minutes: parse_duration(trigger)?, from_end: false }
+            }
+            None => AlarmTrigger::Absolute(
+                NaiveDateTime::parse_from_str(trigger.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()?,
+            ),
+        };
+        Some(Self {
+            trigger,
+            action: AlarmAction::from_ical(action),
+            description: String::new(),
+            extra: Vec::new(),
+        })
+    }
+}
+
+/// Alarms as one org property value, separated by commas.
+pub fn alarms_to_org(alarms: &[Alarm]) -> String {
+    alarms.iter().map(Alarm::to_org).collect::<Vec<_>>().join(", ")
+}
+
+pub fn alarms_from_org(s: &str) -> Vec<Alarm> {
+    s.split(',').filter_map(Alarm::parse_org).collect()
+}
+
+/// Minutes in an RFC 5545 duration such as `-PT15M`, `P1D` or `-P1DT2H30M`.
+/// Seconds are dropped.
+pub fn parse_duration(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (sign, rest) = match s.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let rest = rest.strip_prefix('P')?;
+    let mut minutes = 0i64;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                minutes += match (unit, in_time) {
+                    ('W', false) => n * 7 * 24 * 60,
+                    ('D', false) => n * 24 * 60,
+                    ('H', true) => n * 60,
+                    ('M', true) => n,
+                    ('S', true) => n / 60,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    number.is_empty().then_some(sign * minutes)
+}
+
+/// An RFC 5545 duration for a number of minutes, in the largest whole unit.
+pub fn format_duration(minutes: i64) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    let m = minutes.abs();
+    let body = if m == 0 {
+        "PT0S".to_string()
+    } else if m % (7 * 24 * 60) == 0 {
+        format!("P{}W", m / (7 * 24 * 60))
+    } else if m % (24 * 60) == 0 {
+        format!("P{}D", m / (24 * 60))
+    } else if m % 60 == 0 {
+        format!("PT{}H", m / 60)
+    } else {
+        format!("PT{}M", m)
+    };
+    format!("{}{}", sign, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_and_org_form_roundtrip() {
+        assert_eq!(parse_duration("-PT15M"), Some(-15));
+        assert_eq!(parse_duration("P1DT2H30M"), Some(24 * 60 + 150));
+        assert_eq!(parse_duration("-P1W"), Some(-7 * 24 * 60));
+        assert_eq!(parse_duration("PT0S"), Some(0));
+        assert_eq!(parse_duration("15M"), None);
+        assert_eq!(format_duration(-90), "-PT90M");
+        assert_eq!(format_duration(-1440), "-P1D");
+
+        let at = NaiveDateTime::parse_from_str("2026-03-01 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let alarms = vec![
+            Alarm::before_start(15),
+            Alarm {
+                trigger: AlarmTrigger::Relative { minutes: -5, from_end: true },
+                action: AlarmAction::Email,
+                description: String::new(),
+                extra: Vec::new(),
+            },
+            Alarm { trigger: AlarmTrigger::Absolute(at), ..Alarm::before_start(0) },
+        ];
+        let org = alarms_to_org(&alarms);
+        assert_eq!(org, "-PT15M DISPLAY, END-PT5M EMAIL, 20260301T090000Z DISPLAY");
+        assert_eq!(alarms_from_org(&org), alarms);
+        assert_eq!(alarms[0].fires_at(at, at), at - Duration::minutes(15));
+    }
+}
@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use uuid::Uuid;
+
+use super::task::Priority;
+
+/// Directory next to the org files that captured attachments go into.
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+/// A task written on one line, e.g. `Call Sam about the roof @phone #house !A`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capture {
+    pub title: String,
+    pub contexts: Vec<String>,
+    pub project: Option<String>,
+    pub priority: Option<Priority>,
+}
+
+/// Reply and forward markers mail clients put in front of a subject.
+const SUBJECT_PREFIXES: &[&str] = &["fwd:", "fw:", "re:", "tr:", "wg:", "aw:"];
+
+/// The subject without any leading `Fwd:`/`Re:` markers.
+pub fn strip_subject_prefixes(subject: &str) -> &str {
+    let mut rest = subject.trim();
+    while let Some(prefix) = SUBJECT_PREFIXES
+        .iter()
+        .find(|p| rest.get(..p.len()).is_some_and(|head| head.eq_ignore_ascii_case(p)))
+    {
+        rest = rest[prefix.len()..].trim_start();
+    }
+    rest
+}
+
+fn squash(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Pull `@context`, `#project` and `!A` markers out of a line. Only known
+/// contexts and projects are taken (projects ignoring case, spaces and
+/// punctuation, so `#roof-repair` finds "Roof repair"); anything else stays
+/// in the title.
+pub fn parse_inline(line: &str, contexts: &[String], projects: &[String]) -> Capture {
+    let mut capture = Capture {
+        title: String::new(),
+        contexts: Vec::new(),
+        project: None,
+        priority: None,
+    };
+    let mut words = Vec::new();
+    for word in line.split_whitespace() {
+        if let Some(name) = word.strip_prefix('@').filter(|n| !n.is_empty()) {
+            let known = contexts
+                .iter()
+                .find(|c| c.trim_start_matches('@').eq_ignore_ascii_case(name));
+            if let Some(ctx) = known {
+                if !capture.contexts.contains(ctx) {
+                    capture.contexts.push(ctx.clone());
+                }
+                continue;
+            }
+        } else if let Some(name) = word.strip_prefix('#').filter(|n| !n.is_empty()) {
+            let wanted = squash(name);
+            if let Some(project) = projects.iter().find(|p| squash(p) == wanted) {
+                capture.project = Some(project.clone());
+                continue;
+            }
+        } else if let Some(p) = word.strip_prefix('!') {
+            if let Some(priority) = Priority::from_org(&p.to_uppercase()) {
+                capture.priority = Some(priority);
+                continue;
+            }
+        }
+        words.push(word);
+    }
+    capture.title = words.join(" ");
+    capture
+}
+
+/// A file name that can't leave its directory or break an org link.
+fn safe_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | '[' | ']' | ':' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    match name.trim().trim_start_matches('.') {
+        "" => "attachment".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Write a task's attachments to `attachments/<task id>/` under `org_dir`
+/// and return an org link to each, relative to the org files.
+pub fn save_attachments(
+    org_dir: &Path,
+    task_id: Uuid,
+    attachments: &[(String, Vec<u8>)],
+) -> Result<Vec<String>, String> {
+    if attachments.is_empty() {
+        return Ok(Vec::new());
+    }
+    let relative = Path::new(ATTACHMENTS_DIR).join(task_id.to_string());
+    let dir = org_dir.join(&relative);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let mut links = Vec::new();
+    for (name, contents) in attachments {
+        let mut file_name = safe_file_name(name);
+        // Two attachments with the same name both survive
+        let mut n = 1;
+        while dir.join(&file_name).exists() {
+            n += 1;
+            file_name = format!("{}-{}", n, safe_file_name(name));
+        }
+        let path = dir.join(&file_name);
+        std::fs::write(&path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        links.push(format!(
+            "[[file:{}][{}]]",
+            relative.join(&file_name).display(),
+            file_name
+        ));
+    }
+    Ok(links)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markers_come_out_of_the_title() {
+        let contexts = vec!["@phone".to_string(), "@errands".to_string()];
+        let projects = vec!["Roof repair".to_string()];
+
+        let capture = parse_inline(
+            "Call Sam @Phone about the quote #roof-repair !a",
+            &contexts,
+            &projects,
+        );
+        assert_eq!(capture.title, "Call Sam about the quote");
+        assert_eq!(capture.contexts, vec!["@phone".to_string()]);
+        assert_eq!(capture.project.as_deref(), Some("Roof repair"));
+        assert_eq!(capture.priority, Some(Priority::A));
+
+        // Unknown markers are just words
+        let capture = parse_inline("Book #1 @gym !now", &contexts, &projects);
+        assert_eq!(capture.title, "Book #1 @gym !now");
+        assert!(capture.contexts.is_empty() && capture.project.is_none() && capture.priority.is_none());
+
+        assert_eq!(strip_subject_prefixes("Fwd: RE: fw:Invoice @errands"), "Invoice @errands");
+        assert_eq!(strip_subject_prefixes("Reply needed"), "Reply needed");
+    }
+
+    #[test]
+    fn attachments_are_saved_and_linked() {
+        let dir = std::env::temp_dir().join(format!("lamp-capture-{}", Uuid::new_v4()));
+        let task_id = Uuid::new_v4();
+        let links = save_attachments(
+            &dir,
+            task_id,
+            &[
+                ("../quote [v2].pdf".to_string(), b"%PDF".to_vec()),
+                ("../quote [v2].pdf".to_string(), b"%PDF again".to_vec()),
+            ],
+        )
+        .unwrap();
+        let folder = format!("attachments/{}", task_id);
+        assert_eq!(
+            links,
+            vec![
+                format!("[[file:{}/_quote _v2_.pdf][_quote _v2_.pdf]]", folder),
+                format!("[[file:{}/2-_quote _v2_.pdf][2-_quote _v2_.pdf]]", folder),
+            ]
+        );
+        assert_eq!(
+            std::fs::read(dir.join(&folder).join("2-_quote _v2_.pdf")).unwrap(),
+            b"%PDF again"
+        );
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
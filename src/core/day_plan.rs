@@ -1,6 +1,9 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
 use uuid::Uuid;
 
+use super::event::{CalendarEvent, EventStatus};
+use super::timeblock;
+
 /// A completed task entry: (id, title, esc).
 #[derive(Debug, Clone)]
 pub struct CompletedTask {
@@ -59,3 +62,114 @@ impl DayPlan {
         }
     }
 }
+
+/// Spoons an event costs: its own ESC, else `spoons_per_hour` of its
+/// duration, rounded to the nearest spoon. All-day and cancelled events are free
+/// unless they carry an ESC. Time blocks are free too: their task's ESC is
+/// spent when the task is done.
+pub fn event_spoons(event: &CalendarEvent, spoons_per_hour: u32) -> u32 {
+    match event.esc {
+        _ if event.task_id.is_some() => 0,
+        Some(esc) => esc,
+        None if event.all_day || event.status == EventStatus::Cancelled => 0,
+        None => {
+            let minutes = (event.local_end() - event.local_start()).num_minutes().max(0) as u64;
+            ((minutes * u64::from(spoons_per_hour) + 30) / 60) as u32
+        }
+    }
+}
+
+/// What today's calendar leaves for tasks.
+#[derive(Debug, Clone, Default)]
+pub struct DayLoad {
+    /// Spoons today's events cost, taken off the budget before planning.
+    pub event_spoons: u32,
+    /// Free local intervals in the rest of the working day.
+    pub free: Vec<(NaiveDateTime, NaiveDateTime)>,
+}
+
+impl DayLoad {
+    /// Load of one day's `events`, with free time between `from` and `until`.
+    /// `spoons_for` prices each event (see `event_spoons`).
+    pub fn build(
+        events: &[CalendarEvent],
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+        spoons_for: impl Fn(&CalendarEvent) -> u32,
+    ) -> Self {
+        let event_spoons = events.iter().map(&spoons_for).sum();
+        let free = if from < until {
+            timeblock::free_intervals(events, from, until)
+        } else {
+            Vec::new()
+        };
+        Self { event_spoons, free }
+    }
+
+    pub fn free_minutes(&self) -> i64 {
+        self.free.iter().map(|(start, end)| (*end - *start).num_minutes()).sum()
+    }
+
+    /// Length of the longest free stretch, in minutes.
+    pub fn longest_free(&self) -> i64 {
+        self.free
+            .iter()
+            .map(|(start, end)| (*end - *start).num_minutes())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether a task taking `minutes` fits in one free stretch.
+    pub fn fits(&self, minutes: u32) -> bool {
+        i64::from(minutes) <= self.longest_free()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(h, m, 0)
+            .unwrap()
+    }
+
+    fn meeting(start: NaiveDateTime, end: NaiveDateTime) -> CalendarEvent {
+        let mut event = CalendarEvent::new("Meeting".to_string(), start, end);
+        event.tzid = None;
+        event
+    }
+
+    #[test]
+    fn prices_events_by_duration_unless_costed() {
+        let mut event = meeting(at(9, 0), at(10, 30));
+        assert_eq!(event_spoons(&event, 10), 15);
+        assert_eq!(event_spoons(&meeting(at(9, 0), at(9, 20)), 10), 3);
+        event.esc = Some(4);
+        assert_eq!(event_spoons(&event, 10), 4);
+        event.task_id = Some(Uuid::new_v4());
+        assert_eq!(event_spoons(&event, 10), 0);
+
+        let mut all_day = meeting(at(0, 0), at(0, 0) + chrono::Duration::days(1));
+        all_day.all_day = true;
+        assert_eq!(event_spoons(&all_day, 10), 0);
+    }
+
+    #[test]
+    fn day_load_leaves_free_time_between_events() {
+        let events = vec![meeting(at(9, 0), at(10, 0)), meeting(at(13, 0), at(15, 0))];
+        let load = DayLoad::build(&events, at(8, 0), at(17, 0), |e| event_spoons(e, 10));
+        assert_eq!(load.event_spoons, 30);
+        assert_eq!(load.free, vec![(at(8, 0), at(9, 0)), (at(10, 0), at(13, 0)), (at(15, 0), at(17, 0))]);
+        assert_eq!(load.free_minutes(), 360);
+        assert!(load.fits(180));
+        assert!(!load.fits(181));
+
+        // After hours nothing is free, but the meetings still cost
+        let late = DayLoad::build(&events, at(18, 0), at(17, 0), |e| event_spoons(e, 10));
+        assert!(late.free.is_empty());
+        assert_eq!(late.event_spoons, 30);
+    }
+}
@@ -0,0 +1,321 @@
+use chrono::{Duration, NaiveDate};
+use uuid::Uuid;
+
+use super::task::{Task, TaskState};
+use crate::sync::carddav::Contact;
+
+/// Days after delegation before a WAITING task without an explicit
+/// follow-up date is considered due for a nudge.
+pub const DEFAULT_FOLLOW_UP_DAYS: i64 = 7;
+
+/// Who a WAITING task is waiting on: a linked contact, or free text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WaitingParty {
+    Contact(Uuid),
+    Name(String),
+}
+
+/// All WAITING tasks delegated to the same person.
+#[derive(Debug, Clone)]
+pub struct WaitingGroup<'a> {
+    /// `None` for tasks with no one recorded in `waiting_for`.
+    pub party: Option<WaitingParty>,
+    pub name: String,
+    pub contact: Option<&'a Contact>,
+    pub tasks: Vec<&'a Task>,
+}
+
+/// A message to someone we're waiting on: a delegation request or a
+/// follow-up listing everything outstanding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowUpDraft {
+    pub recipient: String,
+    pub email: Option<String>,
+    pub subject: String,
+    pub body: String,
+}
+
+impl FollowUpDraft {
+    /// A `mailto:` URL for the draft, if the recipient has an email address.
+    pub fn mailto_url(&self) -> Option<String> {
+        let email = self.email.as_deref().filter(|e| !e.is_empty())?;
+        Some(format!(
+            "mailto:{}?subject={}&body={}",
+            email,
+            percent_encode(&self.subject),
+            percent_encode(&self.body)
+        ))
+    }
+}
+
+/// Find a contact by exact (case-insensitive) name.
+pub fn contact_by_name<'a>(name: &str, contacts: &'a [Contact]) -> Option<&'a Contact> {
+    let name = name.trim();
+    contacts.iter().find(|c| c.name.eq_ignore_ascii_case(name))
+}
+
+/// Resolve the contact a task is waiting on: by linked ID first, then by name.
+pub fn resolve_contact<'a>(task: &Task, contacts: &'a [Contact]) -> Option<&'a Contact> {
+    if let Some(id) = task.waiting_for_contact {
+        if let Some(c) = contacts.iter().find(|c| c.id == id) {
+            return Some(c);
+        }
+    }
+    task.waiting_for
+        .as_deref()
+        .and_then(|name| contact_by_name(name, contacts))
+}
+
+/// The date a WAITING task should be followed up on: the explicit
+/// `follow_up`, else `delegated` plus [`DEFAULT_FOLLOW_UP_DAYS`].
+pub fn follow_up_date(task: &Task) -> Option<NaiveDate> {
+    task.follow_up
+        .or_else(|| task.delegated.map(|d| d + Duration::days(DEFAULT_FOLLOW_UP_DAYS)))
+}
+
+/// Whether a WAITING task's follow-up date has arrived.
+pub fn is_follow_up_due(task: &Task, today: NaiveDate) -> bool {
+    task.state == TaskState::Waiting && follow_up_date(task).is_some_and(|d| d <= today)
+}
+
+/// WAITING tasks whose follow-up is due, oldest follow-up date first.
+pub fn overdue_follow_ups<'a>(
+    tasks: impl Iterator<Item = &'a Task>,
+    today: NaiveDate,
+) -> Vec<&'a Task> {
+    let mut due: Vec<&Task> = tasks.filter(|t| is_follow_up_due(t, today)).collect();
+    due.sort_by_key(|t| follow_up_date(t));
+    due
+}
+
+/// Group WAITING tasks by the person they're waiting on.
+/// Named groups are sorted alphabetically; tasks with no one recorded come last.
+pub fn group_waiting<'a>(
+    tasks: impl Iterator<Item = &'a Task>,
+    contacts: &'a [Contact],
+) -> Vec<WaitingGroup<'a>> {
+    let mut groups: Vec<WaitingGroup<'a>> = Vec::new();
+
+    for task in tasks.filter(|t| t.state == TaskState::Waiting) {
+        let (party, name, contact) = match resolve_contact(task, contacts) {
+            Some(c) => (Some(WaitingParty::Contact(c.id)), c.name.clone(), Some(c)),
+            None => match task.waiting_for.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+                Some(wf) => (Some(WaitingParty::Name(wf.to_string())), wf.to_string(), None),
+                None => (None, String::new(), None),
+            },
+        };
+
+        let existing = groups.iter_mut().find(|g| match (&g.party, &party) {
+            (Some(WaitingParty::Name(a)), Some(WaitingParty::Name(b))) => a.eq_ignore_ascii_case(b),
+            (a, b) => a == b,
+        });
+        match existing {
+            Some(g) => g.tasks.push(task),
+            None => groups.push(WaitingGroup {
+                party,
+                name,
+                contact,
+                tasks: vec![task],
+            }),
+        }
+    }
+
+    groups.sort_by(|a, b| {
+        a.party
+            .is_none()
+            .cmp(&b.party.is_none())
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    groups
+}
+
+/// Compose a follow-up listing every outstanding item in a group.
+pub fn compose_follow_up(group: &WaitingGroup, today: NaiveDate) -> FollowUpDraft {
+    let first_name = group.name.split_whitespace().next().unwrap_or(&group.name);
+
+    let subject = match group.tasks.as_slice() {
+        [only] => format!("Following up: {}", only.title),
+        tasks => format!("Following up: {} open items", tasks.len()),
+    };
+
+    let mut body = format!("Hi {},\n\n", first_name);
+    if group.tasks.len() == 1 {
+        body.push_str("Just checking in on something I'm waiting on from you:\n\n");
+    } else {
+        body.push_str("Just checking in on a few things I'm waiting on from you:\n\n");
+    }
+    for task in &group.tasks {
+        body.push_str(&format!("- {}", task.title));
+        let mut details = Vec::new();
+        if let Some(d) = task.delegated {
+            let days = (today - d).num_days();
+            details.push(format!("asked {} ({} days ago)", d.format("%b %d"), days));
+        }
+        if let Some(d) = task.deadline {
+            details.push(format!("needed by {}", d.format("%b %d")));
+        }
+        if !details.is_empty() {
+            body.push_str(&format!(" — {}", details.join(", ")));
+        }
+        body.push('\n');
+    }
+    body.push_str("\nCould you let me know where these stand?\n\nThanks!\n");
+
+    FollowUpDraft {
+        recipient: group.name.clone(),
+        email: group.contact.and_then(|c| c.email.clone()),
+        subject,
+        body,
+    }
+}
+
+/// Ask someone to take on a task, with its deadline and notes.
+pub fn compose_delegation(task: &Task, contact: &Contact) -> FollowUpDraft {
+    let first_name = contact.name.split_whitespace().next().unwrap_or(&contact.name);
+
+    let mut body = format!("Hi {},\n\nCould you take this on for me?\n\n{}\n", first_name, task.title);
+    if let Some(d) = task.deadline {
+        body.push_str(&format!("\nNeeded by {}.\n", d.format("%A, %b %d")));
+    }
+    let notes = task.notes.trim();
+    if !notes.is_empty() {
+        body.push_str(&format!("\n{}\n", notes));
+    }
+    body.push_str("\nThanks!\n");
+
+    FollowUpDraft {
+        recipient: contact.name.clone(),
+        email: contact.email.clone(),
+        subject: task.title.clone(),
+        body,
+    }
+}
+
+/// Percent-encode a string for use in a `mailto:` query component (RFC 6068).
+pub(crate) fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn waiting(title: &str, who: &str) -> Task {
+        let mut t = Task::new(title);
+        t.state = TaskState::Waiting;
+        t.waiting_for = Some(who.to_string());
+        t
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn resolves_by_id_before_name() {
+        let alice = Contact::new("Alice Smith".to_string());
+        let bob = Contact::new("Bob".to_string());
+        let contacts = vec![alice.clone(), bob.clone()];
+
+        let mut task = waiting("Send report", "Bob");
+        assert_eq!(resolve_contact(&task, &contacts).map(|c| c.id), Some(bob.id));
+
+        task.waiting_for_contact = Some(alice.id);
+        assert_eq!(resolve_contact(&task, &contacts).map(|c| c.id), Some(alice.id));
+    }
+
+    #[test]
+    fn groups_by_contact_with_free_text_fallback() {
+        let alice = Contact::new("Alice".to_string());
+        let contacts = vec![alice.clone()];
+
+        let mut linked = waiting("Review PR", "Alice (old name)");
+        linked.waiting_for_contact = Some(alice.id);
+        let by_name = waiting("Sign contract", "alice");
+        let free = waiting("Quote for roof", "Roofer");
+        let nobody = Task {
+            state: TaskState::Waiting,
+            ..Task::new("Parcel")
+        };
+        let tasks = vec![free, nobody, linked, by_name];
+
+        let groups = group_waiting(tasks.iter(), &contacts);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].name, "Alice");
+        assert_eq!(groups[0].tasks.len(), 2);
+        assert_eq!(groups[1].party, Some(WaitingParty::Name("Roofer".to_string())));
+        assert!(groups[2].party.is_none());
+    }
+
+    #[test]
+    fn follow_up_falls_back_to_delegated_date() {
+        let today = date(2026, 3, 10);
+        let mut explicit = waiting("A", "X");
+        explicit.follow_up = Some(date(2026, 3, 11));
+        let mut implied = waiting("B", "X");
+        implied.delegated = Some(date(2026, 3, 1));
+        let mut recent = waiting("C", "X");
+        recent.delegated = Some(date(2026, 3, 8));
+
+        let tasks = vec![explicit, implied, recent];
+        let due = overdue_follow_ups(tasks.iter(), today);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].title, "B");
+    }
+
+    #[test]
+    fn compose_lists_items_and_builds_mailto() {
+        let mut alice = Contact::new("Alice Smith".to_string());
+        alice.email = Some("alice@example.com".to_string());
+        let contacts = vec![alice];
+
+        let mut a = waiting("Review PR", "Alice Smith");
+        a.delegated = Some(date(2026, 3, 1));
+        let b = waiting("Sign contract", "Alice Smith");
+        let tasks = vec![a, b];
+
+        let groups = group_waiting(tasks.iter(), &contacts);
+        let draft = compose_follow_up(&groups[0], date(2026, 3, 10));
+        assert_eq!(draft.subject, "Following up: 2 open items");
+        assert!(draft.body.starts_with("Hi Alice,"));
+        assert!(draft.body.contains("- Review PR — asked Mar 01 (9 days ago)\n"));
+        assert!(draft.body.contains("- Sign contract\n"));
+
+        let url = draft.mailto_url().unwrap();
+        assert!(url.starts_with("mailto:alice@example.com?subject=Following%20up%3A%202%20open%20items&body=Hi%20Alice"));
+    }
+
+    #[test]
+    fn no_mailto_without_email() {
+        let tasks = vec![waiting("Quote", "Roofer")];
+        let groups = group_waiting(tasks.iter(), &[]);
+        let draft = compose_follow_up(&groups[0], date(2026, 3, 10));
+        assert_eq!(draft.subject, "Following up: Quote");
+        assert!(draft.mailto_url().is_none());
+    }
+
+    #[test]
+    fn delegation_has_deadline_and_notes() {
+        let mut sam = Contact::new("Sam Lee".to_string());
+        sam.email = Some("sam@example.com".to_string());
+        let mut task = waiting("Get roof quote", "Sam Lee");
+        task.deadline = Some(date(2026, 3, 13));
+        task.notes = "Two storeys, slate.".to_string();
+
+        let draft = compose_delegation(&task, &sam);
+        assert_eq!(draft.subject, "Get roof quote");
+        assert_eq!(draft.email.as_deref(), Some("sam@example.com"));
+        assert_eq!(
+            draft.body,
+            "Hi Sam,\n\nCould you take this on for me?\n\nGet roof quote\n\n\
+             Needed by Friday, Mar 13.\n\nTwo storeys, slate.\n\nThanks!\n"
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+use super::delegation::percent_encode;
+
+/// The email a task was created from, so it can be found again after archiving.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailLink {
+    /// `Message-ID` without the angle brackets.
+    pub message_id: String,
+    /// IMAP folder the email was in when the task was made.
+    pub folder: String,
+}
+
+impl EmailLink {
+    /// Org property form: `<message-id> folder`. Message-IDs have no spaces,
+    /// folder names may.
+    pub fn to_org(&self) -> String {
+        format!("<{}> {}", self.message_id, self.folder)
+    }
+
+    pub fn from_org(s: &str) -> Option<Self> {
+        let rest = s.trim().strip_prefix('<')?;
+        let (message_id, folder) = rest.split_once('>')?;
+        if message_id.is_empty() {
+            return None;
+        }
+        Some(Self {
+            message_id: message_id.to_string(),
+            folder: folder.trim().to_string(),
+        })
+    }
+
+    /// A `mid:` URL (RFC 2392) that mail clients open the message from.
+    pub fn mid_url(&self) -> String {
+        format!("mid:{}", percent_encode(&self.message_id).replace("%40", "@"))
+    }
+}
+
+/// The first lines of the email body kept in a task's notes, skipping the
+/// `From:`/`Date:` header Lamp writes above it and quoted earlier mail.
+pub fn excerpt(notes: &str, max_chars: usize) -> Option<String> {
+    let body = match notes.split_once("\n\n") {
+        Some((head, body)) if head.lines().all(|l| l.starts_with("From: ") || l.starts_with("Date: ")) => body,
+        _ => notes,
+    };
+    let text = body
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('>'))
+        .take(3)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().count() <= max_chars {
+        return Some(text);
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    Some(format!("{}…", cut.trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn org_form_url_and_excerpt() {
+        let link = EmailLink {
+            message_id: "q1/2@mail.example.com".to_string(),
+            folder: "Follow up".to_string(),
+        };
+        assert_eq!(link.to_org(), "<q1/2@mail.example.com> Follow up");
+        assert_eq!(EmailLink::from_org(&link.to_org()), Some(link.clone()));
+        assert_eq!(EmailLink::from_org("no brackets"), None);
+        assert_eq!(link.mid_url(), "mid:q1%2F2@mail.example.com");
+
+        let notes = "From: Sam Lee\nDate: 2026-03-02 09:30\n\nCan you approve the quote\nby Friday?\n\n> earlier mail";
+        assert_eq!(excerpt(notes, 80).as_deref(), Some("Can you approve the quote by Friday?"));
+        assert_eq!(excerpt(notes, 10).as_deref(), Some("Can you ap…"));
+        assert_eq!(excerpt("From: Sam\n\n", 80), None);
+    }
+}
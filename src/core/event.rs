@@ -1,8 +1,12 @@
-use chrono::NaiveDateTime;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use uuid::Uuid;
 
+use super::alarm::Alarm;
+use super::rrule::{self, RecurrenceRule};
+use super::timezone;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EventStatus {
     Confirmed,
@@ -10,40 +14,475 @@ pub enum EventStatus {
     Cancelled,
 }
 
+/// An attendee's answer to an invitation (RFC 5545 PARTSTAT).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PartStat {
+    #[default]
+    NeedsAction,
+    Accepted,
+    Tentative,
+    Declined,
+    Delegated,
+}
+
+impl PartStat {
+    pub fn as_ical(&self) -> &'static str {
+        match self {
+            PartStat::NeedsAction => "NEEDS-ACTION",
+            PartStat::Accepted => "ACCEPTED",
+            PartStat::Tentative => "TENTATIVE",
+            PartStat::Declined => "DECLINED",
+            PartStat::Delegated => "DELEGATED",
+        }
+    }
+
+    pub fn from_ical(value: &str) -> Self {
+        match value.to_uppercase().as_str() {
+            "ACCEPTED" => PartStat::Accepted,
+            "TENTATIVE" => PartStat::Tentative,
+            "DECLINED" => PartStat::Declined,
+            "DELEGATED" => PartStat::Delegated,
+            _ => PartStat::NeedsAction,
+        }
+    }
+}
+
+/// An ATTENDEE or ORGANIZER of an event.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attendee {
+    /// Address without the `mailto:` prefix.
+    pub email: String,
+    /// Common name (CN), if given.
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub partstat: PartStat,
+    /// Whether the organizer asked for a reply.
+    #[serde(default)]
+    pub rsvp: bool,
+}
+
+impl Attendee {
+    /// The name, falling back to the address.
+    pub fn label(&self) -> &str {
+        if self.name.is_empty() { &self.email } else { &self.name }
+    }
+}
+
+/// Which occurrences of a recurring event an edit or delete applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditScope {
+    This,
+    Following,
+    All,
+}
+
+/// A single event, or the master of a recurring series. Occurrences are only
+/// expanded for display (see `occurrences`); the series is what gets stored
+/// and synced.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalendarEvent {
     pub id: Uuid,
     pub title: String,
+    /// Wall-clock start and end in `tzid`'s zone. Use `local_start`/`local_end` for display.
     pub start: NaiveDateTime,
     pub end: NaiveDateTime,
+    /// IANA zone the event was written in, "UTC" for UTC times, `None` for
+    /// floating times and all-day events.
+    #[serde(default)]
+    pub tzid: Option<String>,
     pub all_day: bool,
     pub location: String,
     pub description: String,
     pub status: EventStatus,
     pub calendar_href: String,
     pub calendar_name: String,
+    /// RRULE value of a recurring series.
+    #[serde(default)]
+    pub rrule: Option<String>,
+    /// Original starts of cancelled occurrences, in the event's zone.
+    #[serde(default)]
+    pub exdates: Vec<NaiveDateTime>,
+    /// Individually edited occurrences (RECURRENCE-ID components).
+    #[serde(default)]
+    pub overrides: Vec<CalendarEvent>,
+    /// On an override or expanded occurrence: the original start it stands for.
+    #[serde(default)]
+    pub recurrence_id: Option<NaiveDateTime>,
+    /// Spoon cost, overriding the default by duration (see `day_plan::event_spoons`).
+    #[serde(default)]
+    pub esc: Option<u32>,
+    /// Task this event is a time block for (see `timeblock`).
+    #[serde(default)]
+    pub task_id: Option<Uuid>,
+    /// Who sent the invitation, for meetings.
+    #[serde(default)]
+    pub organizer: Option<Attendee>,
+    #[serde(default)]
+    pub attendees: Vec<Attendee>,
+    /// Reminders (VALARM components).
+    #[serde(default)]
+    pub alarms: Vec<Alarm>,
+    /// VEVENT properties and components Lamp doesn't interpret, as unfolded
+    /// lines written back unchanged so other clients' data survives an edit.
+    #[serde(default)]
+    pub extra_properties: Vec<String>,
     // Sync metadata
     pub sync_href: Option<String>,
     pub sync_hash: Option<u64>,
+    /// Original UID when it isn't `id` (non-UUID UIDs from other clients).
+    #[serde(default)]
+    pub sync_uid: Option<String>,
 }
 
+/// `calendar_href` prefix of events from read-only ICS subscriptions.
+pub const SUBSCRIPTION_HREF_PREFIX: &str = "subscription:";
+
 impl CalendarEvent {
+    /// New event at local `start`/`end`, pinned to the system zone.
     pub fn new(title: String, start: NaiveDateTime, end: NaiveDateTime) -> Self {
         Self {
             id: Uuid::new_v4(),
             title,
             start,
             end,
+            tzid: timezone::local_zone_name(),
             all_day: false,
             location: String::new(),
             description: String::new(),
             status: EventStatus::Confirmed,
             calendar_href: String::new(),
             calendar_name: String::new(),
+            rrule: None,
+            exdates: Vec::new(),
+            overrides: Vec::new(),
+            recurrence_id: None,
+            esc: None,
+            task_id: None,
+            organizer: None,
+            attendees: Vec::new(),
+            alarms: Vec::new(),
+            extra_properties: Vec::new(),
             sync_href: None,
             sync_hash: None,
+            sync_uid: None,
         }
     }
+
+    fn zone(&self) -> Option<&str> {
+        if self.all_day { None } else { self.tzid.as_deref() }
+    }
+
+    /// Start time in the system zone.
+    pub fn local_start(&self) -> NaiveDateTime {
+        timezone::to_local(self.start, self.zone())
+    }
+
+    /// End time in the system zone.
+    pub fn local_end(&self) -> NaiveDateTime {
+        timezone::to_local(self.end, self.zone())
+    }
+
+    /// Set start and end from system-local times, keeping the event's own zone.
+    pub fn set_local_times(&mut self, start: NaiveDateTime, end: NaiveDateTime) {
+        let zone = self.zone().map(str::to_string);
+        self.start = timezone::convert(start, None, zone.as_deref());
+        self.end = timezone::convert(end, None, zone.as_deref());
+    }
+
+    /// The attendee entry for one of `addresses` (ours), if we're invited.
+    pub fn attendee_for(&self, addresses: &[String]) -> Option<&Attendee> {
+        self.attendees
+            .iter()
+            .find(|a| addresses.iter().any(|addr| addr.eq_ignore_ascii_case(&a.email)))
+    }
+
+    /// Record our answer to the invitation. Returns the updated attendee,
+    /// or `None` when none of `addresses` is invited.
+    pub fn set_partstat(&mut self, addresses: &[String], partstat: PartStat) -> Option<Attendee> {
+        let attendee = self
+            .attendees
+            .iter_mut()
+            .find(|a| addresses.iter().any(|addr| addr.eq_ignore_ascii_case(&a.email)))?;
+        attendee.partstat = partstat;
+        attendee.rsvp = false;
+        Some(attendee.clone())
+    }
+
+    /// Whether the event comes from a read-only ICS subscription.
+    pub fn is_subscribed(&self) -> bool {
+        self.calendar_href.starts_with(SUBSCRIPTION_HREF_PREFIX)
+    }
+
+    pub fn is_recurring(&self) -> bool {
+        self.rrule.is_some()
+    }
+
+    pub fn recurrence_rule(&self) -> Option<RecurrenceRule> {
+        RecurrenceRule::parse(self.rrule.as_deref()?, self.zone())
+    }
+
+    /// Whether single occurrences can be edited or deleted. Rules with parts
+    /// Lamp can't expand might not produce the occurrences other clients see,
+    /// so exceptions keyed to them could land on the wrong day.
+    pub fn occurrences_editable(&self) -> bool {
+        self.rrule.is_none() || self.recurrence_rule().is_some_and(|r| r.is_exact())
+    }
+
+    /// Make the event repeat by `rule`, or stop repeating with `None`.
+    pub fn set_recurrence_rule(&mut self, rule: Option<&RecurrenceRule>) {
+        self.rrule = rule.map(|r| r.to_ical(self.all_day, self.zone()));
+    }
+
+    fn is_excluded(&self, start: NaiveDateTime) -> bool {
+        // Date-only EXDATEs on a timed series cancel that day's occurrence
+        self.exdates
+            .iter()
+            .any(|&ex| ex == start || (ex.time() == NaiveTime::MIN && ex.date() == start.date()))
+    }
+
+    /// Original starts of the series from `from` up to `end`, excluded ones included.
+    fn occurrence_starts(&self, from: NaiveDateTime, end: NaiveDateTime) -> Vec<NaiveDateTime> {
+        match self.recurrence_rule() {
+            Some(rule) => rule.occurrences_between(self.start, from, end),
+            None if self.start <= end => vec![self.start],
+            None => Vec::new(),
+        }
+    }
+
+    /// The occurrence originally starting at `recurrence_id`, with its override applied.
+    pub fn occurrence(&self, recurrence_id: NaiveDateTime) -> CalendarEvent {
+        let base = CalendarEvent {
+            overrides: Vec::new(),
+            exdates: Vec::new(),
+            ..self.clone()
+        };
+        match self.overrides.iter().find(|o| o.recurrence_id == Some(recurrence_id)) {
+            Some(o) => CalendarEvent {
+                id: self.id,
+                rrule: self.rrule.clone(),
+                recurrence_id: Some(recurrence_id),
+                calendar_href: self.calendar_href.clone(),
+                calendar_name: self.calendar_name.clone(),
+                sync_href: self.sync_href.clone(),
+                sync_uid: self.sync_uid.clone(),
+                ..o.clone()
+            },
+            None => CalendarEvent {
+                start: recurrence_id,
+                end: recurrence_id + (self.end - self.start),
+                recurrence_id: Some(recurrence_id),
+                ..base
+            },
+        }
+    }
+
+    /// What to show between local dates `from` and `to` (inclusive), by start date.
+    /// Recurring series are expanded into one event per occurrence.
+    pub fn occurrences(&self, from: NaiveDate, to: NaiveDate) -> Vec<CalendarEvent> {
+        let in_window = |e: &CalendarEvent| {
+            let date = e.local_start().date();
+            date >= from && date <= to
+        };
+        if !self.is_recurring() {
+            return if in_window(self) { vec![self.clone()] } else { Vec::new() };
+        }
+
+        // Window in the series' zone, with a day's slack either side for zone offsets
+        let start = timezone::convert(from.and_time(NaiveTime::MIN), None, self.zone()) - Duration::days(1);
+        let end = to.and_hms_opt(23, 59, 59).unwrap_or(to.and_time(NaiveTime::MIN));
+        let end = timezone::convert(end, None, self.zone()) + Duration::days(1);
+        let starts = self.occurrence_starts(start, end);
+
+        let mut out: Vec<CalendarEvent> = starts
+            .iter()
+            .filter(|&&start| !self.is_excluded(start))
+            .map(|&start| self.occurrence(start))
+            .filter(in_window)
+            .collect();
+        // Overrides moved in from occurrences beyond the generated range
+        out.extend(
+            self.overrides
+                .iter()
+                .filter(|o| o.recurrence_id.is_some_and(|rid| !starts.contains(&rid) && !self.is_excluded(rid)))
+                .filter_map(|o| o.recurrence_id)
+                .map(|rid| self.occurrence(rid))
+                .filter(in_window),
+        );
+        out
+    }
+
+    /// Apply an edited occurrence (from `occurrence`) to the series. `This`
+    /// records an override, `All` shifts the whole series and `Following`
+    /// ends the series before the occurrence and returns a new series that
+    /// continues from the edited one. Series whose occurrences aren't
+    /// editable only take `All`.
+    pub fn edit_occurrence(
+        &mut self,
+        recurrence_id: NaiveDateTime,
+        edited: &CalendarEvent,
+        scope: EditScope,
+    ) -> Option<CalendarEvent> {
+        if scope != EditScope::All && !self.occurrences_editable() {
+            log::warn!("'{}' repeats by a rule Lamp can't expand, not editing one occurrence", self.title);
+            return None;
+        }
+        // Only a real change moves the series: measure against what the
+        // occurrence showed, which an override may already have moved
+        let shown = self.occurrence(recurrence_id);
+        let delta = edited.start - shown.start;
+        let length = (self.end - self.start) + (edited.end - edited.start) - (shown.end - shown.start);
+        let overridden = self.overrides.iter().any(|o| o.recurrence_id == Some(recurrence_id));
+
+        // The calendar is a property of the whole resource
+        self.calendar_href = edited.calendar_href.clone();
+        self.calendar_name = edited.calendar_name.clone();
+
+        match scope {
+            EditScope::This => {
+                self.overrides.retain(|o| o.recurrence_id != Some(recurrence_id));
+                self.overrides.push(Self::as_override(self.id, recurrence_id, edited));
+                None
+            }
+            EditScope::Following if recurrence_id > self.start => {
+                let mut next = CalendarEvent {
+                    id: Uuid::new_v4(),
+                    start: recurrence_id + delta,
+                    end: recurrence_id + delta + length,
+                    exdates: Vec::new(),
+                    overrides: Vec::new(),
+                    recurrence_id: None,
+                    sync_href: None,
+                    sync_hash: None,
+                    sync_uid: None,
+                    ..edited.clone()
+                };
+                // A counted series keeps its total across the split, unless the rule was changed
+                if let (true, Some(rule), Some(count)) =
+                    (edited.rrule == self.rrule, &self.rrule, self.recurrence_rule().and_then(|r| r.count))
+                {
+                    let before = self
+                        .occurrence_starts(self.start, recurrence_id - Duration::seconds(1))
+                        .len() as u32;
+                    next.rrule = Some(rrule::set_count(rule, count.saturating_sub(before).max(1)));
+                }
+                next.exdates = self
+                    .exdates
+                    .iter()
+                    .filter(|&&ex| ex >= recurrence_id)
+                    .map(|&ex| ex + delta)
+                    .collect();
+                next.overrides = self
+                    .overrides
+                    .iter()
+                    .filter(|o| o.recurrence_id.is_some_and(|rid| rid > recurrence_id))
+                    .map(|o| CalendarEvent {
+                        id: next.id,
+                        recurrence_id: o.recurrence_id.map(|rid| rid + delta),
+                        ..o.clone()
+                    })
+                    .collect();
+                // The edited occurrence keeps its own time if it had been moved
+                if overridden && next.rrule.is_some() {
+                    next.overrides.push(Self::as_override(next.id, recurrence_id + delta, edited));
+                }
+                self.end_before(recurrence_id);
+                Some(next)
+            }
+            EditScope::Following | EditScope::All => {
+                self.title = edited.title.clone();
+                self.location = edited.location.clone();
+                self.description = edited.description.clone();
+                self.status = edited.status.clone();
+                self.esc = edited.esc;
+                self.alarms = edited.alarms.clone();
+                self.all_day = edited.all_day;
+                if edited.rrule != self.rrule {
+                    self.rrule = edited.rrule.clone();
+                    // Exceptions belong to the old pattern
+                    if self.rrule.is_none() {
+                        self.exdates.clear();
+                        self.overrides.clear();
+                    }
+                }
+                self.start += delta;
+                self.end = self.start + length;
+                if delta != Duration::zero() {
+                    for ex in &mut self.exdates {
+                        *ex += delta;
+                    }
+                    for o in &mut self.overrides {
+                        o.recurrence_id = o.recurrence_id.map(|rid| rid + delta);
+                    }
+                }
+                if overridden && self.rrule.is_some() {
+                    let rid = recurrence_id + delta;
+                    self.overrides.retain(|o| o.recurrence_id != Some(rid));
+                    self.overrides.push(Self::as_override(self.id, rid, edited));
+                }
+                None
+            }
+        }
+    }
+
+    /// `edited` as the override of the occurrence at `recurrence_id`.
+    fn as_override(id: Uuid, recurrence_id: NaiveDateTime, edited: &CalendarEvent) -> CalendarEvent {
+        CalendarEvent {
+            id,
+            rrule: None,
+            exdates: Vec::new(),
+            overrides: Vec::new(),
+            recurrence_id: Some(recurrence_id),
+            sync_href: None,
+            sync_hash: None,
+            sync_uid: None,
+            ..edited.clone()
+        }
+    }
+
+    /// Remove occurrences from the series. Returns `false` when nothing is
+    /// left and the whole event should be deleted. Series whose occurrences
+    /// aren't editable are left alone unless the scope is `All`.
+    pub fn delete_occurrence(&mut self, recurrence_id: NaiveDateTime, scope: EditScope) -> bool {
+        if scope != EditScope::All && !self.occurrences_editable() {
+            log::warn!("'{}' repeats by a rule Lamp can't expand, not deleting one occurrence", self.title);
+            return true;
+        }
+        match scope {
+            EditScope::This => {
+                self.overrides.retain(|o| o.recurrence_id != Some(recurrence_id));
+                if !self.exdates.contains(&recurrence_id) {
+                    self.exdates.push(recurrence_id);
+                }
+                true
+            }
+            EditScope::Following if recurrence_id > self.start => {
+                self.end_before(recurrence_id);
+                true
+            }
+            EditScope::Following | EditScope::All => false,
+        }
+    }
+
+    /// End the series with the occurrence before `recurrence_id`.
+    fn end_before(&mut self, recurrence_id: NaiveDateTime) {
+        let Some(ref rule) = self.rrule else {
+            return;
+        };
+        let until = rrule::format_until(recurrence_id - Duration::seconds(1), self.all_day, self.zone());
+        self.rrule = Some(rrule::set_until(rule, &until));
+        self.exdates.retain(|&ex| ex < recurrence_id);
+        self.overrides
+            .retain(|o| o.recurrence_id.is_some_and(|rid| rid < recurrence_id));
+    }
+}
+
+/// Expand stored events into what's visible between local dates `from` and `to`,
+/// sorted by start.
+pub fn expand_events(events: &[CalendarEvent], from: NaiveDate, to: NaiveDate) -> Vec<CalendarEvent> {
+    let mut out: Vec<CalendarEvent> = events.iter().flat_map(|e| e.occurrences(from, to)).collect();
+    out.sort_by_key(|e| e.local_start());
+    out
 }
 
 pub fn load_events(path: &Path) -> Vec<CalendarEvent> {
@@ -63,3 +502,131 @@ pub fn save_events(path: &Path, events: &[CalendarEvent]) {
         Err(e) => log::error!("Failed to serialize events: {}", e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(d: u32, h: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap().and_hms_opt(h, 0, 0).unwrap()
+    }
+
+    fn daily_standup() -> CalendarEvent {
+        let mut event = CalendarEvent::new("Standup".to_string(), at(2, 9), at(2, 10));
+        event.tzid = None;
+        event.rrule = Some("FREQ=DAILY;COUNT=10".to_string());
+        event
+    }
+
+    fn window(event: &CalendarEvent) -> Vec<CalendarEvent> {
+        event.occurrences(at(1, 0).date(), at(31, 0).date())
+    }
+
+    #[test]
+    fn edit_this_occurrence_adds_override() {
+        let mut series = daily_standup();
+        let mut edited = series.occurrence(at(4, 9));
+        edited.title = "Planning".to_string();
+        edited.start = at(4, 14);
+        edited.end = at(4, 15);
+        assert!(series.edit_occurrence(at(4, 9), &edited, EditScope::This).is_none());
+
+        let shown = window(&series);
+        assert_eq!(shown.len(), 10);
+        let moved = shown.iter().find(|e| e.recurrence_id == Some(at(4, 9))).unwrap();
+        assert_eq!((moved.title.as_str(), moved.start), ("Planning", at(4, 14)));
+        assert_eq!(moved.id, series.id);
+    }
+
+    #[test]
+    fn edit_following_splits_series_and_keeps_count() {
+        let mut series = daily_standup();
+        series.exdates.push(at(8, 9));
+        let mut edited = series.occurrence(at(6, 9));
+        edited.start = at(6, 8);
+        edited.end = at(6, 9);
+        let next = series.edit_occurrence(at(6, 9), &edited, EditScope::Following).unwrap();
+
+        assert_eq!(window(&series).len(), 4);
+        assert!(series.rrule.as_deref().unwrap().contains("UNTIL=20260306T085959"));
+        assert_eq!(next.rrule.as_deref(), Some("FREQ=DAILY;COUNT=6"));
+        assert_eq!(next.exdates, vec![at(8, 8)]);
+        let shown = window(&next);
+        assert_eq!(shown.len(), 5);
+        assert_eq!(shown[0].start, at(6, 8));
+    }
+
+    #[test]
+    fn edit_all_shifts_series_and_overrides() {
+        let mut series = daily_standup();
+        let over = series.occurrence(at(3, 9));
+        series.edit_occurrence(at(3, 9), &over, EditScope::This);
+        let mut edited = series.occurrence(at(5, 9));
+        edited.start = at(5, 11);
+        edited.end = at(5, 12);
+        series.edit_occurrence(at(5, 9), &edited, EditScope::All);
+
+        assert_eq!((series.start, series.end), (at(2, 11), at(2, 12)));
+        assert_eq!(series.overrides[0].recurrence_id, Some(at(3, 11)));
+        let shown = window(&series);
+        assert_eq!(shown.len(), 10);
+        assert_eq!(shown.iter().filter(|e| e.start.time() == at(1, 11).time()).count(), 9);
+    }
+
+    #[test]
+    fn editing_a_moved_occurrence_keeps_the_series_in_place() {
+        let mut series = daily_standup();
+        let mut moved = series.occurrence(at(4, 9));
+        moved.start = at(4, 14);
+        moved.end = at(4, 15);
+        series.edit_occurrence(at(4, 9), &moved, EditScope::This);
+
+        let mut all = series.clone();
+        let mut edited = all.occurrence(at(4, 9));
+        edited.title = "Planning".to_string();
+        all.edit_occurrence(at(4, 9), &edited, EditScope::All);
+        assert_eq!((all.start, all.end), (at(2, 9), at(2, 10)));
+        assert_eq!(all.title, "Planning");
+        let shown = all.occurrence(at(4, 9));
+        assert_eq!((shown.title.as_str(), shown.start), ("Planning", at(4, 14)));
+
+        let mut following = series.clone();
+        let next = following.edit_occurrence(at(4, 9), &edited, EditScope::Following).unwrap();
+        assert_eq!(following.start, at(2, 9));
+        assert_eq!((next.start, next.end), (at(4, 9), at(4, 10)));
+        let shown = window(&next);
+        assert_eq!(shown.len(), 8);
+        assert_eq!((shown[0].title.as_str(), shown[0].start), ("Planning", at(4, 14)));
+        assert_eq!(shown[1].start, at(5, 9));
+    }
+
+    #[test]
+    fn delete_occurrences() {
+        let mut series = daily_standup();
+        assert!(series.delete_occurrence(at(3, 9), EditScope::This));
+        assert_eq!(window(&series).len(), 9);
+        assert!(series.delete_occurrence(at(7, 9), EditScope::Following));
+        assert_eq!(window(&series).len(), 4);
+        assert!(!series.delete_occurrence(at(2, 9), EditScope::Following));
+    }
+
+    #[test]
+    fn inexact_series_only_change_as_a_whole() {
+        let mut series = daily_standup();
+        series.rrule = Some("FREQ=YEARLY;BYWEEKNO=10;BYDAY=MO".to_string());
+        assert!(!series.occurrences_editable());
+        assert!(daily_standup().occurrences_editable());
+
+        let mut edited = series.occurrence(at(2, 9));
+        edited.title = "Planning".to_string();
+        assert!(series.edit_occurrence(at(2, 9), &edited, EditScope::This).is_none());
+        assert!(series.edit_occurrence(at(2, 9), &edited, EditScope::Following).is_none());
+        assert!(series.delete_occurrence(at(2, 9), EditScope::This));
+        assert!(series.delete_occurrence(at(2, 9), EditScope::Following));
+        assert!(series.overrides.is_empty() && series.exdates.is_empty());
+        assert_eq!(series.rrule.as_deref(), Some("FREQ=YEARLY;BYWEEKNO=10;BYDAY=MO"));
+
+        series.edit_occurrence(at(2, 9), &edited, EditScope::All);
+        assert_eq!(series.title, "Planning");
+    }
+}
@@ -1,5 +1,9 @@
 pub mod account;
+pub mod alarm;
+pub mod capture;
 pub mod day_plan;
+pub mod delegation;
+pub mod email_link;
 pub mod event;
 pub mod habit;
 pub mod link;
@@ -7,6 +11,9 @@ pub mod list_item;
 pub mod note;
 pub mod project;
 pub mod recurrence;
+pub mod rrule;
 pub mod task;
 pub mod temporal;
+pub mod timeblock;
+pub mod timezone;
 
@@ -0,0 +1,801 @@
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, Weekday};
+
+use super::timezone;
+use crate::fl;
+
+/// Upper bound on occurrences returned for one window, guarding against runaway rules.
+const MAX_OCCURRENCES: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// How a repeating event ends, as chosen in the event form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleEnd {
+    Never,
+    Until,
+    Count,
+}
+
+/// The parts of an RFC 5545 RRULE that Lamp expands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    /// Last possible start, as wall-clock time in the series' zone.
+    pub until: Option<NaiveDateTime>,
+    /// Weekdays of a weekly rule; limits a daily rule and expands to every
+    /// such weekday of the month for monthly and yearly ones.
+    pub by_day: Vec<Weekday>,
+    /// Monthly or yearly on the nth weekdays ("1MO,3MO"; -1 is the last).
+    pub nth_weekdays: Vec<(i32, Weekday)>,
+    /// Days of the month ("1,15"; -1 is the last).
+    pub by_month_day: Vec<i32>,
+    /// Months a yearly rule repeats in; limits the other frequencies.
+    pub by_month: Vec<u32>,
+    /// Parts Lamp can't expand, kept verbatim ("BYWEEKNO=20"). Occurrences
+    /// of such a rule may not be the ones other clients see.
+    pub unsupported: Vec<String>,
+}
+
+impl RecurrenceRule {
+    /// Parse an RRULE value like "FREQ=WEEKLY;BYDAY=TU,TH;UNTIL=20260630T000000Z".
+    /// `tzid` is the zone of the series' DTSTART, used to read a UTC UNTIL.
+    pub fn parse(rule: &str, tzid: Option<&str>) -> Option<Self> {
+        let mut freq = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = Vec::new();
+        let mut nth_weekdays = Vec::new();
+        let mut by_month_day = Vec::new();
+        let mut by_month = Vec::new();
+        let mut unsupported = Vec::new();
+        let mut set_pos: Option<(i32, String)> = None;
+        let mut week_start = None;
+
+        for (key, value) in params(rule) {
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => interval = value.parse().ok().filter(|&i| i > 0).unwrap_or(1),
+                "COUNT" => count = value.parse().ok(),
+                "UNTIL" => until = parse_until(value, tzid),
+                "BYDAY" => {
+                    for day in value.split(',').map(str::trim) {
+                        let split = day.len().saturating_sub(2);
+                        let Some(wd) = day.get(split..).and_then(parse_weekday) else {
+                            unsupported.push(format!("{}={}", key, value));
+                            break;
+                        };
+                        match day[..split].trim_start_matches('+') {
+                            "" => by_day.push(wd),
+                            n => match n.parse::<i32>() {
+                                Ok(n) if n != 0 && n.abs() <= 5 => nth_weekdays.push((n, wd)),
+                                _ => {
+                                    unsupported.push(format!("{}={}", key, value));
+                                    break;
+                                }
+                            },
+                        }
+                    }
+                }
+                "BYMONTHDAY" => {
+                    let days: Option<Vec<i32>> = value
+                        .split(',')
+                        .map(|d| d.trim().trim_start_matches('+').parse().ok().filter(|d: &i32| *d != 0 && d.abs() <= 31))
+                        .collect();
+                    match days {
+                        Some(days) => by_month_day = days,
+                        None => unsupported.push(format!("{}={}", key, value)),
+                    }
+                }
+                "BYMONTH" => {
+                    let months: Option<Vec<u32>> = value
+                        .split(',')
+                        .map(|m| m.trim().parse().ok().filter(|m| (1..=12).contains(m)))
+                        .collect();
+                    match months {
+                        Some(months) => by_month = months,
+                        None => unsupported.push(format!("{}={}", key, value)),
+                    }
+                }
+                "BYSETPOS" => set_pos = Some((value.parse().unwrap_or(0), format!("{}={}", key, value))),
+                "WKST" => week_start = Some(value),
+                _ => unsupported.push(format!("{}={}", key, value)),
+            }
+        }
+
+        let freq = freq?;
+        if let Some((n, part)) = set_pos {
+            // Outlook writes "the 2nd Tuesday" as BYDAY=TU;BYSETPOS=2
+            match (by_day.as_slice(), freq) {
+                ([wd], Frequency::Monthly | Frequency::Yearly) if n != 0 && nth_weekdays.is_empty() => {
+                    nth_weekdays.push((n, *wd));
+                    by_day.clear();
+                }
+                _ => unsupported.push(part),
+            }
+        }
+        // Combinations the expansion below doesn't cover
+        let part = |name: &str| {
+            params(rule)
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(k, v)| format!("{}={}", k, v))
+        };
+        let by_weekday = !by_day.is_empty() || !nth_weekdays.is_empty();
+        match freq {
+            Frequency::Daily | Frequency::Weekly if !nth_weekdays.is_empty() => unsupported.extend(part("BYDAY")),
+            Frequency::Weekly if !by_month_day.is_empty() => unsupported.extend(part("BYMONTHDAY")),
+            // Without BYMONTH these count through the whole year
+            Frequency::Yearly if by_month.is_empty() => {
+                if by_weekday {
+                    unsupported.extend(part("BYDAY"));
+                }
+                if !by_month_day.is_empty() {
+                    unsupported.extend(part("BYMONTHDAY"));
+                }
+            }
+            _ => {}
+        }
+        // Weeks are Monday-based; the start day only matters across multi-week gaps
+        if week_start.is_some_and(|wd: &str| !wd.eq_ignore_ascii_case("MO"))
+            && freq == Frequency::Weekly
+            && interval > 1
+            && by_day.len() > 1
+        {
+            unsupported.extend(part("WKST"));
+        }
+        unsupported.dedup();
+
+        Some(Self {
+            freq,
+            interval,
+            count,
+            until,
+            by_day,
+            nth_weekdays,
+            by_month_day,
+            by_month,
+            unsupported,
+        })
+    }
+
+    /// Whether this rule's occurrences are exactly the ones RFC 5545 gives.
+    pub fn is_exact(&self) -> bool {
+        self.unsupported.is_empty()
+    }
+
+    /// Occurrence starts of a series beginning at `dtstart`, up to and including `end`.
+    /// Both are wall-clock times in the series' zone.
+    pub fn occurrences(&self, dtstart: NaiveDateTime, end: NaiveDateTime) -> Vec<NaiveDateTime> {
+        self.occurrences_between(dtstart, dtstart, end)
+    }
+
+    /// Occurrence starts from `from` up to and including `end`. Earlier ones
+    /// still count towards COUNT, but not towards the limit, so an old series
+    /// keeps showing today.
+    pub fn occurrences_between(
+        &self,
+        dtstart: NaiveDateTime,
+        from: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> Vec<NaiveDateTime> {
+        let count = self.count.map_or(usize::MAX, |c| c as usize);
+        let mut counted = 0;
+        let last = match self.until {
+            Some(until) => until.min(end),
+            None => end,
+        };
+        let time = dtstart.time();
+        let date = dtstart.date();
+        let week = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        let first_of_month = date.with_day(1).unwrap_or(date);
+        let weekdays = if self.by_day.is_empty() {
+            vec![date.weekday()]
+        } else {
+            let mut days = self.by_day.clone();
+            days.sort_by_key(|wd| wd.num_days_from_monday());
+            days
+        };
+        let mut starts = Vec::new();
+
+        for k in 0u32.. {
+            let step = k.saturating_mul(self.interval);
+            // First day of the k-th period; candidates in it are in date order
+            let (period_start, candidates): (Option<NaiveDate>, Vec<NaiveDate>) = match self.freq {
+                Frequency::Daily => {
+                    let day = date.checked_add_signed(Duration::days(step as i64));
+                    let days = day
+                        .into_iter()
+                        .filter(|d| self.by_day.is_empty() || self.by_day.contains(&d.weekday()))
+                        .filter(|&d| {
+                            let first = d.with_day(1).unwrap_or(d);
+                            self.by_month_day.is_empty()
+                                || self.by_month_day.iter().any(|&md| month_day(first, md) == Some(d))
+                        })
+                        .collect();
+                    (day, days)
+                }
+                Frequency::Weekly => {
+                    let monday = week.checked_add_signed(Duration::weeks(step as i64));
+                    let days = monday
+                        .into_iter()
+                        .flat_map(|m| {
+                            weekdays
+                                .iter()
+                                .map(move |wd| m + Duration::days(wd.num_days_from_monday() as i64))
+                        })
+                        .collect();
+                    (monday, days)
+                }
+                Frequency::Monthly => {
+                    let first = first_of_month.checked_add_months(Months::new(step));
+                    let days = first.map(|f| self.days_in_month(f, date.day())).unwrap_or_default();
+                    (first, days)
+                }
+                Frequency::Yearly => {
+                    let first = first_of_month.checked_add_months(Months::new(step.saturating_mul(12)));
+                    let january = first.and_then(|f| f.with_month(1));
+                    let months = if self.by_month.is_empty() {
+                        vec![date.month()]
+                    } else {
+                        let mut months = self.by_month.clone();
+                        months.sort_unstable();
+                        months
+                    };
+                    let days = january
+                        .into_iter()
+                        .flat_map(|jan| months.iter().filter_map(move |&m| jan.with_month(m)))
+                        .flat_map(|f| self.days_in_month(f, date.day()))
+                        .collect();
+                    (january, days)
+                }
+            };
+            let Some(period_start) = period_start else {
+                break;
+            };
+            if period_start > last.date() {
+                break;
+            }
+            for day in candidates {
+                if self.freq != Frequency::Yearly && !self.by_month.is_empty() && !self.by_month.contains(&day.month()) {
+                    continue;
+                }
+                let start = day.and_time(time);
+                if start < dtstart {
+                    continue;
+                }
+                if start > last || counted >= count || starts.len() >= MAX_OCCURRENCES {
+                    return starts;
+                }
+                counted += 1;
+                if start >= from {
+                    starts.push(start);
+                }
+            }
+        }
+        starts
+    }
+
+    /// Days of the month starting at `first` this rule falls on, in order.
+    /// BYDAY and BYMONTHDAY together keep the days matching both; with
+    /// neither it's `day`, and months without that day are skipped.
+    fn days_in_month(&self, first: NaiveDate, day: u32) -> Vec<NaiveDate> {
+        let by_weekday = (!self.by_day.is_empty() || !self.nth_weekdays.is_empty()).then(|| {
+            let mut days: Vec<NaiveDate> = self
+                .nth_weekdays
+                .iter()
+                .filter_map(|&(n, wd)| nth_weekday_of_month(first, n, wd))
+                .collect();
+            days.extend(
+                first
+                    .iter_days()
+                    .take_while(|d| d.month() == first.month())
+                    .filter(|d| self.by_day.contains(&d.weekday())),
+            );
+            days
+        });
+        let by_month_day = (!self.by_month_day.is_empty())
+            .then(|| self.by_month_day.iter().filter_map(|&md| month_day(first, md)).collect::<Vec<_>>());
+        let mut days = match (by_weekday, by_month_day) {
+            (Some(weekdays), Some(month_days)) => weekdays.into_iter().filter(|d| month_days.contains(d)).collect(),
+            (Some(days), None) | (None, Some(days)) => days,
+            (None, None) => first.with_day(day).into_iter().collect(),
+        };
+        days.sort();
+        days.dedup();
+        days
+    }
+
+    /// RRULE value for this rule. `all_day` and `tzid` describe the series'
+    /// DTSTART, which decides how UNTIL is written.
+    pub fn to_ical(&self, all_day: bool, tzid: Option<&str>) -> String {
+        let freq = match self.freq {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        let mut parts = vec![format!("FREQ={}", freq)];
+        if self.interval > 1 {
+            parts.push(format!("INTERVAL={}", self.interval));
+        }
+        if !self.by_month.is_empty() {
+            let months: Vec<String> = self.by_month.iter().map(|m| m.to_string()).collect();
+            parts.push(format!("BYMONTH={}", months.join(",")));
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(|d| d.to_string()).collect();
+            parts.push(format!("BYMONTHDAY={}", days.join(",")));
+        }
+        if !self.by_day.is_empty() || !self.nth_weekdays.is_empty() {
+            let days: Vec<String> = self
+                .nth_weekdays
+                .iter()
+                .map(|&(n, wd)| format!("{}{}", n, weekday_code(wd)))
+                .chain(self.by_day.iter().map(|&wd| weekday_code(wd).to_string()))
+                .collect();
+            parts.push(format!("BYDAY={}", days.join(",")));
+        }
+        if let Some(count) = self.count {
+            parts.push(format!("COUNT={}", count));
+        } else if let Some(until) = self.until {
+            parts.push(format!("UNTIL={}", format_until(until, all_day, tzid)));
+        }
+        // Unexpanded parts go back out as they came in, in place of what was read from them
+        for part in &self.unsupported {
+            let key = part.split_once('=').map_or(part.as_str(), |(k, _)| k);
+            parts.retain(|p| !p.split_once('=').is_some_and(|(k, _)| k.eq_ignore_ascii_case(key)));
+            parts.push(part.clone());
+        }
+        parts.join(";")
+    }
+
+    /// Human-readable description, e.g. "Every other Tue & Thu until Jun 30".
+    pub fn summary(&self, dtstart: NaiveDate) -> String {
+        let interval = self.interval;
+        let mut text = match self.freq {
+            Frequency::Daily => match self.by_day.as_slice() {
+                [] => fl!("rrule-daily", interval = interval),
+                days => fl!("rrule-daily-on", interval = interval, days = weekday_list(days)),
+            },
+            Frequency::Weekly => {
+                let days = match self.by_day.as_slice() {
+                    [] => weekday_list(&[dtstart.weekday()]),
+                    days => weekday_list(days),
+                };
+                fl!("rrule-weekly", interval = interval, days = days)
+            }
+            Frequency::Monthly => {
+                let on = self
+                    .month_days_summary()
+                    .unwrap_or_else(|| fl!("rrule-on-day", day = dtstart.day()));
+                fl!("rrule-monthly", interval = interval, on = on)
+            }
+            Frequency::Yearly => {
+                let months = match self.by_month.as_slice() {
+                    [] => dtstart.format("%B").to_string(),
+                    months => join_and(
+                        &months
+                            .iter()
+                            .filter_map(|&m| dtstart.with_day(1)?.with_month(m))
+                            .map(|d| d.format("%B").to_string())
+                            .collect::<Vec<_>>(),
+                    ),
+                };
+                let on = match (self.month_days_summary(), self.by_month.is_empty()) {
+                    (Some(on), _) => fl!("rrule-of-months", on = on, months = months),
+                    (None, true) => fl!("rrule-on-date", date = dtstart.format("%b %-d").to_string()),
+                    (None, false) => fl!(
+                        "rrule-of-months",
+                        on = fl!("rrule-on-day", day = dtstart.day()),
+                        months = months
+                    ),
+                };
+                fl!("rrule-yearly", interval = interval, on = on)
+            }
+        };
+        if !self.unsupported.is_empty() {
+            text = fl!("rrule-unsupported", summary = text, parts = self.unsupported.join(";"));
+        }
+        if let Some(count) = self.count {
+            text = fl!("rrule-count", summary = text, count = count);
+        } else if let Some(until) = self.until {
+            let until = until.date();
+            let format = if until.year() == dtstart.year() { "%b %-d" } else { "%b %-d, %Y" };
+            text = fl!("rrule-until", summary = text, date = until.format(format).to_string());
+        }
+        text
+    }
+
+    /// "on the 1st & 3rd Monday", "on day 1 & 15" or `None` for the start's day.
+    fn month_days_summary(&self) -> Option<String> {
+        let mut on = Vec::new();
+        if !self.nth_weekdays.is_empty() {
+            let days: Vec<String> = self
+                .nth_weekdays
+                .iter()
+                .map(|&(n, wd)| fl!("rrule-nth-weekday", nth = ordinal(n), weekday = weekday_name(wd)))
+                .collect();
+            on.push(fl!("rrule-nth-weekdays", days = join_and(&days)));
+        }
+        if !self.by_day.is_empty() {
+            on.push(fl!("rrule-every-weekday", days = weekday_list(&self.by_day)));
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self
+                .by_month_day
+                .iter()
+                .map(|&d| if d > 0 { d.to_string() } else { fl!("rrule-month-day-from-end", nth = ordinal(d)) })
+                .collect();
+            on.push(fl!("rrule-month-days", days = join_and(&days)));
+        }
+        let on = on.into_iter().reduce(|first, second| fl!("rrule-both", first = first, second = second))?;
+        Some(fl!("rrule-on", days = on))
+    }
+}
+
+/// "Tue & Thu", in week order.
+fn weekday_list(days: &[Weekday]) -> String {
+    let mut days = days.to_vec();
+    days.sort_by_key(|wd| wd.num_days_from_monday());
+    join_and(
+        &days
+            .iter()
+            .map(|&wd| fl!("rrule-weekday-short", day = weekday_code(wd)))
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Day `n` of the month starting at `first`; negative `n` counts from the end.
+fn month_day(first: NaiveDate, n: i32) -> Option<NaiveDate> {
+    if n > 0 {
+        return first.with_day(n as u32);
+    }
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+    let day = last - Duration::days((-n - 1) as i64);
+    (day.month() == first.month()).then_some(day)
+}
+
+fn params(rule: &str) -> impl Iterator<Item = (&str, &str)> {
+    rule.split(';').filter_map(|part| part.split_once('='))
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    match s.trim().to_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+pub fn weekday_code(wd: Weekday) -> &'static str {
+    match wd {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn weekday_name(wd: Weekday) -> String {
+    fl!("rrule-weekday", day = weekday_code(wd))
+}
+
+/// "1st", "22nd", ...; negative `n` counts from the end: "last", "third to last".
+fn ordinal(n: i32) -> String {
+    if n < 0 {
+        let from_end = -n;
+        return fl!("rrule-ordinal-from-end", n = from_end, nth = ordinal(from_end));
+    }
+    fl!("rrule-ordinal", n = n)
+}
+
+/// "Tue", "Tue & Thu", "Mon, Wed & Fri"
+fn join_and(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [first, middle @ .., last] => {
+            let rest = middle
+                .iter()
+                .fold(first.clone(), |rest, item| fl!("rrule-list", rest = rest, item = item.as_str()));
+            fl!("rrule-list-last", rest = rest, last = last.as_str())
+        }
+    }
+}
+
+/// The `n`th `wd` in the month starting at `first`; negative `n` counts from the end.
+pub fn nth_weekday_of_month(first: NaiveDate, n: i32, wd: Weekday) -> Option<NaiveDate> {
+    if n > 0 {
+        return NaiveDate::from_weekday_of_month_opt(first.year(), first.month(), wd, n as u8);
+    }
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+    let back = (7 + last.weekday().num_days_from_monday() - wd.num_days_from_monday()) % 7;
+    let day = last - Duration::days(back as i64) - Duration::weeks((-n - 1) as i64);
+    (day.month() == first.month()).then_some(day)
+}
+
+/// The position of `date`'s weekday in its month, as used for "monthly on the
+/// nth weekday": the last week of the month is -1.
+pub fn weekday_position(date: NaiveDate) -> (i32, Weekday) {
+    let n = (date.day() as i32 - 1) / 7 + 1;
+    let n = if n >= 5 { -1 } else { n };
+    (n, date.weekday())
+}
+
+/// UNTIL value: a date for all-day series, UTC for zoned ones (RFC 5545),
+/// otherwise floating.
+pub fn format_until(until: NaiveDateTime, all_day: bool, tzid: Option<&str>) -> String {
+    if all_day {
+        until.format("%Y%m%d").to_string()
+    } else if let Some(zone) = tzid {
+        timezone::to_utc(until, Some(zone)).format("%Y%m%dT%H%M%SZ").to_string()
+    } else {
+        until.format("%Y%m%dT%H%M%S").to_string()
+    }
+}
+
+/// A date-only UNTIL includes that whole day; a UTC one is moved into the series' zone.
+fn parse_until(value: &str, tzid: Option<&str>) -> Option<NaiveDateTime> {
+    if let Ok(dt) = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S") {
+        return Some(if value.ends_with('Z') {
+            timezone::from_utc(dt, tzid)
+        } else {
+            dt
+        });
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(23, 59, 59))
+}
+
+/// Rewrite `rule` to end with UNTIL=`until`, dropping any COUNT.
+pub fn set_until(rule: &str, until: &str) -> String {
+    let mut parts: Vec<String> = params(rule)
+        .filter(|(k, _)| !k.eq_ignore_ascii_case("UNTIL") && !k.eq_ignore_ascii_case("COUNT"))
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    parts.push(format!("UNTIL={}", until));
+    parts.join(";")
+}
+
+/// Rewrite `rule` with COUNT=`count`.
+pub fn set_count(rule: &str, count: u32) -> String {
+    params(rule)
+        .map(|(k, v)| {
+            if k.eq_ignore_ascii_case("COUNT") {
+                format!("{}={}", k, count)
+            } else {
+                format!("{}={}", k, v)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fluent wraps arguments in bidi isolation marks.
+    fn plain(text: String) -> String {
+        text.replace(['\u{2068}', '\u{2069}'], "")
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn weekly_by_day_with_interval() {
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,TH", None).unwrap();
+        // Tue 3 Feb 2026
+        let starts = rule.occurrences(at(2026, 2, 3, 9), at(2026, 2, 28, 0));
+        assert_eq!(
+            starts,
+            vec![at(2026, 2, 3, 9), at(2026, 2, 5, 9), at(2026, 2, 17, 9), at(2026, 2, 19, 9)]
+        );
+    }
+
+    #[test]
+    fn monthly_skips_short_months_and_honours_until() {
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;UNTIL=20260601", None).unwrap();
+        let starts = rule.occurrences(at(2026, 1, 31, 10), at(2027, 1, 1, 0));
+        assert_eq!(starts, vec![at(2026, 1, 31, 10), at(2026, 3, 31, 10), at(2026, 5, 31, 10)]);
+    }
+
+    #[test]
+    fn utc_until_is_read_in_series_zone() {
+        // 23:00 UTC on the 9th is 01:00 on the 10th in Berlin (summer)
+        let rule =
+            RecurrenceRule::parse("FREQ=DAILY;UNTIL=20260709T230000Z", Some("Europe/Berlin")).unwrap();
+        let starts = rule.occurrences(at(2026, 7, 8, 1), at(2026, 8, 1, 0));
+        assert_eq!(starts, vec![at(2026, 7, 8, 1), at(2026, 7, 9, 1), at(2026, 7, 10, 1)]);
+    }
+
+    #[test]
+    fn monthly_nth_weekday() {
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;BYDAY=-1FR;COUNT=3", None).unwrap();
+        let starts = rule.occurrences(at(2026, 1, 30, 17), at(2027, 1, 1, 0));
+        assert_eq!(starts, vec![at(2026, 1, 30, 17), at(2026, 2, 27, 17), at(2026, 3, 27, 17)]);
+
+        // Outlook's BYSETPOS form reads the same as BYDAY=2TU
+        let outlook = RecurrenceRule::parse("FREQ=MONTHLY;BYDAY=TU;BYSETPOS=2", None).unwrap();
+        assert_eq!(outlook.nth_weekdays, vec![(2, Weekday::Tue)]);
+        assert_eq!(outlook.to_ical(false, None), "FREQ=MONTHLY;BYDAY=2TU");
+        assert_eq!(weekday_position(at(2026, 1, 30, 0).date()), (-1, Weekday::Fri));
+    }
+
+    #[test]
+    fn daily_by_day_skips_other_days() {
+        let rule = RecurrenceRule::parse("FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR", None).unwrap();
+        assert!(rule.is_exact());
+        // Fri 6 Mar 2026
+        let starts = rule.occurrences(at(2026, 3, 6, 8), at(2026, 3, 10, 23));
+        assert_eq!(starts, vec![at(2026, 3, 6, 8), at(2026, 3, 9, 8), at(2026, 3, 10, 8)]);
+        assert_eq!(plain(rule.summary(at(2026, 3, 6, 8).date())), "Daily on Mon, Tue, Wed, Thu & Fri");
+        assert_eq!(rule.to_ical(false, None), "FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR");
+    }
+
+    #[test]
+    fn ordinals() {
+        let cases = [
+            (1, "1st"),
+            (2, "2nd"),
+            (3, "3rd"),
+            (4, "4th"),
+            (11, "11th"),
+            (12, "12th"),
+            (13, "13th"),
+            (21, "21st"),
+            (22, "22nd"),
+            (23, "23rd"),
+            (31, "31st"),
+            (-1, "last"),
+            (-2, "second to last"),
+            (-3, "third to last"),
+            (-4, "fourth to last"),
+            (-5, "fifth to last"),
+            (-22, "22nd to last"),
+        ];
+        for (n, text) in cases {
+            assert_eq!(plain(ordinal(n)), text);
+        }
+    }
+
+    #[test]
+    fn old_series_still_reach_the_window() {
+        let rule = RecurrenceRule::parse("FREQ=DAILY", None).unwrap();
+        let starts = rule.occurrences_between(at(2006, 1, 2, 9), at(2026, 3, 2, 0), at(2026, 3, 4, 23));
+        assert_eq!(starts, vec![at(2026, 3, 2, 9), at(2026, 3, 3, 9), at(2026, 3, 4, 9)]);
+
+        // COUNT still counts from DTSTART
+        let rule = RecurrenceRule::parse("FREQ=DAILY;COUNT=5", None).unwrap();
+        let starts = rule.occurrences_between(at(2026, 3, 1, 9), at(2026, 3, 4, 0), at(2026, 3, 31, 0));
+        assert_eq!(starts, vec![at(2026, 3, 4, 9), at(2026, 3, 5, 9)]);
+    }
+
+    #[test]
+    fn several_nth_weekdays_all_repeat() {
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;BYDAY=1MO,3MO", None).unwrap();
+        assert!(rule.is_exact());
+        assert_eq!(rule.nth_weekdays, vec![(1, Weekday::Mon), (3, Weekday::Mon)]);
+        let starts = rule.occurrences(at(2026, 3, 2, 18), at(2026, 4, 30, 0));
+        assert_eq!(
+            starts,
+            vec![at(2026, 3, 2, 18), at(2026, 3, 16, 18), at(2026, 4, 6, 18), at(2026, 4, 20, 18)]
+        );
+        assert_eq!(plain(rule.summary(at(2026, 3, 2, 18).date())), "Monthly on the 1st Monday & 3rd Monday");
+        assert_eq!(rule.to_ical(false, None), "FREQ=MONTHLY;BYDAY=1MO,3MO");
+    }
+
+    #[test]
+    fn month_day_and_month_lists() {
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;BYMONTHDAY=1,-1;COUNT=4", None).unwrap();
+        assert!(rule.is_exact());
+        let starts = rule.occurrences(at(2026, 2, 1, 9), at(2027, 1, 1, 0));
+        assert_eq!(starts, vec![at(2026, 2, 1, 9), at(2026, 2, 28, 9), at(2026, 3, 1, 9), at(2026, 3, 31, 9)]);
+
+        let rule = RecurrenceRule::parse("FREQ=YEARLY;BYMONTH=3,9;BYMONTHDAY=15", None).unwrap();
+        assert!(rule.is_exact());
+        let starts = rule.occurrences(at(2026, 3, 15, 9), at(2027, 12, 31, 0));
+        assert_eq!(
+            starts,
+            vec![at(2026, 3, 15, 9), at(2026, 9, 15, 9), at(2027, 3, 15, 9), at(2027, 9, 15, 9)]
+        );
+        assert_eq!(plain(rule.summary(at(2026, 3, 15, 9).date())), "Yearly on day 15 of March & September");
+
+        // BYMONTH limits the other frequencies
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=SA;BYMONTH=6", None).unwrap();
+        let starts = rule.occurrences(at(2026, 5, 30, 10), at(2026, 7, 31, 0));
+        assert_eq!(starts, vec![at(2026, 6, 6, 10), at(2026, 6, 13, 10), at(2026, 6, 20, 10), at(2026, 6, 27, 10)]);
+    }
+
+    #[test]
+    fn unsupported_parts_are_recorded_and_kept() {
+        let rule = RecurrenceRule::parse("FREQ=YEARLY;BYWEEKNO=20;BYDAY=MO", None).unwrap();
+        assert!(!rule.is_exact());
+        assert_eq!(rule.unsupported, vec!["BYWEEKNO=20".to_string(), "BYDAY=MO".to_string()]);
+        assert_eq!(rule.to_ical(false, None), "FREQ=YEARLY;BYWEEKNO=20;BYDAY=MO");
+
+        for rule in [
+            "FREQ=WEEKLY;BYDAY=1MO",
+            "FREQ=YEARLY;BYDAY=-1SU",
+            "FREQ=YEARLY;BYMONTHDAY=1",
+            "FREQ=MONTHLY;BYDAY=MO,TU;BYSETPOS=-1",
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,SU;WKST=SU",
+        ] {
+            assert!(!RecurrenceRule::parse(rule, None).unwrap().is_exact(), "{}", rule);
+        }
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=MO;WKST=SU", None).unwrap().is_exact());
+    }
+
+    #[test]
+    fn builds_rrule_and_summary() {
+        let rule = RecurrenceRule {
+            freq: Frequency::Weekly,
+            interval: 2,
+            count: None,
+            until: Some(at(2026, 6, 30, 23) + Duration::minutes(59)),
+            by_day: vec![Weekday::Thu, Weekday::Tue],
+            nth_weekdays: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            unsupported: Vec::new(),
+        };
+        let start = at(2026, 2, 3, 9).date();
+        assert_eq!(plain(rule.summary(start)), "Every other Tue & Thu until Jun 30");
+        assert_eq!(rule.to_ical(true, None), "FREQ=WEEKLY;INTERVAL=2;BYDAY=TH,TU;UNTIL=20260630");
+        assert_eq!(
+            rule.to_ical(false, Some("Europe/Berlin")),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=TH,TU;UNTIL=20260630T215900Z"
+        );
+        let parsed = RecurrenceRule::parse(&rule.to_ical(false, None), None).unwrap();
+        assert_eq!(parsed, rule);
+
+        let monthly = RecurrenceRule {
+            freq: Frequency::Monthly,
+            interval: 1,
+            count: Some(6),
+            until: None,
+            by_day: Vec::new(),
+            nth_weekdays: vec![(2, Weekday::Tue)],
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            unsupported: Vec::new(),
+        };
+        assert_eq!(plain(monthly.summary(start)), "Monthly on the 2nd Tuesday, 6 times");
+    }
+
+    #[test]
+    fn rule_rewrites_keep_other_parts() {
+        assert_eq!(
+            set_until("FREQ=WEEKLY;COUNT=10;BYDAY=MO", "20260301T000000Z"),
+            "FREQ=WEEKLY;BYDAY=MO;UNTIL=20260301T000000Z"
+        );
+        assert_eq!(set_count("FREQ=DAILY;COUNT=10", 4), "FREQ=DAILY;COUNT=4");
+    }
+}
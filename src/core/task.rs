@@ -2,6 +2,8 @@ use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::alarm::Alarm;
+use super::email_link::EmailLink;
 use super::recurrence::Recurrence;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -88,13 +90,29 @@ pub struct Task {
     pub completed: Option<NaiveDateTime>,
     pub project: Option<String>,
     pub waiting_for: Option<String>,
+    /// Contact the task is delegated to; `waiting_for` keeps the display name as fallback.
+    pub waiting_for_contact: Option<Uuid>,
     pub esc: Option<u32>,
+    /// Expected duration in minutes (org `EFFORT`), used when time-blocking.
+    pub estimate: Option<u32>,
     pub delegated: Option<NaiveDate>,
     pub follow_up: Option<NaiveDate>,
     pub sync_href: Option<String>,
     pub sync_hash: Option<u64>,
     /// Original CalDAV UID string (preserved for case-sensitive roundtrip)
     pub sync_uid: Option<String>,
+    /// UID of the parent VTODO (`RELATED-TO`) when projects sync as parent tasks
+    pub sync_parent: Option<String>,
+    /// Reminders (VALARM components), relative to the scheduled date or deadline.
+    #[serde(default)]
+    pub alarms: Vec<Alarm>,
+    /// The email the task was created from (org `EMAIL_ID`).
+    #[serde(default)]
+    pub email: Option<EmailLink>,
+    /// `Message-ID` of the delegation request we sent (org `DELEGATION_ID`);
+    /// a reply to it ends the wait.
+    #[serde(default)]
+    pub delegation_id: Option<String>,
 }
 
 impl Task {
@@ -113,12 +131,18 @@ impl Task {
             completed: None,
             project: None,
             waiting_for: None,
+            waiting_for_contact: None,
             esc: None,
+            estimate: None,
             delegated: None,
             follow_up: None,
             sync_href: None,
             sync_hash: None,
             sync_uid: None,
+            sync_parent: None,
+            alarms: Vec::new(),
+            email: None,
+            delegation_id: None,
         }
     }
 
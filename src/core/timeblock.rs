@@ -0,0 +1,311 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use uuid::Uuid;
+
+use super::event::{CalendarEvent, EventStatus};
+use super::task::Task;
+
+/// Block length for tasks with neither an estimate nor an ESC.
+pub const DEFAULT_BLOCK_MINUTES: u32 = 30;
+/// Minutes per spoon when a block is sized from a task's ESC.
+const MINUTES_PER_SPOON: u32 = 15;
+/// Longest block sized from ESC alone.
+const MAX_ESC_MINUTES: u32 = 4 * 60;
+/// Granularity of the timeline's drop slots.
+pub const SLOT_MINUTES: i64 = 30;
+
+/// How long a block for `task` should be: its estimate, else its ESC at a
+/// quarter hour per spoon, else half an hour.
+pub fn block_minutes(task: &Task) -> u32 {
+    match (task.estimate, task.esc) {
+        (Some(minutes), _) if minutes > 0 => minutes,
+        (_, Some(esc)) if esc > 0 => (esc * MINUTES_PER_SPOON).min(MAX_ESC_MINUTES),
+        _ => DEFAULT_BLOCK_MINUTES,
+    }
+}
+
+/// A time block for `task` starting at local `start`.
+pub fn block_for_task(task: &Task, start: NaiveDateTime) -> CalendarEvent {
+    let end = start + Duration::minutes(i64::from(block_minutes(task)));
+    let mut block = CalendarEvent::new(task.title.clone(), start, end);
+    block.task_id = Some(task.id);
+    block
+}
+
+/// Busy local intervals on `day` from timed, non-cancelled events, sorted and merged.
+pub fn busy_intervals(events: &[CalendarEvent], day: NaiveDate) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let day_start = day.and_time(NaiveTime::MIN);
+    let day_end = day_start + Duration::days(1);
+    let busy = events
+        .iter()
+        .filter(|e| !e.all_day && e.status != EventStatus::Cancelled)
+        .map(|e| (e.local_start().max(day_start), e.local_end().min(day_end)))
+        .filter(|(start, end)| start < end)
+        .collect();
+    merge_intervals(busy)
+}
+
+/// Sort intervals and merge the ones that overlap or touch.
+pub fn merge_intervals(
+    mut busy: Vec<(NaiveDateTime, NaiveDateTime)>,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    busy.sort();
+    let mut merged: Vec<(NaiveDateTime, NaiveDateTime)> = Vec::new();
+    for (start, end) in busy {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Free local intervals between `from` and `until` on one day, around `events`.
+pub fn free_intervals(
+    events: &[CalendarEvent],
+    from: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let mut free = Vec::new();
+    let mut cursor = from;
+    for (start, end) in busy_intervals(events, from.date()) {
+        if start > cursor {
+            free.push((cursor, start.min(until)));
+        }
+        cursor = cursor.max(end);
+        if cursor >= until {
+            break;
+        }
+    }
+    if cursor < until {
+        free.push((cursor, until));
+    }
+    free.retain(|(start, end)| start < end);
+    free
+}
+
+/// Earliest slot-aligned start between `from` and `until` where `minutes`
+/// fit without overlapping `events`.
+pub fn first_fit(
+    events: &[CalendarEvent],
+    from: NaiveDateTime,
+    until: NaiveDateTime,
+    minutes: u32,
+) -> Option<NaiveDateTime> {
+    let length = Duration::minutes(i64::from(minutes));
+    free_intervals(events, from, until)
+        .into_iter()
+        .find_map(|(start, end)| {
+            let start = align_up(start);
+            (start + length <= end).then_some(start)
+        })
+}
+
+/// The first `count` slots of `minutes` that fit around `busy`, at most one
+/// per free stretch, searching each of `windows` (working hours) in turn.
+pub fn suggest_slots(
+    busy: &[(NaiveDateTime, NaiveDateTime)],
+    windows: &[(NaiveDateTime, NaiveDateTime)],
+    minutes: u32,
+    count: usize,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let length = Duration::minutes(i64::from(minutes));
+    let busy = merge_intervals(busy.to_vec());
+    let mut slots = Vec::new();
+    for &(from, until) in windows {
+        let mut cursor = from;
+        let gaps = busy
+            .iter()
+            .filter(|(start, end)| *end > from && *start < until)
+            .copied()
+            .chain([(until, until)]);
+        for (start, end) in gaps {
+            let slot = align_up(cursor);
+            if slot + length <= start.min(until) {
+                slots.push((slot, slot + length));
+                if slots.len() == count {
+                    return slots;
+                }
+            }
+            cursor = cursor.max(end);
+        }
+    }
+    slots
+}
+
+/// Round up to the next slot boundary.
+fn align_up(dt: NaiveDateTime) -> NaiveDateTime {
+    let day_start = dt.date().and_time(NaiveTime::MIN);
+    let minutes = (dt - day_start).num_minutes();
+    let aligned = (minutes + SLOT_MINUTES - 1) / SLOT_MINUTES * SLOT_MINUTES;
+    let rounded = day_start + Duration::minutes(aligned);
+    // Seconds past a boundary still need the next one
+    if rounded < dt { rounded + Duration::minutes(SLOT_MINUTES) } else { rounded }
+}
+
+/// Changes made by `follow_tasks`.
+#[derive(Debug, Default)]
+pub struct BlockUpdate {
+    /// Whether any block was moved, renamed, trimmed or removed.
+    pub changed: bool,
+    /// Blocks dropped because their task is done or unscheduled; synced ones
+    /// need deleting from the server.
+    pub removed: Vec<CalendarEvent>,
+}
+
+/// Keep time blocks in step with their tasks. `tasks` are the active tasks;
+/// a block whose task is gone has been completed or deleted.
+///
+/// - Completed: blocks still ahead are removed, one in progress ends now.
+/// - Rescheduled: the block moves to the new date at the same time.
+/// - Unscheduled: blocks still ahead are removed.
+///
+/// Past blocks are left alone as a record of the time spent.
+pub fn follow_tasks(events: &mut Vec<CalendarEvent>, tasks: &[Task], now: NaiveDateTime) -> BlockUpdate {
+    let mut update = BlockUpdate::default();
+    let mut remove: Vec<Uuid> = Vec::new();
+
+    for block in events.iter_mut().filter(|e| e.task_id.is_some() && !e.is_recurring()) {
+        let start = block.local_start();
+        let end = block.local_end();
+        if end <= now {
+            continue;
+        }
+        let task = tasks
+            .iter()
+            .find(|t| Some(t.id) == block.task_id)
+            .filter(|t| !t.state.is_done());
+
+        match task.map(|t| (t, t.scheduled)) {
+            None if start < now => {
+                block.set_local_times(start, now);
+                update.changed = true;
+            }
+            None | Some((_, None)) => remove.push(block.id),
+            Some((task, Some(date))) => {
+                if date != start.date() {
+                    let shift = date - start.date();
+                    block.set_local_times(start + shift, end + shift);
+                    update.changed = true;
+                }
+                if block.title != task.title {
+                    block.title = task.title.clone();
+                    update.changed = true;
+                }
+            }
+        }
+    }
+
+    if !remove.is_empty() {
+        let (removed, kept) = std::mem::take(events)
+            .into_iter()
+            .partition(|e| remove.contains(&e.id));
+        *events = kept;
+        update.removed = removed;
+        update.changed = true;
+    }
+    update
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, h: u32, m: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, day)
+            .unwrap()
+            .and_hms_opt(h, m, 0)
+            .unwrap()
+    }
+
+    fn floating(title: &str, start: NaiveDateTime, end: NaiveDateTime) -> CalendarEvent {
+        let mut event = CalendarEvent::new(title.to_string(), start, end);
+        event.tzid = None;
+        event
+    }
+
+    #[test]
+    fn sizes_blocks_from_estimate_then_esc() {
+        let mut task = Task::new("Write report");
+        assert_eq!(block_minutes(&task), DEFAULT_BLOCK_MINUTES);
+        task.esc = Some(3);
+        assert_eq!(block_minutes(&task), 45);
+        task.esc = Some(40);
+        assert_eq!(block_minutes(&task), MAX_ESC_MINUTES);
+        task.estimate = Some(90);
+        assert_eq!(block_minutes(&task), 90);
+    }
+
+    #[test]
+    fn finds_first_free_slot_around_meetings() {
+        let events = vec![
+            floating("Standup", at(2, 9, 0), at(2, 9, 15)),
+            floating("Review", at(2, 10, 0), at(2, 11, 0)),
+            floating("Overlap", at(2, 10, 30), at(2, 11, 20)),
+        ];
+        assert_eq!(
+            busy_intervals(&events, at(2, 0, 0).date()),
+            vec![(at(2, 9, 0), at(2, 9, 15)), (at(2, 10, 0), at(2, 11, 20))]
+        );
+        // 09:15 rounds up to 09:30, leaving half an hour before the review
+        assert_eq!(first_fit(&events, at(2, 9, 0), at(2, 17, 0), 30), Some(at(2, 9, 30)));
+        assert_eq!(first_fit(&events, at(2, 9, 0), at(2, 17, 0), 60), Some(at(2, 11, 30)));
+        assert_eq!(first_fit(&events, at(2, 9, 0), at(2, 12, 0), 60), None);
+    }
+
+    #[test]
+    fn suggests_one_slot_per_gap_within_working_hours() {
+        let busy = vec![
+            (at(2, 10, 0), at(2, 11, 0)),
+            (at(2, 9, 0), at(2, 9, 45)),
+            (at(2, 13, 0), at(2, 17, 0)),
+            (at(3, 8, 0), at(3, 12, 0)),
+        ];
+        let windows = vec![(at(2, 9, 0), at(2, 17, 0)), (at(3, 9, 0), at(3, 17, 0))];
+
+        // 09:45–10:00 is too short; 11:00 fits, then the next day after the morning block
+        assert_eq!(
+            suggest_slots(&busy, &windows, 60, 3),
+            vec![(at(2, 11, 0), at(2, 12, 0)), (at(3, 12, 0), at(3, 13, 0))]
+        );
+        assert_eq!(suggest_slots(&busy, &windows, 60, 1).len(), 1);
+        assert!(suggest_slots(&busy, &windows, 6 * 60, 3).is_empty());
+    }
+
+    #[test]
+    fn blocks_follow_completion_and_rescheduling() {
+        let mut moved = Task::new("Draft slides");
+        moved.scheduled = Some(at(3, 0, 0).date());
+        let unscheduled = Task::new("Call bank");
+        let in_progress = Task::new("Inbox zero");
+        let mut later = Task::new("Groceries");
+        later.scheduled = Some(at(2, 0, 0).date());
+
+        let mut events = vec![
+            floating("Draft slides", at(2, 14, 0), at(2, 15, 0)),
+            floating("Call bank", at(2, 16, 0), at(2, 16, 30)),
+            floating("Inbox zero", at(2, 9, 0), at(2, 10, 0)),
+            floating("Groceries", at(2, 17, 0), at(2, 17, 30)),
+            floating("Meeting", at(2, 11, 0), at(2, 12, 0)),
+        ];
+        events[0].task_id = Some(moved.id);
+        events[1].task_id = Some(unscheduled.id);
+        events[2].task_id = Some(in_progress.id);
+        events[3].task_id = Some(later.id);
+
+        // "Inbox zero" was completed (no longer active) halfway through its block
+        let active = vec![moved.clone(), unscheduled, later];
+        let update = follow_tasks(&mut events, &active, at(2, 9, 30));
+
+        assert!(update.changed);
+        assert_eq!(update.removed.len(), 1);
+        assert_eq!(update.removed[0].title, "Call bank");
+        let find = |title: &str| events.iter().find(|e| e.title == title).unwrap();
+        assert_eq!(find("Draft slides").start, at(3, 14, 0));
+        assert_eq!(find("Inbox zero").end, at(2, 9, 30));
+        assert_eq!(find("Groceries").start, at(2, 17, 0));
+        assert_eq!(find("Meeting").start, at(2, 11, 0));
+
+        // Nothing left to do once blocks match their tasks
+        assert!(!follow_tasks(&mut events, &active, at(2, 9, 30)).changed);
+    }
+}
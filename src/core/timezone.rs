@@ -0,0 +1,129 @@
+use chrono::{Duration, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Zone name used for UTC times (iCalendar values ending in `Z`).
+pub const UTC: &str = "UTC";
+
+/// IANA name of the system zone, if it can be determined.
+pub fn local_zone_name() -> Option<String> {
+    iana_time_zone::get_timezone()
+        .ok()
+        .filter(|name| resolve(name).is_some())
+}
+
+/// Look up an IANA zone, tolerating the path-style TZIDs some clients
+/// emit (e.g. `/freeassociation.sourceforge.net/Tzfile/Europe/Berlin`).
+pub fn resolve(tzid: &str) -> Option<Tz> {
+    let tzid = tzid.trim().trim_matches('"');
+    if let Ok(tz) = tzid.parse::<Tz>() {
+        return Some(tz);
+    }
+    // Try progressively shorter suffixes of a path-style TZID
+    let parts: Vec<&str> = tzid.split('/').filter(|p| !p.is_empty()).collect();
+    (1..parts.len())
+        .map(|skip| parts[skip..].join("/"))
+        .find_map(|candidate| candidate.parse::<Tz>().ok())
+}
+
+/// Interpret a wall-clock time in `tz`. Times falling in a DST gap are moved
+/// forward an hour; ambiguous times take the earlier instant.
+fn wall_to_utc(tz: Tz, wall: NaiveDateTime) -> NaiveDateTime {
+    tz.from_local_datetime(&wall)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(wall + Duration::hours(1))).earliest())
+        .map(|dt| dt.naive_utc())
+        .unwrap_or(wall)
+}
+
+/// Convert a wall-clock time in zone `tzid` to UTC. `None` (floating) and
+/// unknown zones are taken as system-local.
+pub fn to_utc(wall: NaiveDateTime, tzid: Option<&str>) -> NaiveDateTime {
+    match tzid {
+        Some(UTC) => wall,
+        Some(name) => match resolve(name) {
+            Some(tz) => wall_to_utc(tz, wall),
+            None => local_to_utc(wall),
+        },
+        None => local_to_utc(wall),
+    }
+}
+
+/// Convert a UTC time to wall-clock time in zone `tzid` (`None` = system-local).
+pub fn from_utc(utc: NaiveDateTime, tzid: Option<&str>) -> NaiveDateTime {
+    match tzid.map(|name| (name, resolve(name))) {
+        Some((UTC, _)) => utc,
+        Some((_, Some(tz))) => tz.from_utc_datetime(&utc).naive_local(),
+        _ => Local.from_utc_datetime(&utc).naive_local(),
+    }
+}
+
+/// Convert a wall-clock time in zone `from` to wall-clock time in zone `to`.
+pub fn convert(wall: NaiveDateTime, from: Option<&str>, to: Option<&str>) -> NaiveDateTime {
+    if from == to {
+        return wall;
+    }
+    from_utc(to_utc(wall, from), to)
+}
+
+/// Wall-clock time in zone `tzid` shown in the system zone.
+pub fn to_local(wall: NaiveDateTime, tzid: Option<&str>) -> NaiveDateTime {
+    match tzid {
+        None => wall,
+        Some(_) => convert(wall, tzid, None),
+    }
+}
+
+fn local_to_utc(wall: NaiveDateTime) -> NaiveDateTime {
+    Local
+        .from_local_datetime(&wall)
+        .earliest()
+        .or_else(|| Local.from_local_datetime(&(wall + Duration::hours(1))).earliest())
+        .map(|dt| dt.naive_utc())
+        .unwrap_or(wall)
+}
+
+/// Current time in UTC, for iCalendar stamps like LAST-MODIFIED.
+pub fn now_utc() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn resolves_iana_and_path_style_tzids() {
+        assert_eq!(resolve("Europe/Berlin"), Some(chrono_tz::Europe::Berlin));
+        assert_eq!(
+            resolve("/freeassociation.sourceforge.net/Tzfile/Europe/Berlin"),
+            Some(chrono_tz::Europe::Berlin)
+        );
+        assert_eq!(resolve("\"America/New_York\""), Some(chrono_tz::America::New_York));
+        assert_eq!(resolve("Mars/Olympus_Mons"), None);
+    }
+
+    #[test]
+    fn converts_between_zones_across_dst() {
+        // Winter: Berlin is UTC+1, New York UTC-5
+        let winter = at(2026, 1, 15, 9, 0);
+        assert_eq!(to_utc(winter, Some("Europe/Berlin")), at(2026, 1, 15, 8, 0));
+        assert_eq!(
+            convert(winter, Some("Europe/Berlin"), Some("America/New_York")),
+            at(2026, 1, 15, 3, 0)
+        );
+        // Summer: Berlin is UTC+2
+        let summer = at(2026, 7, 15, 9, 0);
+        assert_eq!(to_utc(summer, Some("Europe/Berlin")), at(2026, 7, 15, 7, 0));
+        assert_eq!(from_utc(at(2026, 7, 15, 7, 0), Some(UTC)), at(2026, 7, 15, 7, 0));
+        // 02:30 doesn't exist in Berlin on the spring-forward day
+        assert_eq!(to_utc(at(2026, 3, 29, 2, 30), Some("Europe/Berlin")), at(2026, 3, 29, 1, 30));
+    }
+}
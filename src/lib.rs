@@ -2,6 +2,7 @@
 
 pub mod config;
 pub mod core;
+pub mod localize;
 pub mod org;
 pub mod sync;
 
@@ -6,12 +6,13 @@ use cosmic::iced::Limits;
 
 mod application;
 mod components;
-mod localize;
 mod message;
 mod pages;
 
 use lamp::config;
 use lamp::core;
+use lamp::fl;
+use lamp::localize;
 use lamp::org;
 use lamp::sync;
 
@@ -22,6 +23,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cosmic_cfg = cosmic::cosmic_config::Config::new("dev.lamp.app", CONFIG_VERSION)
         .expect("Failed to create cosmic config");
     let config = LampConfig::get_entry(&cosmic_cfg).unwrap_or_else(|(_, cfg)| cfg);
+    sync::keyring::set_backend(config.secret_backend);
 
     // Set up logging to the systemd user journal (`journalctl --user -t lamp -f`).
     // Wrapper filters: lamp crate at info/debug (per config), everything else at warn.
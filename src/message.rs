@@ -1,21 +1,28 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime, Weekday};
 
-use crate::config::CalendarPurpose;
+use crate::config::{CalendarPurpose, ProjectSyncMode};
+use crate::core::delegation::{FollowUpDraft, WaitingParty};
+use crate::core::event::{EditScope, PartStat};
 use crate::core::link::LinkTarget;
+use crate::core::rrule::{Frequency, RuleEnd};
 use crate::core::task::{Priority, TaskState};
 use crate::sync::caldav::CalendarInfo;
 use crate::sync::carddav::{Contact, ContactCategory};
-use crate::sync::imap::ImapEmail;
+use crate::sync::freebusy::BusyPeriod;
+use crate::sync::imap::{CapturedEmail, ImapEmail, MailCache};
+use crate::sync::itip::ItipReply;
+use crate::sync::subscription::FeedFetch;
 use crate::sync::webdav::NoteSyncResult;
 use crate::sync::SyncResult;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ServiceKind {
     Calendars,
     Contacts,
     Notes,
     Imap,
+    Smtp,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,6 +41,16 @@ pub enum AccountField {
     Notes,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriageRuleField {
+    Name,
+    From,
+    Subject,
+    ListId,
+    Title,
+    Esc,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NoteField {
     Title,
@@ -74,6 +91,15 @@ pub enum Message {
     SetWaitingFor(Uuid, String),
     WaitingForInputChanged(Uuid, String),
     SetFollowUp(Uuid, Option<NaiveDate>),
+    SetWaitingForContact(Uuid, Uuid),
+    ComposeFollowUp(WaitingParty),
+    DismissFollowUpDraft,
+    /// Email the delegation request to the contact the task waits on
+    SendDelegationEmail(Uuid),
+    /// The sent request's Message-ID
+    DelegationSent(Uuid, Result<String, String>),
+    /// Tasks the follow-up covered, and the draft to fall back to on failure
+    FollowUpSent(Vec<Uuid>, FollowUpDraft, Result<String, String>),
 
     // Dates
     SetScheduled(Uuid, Option<NaiveDate>),
@@ -179,6 +205,22 @@ pub enum Message {
     SettingsRemoveContext(usize),
     SetBrowserCommand(String),
     ToggleDebugLogging,
+    SetSyncInterval(u32),
+    SetMeetingSpoonRate(u32),
+
+    // .ics import/export
+    SetIcsImportPath(String),
+    ImportIcs,
+    SetIcsExportPath(String),
+    SetIcsExportProject(Option<String>),
+    SetIcsExportCalendar(Option<String>),
+    SetIcsExportFrom(String),
+    SetIcsExportTo(String),
+    ToggleIcsExportTasks,
+    ToggleIcsExportEvents,
+    ExportIcs,
+    /// Account id, calendar href, spoons per hour (`None` = the default rate)
+    SetCalendarSpoonRate(String, String, Option<u32>),
 
     // Review checklist
     ToggleReviewStep(usize),
@@ -208,38 +250,133 @@ pub enum Message {
 
     // Sync — flat per-service config
     SyncNow,
-    SyncCompleted(Result<SyncResult, String>),
-    SetServiceUrl(ServiceKind, String),
-    SetServiceUsername(ServiceKind, String),
-    SetServicePassword(ServiceKind, String),
-    TestServiceConnection(ServiceKind),
-    ServiceConnectionTested(ServiceKind, Result<String, String>, Vec<CalendarInfo>),
-    SetCalendarPurpose(String, CalendarPurpose),
+    /// Per calendar account: (account id, result).
+    SyncCompleted(Vec<(String, Result<SyncResult, String>)>),
+    RetryOutbox,
+    /// Clear the outbox back-off and sync straight away.
+    RetryOutboxNow,
+    /// Drop a queued write; the next sync compares the task with the server again.
+    DiscardOutboxEntry(Uuid),
+    SyncTick,
+    SyncPollCompleted(Result<bool, String>),
+    // Service accounts: (kind, account index, ...). Notes and IMAP have a single account, index 0.
+    AddServiceAccount(ServiceKind),
+    RemoveServiceAccount(ServiceKind, usize),
+    SetServiceName(ServiceKind, usize, String),
+    SetServiceUrl(ServiceKind, usize, String),
+    SetServiceUsername(ServiceKind, usize, String),
+    SetServicePassword(ServiceKind, usize, String),
+    TestServiceConnection(ServiceKind, usize),
+    ServiceConnectionTested(ServiceKind, usize, Result<String, String>, Vec<CalendarInfo>),
+    /// (account id, calendar href, purpose)
+    SetCalendarPurpose(String, String, CalendarPurpose),
+    TaskRouteInput(String),
+    SetTaskRouteCalendar(usize),
+    AddTaskRoute,
+    RemoveTaskRoute(usize),
+    SetProjectSyncMode(ProjectSyncMode),
     SyncNotesCompleted(Result<NoteSyncResult, String>),
-    ContactsFetched(Result<Vec<Contact>, String>),
+    ContactsFetched(String, Result<Vec<Contact>, String>),
     ContactDeleted(Result<(), String>),
 
+    // Meeting invitations
+    RespondToInvitation(Uuid, PartStat),
+
+    // Read-only ICS subscriptions
+    AddSubscription,
+    RemoveSubscription(usize),
+    SetSubscriptionName(usize, String),
+    SetSubscriptionUrl(usize, String),
+    RefreshSubscriptions,
+    /// (subscription id, fetch result) for each feed.
+    SubscriptionsRefreshed(Vec<(String, Result<FeedFetch, String>)>),
+
     // IMAP email integration
-    ImapFetched(Result<Vec<ImapEmail>, String>),
+    ImapFetched(Result<MailCache, String>),
+    ImapBodiesFetched(Result<Vec<ImapEmail>, String>),
+    /// Message-IDs of waiting tasks' emails that got a reply
+    EmailRepliesFound(Result<Vec<String>, String>),
+    OpenTaskEmail(Uuid),
+    /// An IMAP IDLE wait ended; `true` when the folder changed
+    ImapIdleEnded(Result<bool, String>),
     ArchiveEmail(u32),
     EmailArchived(Result<u32, String>),
     SetImapFolder(String),
+    SetImapCaptureFolder(String),
+    SetSmtpFrom(String),
+    /// Each queued meeting reply with its send result
+    RepliesSent(Vec<(ItipReply, Result<String, String>)>),
+    SetImapCaptureAddress(String),
+    /// Mail sent to the capture folder or address, to become tasks
+    CapturesFetched(Result<Vec<CapturedEmail>, String>),
+    CaptureArchived(Result<u32, String>),
+
+    // Email triage rules
+    AddTriageRule,
+    RemoveTriageRule(usize),
+    SetTriageRuleField(usize, TriageRuleField, String),
+    ToggleTriageRuleContext(usize, String),
+    /// Index into "none" followed by the project names
+    SetTriageRuleProject(usize, usize),
+    /// Index into "none", A, B, C
+    SetTriageRulePriority(usize, usize),
+    /// Index into any, with attachments, without
+    SetTriageRuleAttachment(usize, usize),
+    SetTriageRuleCreateTask(usize, bool),
+    SetTriageRuleArchive(usize, bool),
+    SetTriageAiFallback(bool),
 
     // AI batch email suggestions
-    SetAnthropicApiKey(String),
-    TestAnthropicApiKey,
-    AnthropicKeyTested(Result<String, String>),
+    SetLlmBackend(crate::config::LlmBackend),
+    SetLlmEndpoint(String),
+    SetLlmModel(String),
+    SetLlmApiKey(String),
+    TestLlmProvider,
+    LlmProviderTested(Result<String, String>),
+
+    // Secret storage
+    SetSecretBackend(crate::config::SecretBackend),
+    SetVaultPassphrase(String),
+    /// The passphrase again, when the vault is being created.
+    SetVaultPassphraseRepeat(String),
+    UnlockVault,
+    SecretStoreChecked(Result<String, String>),
+    /// Move every secret into this backend from the other one
+    MigrateSecrets(crate::config::SecretBackend),
+    SecretsMigrated(crate::config::SecretBackend, Result<usize, String>),
     SuggestEmailTasks,
-    BatchSuggestionsReady(Result<Vec<(u32, crate::sync::anthropic::BatchEmailSuggestion)>, String>),
+    BatchSuggestionsReady(Result<Vec<(u32, crate::sync::llm::BatchEmailSuggestion)>, String>),
     ApproveSuggestion(u32),
     DismissSuggestion(u32),
 
+    // AI weekly review suggestions, by position in the list
+    SuggestReview,
+    ReviewSuggestionsReady(Result<Vec<crate::sync::assistant::ReviewSuggestion>, String>),
+    ApproveReviewSuggestion(usize),
+    DismissReviewSuggestion(usize),
+
+    // AI task breakdown, edited in the context drawer before it's filed
+    BreakDownTask(Uuid),
+    BreakdownReady(Uuid, Result<crate::sync::assistant::TaskBreakdown, String>),
+    SetBreakdownProject(String),
+    SetBreakdownOutcome(String),
+    SetBreakdownStepTitle(usize, String),
+    SetBreakdownStepEsc(usize, Option<u32>),
+    SetBreakdownStepContext(usize, Option<String>),
+    AddBreakdownStep,
+    RemoveBreakdownStep(usize),
+    ApplyBreakdown,
+    CloseBreakdown,
+
     // Event CRUD
     CreateEvent,
     SubmitEvent,
-    EditEvent(Uuid),
+    /// Event id, plus the original start when it's one occurrence of a series
+    EditEvent(Uuid, Option<NaiveDateTime>),
     UpdateEvent(Uuid),
-    DeleteEvent(Uuid),
+    DeleteEvent(Uuid, Option<NaiveDateTime>),
+    ConfirmDeleteEvent(EditScope),
+    CancelDeleteEvent,
     CancelEventForm,
 
     // Event form fields
@@ -250,12 +387,44 @@ pub enum Message {
     SetEventLocation(String),
     SetEventDescription(String),
     SetEventCalendar(String),
+    SetEventEsc(String),
+    /// Add a reminder this many minutes before the start.
+    AddEventAlarm(i64),
+    RemoveEventAlarm(usize),
+    SetEventScope(EditScope),
+    SetEventRepeat(Option<Frequency>),
+    SetEventRepeatInterval(String),
+    ToggleEventRepeatWeekday(Weekday),
+    SetEventRepeatNthWeekday(bool),
+    SetEventRepeatEnd(RuleEnd),
+    SetEventRepeatUntil(String),
+    SetEventRepeatCount(String),
 
     // Month calendar
     CalendarPrevMonth,
     CalendarNextMonth,
     CalendarSelectDay(NaiveDate),
 
+    // Time-blocking timeline
+    SetAgendaLayout(AgendaLayout),
+    TimelinePrev,
+    TimelineNext,
+    TimelineToday,
+    /// Pick a task to drop into a timeline slot (again to put it back)
+    PickBlockTask(Uuid),
+    PlaceTaskBlock(Uuid, NaiveDateTime),
+    /// Drop a task into the first free slot of the days on display
+    AutoPlaceTask(Uuid),
+
+    // Free/busy
+    RefreshFreeBusy,
+    FreeBusyLoaded(Vec<(String, Result<Vec<BusyPeriod>, String>)>),
+    ToggleFreeBusyCalendar(String),
+    /// Meeting length in minutes to find slots for
+    SetFreeBusyDuration(String),
+    /// Open the event form for a suggested slot
+    CreateEventAt(NaiveDateTime, NaiveDateTime),
+
     // Conflict resolution
     ImportConflictTask(usize),
     DeleteConflict(usize),
@@ -269,6 +438,17 @@ pub enum ListKind {
     Shopping,
 }
 
+/// How the agenda is laid out: a dated list, a timeline to block time in,
+/// or a week of free/busy time across calendars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AgendaLayout {
+    #[default]
+    List,
+    Day,
+    Week,
+    FreeBusy,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WhatPage {
     DailyPlanning,
@@ -3,6 +3,8 @@ use regex::Regex;
 use std::sync::LazyLock;
 use uuid::Uuid;
 
+use crate::core::alarm::alarms_from_org;
+use crate::core::email_link::EmailLink;
 use crate::core::recurrence::Recurrence;
 use crate::core::task::{Priority, Task, TaskState};
 
@@ -202,6 +204,15 @@ impl OrgParser {
     }
 }
 
+/// Parse an org `EFFORT` value ("1:30", "0:45" or plain minutes) into minutes.
+pub fn parse_effort(s: &str) -> Option<u32> {
+    let s = s.trim();
+    match s.split_once(':') {
+        Some((h, m)) => Some(h.trim().parse::<u32>().ok()? * 60 + m.trim().parse::<u32>().ok()?),
+        None => s.parse().ok(),
+    }
+}
+
 /// Convert a ParsedHeading into a Task.
 pub fn heading_to_task(heading: &ParsedHeading) -> Task {
     let id = OrgParser::get_property(&heading.properties, "ID")
@@ -227,9 +238,14 @@ pub fn heading_to_task(heading: &ParsedHeading) -> Task {
         .map(|s| s.to_string())
         .filter(|s| !s.is_empty());
 
+    let waiting_for_contact = OrgParser::get_property(&heading.properties, "WAITING_FOR_CONTACT")
+        .and_then(|s| Uuid::parse_str(s.trim()).ok());
+
     let esc = OrgParser::get_property(&heading.properties, "ESC")
         .and_then(|s| s.trim().parse::<u32>().ok());
 
+    let estimate = OrgParser::get_property(&heading.properties, "EFFORT").and_then(parse_effort);
+
     let delegated = OrgParser::get_property(&heading.properties, "DELEGATED")
         .and_then(|s| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok());
 
@@ -255,6 +271,20 @@ pub fn heading_to_task(heading: &ParsedHeading) -> Task {
         .map(|s| s.to_string())
         .filter(|s| !s.is_empty());
 
+    let sync_parent = OrgParser::get_property(&heading.properties, "SYNC_PARENT")
+        .map(|s| s.to_string())
+        .filter(|s| !s.is_empty());
+
+    let alarms = OrgParser::get_property(&heading.properties, "ALARMS")
+        .map(alarms_from_org)
+        .unwrap_or_default();
+
+    let email = OrgParser::get_property(&heading.properties, "EMAIL_ID").and_then(EmailLink::from_org);
+
+    let delegation_id = OrgParser::get_property(&heading.properties, "DELEGATION_ID")
+        .map(|s| s.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+        .filter(|s| !s.is_empty());
+
     Task {
         id,
         title: heading.title.clone(),
@@ -269,12 +299,18 @@ pub fn heading_to_task(heading: &ParsedHeading) -> Task {
         completed,
         project: None,
         waiting_for,
+        waiting_for_contact,
         esc,
+        estimate,
         delegated,
         follow_up,
         sync_href,
         sync_hash,
         sync_uid,
+        sync_parent,
+        alarms,
+        email,
+        delegation_id,
     }
 }
 
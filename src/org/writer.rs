@@ -2,6 +2,7 @@ use chrono::NaiveDateTime;
 use std::io::Write;
 
 use crate::core::account::Account;
+use crate::core::alarm::alarms_to_org;
 use crate::core::day_plan::DayPlan;
 use crate::core::list_item::ListItem;
 use crate::core::note::Note;
@@ -99,15 +100,30 @@ impl OrgWriter {
         if let Some(esc) = task.esc {
             out.push_str(&format!("{indent}:ESC: {}\n", esc));
         }
+        if let Some(minutes) = task.estimate {
+            out.push_str(&format!("{indent}:EFFORT: {}:{:02}\n", minutes / 60, minutes % 60));
+        }
         if let Some(ref wf) = task.waiting_for {
             out.push_str(&format!("{indent}:WAITING_FOR: {}\n", wf));
         }
+        if let Some(contact_id) = task.waiting_for_contact {
+            out.push_str(&format!("{indent}:WAITING_FOR_CONTACT: {}\n", contact_id));
+        }
         if let Some(delegated) = task.delegated {
             out.push_str(&format!("{indent}:DELEGATED: {}\n", delegated.format("%Y-%m-%d")));
         }
         if let Some(follow_up) = task.follow_up {
             out.push_str(&format!("{indent}:FOLLOW_UP: {}\n", follow_up.format("%Y-%m-%d")));
         }
+        if !task.alarms.is_empty() {
+            out.push_str(&format!("{indent}:ALARMS: {}\n", alarms_to_org(&task.alarms)));
+        }
+        if let Some(ref email) = task.email {
+            out.push_str(&format!("{indent}:EMAIL_ID: {}\n", email.to_org()));
+        }
+        if let Some(ref delegation_id) = task.delegation_id {
+            out.push_str(&format!("{indent}:DELEGATION_ID: <{}>\n", delegation_id));
+        }
         if let Some(ref sync_href) = task.sync_href {
             out.push_str(&format!("{indent}:SYNC_HREF: {}\n", sync_href));
         }
@@ -117,6 +133,9 @@ impl OrgWriter {
         if let Some(ref sync_uid) = task.sync_uid {
             out.push_str(&format!("{indent}:SYNC_UID: {}\n", sync_uid));
         }
+        if let Some(ref sync_parent) = task.sync_parent {
+            out.push_str(&format!("{indent}:SYNC_PARENT: {}\n", sync_parent));
+        }
         out.push_str(&format!("{indent}:END:\n"));
 
         // Notes
@@ -477,11 +496,14 @@ mod tests {
             priority: Some(Priority::A),
             contexts: vec!["@home".to_string()],
             scheduled: Some(NaiveDate::from_ymd_opt(2026, 2, 24).unwrap()),
+            estimate: Some(90),
             ..Task::new("unused")
         };
         let output = OrgWriter::write_task(&task);
         assert!(output.contains("* TODO [#A] Fix the faucet :@home:"));
         assert!(output.contains("SCHEDULED: <2026-02-24 Tue>"));
+        assert!(output.contains(":EFFORT: 1:30"));
+        assert_eq!(crate::org::parser::parse_effort("1:30"), Some(90));
         assert!(output.contains(":ID:"));
         assert!(output.contains(":CREATED:"));
     }
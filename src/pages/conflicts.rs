@@ -5,9 +5,13 @@ use cosmic::Element;
 use crate::fl;
 use crate::message::Message;
 use crate::sync::SyncConflict;
+use crate::sync::outbox::OutboxEntry;
 
-pub fn conflicts_view(conflicts: &[SyncConflict]) -> Element<'static, Message> {
-    if conflicts.is_empty() {
+pub fn conflicts_view(
+    conflicts: &[SyncConflict],
+    outbox: &[OutboxEntry],
+) -> Element<'static, Message> {
+    if conflicts.is_empty() && outbox.is_empty() {
         return container(text::body(fl!("conflicts-empty")))
             .padding(32)
             .center_x(Length::Fill)
@@ -31,6 +35,64 @@ pub fn conflicts_view(conflicts: &[SyncConflict]) -> Element<'static, Message> {
 
     let mut sections = column().spacing(24);
 
+    // Queued writes waiting to reach the server
+    if !outbox.is_empty() {
+        let mut section = column().spacing(8);
+        let header = row()
+            .spacing(8)
+            .align_y(cosmic::iced::Alignment::Center)
+            .push(
+                text::title4(fl!("conflicts-outbox", count = outbox.len()))
+                    .width(Length::Fill),
+            )
+            .push(button::standard(fl!("conflicts-outbox-retry")).on_press(Message::RetryOutboxNow));
+        section = section.push(header);
+        for entry in outbox {
+            let name = entry
+                .op
+                .href()
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let mut entry_col = column()
+                .spacing(2)
+                .push(text::body(format!("{} — {}", entry.op.label(), name)));
+            let mut status = vec![fl!(
+                "conflicts-outbox-queued",
+                time = entry
+                    .queued
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            )];
+            if entry.attempts > 0 {
+                status.push(fl!("conflicts-outbox-attempts", count = entry.attempts));
+            }
+            if let Some(next) = entry.next_attempt {
+                status.push(fl!(
+                    "conflicts-outbox-next",
+                    time = next.with_timezone(&chrono::Local).format("%H:%M").to_string()
+                ));
+            }
+            entry_col = entry_col.push(text::caption(status.join(", ")));
+            if let Some(ref err) = entry.last_error {
+                entry_col = entry_col.push(text::caption(err.clone()));
+            }
+            // A write the server keeps refusing would otherwise retry forever
+            let entry_row = row()
+                .spacing(16)
+                .align_y(cosmic::iced::Alignment::Center)
+                .push(container(entry_col).width(Length::Fill))
+                .push(
+                    button::destructive(fl!("conflicts-outbox-discard"))
+                        .on_press(Message::DiscardOutboxEntry(entry.id)),
+                );
+            section = section.push(entry_row);
+        }
+        sections = sections.push(section);
+    }
+
     // Status Mismatches section
     if !mismatches.is_empty() {
         let mut section = column().spacing(8);
@@ -4,9 +4,10 @@ use cosmic::iced::{Alignment, Length};
 use cosmic::widget::{button, column, container, row, scrollable, text};
 use cosmic::Element;
 
-use crate::core::day_plan::DayPlan;
+use crate::core::day_plan::{DayLoad, DayPlan};
 use crate::core::list_item::ListItem;
 use crate::core::task::{Priority, Task, TaskState};
+use crate::core::timeblock;
 use crate::message::Message;
 
 const BUDGET_PRESETS: &[u32] = &[5, 10, 20, 30, 50, 75, 100];
@@ -18,6 +19,7 @@ pub fn daily_planning_view<'a>(
     shopping_items: &[ListItem],
     contexts: &[String],
     rejected: &HashSet<uuid::Uuid>,
+    load: &DayLoad,
 ) -> Element<'a, Message> {
     let budget = day_plan.as_ref().map(|dp| dp.spoon_budget).unwrap_or(50);
     let active_contexts: Vec<String> = day_plan
@@ -41,7 +43,8 @@ pub fn daily_planning_view<'a>(
         .as_ref()
         .map(|dp| dp.spent_spoons)
         .unwrap_or(0);
-    let remaining = budget.saturating_sub(spent);
+    // Today's meetings are paid for up front
+    let remaining = budget.saturating_sub(spent).saturating_sub(load.event_spoons);
 
     let mut content = column().spacing(24).padding(16).width(Length::Fill);
 
@@ -57,6 +60,31 @@ pub fn daily_planning_view<'a>(
         budget_row = budget_row.push(btn.on_press(Message::SetSpoonBudget(preset)));
     }
     content = content.push(budget_row);
+    if load.event_spoons > 0 {
+        content = content.push(text::caption(format!(
+            "Today's events take {} spoons, leaving {} for tasks.",
+            load.event_spoons,
+            budget.saturating_sub(load.event_spoons)
+        )));
+    }
+
+    // Free time between today's events
+    let free_minutes = load.free_minutes();
+    content = content.push(text::title4(format!(
+        "Free Time ({}h {:02}m)",
+        free_minutes / 60,
+        free_minutes % 60
+    )));
+    if load.free.is_empty() {
+        content = content.push(text::body("No free time left in today's working hours."));
+    } else {
+        let slots: Vec<String> = load
+            .free
+            .iter()
+            .map(|(start, end)| format!("{}–{}", start.format("%H:%M"), end.format("%H:%M")))
+            .collect();
+        content = content.push(text::body(slots.join("  ·  ")));
+    }
 
     // Section 2: Active Contexts
     content = content.push(text::title4("Active Contexts"));
@@ -116,13 +144,17 @@ pub fn daily_planning_view<'a>(
     }
 
     // Section 4: Suggestions
-    let header_text = format!("Suggested tasks ({}/{} spoons spent)", spent, budget);
+    let header_text = format!(
+        "Suggested tasks ({}/{} spoons spent)",
+        spent + load.event_spoons,
+        budget
+    );
     content = content.push(text::title4(header_text));
 
-    let suggestions = build_suggestions(all_tasks, &confirmed_ids, rejected, &active_contexts, remaining, today);
+    let suggestions = build_suggestions(all_tasks, &confirmed_ids, rejected, &active_contexts, remaining, load, today);
 
     if suggestions.is_empty() {
-        content = content.push(text::body("No more suggestions fit your remaining budget."));
+        content = content.push(text::body("No more suggestions fit your remaining budget and free time."));
     } else {
         let mut suggestion_col = column().spacing(4);
         for task in &suggestions {
@@ -234,6 +266,7 @@ fn build_suggestions(
     rejected: &HashSet<uuid::Uuid>,
     active_contexts: &[String],
     remaining_budget: u32,
+    load: &DayLoad,
     today: chrono::NaiveDate,
 ) -> Vec<Task> {
     let mut candidates: Vec<Task> = all_tasks
@@ -256,6 +289,10 @@ fn build_suggestions(
                     return false;
                 }
             }
+            // Fits a free stretch between events
+            if !load.fits(timeblock::block_minutes(t)) {
+                return false;
+            }
             // ESC fits remaining budget (None always eligible)
             match t.esc {
                 Some(esc) => esc <= remaining_budget,
@@ -19,6 +19,7 @@ pub fn do_mode_view<'a>(
     shopping_items: &[ListItem],
     expanded_task: Option<uuid::Uuid>,
     note_inputs: &HashMap<uuid::Uuid, String>,
+    event_spoons: u32,
 ) -> Element<'a, Message> {
     let today = chrono::Local::now().date_naive();
 
@@ -34,7 +35,8 @@ pub fn do_mode_view<'a>(
         .into();
     };
 
-    let remaining = plan.remaining_budget();
+    // Spoons today's events take are spoken for
+    let remaining = plan.remaining_budget().saturating_sub(event_spoons);
     let budget = plan.spoon_budget;
 
     let mut content = column().spacing(24).padding(16).width(Length::Fill);
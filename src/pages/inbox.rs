@@ -93,8 +93,11 @@ pub fn inbox_view<'a>(
                         let title = s.title.clone().unwrap_or_default();
                         let mut suggestion_row = row().spacing(8).align_y(Alignment::Center);
                         let mut detail_col = column().spacing(2);
-                        detail_col =
-                            detail_col.push(text::caption(fl!("email-suggested-task")));
+                        let heading = match s.rule {
+                            Some(ref rule) => fl!("email-suggested-by-rule", rule = rule.as_str()),
+                            None => fl!("email-suggested-task"),
+                        };
+                        detail_col = detail_col.push(text::caption(heading));
                         detail_col = detail_col.push(text::body(title));
 
                         // Show metadata summary
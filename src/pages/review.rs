@@ -1,8 +1,8 @@
 use std::collections::HashSet;
 
 use chrono::Duration;
-use cosmic::iced::Length;
-use cosmic::widget::{checkbox, column, container, scrollable, text};
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{button, checkbox, column, container, row, scrollable, text};
 use cosmic::Element;
 
 use crate::core::habit::Habit;
@@ -10,6 +10,7 @@ use crate::core::project::Project;
 use crate::core::task::{Task, TaskState};
 use crate::fl;
 use crate::message::Message;
+use crate::sync::assistant::ReviewSuggestion;
 
 /// A task is "unprocessed" (belongs in inbox) if it's still Todo
 /// and has none of priority, contexts, project, or ESC set.
@@ -26,6 +27,9 @@ pub fn review_view(
     projects: &[Project],
     habits: &[Habit],
     checked: &HashSet<usize>,
+    suggestions: &[ReviewSuggestion],
+    ai_running: bool,
+    ai_error: Option<&str>,
 ) -> Element<'static, Message> {
     let mut content = column().spacing(16);
     let today = chrono::Local::now().naive_local();
@@ -75,6 +79,28 @@ pub fn review_view(
 
     let total_steps: usize = 9;
 
+    // Assistant pass: suggestions appear under the step they belong to
+    let mut ai_row = row().spacing(8).align_y(Alignment::Center);
+    ai_row = ai_row.push(match ai_error {
+        Some(e) => text::caption(fl!("review-ai-error", error = e.to_string())).width(Length::Fill),
+        None => text::caption(fl!("review-ai-hint")).width(Length::Fill),
+    });
+    if ai_running {
+        ai_row = ai_row.push(text::caption(fl!("review-ai-running")));
+    } else {
+        ai_row = ai_row.push(button::standard(fl!("review-ai-ask")).on_press(Message::SuggestReview));
+    }
+    content = content.push(ai_row);
+    let suggestion_cards = |wanted: fn(&ReviewSuggestion) -> bool| {
+        let mut cards = column().spacing(4).padding([0, 0, 0, 28]);
+        let mut any = false;
+        for (idx, suggestion) in suggestions.iter().enumerate().filter(|(_, s)| wanted(s)) {
+            cards = cards.push(suggestion_card(idx, suggestion));
+            any = true;
+        }
+        any.then_some(cards)
+    };
+
     // ── Phase 1: Get Clear ──
     content = content.push(text::title3(fl!("review-phase-clear")));
 
@@ -99,6 +125,9 @@ pub fn review_view(
     content = content.push(
         text::body(format!("{} next actions", next_count)).size(13.0),
     );
+    if let Some(cards) = suggestion_cards(|s| matches!(s, ReviewSuggestion::Rewrite { .. })) {
+        content = content.push(cards);
+    }
 
     // Step 3: Review Waiting For
     content = content.push(review_step(3, &fl!("review-step-waiting"), checked));
@@ -139,6 +168,9 @@ pub fn review_view(
         }
         content = content.push(stuck_col);
     }
+    if let Some(cards) = suggestion_cards(|s| matches!(s, ReviewSuggestion::NextAction { .. })) {
+        content = content.push(cards);
+    }
 
     // Step 5: Review Someday/Maybe
     content = content.push(review_step(5, &fl!("review-step-someday"), checked));
@@ -149,6 +181,9 @@ pub fn review_view(
         }
         content = content.push(someday_col);
     }
+    if let Some(cards) = suggestion_cards(|s| matches!(s, ReviewSuggestion::Activate { .. })) {
+        content = content.push(cards);
+    }
 
     // ── Phase 3: Get Creative ──
     content = content.push(text::title3(fl!("review-phase-creative")));
@@ -207,3 +242,40 @@ fn review_step(idx: usize, label: &str, checked: &HashSet<usize>) -> Element<'st
         .on_toggle(move |_| Message::ToggleReviewStep(step_idx))
         .into()
 }
+
+/// One assistant suggestion with its approve and dismiss buttons.
+fn suggestion_card(idx: usize, suggestion: &ReviewSuggestion) -> Element<'static, Message> {
+    let (heading, body) = match suggestion {
+        ReviewSuggestion::NextAction { project, title, contexts } => {
+            let mut body = title.clone();
+            if !contexts.is_empty() {
+                body = format!("{}  {}", body, contexts.join(", "));
+            }
+            (fl!("review-ai-next-action", project = project.clone()), body)
+        }
+        ReviewSuggestion::Rewrite { old, title, .. } => {
+            (fl!("review-ai-rewrite", title = old.clone()), title.clone())
+        }
+        ReviewSuggestion::Activate { title, reason, .. } => {
+            let body = if reason.is_empty() {
+                title.clone()
+            } else {
+                format!("{} — {}", title, reason)
+            };
+            (fl!("review-ai-activate"), body)
+        }
+    };
+    row()
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .push(
+            column()
+                .spacing(2)
+                .push(text::caption(heading))
+                .push(text::body(body).size(13.0))
+                .width(Length::Fill),
+        )
+        .push(button::suggested(fl!("review-ai-approve")).on_press(Message::ApproveReviewSuggestion(idx)))
+        .push(button::standard(fl!("review-ai-dismiss")).on_press(Message::DismissReviewSuggestion(idx)))
+        .into()
+}
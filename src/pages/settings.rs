@@ -1,21 +1,40 @@
+use std::collections::HashMap;
+
 use cosmic::iced::{Alignment, Length};
 use cosmic::widget::{button, column, container, icon, row, scrollable, text, text_input};
 use cosmic::Element;
 
-use crate::config::{CalendarPurpose, LampConfig};
-use crate::message::{Message, ServiceKind};
+use crate::application::IcsForm;
+use crate::config::{CalendarPurpose, LampConfig, LlmBackend, ProjectSyncMode, SecretBackend, ServiceConfig, TriageRule};
+use crate::message::{Message, ServiceKind, TriageRuleField};
 use crate::sync::caldav::CalendarInfo;
 use crate::sync::SyncStatus;
 
+/// Background sync interval choices in minutes (0 = off).
+const SYNC_INTERVAL_PRESETS: &[u32] = &[0, 5, 15, 30, 60];
+
+/// Spoons-per-hour choices for pricing events.
+const MEETING_SPOON_PRESETS: &[u32] = &[0, 5, 10, 15, 20];
+
 pub fn settings_view<'a>(
     config: &'a LampConfig,
     settings_context_input: &'a str,
-    service_passwords: &[String; 4],
-    service_test_status: &[Option<Result<String, String>>; 4],
-    discovered_calendars: &[CalendarInfo],
-    anthropic_api_key_input: &str,
-    anthropic_test_status: &Option<Result<String, String>>,
+    service_passwords: &HashMap<(ServiceKind, usize), String>,
+    service_test_status: &HashMap<(ServiceKind, usize), Result<String, String>>,
+    discovered_calendars: &HashMap<String, Vec<CalendarInfo>>,
+    account_sync_status: &HashMap<String, SyncStatus>,
+    task_route_input: &'a str,
+    task_route_calendar: usize,
+    task_calendars: Vec<(String, String, String)>,
+    llm_api_key_input: &str,
+    llm_test_status: &Option<Result<String, String>>,
     sync_status: &SyncStatus,
+    ics_form: &'a IcsForm,
+    project_names: Vec<String>,
+    subscription_status: &HashMap<String, SyncStatus>,
+    vault_passphrase_input: &str,
+    vault_passphrase_repeat: &str,
+    secret_store_status: &Option<Result<String, String>>,
 ) -> Element<'a, Message> {
     let mut content = column().spacing(12);
 
@@ -62,6 +81,38 @@ pub fn settings_view<'a>(
             ),
     );
 
+    // --- Background sync ---
+    content = content.push(text::title4(crate::fl!("settings-sync-interval")));
+    let mut interval_row = row().spacing(8);
+    for &minutes in SYNC_INTERVAL_PRESETS {
+        let label = if minutes == 0 {
+            crate::fl!("settings-sync-interval-off")
+        } else {
+            crate::fl!("settings-sync-interval-minutes", minutes = minutes)
+        };
+        let btn = if config.sync_interval_minutes == minutes {
+            button::suggested(label)
+        } else {
+            button::standard(label)
+        };
+        interval_row = interval_row.push(btn.on_press(Message::SetSyncInterval(minutes)));
+    }
+    content = content.push(interval_row);
+
+    // --- Meeting cost ---
+    content = content.push(text::title4(crate::fl!("settings-meeting-spoons")));
+    content = content.push(text::caption(crate::fl!("settings-meeting-spoons-hint")));
+    let mut spoons_row = row().spacing(8);
+    for &spoons in MEETING_SPOON_PRESETS {
+        let btn = if config.meeting_spoons_per_hour == spoons {
+            button::suggested(spoons.to_string())
+        } else {
+            button::standard(spoons.to_string())
+        };
+        spoons_row = spoons_row.push(btn.on_press(Message::SetMeetingSpoonRate(spoons)));
+    }
+    content = content.push(spoons_row);
+
     // --- Browser ---
     content = content.push(text::title4(crate::fl!("settings-browser")));
     content = content.push(
@@ -72,69 +123,63 @@ pub fn settings_view<'a>(
 
     // --- Calendars (CalDAV) ---
     content = content.push(text::title4(crate::fl!("sync-calendars")));
-    content = content.push(
-        text_input::text_input(
-            crate::fl!("sync-service-url"),
-            &config.calendars.url,
-        )
-        .on_input(|v| Message::SetServiceUrl(ServiceKind::Calendars, v))
-        .width(Length::Fill),
-    );
-    content = content.push(
-        text_input::text_input(
-            crate::fl!("sync-service-username"),
-            &config.calendars.username,
-        )
-        .on_input(|v| Message::SetServiceUsername(ServiceKind::Calendars, v))
-        .width(Length::Fill),
-    );
-    content = content.push(
-        text_input::secure_input(
-            crate::fl!("sync-service-password"),
-            service_passwords[0].clone(),
-            None::<Message>,
-            true,
-        )
-        .on_input(|v| Message::SetServicePassword(ServiceKind::Calendars, v))
-        .width(Length::Fill),
-    );
-    {
-        let mut test_row = row().spacing(8).align_y(Alignment::Center);
-        test_row = test_row.push(
-            button::standard(crate::fl!("sync-test-connection"))
-                .on_press(Message::TestServiceConnection(ServiceKind::Calendars)),
-        );
-        if let Some(ref result) = service_test_status[0] {
-            match result {
-                Ok(msg) => test_row = test_row.push(text::body(format!("✓ {}", msg))),
-                Err(e) => test_row = test_row.push(text::body(format!("✗ {}", e))),
-            }
-        }
-        content = content.push(test_row);
-    }
+    for (idx, account) in config.calendar_accounts.iter().enumerate() {
+        content = content.push(account_fields(
+            ServiceKind::Calendars,
+            idx,
+            account,
+            service_passwords,
+            service_test_status,
+        ));
 
-    // Show discovered calendars with purpose dropdowns
-    for cal in discovered_calendars {
-        let current_purpose = config
-            .calendar_assignments
-            .iter()
-            .find(|a| a.calendar_href == cal.href)
-            .map(|a| a.purpose.clone())
-            .unwrap_or(CalendarPurpose::Disabled);
-
-        let purpose_names = vec![
-            crate::fl!("sync-calendar-purpose-disabled"),
-            crate::fl!("sync-calendar-purpose-tasks"),
-            crate::fl!("sync-calendar-purpose-events"),
-        ];
-        let selected_purpose = match current_purpose {
-            CalendarPurpose::Disabled => Some(0),
-            CalendarPurpose::Tasks => Some(1),
-            CalendarPurpose::Events => Some(2),
-        };
-        let cal_href = cal.href.clone();
-        content = content.push(
-            row()
+        // Discovered calendars with purpose dropdowns
+        for cal in discovered_calendars.get(&account.id).into_iter().flatten() {
+            let assignment = config
+                .calendar_assignments
+                .iter()
+                .find(|a| a.account == account.id && a.calendar_href == cal.href);
+            let current_purpose = assignment
+                .map(|a| a.purpose.clone())
+                .unwrap_or(CalendarPurpose::Disabled);
+
+            let purpose_names = vec![
+                crate::fl!("sync-calendar-purpose-disabled"),
+                crate::fl!("sync-calendar-purpose-tasks"),
+                crate::fl!("sync-calendar-purpose-events"),
+            ];
+            let selected_purpose = match current_purpose {
+                CalendarPurpose::Disabled => Some(0),
+                CalendarPurpose::Tasks => Some(1),
+                CalendarPurpose::Events => Some(2),
+            };
+            let account_id = account.id.clone();
+            let cal_href = cal.href.clone();
+
+            // Events calendars can price their events differently from the default
+            let spoon_rate = (current_purpose == CalendarPurpose::Events).then(|| {
+                let mut names = vec![crate::fl!("settings-calendar-spoons-default")];
+                names.extend(
+                    MEETING_SPOON_PRESETS
+                        .iter()
+                        .map(|s| crate::fl!("settings-calendar-spoons", spoons = *s)),
+                );
+                let selected = match assignment.and_then(|a| a.spoons_per_hour) {
+                    None => Some(0),
+                    Some(rate) => MEETING_SPOON_PRESETS.iter().position(|&s| s == rate).map(|i| i + 1),
+                };
+                let account_id = account_id.clone();
+                let cal_href = cal_href.clone();
+                cosmic::widget::dropdown(names, selected, move |sel| {
+                    Message::SetCalendarSpoonRate(
+                        account_id.clone(),
+                        cal_href.clone(),
+                        sel.checked_sub(1).map(|i| MEETING_SPOON_PRESETS[i]),
+                    )
+                })
+                .width(Length::Fixed(120.0))
+            });
+
+            let mut cal_row = row()
                 .spacing(8)
                 .align_y(Alignment::Center)
                 .push(
@@ -152,58 +197,155 @@ pub fn settings_view<'a>(
                                 _ => CalendarPurpose::Disabled,
                             };
                             Message::SetCalendarPurpose(
+                                account_id.clone(),
                                 cal_href.clone(),
                                 purpose,
                             )
                         },
                     )
                     .width(Length::Fixed(120.0)),
+                );
+            if let Some(spoon_rate) = spoon_rate {
+                cal_row = cal_row.push(spoon_rate);
+            }
+            content = content.push(cal_row);
+        }
+
+        if let Some(status) = account_sync_status.get(&account.id) {
+            content = content.push(text::caption(sync_status_text(status)));
+        }
+    }
+    content = content.push(
+        button::standard(crate::fl!("sync-add-account"))
+            .on_press(Message::AddServiceAccount(ServiceKind::Calendars)),
+    );
+
+    // --- Subscriptions (read-only ICS feeds) ---
+    content = content.push(text::title4(crate::fl!("sync-subscriptions")));
+    content = content.push(text::caption(crate::fl!("sync-subscriptions-hint")));
+    for (idx, sub) in config.subscriptions.iter().enumerate() {
+        content = content.push(
+            row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(
+                    text_input::text_input(crate::fl!("sync-service-name"), &sub.name)
+                        .on_input(move |v| Message::SetSubscriptionName(idx, v))
+                        .width(Length::FillPortion(1)),
+                )
+                .push(
+                    text_input::text_input(crate::fl!("sync-subscription-url"), &sub.url)
+                        .on_input(move |v| Message::SetSubscriptionUrl(idx, v))
+                        .width(Length::FillPortion(3)),
+                )
+                .push(
+                    button::icon(icon::from_name("edit-delete-symbolic"))
+                        .on_press(Message::RemoveSubscription(idx)),
                 ),
         );
+        if let Some(status) = subscription_status.get(&sub.id) {
+            content = content.push(text::caption(sync_status_text(status)));
+        }
     }
+    content = content.push(
+        row()
+            .spacing(8)
+            .push(
+                button::standard(crate::fl!("sync-add-subscription"))
+                    .on_press(Message::AddSubscription),
+            )
+            .push(
+                button::standard(crate::fl!("sync-refresh-subscriptions"))
+                    .on_press(Message::RefreshSubscriptions),
+            ),
+    );
+
+    // --- Task routing ---
+    content = content.push(text::title4(crate::fl!("sync-task-routing")));
+    content = content.push(text::caption(crate::fl!("sync-task-routing-hint")));
+    for (idx, route) in config.task_routes.iter().enumerate() {
+        let target = task_calendars
+            .iter()
+            .find(|(account, href, _)| *account == route.account && *href == route.calendar_href)
+            .map(|(_, _, label)| label.clone())
+            .unwrap_or_else(|| route.calendar_href.clone());
+        content = content.push(
+            row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(text::body(format!("{} → {}", route.key, target)).width(Length::Fill))
+                .push(
+                    button::icon(icon::from_name("edit-delete-symbolic"))
+                        .on_press(Message::RemoveTaskRoute(idx)),
+                ),
+        );
+    }
+    if !task_calendars.is_empty() {
+        let selected = task_route_calendar.min(task_calendars.len() - 1);
+        let calendar_names: Vec<String> =
+            task_calendars.into_iter().map(|(_, _, label)| label).collect();
+        content = content.push(
+            row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(
+                    text_input::text_input(crate::fl!("sync-task-route-key"), task_route_input)
+                        .on_input(Message::TaskRouteInput)
+                        .on_submit(|_| Message::AddTaskRoute)
+                        .width(Length::Fill),
+                )
+                .push(
+                    cosmic::widget::dropdown(
+                        calendar_names,
+                        Some(selected),
+                        Message::SetTaskRouteCalendar,
+                    )
+                    .width(Length::Fixed(200.0)),
+                )
+                .push(
+                    button::icon(icon::from_name("list-add-symbolic"))
+                        .on_press(Message::AddTaskRoute),
+                ),
+        );
+    }
+
+    // --- Projects on the server ---
+    content = content.push(text::title4(crate::fl!("sync-project-mode")));
+    let mut mode_row = row().spacing(8);
+    for (mode, label) in [
+        (ProjectSyncMode::Property, crate::fl!("sync-project-mode-property")),
+        (ProjectSyncMode::Collections, crate::fl!("sync-project-mode-collections")),
+        (ProjectSyncMode::RelatedTo, crate::fl!("sync-project-mode-related")),
+    ] {
+        let btn = if config.project_sync_mode == mode {
+            button::suggested(label)
+        } else {
+            button::standard(label)
+        };
+        mode_row = mode_row.push(btn.on_press(Message::SetProjectSyncMode(mode)));
+    }
+    content = content.push(mode_row);
+    content = content.push(text::caption(match config.project_sync_mode {
+        ProjectSyncMode::Property => crate::fl!("sync-project-mode-property-hint"),
+        ProjectSyncMode::Collections => crate::fl!("sync-project-mode-collections-hint"),
+        ProjectSyncMode::RelatedTo => crate::fl!("sync-project-mode-related-hint"),
+    }));
 
     // --- Contacts (CardDAV) ---
     content = content.push(text::title4(crate::fl!("sync-contacts-service")));
+    for (idx, account) in config.contact_accounts.iter().enumerate() {
+        content = content.push(account_fields(
+            ServiceKind::Contacts,
+            idx,
+            account,
+            service_passwords,
+            service_test_status,
+        ));
+    }
     content = content.push(
-        text_input::text_input(
-            crate::fl!("sync-service-url"),
-            &config.contacts.url,
-        )
-        .on_input(|v| Message::SetServiceUrl(ServiceKind::Contacts, v))
-        .width(Length::Fill),
-    );
-    content = content.push(
-        text_input::text_input(
-            crate::fl!("sync-service-username"),
-            &config.contacts.username,
-        )
-        .on_input(|v| Message::SetServiceUsername(ServiceKind::Contacts, v))
-        .width(Length::Fill),
-    );
-    content = content.push(
-        text_input::secure_input(
-            crate::fl!("sync-service-password"),
-            service_passwords[1].clone(),
-            None::<Message>,
-            true,
-        )
-        .on_input(|v| Message::SetServicePassword(ServiceKind::Contacts, v))
-        .width(Length::Fill),
+        button::standard(crate::fl!("sync-add-account"))
+            .on_press(Message::AddServiceAccount(ServiceKind::Contacts)),
     );
-    {
-        let mut test_row = row().spacing(8).align_y(Alignment::Center);
-        test_row = test_row.push(
-            button::standard(crate::fl!("sync-test-connection"))
-                .on_press(Message::TestServiceConnection(ServiceKind::Contacts)),
-        );
-        if let Some(ref result) = service_test_status[1] {
-            match result {
-                Ok(msg) => test_row = test_row.push(text::body(format!("✓ {}", msg))),
-                Err(e) => test_row = test_row.push(text::body(format!("✗ {}", e))),
-            }
-        }
-        content = content.push(test_row);
-    }
 
     // --- Notes (WebDAV) ---
     content = content.push(text::title4(crate::fl!("sync-notes-webdav")));
@@ -212,7 +354,7 @@ pub fn settings_view<'a>(
             crate::fl!("sync-service-url"),
             &config.notes_sync.url,
         )
-        .on_input(|v| Message::SetServiceUrl(ServiceKind::Notes, v))
+        .on_input(|v| Message::SetServiceUrl(ServiceKind::Notes, 0, v))
         .width(Length::Fill),
     );
     content = content.push(
@@ -220,33 +362,27 @@ pub fn settings_view<'a>(
             crate::fl!("sync-service-username"),
             &config.notes_sync.username,
         )
-        .on_input(|v| Message::SetServiceUsername(ServiceKind::Notes, v))
+        .on_input(|v| Message::SetServiceUsername(ServiceKind::Notes, 0, v))
         .width(Length::Fill),
     );
     content = content.push(
         text_input::secure_input(
             crate::fl!("sync-service-password"),
-            service_passwords[2].clone(),
+            service_passwords
+                .get(&(ServiceKind::Notes, 0))
+                .cloned()
+                .unwrap_or_default(),
             None::<Message>,
             true,
         )
-        .on_input(|v| Message::SetServicePassword(ServiceKind::Notes, v))
+        .on_input(|v| Message::SetServicePassword(ServiceKind::Notes, 0, v))
         .width(Length::Fill),
     );
-    {
-        let mut test_row = row().spacing(8).align_y(Alignment::Center);
-        test_row = test_row.push(
-            button::standard(crate::fl!("sync-test-connection"))
-                .on_press(Message::TestServiceConnection(ServiceKind::Notes)),
-        );
-        if let Some(ref result) = service_test_status[2] {
-            match result {
-                Ok(msg) => test_row = test_row.push(text::body(format!("✓ {}", msg))),
-                Err(e) => test_row = test_row.push(text::body(format!("✗ {}", e))),
-            }
-        }
-        content = content.push(test_row);
-    }
+    content = content.push(test_row(
+        ServiceKind::Notes,
+        0,
+        service_test_status.get(&(ServiceKind::Notes, 0)),
+    ));
 
     // --- Email Inbox (IMAP) ---
     content = content.push(text::title4(crate::fl!("sync-imap")));
@@ -255,7 +391,7 @@ pub fn settings_view<'a>(
             crate::fl!("sync-imap-host"),
             &config.imap.host,
         )
-        .on_input(|v| Message::SetServiceUrl(ServiceKind::Imap, v))
+        .on_input(|v| Message::SetServiceUrl(ServiceKind::Imap, 0, v))
         .width(Length::Fill),
     );
     content = content.push(
@@ -263,17 +399,20 @@ pub fn settings_view<'a>(
             crate::fl!("sync-service-username"),
             &config.imap.username,
         )
-        .on_input(|v| Message::SetServiceUsername(ServiceKind::Imap, v))
+        .on_input(|v| Message::SetServiceUsername(ServiceKind::Imap, 0, v))
         .width(Length::Fill),
     );
     content = content.push(
         text_input::secure_input(
             crate::fl!("sync-service-password"),
-            service_passwords[3].clone(),
+            service_passwords
+                .get(&(ServiceKind::Imap, 0))
+                .cloned()
+                .unwrap_or_default(),
             None::<Message>,
             true,
         )
-        .on_input(|v| Message::SetServicePassword(ServiceKind::Imap, v))
+        .on_input(|v| Message::SetServicePassword(ServiceKind::Imap, 0, v))
         .width(Length::Fill),
     );
     content = content.push(
@@ -284,40 +423,146 @@ pub fn settings_view<'a>(
         .on_input(|v| Message::SetImapFolder(v))
         .width(Length::Fill),
     );
-    {
-        let mut test_row = row().spacing(8).align_y(Alignment::Center);
-        test_row = test_row.push(
-            button::standard(crate::fl!("sync-test-connection"))
-                .on_press(Message::TestServiceConnection(ServiceKind::Imap)),
-        );
-        if let Some(ref result) = service_test_status[3] {
-            match result {
-                Ok(msg) => test_row = test_row.push(text::body(format!("✓ {}", msg))),
-                Err(e) => test_row = test_row.push(text::body(format!("✗ {}", e))),
-            }
-        }
-        content = content.push(test_row);
-    }
+    content = content.push(text::caption(crate::fl!("sync-imap-capture-hint")));
+    content = content.push(
+        row()
+            .spacing(8)
+            .push(
+                text_input::text_input(crate::fl!("sync-imap-capture-folder"), &config.imap.capture_folder)
+                    .on_input(Message::SetImapCaptureFolder)
+                    .width(Length::FillPortion(1)),
+            )
+            .push(
+                text_input::text_input(crate::fl!("sync-imap-capture-address"), &config.imap.capture_address)
+                    .on_input(Message::SetImapCaptureAddress)
+                    .width(Length::FillPortion(1)),
+            ),
+    );
+    content = content.push(test_row(
+        ServiceKind::Imap,
+        0,
+        service_test_status.get(&(ServiceKind::Imap, 0)),
+    ));
 
-    // --- AI Task Extraction ---
-    content = content.push(text::title4(crate::fl!("settings-ai")));
+    // --- Outgoing mail (SMTP) ---
+    content = content.push(text::title4(crate::fl!("sync-smtp")));
+    content = content.push(text::caption(crate::fl!("sync-smtp-hint")));
+    content = content.push(
+        text_input::text_input(crate::fl!("sync-smtp-host"), &config.smtp.host)
+            .on_input(|v| Message::SetServiceUrl(ServiceKind::Smtp, 0, v))
+            .width(Length::Fill),
+    );
+    content = content.push(
+        text_input::text_input(crate::fl!("sync-service-username"), &config.smtp.username)
+            .on_input(|v| Message::SetServiceUsername(ServiceKind::Smtp, 0, v))
+            .width(Length::Fill),
+    );
     content = content.push(
         text_input::secure_input(
-            crate::fl!("settings-ai-api-key"),
-            anthropic_api_key_input.to_string(),
+            crate::fl!("sync-service-password"),
+            service_passwords
+                .get(&(ServiceKind::Smtp, 0))
+                .cloned()
+                .unwrap_or_default(),
             None::<Message>,
             true,
         )
-        .on_input(Message::SetAnthropicApiKey)
+        .on_input(|v| Message::SetServicePassword(ServiceKind::Smtp, 0, v))
         .width(Length::Fill),
     );
+    content = content.push(
+        text_input::text_input(crate::fl!("sync-smtp-from"), &config.smtp.from)
+            .on_input(Message::SetSmtpFrom)
+            .width(Length::Fill),
+    );
+    content = content.push(test_row(
+        ServiceKind::Smtp,
+        0,
+        service_test_status.get(&(ServiceKind::Smtp, 0)),
+    ));
+
+    // --- Email rules ---
+    content = content.push(text::title4(crate::fl!("triage-rules")));
+    content = content.push(text::caption(crate::fl!("triage-rules-hint")));
+    for (idx, rule) in config.triage_rules.iter().enumerate() {
+        content = content.push(triage_rule_fields(idx, rule, &config.contexts, &project_names));
+    }
+    content = content.push(
+        button::standard(crate::fl!("triage-add-rule")).on_press(Message::AddTriageRule),
+    );
+    content = content.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(text::body(crate::fl!("triage-ai-fallback")).width(Length::Fill))
+            .push(
+                cosmic::widget::toggler(config.triage_ai_fallback)
+                    .on_toggle(Message::SetTriageAiFallback),
+            ),
+    );
+
+    // --- AI Task Extraction ---
+    content = content.push(text::title4(crate::fl!("settings-ai")));
+    let backend = config.llm.backend;
+    let backend_names: Vec<String> = LlmBackend::ALL.iter().map(|b| llm_backend_label(*b)).collect();
+    content = content.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(text::body(crate::fl!("settings-ai-backend")).width(Length::Fill))
+            .push(
+                cosmic::widget::dropdown(
+                    backend_names,
+                    LlmBackend::ALL.iter().position(|b| *b == backend),
+                    |idx| Message::SetLlmBackend(LlmBackend::ALL[idx]),
+                )
+                .width(Length::Fixed(200.0)),
+            ),
+    );
+    if backend != LlmBackend::Mock {
+        // Empty fields fall back to the backend's defaults, shown as placeholders
+        content = content.push(
+            row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(text::body(crate::fl!("settings-ai-endpoint")).width(Length::Fixed(120.0)))
+                .push(
+                    text_input::text_input(backend.default_endpoint(), &config.llm.endpoint)
+                        .on_input(Message::SetLlmEndpoint)
+                        .width(Length::Fill),
+                ),
+        );
+        content = content.push(
+            row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(text::body(crate::fl!("settings-ai-model")).width(Length::Fixed(120.0)))
+                .push(
+                    text_input::text_input(backend.default_model(), &config.llm.model)
+                        .on_input(Message::SetLlmModel)
+                        .width(Length::Fill),
+                ),
+        );
+    }
+    if backend.needs_api_key() {
+        content = content.push(
+            text_input::secure_input(
+                crate::fl!("settings-ai-api-key"),
+                llm_api_key_input.to_string(),
+                None::<Message>,
+                true,
+            )
+            .on_input(Message::SetLlmApiKey)
+            .width(Length::Fill),
+        );
+    }
     {
         let mut test_row = row().spacing(8).align_y(Alignment::Center);
         test_row = test_row.push(
             button::standard(crate::fl!("sync-test-connection"))
-                .on_press(Message::TestAnthropicApiKey),
+                .on_press(Message::TestLlmProvider),
         );
-        if let Some(result) = anthropic_test_status {
+        if let Some(result) = llm_test_status {
             match result {
                 Ok(msg) => test_row = test_row.push(text::body(format!("✓ {}", msg))),
                 Err(e) => test_row = test_row.push(text::body(format!("✗ {}", e))),
@@ -326,16 +571,449 @@ pub fn settings_view<'a>(
         content = content.push(test_row);
     }
 
+    // --- Secret storage ---
+    content = content.push(secret_storage_section(
+        config,
+        vault_passphrase_input,
+        vault_passphrase_repeat,
+        secret_store_status,
+    ));
+
+    // --- Import / export ---
+    content = content.push(ics_section(config, discovered_calendars, ics_form, project_names));
+
     // Sync status
-    let status_text = match sync_status {
-        SyncStatus::Idle => crate::fl!("sync-status-never"),
-        SyncStatus::Syncing => crate::fl!("sync-status-syncing"),
-        SyncStatus::Error(e) => crate::fl!("sync-status-error", error = e.as_str()),
-        SyncStatus::LastSynced(t) => crate::fl!("sync-status-idle", time = t.as_str()),
-    };
-    content = content.push(text::caption(status_text));
+    content = content.push(text::caption(sync_status_text(sync_status)));
 
     container(scrollable(content.padding(16)))
         .width(Length::Fill)
         .into()
 }
+
+/// One email rule: what it matches, the task it makes, and what happens to the email.
+fn triage_rule_fields<'a>(
+    idx: usize,
+    rule: &'a TriageRule,
+    contexts: &[String],
+    project_names: &[String],
+) -> Element<'a, Message> {
+    let field = move |field: TriageRuleField| move |v| Message::SetTriageRuleField(idx, field, v);
+
+    let attachment_options = vec![
+        crate::fl!("triage-attachment-any"),
+        crate::fl!("triage-attachment-with"),
+        crate::fl!("triage-attachment-without"),
+    ];
+    let attachment = match rule.has_attachment {
+        None => 0,
+        Some(true) => 1,
+        Some(false) => 2,
+    };
+    let matches = row()
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .push(
+            text_input::text_input(crate::fl!("triage-from"), &rule.from)
+                .on_input(field(TriageRuleField::From))
+                .width(Length::FillPortion(1)),
+        )
+        .push(
+            text_input::text_input(crate::fl!("triage-subject"), &rule.subject)
+                .on_input(field(TriageRuleField::Subject))
+                .width(Length::FillPortion(1)),
+        )
+        .push(
+            text_input::text_input(crate::fl!("triage-list-id"), &rule.list_id)
+                .on_input(field(TriageRuleField::ListId))
+                .width(Length::FillPortion(1)),
+        )
+        .push(
+            cosmic::widget::dropdown(attachment_options, Some(attachment), move |choice| {
+                Message::SetTriageRuleAttachment(idx, choice)
+            })
+            .width(Length::Fixed(160.0)),
+        );
+
+    let mut project_options = vec![crate::fl!("triage-none")];
+    project_options.extend(project_names.iter().cloned());
+    let project = project_names
+        .iter()
+        .position(|p| *p == rule.project)
+        .map_or(0, |i| i + 1);
+    let priority = ["A", "B", "C"]
+        .iter()
+        .position(|p| *p == rule.priority)
+        .map_or(0, |i| i + 1);
+    let priority_options = vec![
+        crate::fl!("triage-none"),
+        "A".to_string(),
+        "B".to_string(),
+        "C".to_string(),
+    ];
+    let task = row()
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .push(
+            text_input::text_input(crate::fl!("triage-title"), &rule.title)
+                .on_input(field(TriageRuleField::Title))
+                .width(Length::Fill),
+        )
+        .push(
+            cosmic::widget::dropdown(project_options, Some(project), move |choice| {
+                Message::SetTriageRuleProject(idx, choice)
+            })
+            .width(Length::Fixed(160.0)),
+        )
+        .push(
+            cosmic::widget::dropdown(priority_options, Some(priority), move |choice| {
+                Message::SetTriageRulePriority(idx, choice)
+            })
+            .width(Length::Fixed(80.0)),
+        )
+        .push(
+            text_input::text_input(
+                crate::fl!("triage-esc"),
+                rule.esc.map(|e| e.to_string()).unwrap_or_default(),
+            )
+            .on_input(field(TriageRuleField::Esc))
+            .width(Length::Fixed(80.0)),
+        );
+
+    let mut context_row = row().spacing(4);
+    for ctx in contexts {
+        let label = ctx.clone();
+        let btn = if rule.contexts.contains(ctx) {
+            button::suggested(label)
+        } else {
+            button::standard(label)
+        };
+        context_row =
+            context_row.push(btn.on_press(Message::ToggleTriageRuleContext(idx, ctx.clone())));
+    }
+
+    column()
+        .spacing(8)
+        .push(
+            row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(
+                    text_input::text_input(crate::fl!("triage-name"), &rule.name)
+                        .on_input(field(TriageRuleField::Name))
+                        .width(Length::Fill),
+                )
+                .push(
+                    button::icon(icon::from_name("edit-delete-symbolic"))
+                        .on_press(Message::RemoveTriageRule(idx)),
+                ),
+        )
+        .push(matches)
+        .push(task)
+        .push(context_row)
+        .push(
+            row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(text::body(crate::fl!("triage-create-task")).width(Length::Fill))
+                .push(
+                    cosmic::widget::toggler(rule.create_task)
+                        .on_toggle(move |on| Message::SetTriageRuleCreateTask(idx, on)),
+                )
+                .push(text::body(crate::fl!("triage-archive")))
+                .push(
+                    cosmic::widget::toggler(rule.archive)
+                        .on_toggle(move |on| Message::SetTriageRuleArchive(idx, on)),
+                ),
+        )
+        .into()
+}
+
+/// Where passwords and API keys live, unlocking the vault, and moving
+/// secrets between the vault and the Secret Service.
+fn secret_storage_section<'a>(
+    config: &LampConfig,
+    vault_passphrase_input: &str,
+    vault_passphrase_repeat: &str,
+    status: &Option<Result<String, String>>,
+) -> Element<'a, Message> {
+    let mut section = column().spacing(12);
+    section = section.push(text::title4(crate::fl!("secrets")));
+    section = section.push(text::caption(crate::fl!(
+        "secrets-hint",
+        path = crate::sync::keyring::vault_path()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default()
+    )));
+    let names: Vec<String> = SecretBackend::ALL.iter().map(|b| secret_backend_label(*b)).collect();
+    section = section.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(text::body(crate::fl!("secrets-backend")).width(Length::Fill))
+            .push(
+                cosmic::widget::dropdown(
+                    names,
+                    SecretBackend::ALL.iter().position(|b| *b == config.secret_backend),
+                    |idx| Message::SetSecretBackend(SecretBackend::ALL[idx]),
+                )
+                .width(Length::Fixed(200.0)),
+            ),
+    );
+    if config.secret_backend != SecretBackend::SecretService {
+        section = section.push(
+            row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(
+                    text_input::secure_input(
+                        crate::fl!("secrets-passphrase"),
+                        vault_passphrase_input.to_string(),
+                        None::<Message>,
+                        true,
+                    )
+                    .on_input(Message::SetVaultPassphrase)
+                    .on_submit(|_| Message::UnlockVault)
+                    .width(Length::Fill),
+                )
+                .push(button::standard(crate::fl!("secrets-unlock")).on_press(Message::UnlockVault)),
+        );
+        // No vault yet: the passphrase is being chosen, so ask for it twice
+        if !crate::sync::keyring::vault_exists() {
+            section = section.push(
+                text_input::secure_input(
+                    crate::fl!("secrets-passphrase-repeat"),
+                    vault_passphrase_repeat.to_string(),
+                    None::<Message>,
+                    true,
+                )
+                .on_input(Message::SetVaultPassphraseRepeat)
+                .on_submit(|_| Message::UnlockVault)
+                .width(Length::Fill),
+            );
+        }
+    }
+    section = section.push(
+        row()
+            .spacing(8)
+            .push(
+                button::standard(crate::fl!("secrets-move-to-vault"))
+                    .on_press(Message::MigrateSecrets(SecretBackend::Vault)),
+            )
+            .push(
+                button::standard(crate::fl!("secrets-move-to-service"))
+                    .on_press(Message::MigrateSecrets(SecretBackend::SecretService)),
+            ),
+    );
+    match status {
+        Some(Ok(msg)) => section = section.push(text::body(format!("✓ {}", msg))),
+        Some(Err(e)) => section = section.push(text::body(format!("✗ {}", e))),
+        None => {}
+    }
+    section = section.push(text::caption(crate::fl!("secrets-env-hint")));
+    section.into()
+}
+
+fn secret_backend_label(backend: SecretBackend) -> String {
+    match backend {
+        SecretBackend::Auto => crate::fl!("secrets-auto"),
+        SecretBackend::SecretService => crate::fl!("secrets-service"),
+        SecretBackend::Vault => crate::fl!("secrets-vault"),
+    }
+}
+
+fn llm_backend_label(backend: LlmBackend) -> String {
+    match backend {
+        LlmBackend::Anthropic => crate::fl!("settings-ai-anthropic"),
+        LlmBackend::OpenAi => crate::fl!("settings-ai-openai"),
+        LlmBackend::Ollama => crate::fl!("settings-ai-ollama"),
+        LlmBackend::Mock => crate::fl!("settings-ai-mock"),
+    }
+}
+
+/// Import an `.ics` file, or export tasks and events filtered by project,
+/// calendar and date range.
+fn ics_section<'a>(
+    config: &LampConfig,
+    discovered_calendars: &HashMap<String, Vec<CalendarInfo>>,
+    form: &'a IcsForm,
+    project_names: Vec<String>,
+) -> Element<'a, Message> {
+    let mut section = column().spacing(8).push(text::title4(crate::fl!("ics-title")));
+
+    section = section.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(
+                text_input::text_input(crate::fl!("ics-import-path"), &form.import_path)
+                    .on_input(Message::SetIcsImportPath)
+                    .on_submit(|_| Message::ImportIcs)
+                    .width(Length::Fill),
+            )
+            .push(button::standard(crate::fl!("ics-import")).on_press(Message::ImportIcs)),
+    );
+
+    // Filters: "All" first, then each project or events calendar
+    let mut project_options = vec![crate::fl!("ics-all-projects")];
+    project_options.extend(project_names.iter().cloned());
+    let selected_project = match form.project {
+        None => Some(0),
+        Some(ref name) => project_names.iter().position(|p| p == name).map(|i| i + 1),
+    };
+
+    let calendars: Vec<(String, String)> = config
+        .calendar_assignments
+        .iter()
+        .filter(|a| a.purpose == CalendarPurpose::Events)
+        .map(|a| {
+            let name = discovered_calendars
+                .get(&a.account)
+                .into_iter()
+                .flatten()
+                .find(|c| c.href == a.calendar_href)
+                .map(|c| c.display_name.clone())
+                .unwrap_or_else(|| a.calendar_href.clone());
+            (a.calendar_href.clone(), name)
+        })
+        .collect();
+    let mut calendar_options = vec![crate::fl!("ics-all-calendars")];
+    calendar_options.extend(calendars.iter().map(|(_, name)| name.clone()));
+    let selected_calendar = match form.calendar_href {
+        None => Some(0),
+        Some(ref href) => calendars.iter().position(|(h, _)| h == href).map(|i| i + 1),
+    };
+    let calendar_hrefs: Vec<String> = calendars.into_iter().map(|(href, _)| href).collect();
+
+    section = section.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(
+                cosmic::widget::dropdown(project_options, selected_project, move |sel| {
+                    Message::SetIcsExportProject(sel.checked_sub(1).and_then(|i| project_names.get(i).cloned()))
+                })
+                .width(Length::Fixed(160.0)),
+            )
+            .push(
+                cosmic::widget::dropdown(calendar_options, selected_calendar, move |sel| {
+                    Message::SetIcsExportCalendar(sel.checked_sub(1).and_then(|i| calendar_hrefs.get(i).cloned()))
+                })
+                .width(Length::Fixed(160.0)),
+            )
+            .push(
+                text_input::text_input(crate::fl!("ics-from"), &form.from)
+                    .on_input(Message::SetIcsExportFrom)
+                    .width(Length::Fixed(120.0)),
+            )
+            .push(
+                text_input::text_input(crate::fl!("ics-to"), &form.to)
+                    .on_input(Message::SetIcsExportTo)
+                    .width(Length::Fixed(120.0)),
+            ),
+    );
+
+    let toggle = |label: String, on: bool, msg: Message| {
+        if on {
+            button::suggested(label).on_press(msg)
+        } else {
+            button::standard(label).on_press(msg)
+        }
+    };
+    section = section.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(toggle(crate::fl!("ics-tasks"), form.tasks, Message::ToggleIcsExportTasks))
+            .push(toggle(crate::fl!("ics-events"), form.events, Message::ToggleIcsExportEvents))
+            .push(
+                text_input::text_input(crate::fl!("ics-export-path"), &form.export_path)
+                    .on_input(Message::SetIcsExportPath)
+                    .width(Length::Fill),
+            )
+            .push(button::standard(crate::fl!("ics-export")).on_press(Message::ExportIcs)),
+    );
+
+    match form.status {
+        Some(Ok(ref msg)) => section = section.push(text::body(format!("✓ {}", msg))),
+        Some(Err(ref e)) => section = section.push(text::body(format!("✗ {}", e))),
+        None => {}
+    }
+
+    section.into()
+}
+
+/// Name, URL, credentials and test/remove buttons for one CalDAV or CardDAV account.
+fn account_fields<'a>(
+    kind: ServiceKind,
+    idx: usize,
+    account: &'a ServiceConfig,
+    service_passwords: &HashMap<(ServiceKind, usize), String>,
+    service_test_status: &HashMap<(ServiceKind, usize), Result<String, String>>,
+) -> Element<'a, Message> {
+    let header = row()
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .push(
+            text_input::text_input(crate::fl!("sync-service-name"), &account.name)
+                .on_input(move |v| Message::SetServiceName(kind, idx, v))
+                .width(Length::Fill),
+        )
+        .push(
+            button::icon(icon::from_name("edit-delete-symbolic"))
+                .on_press(Message::RemoveServiceAccount(kind, idx)),
+        );
+
+    column()
+        .spacing(8)
+        .push(header)
+        .push(
+            text_input::text_input(crate::fl!("sync-service-url"), &account.url)
+                .on_input(move |v| Message::SetServiceUrl(kind, idx, v))
+                .width(Length::Fill),
+        )
+        .push(
+            text_input::text_input(crate::fl!("sync-service-username"), &account.username)
+                .on_input(move |v| Message::SetServiceUsername(kind, idx, v))
+                .width(Length::Fill),
+        )
+        .push(
+            text_input::secure_input(
+                crate::fl!("sync-service-password"),
+                service_passwords.get(&(kind, idx)).cloned().unwrap_or_default(),
+                None::<Message>,
+                true,
+            )
+            .on_input(move |v| Message::SetServicePassword(kind, idx, v))
+            .width(Length::Fill),
+        )
+        .push(test_row(kind, idx, service_test_status.get(&(kind, idx))))
+        .into()
+}
+
+/// "Test connection" button followed by the last result.
+fn test_row<'a>(
+    kind: ServiceKind,
+    idx: usize,
+    result: Option<&Result<String, String>>,
+) -> Element<'a, Message> {
+    let mut test_row = row().spacing(8).align_y(Alignment::Center);
+    test_row = test_row.push(
+        button::standard(crate::fl!("sync-test-connection"))
+            .on_press(Message::TestServiceConnection(kind, idx)),
+    );
+    match result {
+        Some(Ok(msg)) => test_row = test_row.push(text::body(format!("✓ {}", msg))),
+        Some(Err(e)) => test_row = test_row.push(text::body(format!("✗ {}", e))),
+        None => {}
+    }
+    test_row.into()
+}
+
+fn sync_status_text(status: &SyncStatus) -> String {
+    match status {
+        SyncStatus::Idle => crate::fl!("sync-status-never"),
+        SyncStatus::Syncing => crate::fl!("sync-status-syncing"),
+        SyncStatus::Error(e) => crate::fl!("sync-status-error", error = e.as_str()),
+        SyncStatus::LastSynced(t) => crate::fl!("sync-status-idle", time = t.as_str()),
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::{BTreeMap, HashSet};
 
-use chrono::NaiveDate;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Timelike, Weekday};
 use cosmic::iced::{Alignment, Length};
 use cosmic::widget::{button, column, container, icon, row, scrollable, text, text_input};
 use cosmic::Element;
@@ -9,11 +9,15 @@ use crate::application::EventForm;
 use crate::components::habit_chart::habit_chart;
 use crate::components::month_calendar::{MonthCalendarState, month_calendar};
 use crate::components::task_row::{task_grid, TaskRowCtx};
-use crate::core::event::CalendarEvent;
+use crate::core::alarm::{Alarm, AlarmAction, AlarmTrigger};
+use crate::core::event::{CalendarEvent, EditScope, PartStat};
 use crate::core::habit::Habit;
-use crate::core::task::Task;
+use crate::core::rrule::{Frequency, RuleEnd};
+use crate::core::task::{Task, TaskState};
+use crate::core::timeblock;
+use crate::core::timezone;
 use crate::fl;
-use crate::message::Message;
+use crate::message::{AgendaLayout, Message};
 use crate::sync::caldav::CalendarInfo;
 
 struct DayItems<'a> {
@@ -45,7 +49,10 @@ pub fn agenda_view(
     tasks: &[Task],
     habits: &[Habit],
     events: &[CalendarEvent],
+    invitations: &[(CalendarEvent, String)],
+    pending_replies: usize,
     event_form: Option<&EventForm>,
+    event_delete_prompt: Option<(String, bool)>,
     ctx: &TaskRowCtx,
     discovered_calendars: &[CalendarInfo],
     month_state: &MonthCalendarState,
@@ -88,7 +95,7 @@ pub fn agenda_view(
 
     // Collect events
     for event in events {
-        let date = event.start.date();
+        let date = event.local_start().date();
         if date >= today && date <= horizon {
             days.entry(date).or_insert_with(DayItems::new).events.push(event);
         }
@@ -103,7 +110,7 @@ pub fn agenda_view(
 
     // Sort events within each day by start time
     for day in days.values_mut() {
-        day.events.sort_by_key(|e| e.start);
+        day.events.sort_by_key(|e| e.local_start());
     }
 
     let total_items = overdue_tasks.len()
@@ -139,6 +146,64 @@ pub fn agenda_view(
             ),
     );
 
+    // Meeting requests waiting for an answer
+    if !invitations.is_empty() || pending_replies > 0 {
+        let mut section = column().spacing(4).push(text::title4(fl!("invitations")));
+        for (event, from) in invitations {
+            let when = if event.all_day {
+                event.local_start().format("%a %b %e").to_string()
+            } else {
+                event.local_start().format("%a %b %e, %H:%M").to_string()
+            };
+            section = section.push(
+                row()
+                    .spacing(8)
+                    .align_y(Alignment::Center)
+                    .push(icon::from_name("mail-unread-symbolic").size(16).icon())
+                    .push(
+                        column()
+                            .push(text::body(event.title.clone()))
+                            .push(text::caption(fl!("invitation-from", when = when, from = from.clone())))
+                            .width(Length::Fill),
+                    )
+                    .push(rsvp_buttons(event.id, None)),
+            );
+        }
+        if pending_replies > 0 {
+            section = section.push(text::caption(fl!("invitation-replies-waiting", count = pending_replies)));
+        }
+        content = content.push(section);
+    }
+
+    // Scope choice for deleting one occurrence of a recurring event
+    if let Some((title, occurrences_editable)) = event_delete_prompt {
+        let mut buttons = row().spacing(8);
+        if occurrences_editable {
+            buttons = buttons
+                .push(
+                    button::destructive(fl!("agenda-event-scope-this"))
+                        .on_press(Message::ConfirmDeleteEvent(EditScope::This)),
+                )
+                .push(
+                    button::destructive(fl!("agenda-event-scope-following"))
+                        .on_press(Message::ConfirmDeleteEvent(EditScope::Following)),
+                );
+        }
+        buttons = buttons
+            .push(
+                button::destructive(fl!("agenda-event-scope-all"))
+                    .on_press(Message::ConfirmDeleteEvent(EditScope::All)),
+            )
+            .push(button::standard(fl!("agenda-event-cancel")).on_press(Message::CancelDeleteEvent));
+        let mut prompt = column()
+            .spacing(8)
+            .push(text::body(fl!("agenda-event-delete-recurring", title = title)));
+        if !occurrences_editable {
+            prompt = prompt.push(text::caption(fl!("agenda-event-series-only")));
+        }
+        content = content.push(container(prompt.push(buttons)).padding(12).width(Length::Fill));
+    }
+
     // Event form (inline when present)
     if let Some(form) = event_form {
         content = content.push(event_form_view(form.clone(), discovered_calendars.to_vec()));
@@ -214,6 +279,362 @@ pub fn agenda_view(
         .into()
 }
 
+/// Layout switcher above the agenda, with day navigation for the timeline.
+pub fn agenda_toolbar(layout: AgendaLayout, first_day: NaiveDate, days: i64) -> Element<'static, Message> {
+    let layout_button = |label: String, choice: AgendaLayout| {
+        if layout == choice {
+            button::suggested(label).on_press(Message::SetAgendaLayout(choice))
+        } else {
+            button::standard(label).on_press(Message::SetAgendaLayout(choice))
+        }
+    };
+    let mut bar = row()
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .push(layout_button(fl!("agenda-layout-list"), AgendaLayout::List))
+        .push(layout_button(fl!("agenda-layout-day"), AgendaLayout::Day))
+        .push(layout_button(fl!("agenda-layout-week"), AgendaLayout::Week))
+        .push(layout_button(fl!("agenda-layout-free-busy"), AgendaLayout::FreeBusy));
+
+    if layout != AgendaLayout::List {
+        let last_day = first_day + Duration::days(days - 1);
+        let label = if days == 1 {
+            first_day.format("%A, %b %e").to_string()
+        } else {
+            format!("{} – {}", first_day.format("%b %e"), last_day.format("%b %e"))
+        };
+        bar = bar
+            .push(cosmic::widget::horizontal_space())
+            .push(button::icon(icon::from_name("go-previous-symbolic")).on_press(Message::TimelinePrev))
+            .push(button::standard(fl!("agenda-today")).on_press(Message::TimelineToday))
+            .push(button::icon(icon::from_name("go-next-symbolic")).on_press(Message::TimelineNext))
+            .push(text::body(label));
+    }
+
+    container(bar).padding([8, 16]).width(Length::Fill).into()
+}
+
+/// One calendar in the free/busy layout.
+pub struct FreeBusyRow {
+    pub href: String,
+    pub name: String,
+    /// Whether its busy time counts when looking for free slots.
+    pub shown: bool,
+    /// Busy local intervals in the days on display.
+    pub busy: Vec<(NaiveDateTime, NaiveDateTime)>,
+    /// Why the server's free/busy wasn't available, if it wasn't.
+    pub error: Option<String>,
+}
+
+/// A week of busy time per calendar, and the next free slots of `minutes`
+/// in working hours. Picking a slot opens the event form for it.
+pub fn free_busy_view(
+    rows: &[FreeBusyRow],
+    days: &[NaiveDate],
+    minutes: &str,
+    slots: &[(NaiveDateTime, NaiveDateTime)],
+    loading: bool,
+) -> Element<'static, Message> {
+    let mut content = column().spacing(12);
+
+    let refresh = if loading {
+        button::standard(fl!("free-busy-loading"))
+    } else {
+        button::standard(fl!("free-busy-refresh")).on_press(Message::RefreshFreeBusy)
+    };
+    content = content.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(text::title4(fl!("free-busy-calendars")).width(Length::Fill))
+            .push(refresh),
+    );
+    for cal in rows {
+        let href = cal.href.clone();
+        let mut line = row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(
+                cosmic::widget::toggler(cal.shown)
+                    .on_toggle(move |_| Message::ToggleFreeBusyCalendar(href.clone())),
+            )
+            .push(text::body(cal.name.clone()).width(Length::Fill));
+        if cal.error.is_some() {
+            line = line.push(text::caption(fl!("free-busy-local-only")));
+        }
+        content = content.push(line);
+    }
+
+    // Find a time
+    content = content.push(text::title4(fl!("free-busy-find")));
+    content = content.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(text::body(fl!("free-busy-duration")))
+            .push(
+                text_input::text_input("60", minutes.to_string())
+                    .on_input(Message::SetFreeBusyDuration)
+                    .width(Length::Fixed(80.0)),
+            ),
+    );
+    if slots.is_empty() {
+        content = content.push(text::caption(fl!("free-busy-no-slots")));
+    }
+    for &(start, end) in slots {
+        let label = format!(
+            "{} {}–{}",
+            start.format("%a %b %e"),
+            start.format("%H:%M"),
+            end.format("%H:%M")
+        );
+        content = content.push(button::standard(label).on_press(Message::CreateEventAt(start, end)));
+    }
+
+    // Busy time day by day
+    for &day in days {
+        content = content.push(text::heading(day.format("%A, %b %e").to_string()));
+        let day_start = day.and_time(chrono::NaiveTime::MIN);
+        let day_end = day_start + Duration::days(1);
+        let mut any = false;
+        for cal in rows.iter().filter(|r| r.shown) {
+            let busy = timeblock::merge_intervals(
+                cal.busy
+                    .iter()
+                    .map(|&(start, end)| (start.max(day_start), end.min(day_end)))
+                    .filter(|(start, end)| start < end)
+                    .collect(),
+            );
+            if busy.is_empty() {
+                continue;
+            }
+            any = true;
+            let times: Vec<String> = busy
+                .iter()
+                .map(|(start, end)| format!("{}–{}", start.format("%H:%M"), end.format("%H:%M")))
+                .collect();
+            content = content.push(
+                row()
+                    .spacing(8)
+                    .push(text::caption(cal.name.clone()).width(Length::Fixed(160.0)))
+                    .push(text::body(times.join(", ")).width(Length::Fill)),
+            );
+        }
+        if !any {
+            content = content.push(text::caption(fl!("free-busy-free-day")));
+        }
+    }
+
+    container(scrollable(content.padding(16).width(Length::Fill)))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+/// Height of one half-hour slot in the timeline.
+const SLOT_HEIGHT: f32 = 32.0;
+
+/// Day or week timeline: tasks waiting for a block on the left, working
+/// hours on the right. Pick a task, then a free slot to block time for it.
+/// `days` holds each day on display with its working hours.
+pub fn timeline_view(
+    tasks: &[Task],
+    events: &[CalendarEvent],
+    days: &[(NaiveDate, NaiveDateTime, NaiveDateTime)],
+    picked: Option<uuid::Uuid>,
+    now: NaiveDateTime,
+) -> Element<'static, Message> {
+    let Some(&(last_day, _, _)) = days.last() else {
+        return column().into();
+    };
+
+    // Tasks worth blocking: due on the days shown or overdue, or unscheduled next actions
+    let blocked: HashSet<uuid::Uuid> = events
+        .iter()
+        .filter(|e| e.local_end() > now)
+        .filter_map(|e| e.task_id)
+        .collect();
+    let mut candidates: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| !t.state.is_done() && !matches!(t.state, TaskState::Waiting | TaskState::Someday))
+        .filter(|t| !blocked.contains(&t.id))
+        .filter(|t| match t.scheduled {
+            Some(date) => date <= last_day,
+            None => t.state == TaskState::Next,
+        })
+        .collect();
+    candidates.sort_by_key(|t| (t.scheduled.is_none(), t.scheduled, t.priority.is_none(), t.priority));
+
+    let mut task_list = column().spacing(8).width(Length::FillPortion(1));
+    task_list = task_list.push(text::title4(fl!("timeline-tasks")));
+    if candidates.is_empty() {
+        task_list = task_list.push(text::caption(fl!("timeline-no-tasks")));
+    }
+    for task in candidates {
+        let minutes = timeblock::block_minutes(task);
+        let pick = if picked == Some(task.id) {
+            button::suggested(fl!("timeline-picked"))
+        } else {
+            button::standard(fl!("timeline-pick"))
+        };
+        task_list = task_list.push(
+            column()
+                .spacing(4)
+                .push(text::body(task.title.clone()))
+                .push(
+                    row()
+                        .spacing(8)
+                        .align_y(Alignment::Center)
+                        .push(text::caption(fl!("timeline-minutes", minutes = minutes)).width(Length::Fill))
+                        .push(pick.on_press(Message::PickBlockTask(task.id)))
+                        .push(
+                            button::standard(fl!("timeline-auto"))
+                                .on_press(Message::AutoPlaceTask(task.id)),
+                        ),
+                ),
+        );
+    }
+
+    // Slot times are taken from the first day; every day shares the same working hours
+    let (_, day_from, day_until) = days[0];
+    let slot = Duration::minutes(timeblock::SLOT_MINUTES);
+    let offsets: Vec<Duration> = std::iter::successors(Some(Duration::zero()), |o| Some(*o + slot))
+        .take_while(|o| day_from + *o < day_until)
+        .collect();
+
+    let mut grid = row().spacing(4).width(Length::FillPortion(3));
+    let mut times = column().width(Length::Fixed(48.0));
+    if days.len() > 1 {
+        times = times.push(text::caption(String::new()).height(Length::Fixed(SLOT_HEIGHT)));
+    }
+    for offset in &offsets {
+        let at = day_from + *offset;
+        let label = if at.minute() == 0 { at.format("%H:%M").to_string() } else { String::new() };
+        times = times.push(text::caption(label).height(Length::Fixed(SLOT_HEIGHT)));
+    }
+    grid = grid.push(times);
+
+    for &(day, from, _) in days {
+        let mut col = column().spacing(0).width(Length::Fill);
+        if days.len() > 1 {
+            let header = day.format("%a %e").to_string();
+            let header = if day == now.date() { text::heading(header) } else { text::body(header) };
+            col = col.push(container(header).center_x(Length::Fill).height(Length::Fixed(SLOT_HEIGHT)));
+        }
+        let day_events: Vec<&CalendarEvent> = events
+            .iter()
+            .filter(|e| !e.all_day && e.local_start().date() <= day && e.local_end().date() >= day)
+            .collect();
+        for offset in &offsets {
+            let start = from + *offset;
+            let end = start + slot;
+            col = col.push(
+                container(slot_cell(&day_events, start, end, from, picked, now))
+                    .height(Length::Fixed(SLOT_HEIGHT))
+                    .width(Length::Fill),
+            );
+        }
+        grid = grid.push(col);
+    }
+
+    container(scrollable(
+        row()
+            .spacing(24)
+            .padding(16)
+            .push(task_list)
+            .push(grid),
+    ))
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .into()
+}
+
+/// One half-hour cell: the event occupying it, a drop target, or nothing.
+fn slot_cell(
+    events: &[&CalendarEvent],
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    day_from: NaiveDateTime,
+    picked: Option<uuid::Uuid>,
+    now: NaiveDateTime,
+) -> Element<'static, Message> {
+    let occupant = events
+        .iter()
+        .filter(|e| e.status != crate::core::event::EventStatus::Cancelled)
+        .find(|e| e.local_start() < end && e.local_end() > start);
+
+    match (occupant, picked) {
+        (Some(event), _) if event.is_subscribed() => {
+            // Subscribed feeds are read-only: shown, but not editable
+            let first = event.local_start() >= start || start == day_from;
+            let label = if first {
+                format!("{} {}", event.local_start().format("%H:%M"), event.title)
+            } else {
+                String::new()
+            };
+            container(text::caption(label))
+                .class(cosmic::theme::Container::Card)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into()
+        }
+        (Some(event), _) => {
+            // Label the event where it starts (or at the top of the day); shade the rest
+            let first = event.local_start() >= start || start == day_from;
+            let label = if first {
+                format!("{} {}", event.local_start().format("%H:%M"), event.title)
+            } else {
+                String::new()
+            };
+            let class = if event.task_id.is_some() {
+                cosmic::theme::Button::Suggested
+            } else {
+                cosmic::theme::Button::Standard
+            };
+            button::custom(text::caption(label))
+                .class(class)
+                .on_press(Message::EditEvent(event.id, event.recurrence_id))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into()
+        }
+        (None, Some(task_id)) if end > now => button::custom(text::caption("+"))
+            .class(cosmic::theme::Button::Text)
+            .on_press(Message::PlaceTaskBlock(task_id, start))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into(),
+        _ => text::caption(String::new()).into(),
+    }
+}
+
+/// Accept / Maybe / Decline, with our current answer highlighted.
+fn rsvp_buttons(id: uuid::Uuid, current: Option<PartStat>) -> Element<'static, Message> {
+    let choice = |label: String, partstat: PartStat| {
+        if current == Some(partstat) {
+            button::suggested(label).on_press(Message::RespondToInvitation(id, partstat))
+        } else {
+            button::standard(label).on_press(Message::RespondToInvitation(id, partstat))
+        }
+    };
+    row()
+        .spacing(4)
+        .push(choice(fl!("invitation-accept"), PartStat::Accepted))
+        .push(choice(fl!("invitation-tentative"), PartStat::Tentative))
+        .push(choice(fl!("invitation-decline"), PartStat::Declined))
+        .into()
+}
+
+fn partstat_label(partstat: PartStat) -> String {
+    match partstat {
+        PartStat::NeedsAction => fl!("partstat-needs-action"),
+        PartStat::Accepted => fl!("partstat-accepted"),
+        PartStat::Tentative => fl!("partstat-tentative"),
+        PartStat::Declined => fl!("partstat-declined"),
+        PartStat::Delegated => fl!("partstat-delegated"),
+    }
+}
+
 fn format_day_header(date: NaiveDate, today: NaiveDate) -> String {
     if date == today {
         let day_name = date.format("%A").to_string();
@@ -230,7 +651,7 @@ fn event_row(event: &CalendarEvent) -> Element<'static, Message> {
     let time_str = if event.all_day {
         "All day".to_string()
     } else {
-        event.start.format("%H:%M").to_string()
+        event.local_start().format("%H:%M").to_string()
     };
 
     let cal_label = if event.calendar_name.is_empty() {
@@ -240,8 +661,15 @@ fn event_row(event: &CalendarEvent) -> Element<'static, Message> {
     };
 
     let id = event.id;
+    let occurrence = event.recurrence_id;
 
-    row()
+    // Subscribed feeds are read-only: a feed icon and no edit or delete
+    let icon_name = if event.is_subscribed() {
+        "application-rss+xml-symbolic"
+    } else {
+        "x-office-calendar-symbolic"
+    };
+    let r = row()
         .spacing(8)
         .align_y(Alignment::Center)
         .push(
@@ -249,7 +677,7 @@ fn event_row(event: &CalendarEvent) -> Element<'static, Message> {
                 .width(Length::Fixed(56.0)),
         )
         .push(
-            icon::from_name("x-office-calendar-symbolic")
+            icon::from_name(icon_name)
                 .size(16)
                 .icon(),
         )
@@ -257,19 +685,23 @@ fn event_row(event: &CalendarEvent) -> Element<'static, Message> {
             text::body(event.title.clone())
                 .width(Length::Fill),
         )
-        .push(text::caption(cal_label))
-        .push(
-            button::icon(icon::from_name("document-edit-symbolic"))
-                .on_press(Message::EditEvent(id)),
-        )
-        .push(
-            button::icon(icon::from_name("edit-delete-symbolic"))
-                .on_press(Message::DeleteEvent(id)),
-        )
-        .into()
+        .push(text::caption(cal_label));
+    if event.is_subscribed() {
+        return r.into();
+    }
+
+    r.push(
+        button::icon(icon::from_name("document-edit-symbolic"))
+            .on_press(Message::EditEvent(id, occurrence)),
+    )
+    .push(
+        button::icon(icon::from_name("edit-delete-symbolic"))
+            .on_press(Message::DeleteEvent(id, occurrence)),
+    )
+    .into()
 }
 
-fn event_form_view(
+pub fn event_form_view(
     form: EventForm,
     discovered_calendars: Vec<CalendarInfo>,
 ) -> Element<'static, Message> {
@@ -298,6 +730,25 @@ fn event_form_view(
             .width(Length::Fill),
     );
 
+    // Meeting: who's invited and how they answered
+    if !form.attendees.is_empty() {
+        if let Some(ref organizer) = form.organizer {
+            content = content.push(text::caption(fl!("agenda-event-organizer", name = organizer.clone())));
+        }
+        content = content.push(text::caption(fl!("agenda-event-attendees")));
+        for (name, partstat) in &form.attendees {
+            content = content.push(
+                row()
+                    .spacing(8)
+                    .push(text::body(name.clone()).width(Length::Fill))
+                    .push(text::caption(partstat_label(*partstat))),
+            );
+        }
+        if let (Some(id), Some(rsvp)) = (form.editing, form.rsvp) {
+            content = content.push(rsvp_buttons(id, Some(rsvp)));
+        }
+    }
+
     // All day toggle
     content = content.push(
         row()
@@ -374,6 +825,48 @@ fn event_form_view(
             .width(Length::Fill),
     );
 
+    // Spoon cost, priced by duration when left empty
+    content = content.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(text::body(fl!("agenda-event-esc")))
+            .push(
+                text_input::text_input(fl!("agenda-event-esc-auto"), form.esc.clone())
+                    .on_input(Message::SetEventEsc)
+                    .width(Length::Fixed(80.0)),
+            ),
+    );
+
+    // Reminders, with a dropdown of common ones to add
+    content = content.push(text::caption(fl!("agenda-event-alarms")));
+    for (i, alarm) in form.alarms.iter().enumerate() {
+        content = content.push(
+            row()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(text::body(alarm_label(alarm)).width(Length::Fill))
+                .push(
+                    button::icon(icon::from_name("edit-delete-symbolic"))
+                        .on_press(Message::RemoveEventAlarm(i)),
+                ),
+        );
+    }
+    let presets: [i64; 6] = [0, 5, 15, 30, 60, 24 * 60];
+    let preset_names: Vec<String> = presets
+        .iter()
+        .map(|&minutes| alarm_label(&Alarm::before_start(minutes)))
+        .collect();
+    content = content.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(text::body(fl!("agenda-event-add-alarm")))
+            .push(cosmic::widget::dropdown(preset_names, None, move |idx| {
+                Message::AddEventAlarm(presets[idx])
+            })),
+    );
+
     // Calendar dropdown
     let event_cals: Vec<&CalendarInfo> = discovered_calendars
         .iter()
@@ -391,6 +884,31 @@ fn event_form_view(
         );
     }
 
+    // Repeat rule; a single occurrence can't change the series' pattern
+    if form.occurrence.is_none() || form.scope != EditScope::This {
+        content = content.push(repeat_view(&form));
+    }
+
+    // Which occurrences of a recurring event the edit applies to
+    if form.occurrence.is_some() && form.series_only {
+        content = content.push(text::caption(fl!("agenda-event-series-only")));
+    } else if form.occurrence.is_some() {
+        let scope_button = |label: String, scope: EditScope| {
+            if form.scope == scope {
+                button::suggested(label).on_press(Message::SetEventScope(scope))
+            } else {
+                button::standard(label).on_press(Message::SetEventScope(scope))
+            }
+        };
+        content = content.push(
+            row()
+                .spacing(8)
+                .push(scope_button(fl!("agenda-event-scope-this"), EditScope::This))
+                .push(scope_button(fl!("agenda-event-scope-following"), EditScope::Following))
+                .push(scope_button(fl!("agenda-event-scope-all"), EditScope::All)),
+        );
+    }
+
     // Save / Cancel buttons
     let save_msg = if let Some(id) = form.editing {
         Message::UpdateEvent(id)
@@ -416,3 +934,171 @@ fn event_form_view(
         .width(Length::Fill)
         .into()
 }
+
+/// "15 min before", "1 h before end", "At 2026-03-01 09:00"; email alarms say so.
+fn alarm_label(alarm: &Alarm) -> String {
+    let span = |minutes: i64| {
+        let m = minutes.abs();
+        if m % (24 * 60) == 0 {
+            fl!("alarm-days", days = m / (24 * 60))
+        } else if m % 60 == 0 {
+            fl!("alarm-hours", hours = m / 60)
+        } else {
+            fl!("timeline-minutes", minutes = m)
+        }
+    };
+    let when = match alarm.trigger {
+        AlarmTrigger::Relative { minutes: 0, from_end: false } => fl!("alarm-at-start"),
+        AlarmTrigger::Relative { minutes: 0, from_end: true } => fl!("alarm-at-end"),
+        AlarmTrigger::Relative { minutes, from_end: false } if minutes < 0 => {
+            fl!("alarm-before", time = span(minutes))
+        }
+        AlarmTrigger::Relative { minutes, from_end: false } => fl!("alarm-after", time = span(minutes)),
+        AlarmTrigger::Relative { minutes, from_end: true } if minutes < 0 => {
+            fl!("alarm-before-end", time = span(minutes))
+        }
+        AlarmTrigger::Relative { minutes, from_end: true } => fl!("alarm-after-end", time = span(minutes)),
+        AlarmTrigger::Absolute(at) => {
+            let local = timezone::to_local(at, Some(timezone::UTC));
+            fl!("alarm-at", time = local.format("%Y-%m-%d %H:%M").to_string())
+        }
+    };
+    match alarm.action {
+        AlarmAction::Email => fl!("alarm-email", alarm = when),
+        _ => when,
+    }
+}
+
+fn choice_button(label: String, selected: bool, msg: Message) -> Element<'static, Message> {
+    if selected {
+        button::suggested(label).on_press(msg).into()
+    } else {
+        button::standard(label).on_press(msg).into()
+    }
+}
+
+fn repeat_view(form: &EventForm) -> Element<'static, Message> {
+    let repeat = &form.repeat;
+    let mut content = column().spacing(8);
+    content = content.push(text::caption(fl!("agenda-repeat")));
+
+    let freqs = [
+        (None, fl!("agenda-repeat-never")),
+        (Some(Frequency::Daily), fl!("agenda-repeat-daily")),
+        (Some(Frequency::Weekly), fl!("agenda-repeat-weekly")),
+        (Some(Frequency::Monthly), fl!("agenda-repeat-monthly")),
+        (Some(Frequency::Yearly), fl!("agenda-repeat-yearly")),
+    ];
+    let mut freq_row = row().spacing(8);
+    for (freq, label) in freqs {
+        freq_row = freq_row.push(choice_button(label, repeat.freq == freq, Message::SetEventRepeat(freq)));
+    }
+    content = content.push(freq_row);
+
+    let Some(freq) = repeat.freq else {
+        return content.into();
+    };
+
+    let unit = match freq {
+        Frequency::Daily => fl!("agenda-repeat-days"),
+        Frequency::Weekly => fl!("agenda-repeat-weeks"),
+        Frequency::Monthly => fl!("agenda-repeat-months"),
+        Frequency::Yearly => fl!("agenda-repeat-years"),
+    };
+    content = content.push(
+        row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(text::body(fl!("agenda-repeat-every")))
+            .push(
+                text_input::text_input("1", repeat.interval.clone())
+                    .on_input(Message::SetEventRepeatInterval)
+                    .width(Length::Fixed(60.0)),
+            )
+            .push(text::body(unit)),
+    );
+
+    match freq {
+        Frequency::Weekly => {
+            let mut days = row().spacing(4);
+            for wd in [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+            ] {
+                days = days.push(choice_button(
+                    wd.to_string(),
+                    repeat.weekdays.contains(&wd),
+                    Message::ToggleEventRepeatWeekday(wd),
+                ));
+            }
+            content = content.push(days);
+        }
+        Frequency::Monthly => {
+            content = content.push(
+                row()
+                    .spacing(8)
+                    .push(choice_button(
+                        fl!("agenda-repeat-same-date"),
+                        !repeat.nth_weekday,
+                        Message::SetEventRepeatNthWeekday(false),
+                    ))
+                    .push(choice_button(
+                        fl!("agenda-repeat-same-weekday"),
+                        repeat.nth_weekday,
+                        Message::SetEventRepeatNthWeekday(true),
+                    )),
+            );
+        }
+        Frequency::Daily | Frequency::Yearly => {}
+    }
+
+    let mut end_row = row()
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .push(choice_button(
+            fl!("agenda-repeat-forever"),
+            repeat.end == RuleEnd::Never,
+            Message::SetEventRepeatEnd(RuleEnd::Never),
+        ))
+        .push(choice_button(
+            fl!("agenda-repeat-until"),
+            repeat.end == RuleEnd::Until,
+            Message::SetEventRepeatEnd(RuleEnd::Until),
+        ))
+        .push(choice_button(
+            fl!("agenda-repeat-count"),
+            repeat.end == RuleEnd::Count,
+            Message::SetEventRepeatEnd(RuleEnd::Count),
+        ));
+    match repeat.end {
+        RuleEnd::Until => {
+            end_row = end_row.push(
+                text_input::text_input("YYYY-MM-DD", repeat.until.clone())
+                    .on_input(Message::SetEventRepeatUntil)
+                    .width(Length::Fixed(120.0)),
+            );
+        }
+        RuleEnd::Count => {
+            end_row = end_row
+                .push(
+                    text_input::text_input("10", repeat.count.clone())
+                        .on_input(Message::SetEventRepeatCount)
+                        .width(Length::Fixed(60.0)),
+                )
+                .push(text::body(fl!("agenda-repeat-times")));
+        }
+        RuleEnd::Never => {}
+    }
+    content = content.push(end_row);
+
+    if let Some(summary) = form.repeat_summary() {
+        content = content.push(text::caption(summary));
+    }
+
+    content.into()
+}
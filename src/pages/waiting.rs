@@ -1,22 +1,20 @@
-use cosmic::iced::Length;
-use cosmic::widget::{container, scrollable, text};
-use cosmic::Element;
+use chrono::Local;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{button, column, container, row, scrollable, text};
+use cosmic::{Element, theme};
 
 use crate::components::task_row::{TaskRowCtx, task_grid};
-use crate::core::task::Task;
+use crate::core::delegation::{self, FollowUpDraft};
+use crate::core::task::{Task, TaskState};
 use crate::fl;
 use crate::message::Message;
 
 pub fn waiting_view(
     tasks: &[Task],
     ctx: &TaskRowCtx,
+    follow_up_draft: Option<&FollowUpDraft>,
 ) -> Element<'static, Message> {
-    let waiting_tasks: Vec<&Task> = tasks
-        .iter()
-        .filter(|t| matches!(t.state, crate::core::task::TaskState::Waiting))
-        .collect();
-
-    if waiting_tasks.is_empty() {
+    if !tasks.iter().any(|t| t.state == TaskState::Waiting) {
         return container(text::body(fl!("waiting-empty")))
             .padding(32)
             .center_x(Length::Fill)
@@ -25,7 +23,56 @@ pub fn waiting_view(
             .into();
     }
 
-    container(scrollable(container(task_grid(waiting_tasks.into_iter(), ctx, None)).padding(16)))
+    let today = Local::now().date_naive();
+    let mut content = column().spacing(12);
+
+    // Draft shown when the recipient has no email address to open a mailto: link with
+    if let Some(draft) = follow_up_draft {
+        let header = row()
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .push(
+                text::title4(fl!("waiting-draft-title", name = draft.recipient.clone()))
+                    .width(Length::Fill),
+            )
+            .push(
+                button::standard(fl!("waiting-draft-dismiss"))
+                    .on_press(Message::DismissFollowUpDraft),
+            );
+        let draft_col = column()
+            .spacing(4)
+            .push(header)
+            .push(text::body(draft.subject.clone()))
+            .push(text::caption(draft.body.clone()));
+        content = content.push(container(draft_col).padding(8).class(theme::Container::Card));
+    }
+
+    let overdue = delegation::overdue_follow_ups(tasks.iter(), today);
+    if !overdue.is_empty() {
+        content = content.push(text::title4(fl!("waiting-overdue")));
+        content = content.push(task_grid(overdue.into_iter(), ctx, None));
+    }
+
+    for group in delegation::group_waiting(tasks.iter(), ctx.contacts) {
+        let mut header = row().spacing(8).align_y(Alignment::Center);
+        match group.party {
+            Some(party) => {
+                header = header
+                    .push(text::title4(group.name.clone()).width(Length::Fill))
+                    .push(
+                        button::standard(fl!("waiting-compose"))
+                            .on_press(Message::ComposeFollowUp(party)),
+                    );
+            }
+            None => {
+                header = header.push(text::title4(fl!("waiting-unassigned")).width(Length::Fill));
+            }
+        }
+        content = content.push(header);
+        content = content.push(task_grid(group.tasks.into_iter(), ctx, None));
+    }
+
+    container(scrollable(content.padding(16).width(Length::Fill)))
         .width(Length::Fill)
         .height(Length::Fill)
         .into()
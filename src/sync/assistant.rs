@@ -0,0 +1,523 @@
+//! Planning help from the configured language model: weekly review
+//! suggestions and task breakdowns. Nothing here changes a task; the app
+//! asks before applying any of it.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::llm::{parse_json_reply, LlmProvider, LlmRequest};
+use crate::core::project::Project;
+use crate::core::task::{Task, TaskState};
+
+/// Open tasks shown to the model for vague titles, newest first.
+const MAX_REVIEW_TASKS: usize = 60;
+
+/// Someday items shown to the model, newest first.
+const MAX_SOMEDAY_TASKS: usize = 40;
+
+/// Heads every review prompt, so providers can tell it from an email batch.
+pub(super) const REVIEW_HEADER: &str = "--- Weekly review ---";
+
+/// Heads every breakdown prompt.
+pub(super) const BREAKDOWN_HEADER: &str = "--- Break down ---";
+
+/// The energy costs tasks are estimated in, as offered in the task row.
+pub const ESC_SCALE: &[u32] = &[5, 10, 15, 20, 25, 30, 40, 50, 75, 100];
+
+/// How many steps a breakdown may have.
+pub const BREAKDOWN_STEPS: std::ops::RangeInclusive<usize> = 3..=10;
+
+/// Something the assistant proposes during the weekly review.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewSuggestion {
+    /// A first next action for a stuck project.
+    NextAction {
+        project: String,
+        title: String,
+        contexts: Vec<String>,
+    },
+    /// A clearer title for a task whose title doesn't say what to do.
+    Rewrite { task: Uuid, old: String, title: String },
+    /// A someday item worth starting now, and why.
+    Activate { task: Uuid, title: String, reason: String },
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ReviewReply {
+    #[serde(default)]
+    next_actions: Vec<NextActionReply>,
+    #[serde(default)]
+    rewrites: Vec<RewriteReply>,
+    #[serde(default)]
+    activate: Vec<ActivateReply>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NextActionReply {
+    project: String,
+    title: String,
+    #[serde(default)]
+    contexts: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RewriteReply {
+    task: String,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActivateReply {
+    task: String,
+    #[serde(default)]
+    reason: String,
+}
+
+/// What the model gets to see, under the short keys it answers with
+/// (`P1`, `T1`, `S1`) so it never has to copy a UUID.
+struct ReviewInput<'a> {
+    projects: Vec<(String, &'a Project)>,
+    tasks: Vec<(String, &'a Task)>,
+    someday: Vec<(String, &'a Task)>,
+}
+
+impl<'a> ReviewInput<'a> {
+    fn new(projects: &'a [Project], tasks: &'a [Task]) -> Self {
+        let keyed = |prefix: &str, items: Vec<&'a Task>| -> Vec<(String, &'a Task)> {
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(i, t)| (format!("{}{}", prefix, i + 1), t))
+                .collect()
+        };
+        let newest_first = |mut items: Vec<&'a Task>, max: usize| {
+            items.sort_by_key(|t| std::cmp::Reverse(t.created));
+            items.truncate(max);
+            items
+        };
+        let open = tasks
+            .iter()
+            .filter(|t| matches!(t.state, TaskState::Todo | TaskState::Next | TaskState::Waiting))
+            .collect();
+        let someday = tasks.iter().filter(|t| t.state == TaskState::Someday).collect();
+        Self {
+            projects: projects
+                .iter()
+                .filter(|p| p.is_stuck())
+                .enumerate()
+                .map(|(i, p)| (format!("P{}", i + 1), p))
+                .collect(),
+            tasks: keyed("T", newest_first(open, MAX_REVIEW_TASKS)),
+            someday: keyed("S", newest_first(someday, MAX_SOMEDAY_TASKS)),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.projects.is_empty() && self.tasks.is_empty() && self.someday.is_empty()
+    }
+
+    fn user_message(&self, today: &str) -> String {
+        let mut msg = format!("{}\nToday: {}\n", REVIEW_HEADER, today);
+        if !self.projects.is_empty() {
+            msg.push_str("\nStuck projects:\n");
+            for (key, project) in &self.projects {
+                msg.push_str(&format!("[{}] {}\n", key, project.name));
+                for (label, text) in [
+                    ("Purpose", &project.purpose),
+                    ("Outcome", &project.outcome),
+                    ("Brainstorm", &project.brainstorm),
+                ] {
+                    if !text.trim().is_empty() {
+                        let capped: String = text.trim().chars().take(1000).collect();
+                        msg.push_str(&format!("{}: {}\n", label, capped));
+                    }
+                }
+                let open: Vec<&str> = project
+                    .tasks
+                    .iter()
+                    .filter(|t| t.state.is_active())
+                    .map(|t| t.title.as_str())
+                    .collect();
+                if !open.is_empty() {
+                    msg.push_str(&format!("Open tasks: {}\n", open.join("; ")));
+                }
+            }
+        }
+        if !self.tasks.is_empty() {
+            msg.push_str("\nOpen tasks:\n");
+            for (key, task) in &self.tasks {
+                msg.push_str(&format!("[{}] {}\n", key, task.title));
+            }
+        }
+        if !self.someday.is_empty() {
+            msg.push_str("\nSomeday/maybe:\n");
+            for (key, task) in &self.someday {
+                msg.push_str(&format!("[{}] {}\n", key, task.title));
+            }
+        }
+        msg
+    }
+}
+
+fn review_system_prompt(contexts: &[String]) -> String {
+    format!(
+        r#"You help with a GTD weekly review. Answer with a JSON object only:
+{{"next_actions": [{{"project": "P1", "title": "...", "contexts": ["@phone"]}}],
+ "rewrites": [{{"task": "T1", "title": "..."}}],
+ "activate": [{{"task": "S1", "reason": "..."}}]}}
+
+- next_actions: for each stuck project, one concrete, physical next action that moves it toward its outcome, based on its purpose, outcome and brainstorm. Start with a verb.
+- rewrites: only for open tasks whose title doesn't say what to do (e.g. "Mom", "Taxes", "Website"). The new title starts with a verb and keeps the original meaning. Leave clear titles alone.
+- activate: at most three someday items that fit well with current projects or the time of year, with a one-sentence reason.
+- Refer to projects and tasks only by the keys in brackets. Any list may be empty.
+- Contexts must come from: {}"#,
+        if contexts.is_empty() { "(none)".to_string() } else { contexts.join(", ") }
+    )
+}
+
+/// A configured context matching `wanted`, ignoring case and a missing `@`.
+fn known_context(wanted: &str, contexts: &[String]) -> Option<String> {
+    let wanted = wanted.trim().trim_start_matches('@');
+    contexts
+        .iter()
+        .find(|c| c.trim_start_matches('@').eq_ignore_ascii_case(wanted))
+        .cloned()
+}
+
+/// Turn a reply into suggestions, leaving out anything that names an unknown
+/// key, has an empty title or repeats an earlier suggestion for the same item.
+fn parse_review_reply(
+    text: &str,
+    input: &ReviewInput,
+    contexts: &[String],
+) -> Result<Vec<ReviewSuggestion>, String> {
+    let reply: ReviewReply =
+        parse_json_reply(text).map_err(|e| format!("Reply was not a JSON object: {}", e))?;
+    let projects: HashMap<&str, &Project> =
+        input.projects.iter().map(|(k, p)| (k.as_str(), *p)).collect();
+    let tasks: HashMap<&str, &Task> = input.tasks.iter().map(|(k, t)| (k.as_str(), *t)).collect();
+    let someday: HashMap<&str, &Task> = input.someday.iter().map(|(k, t)| (k.as_str(), *t)).collect();
+
+    let mut suggestions = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+    let mut first = |key: &str| {
+        let key = key.trim().to_uppercase();
+        let new = !seen.contains(&key);
+        seen.push(key);
+        new
+    };
+
+    for item in reply.next_actions {
+        let title = item.title.trim();
+        match projects.get(item.project.trim().to_uppercase().as_str()) {
+            Some(project) if !title.is_empty() && first(&item.project) => {
+                suggestions.push(ReviewSuggestion::NextAction {
+                    project: project.name.clone(),
+                    title: title.to_string(),
+                    contexts: item.contexts.iter().filter_map(|c| known_context(c, contexts)).collect(),
+                });
+            }
+            _ => log::warn!("Ignoring review next action for \"{}\"", item.project),
+        }
+    }
+    for item in reply.rewrites {
+        let title = item.title.trim();
+        match tasks.get(item.task.trim().to_uppercase().as_str()) {
+            Some(task)
+                if !title.is_empty() && !title.eq_ignore_ascii_case(task.title.trim()) && first(&item.task) =>
+            {
+                suggestions.push(ReviewSuggestion::Rewrite {
+                    task: task.id,
+                    old: task.title.clone(),
+                    title: title.to_string(),
+                });
+            }
+            _ => log::warn!("Ignoring review rewrite for \"{}\"", item.task),
+        }
+    }
+    for item in reply.activate {
+        match someday.get(item.task.trim().to_uppercase().as_str()) {
+            Some(task) if first(&item.task) => {
+                suggestions.push(ReviewSuggestion::Activate {
+                    task: task.id,
+                    title: task.title.clone(),
+                    reason: item.reason.trim().to_string(),
+                });
+            }
+            _ => log::warn!("Ignoring review activation for \"{}\"", item.task),
+        }
+    }
+    Ok(suggestions)
+}
+
+/// Ask the model for next actions for stuck projects, clearer titles for
+/// vague tasks and someday items worth activating. `tasks` is every task,
+/// project tasks included.
+pub async fn suggest_review(
+    provider: &impl LlmProvider,
+    projects: &[Project],
+    tasks: &[Task],
+    contexts: &[String],
+    today: &str,
+) -> Result<Vec<ReviewSuggestion>, String> {
+    let input = ReviewInput::new(projects, tasks);
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+    let request = LlmRequest {
+        system: review_system_prompt(contexts),
+        user: input.user_message(today),
+        max_tokens: 2048,
+    };
+    let text = provider.complete(&request).await?;
+    parse_review_reply(&text, &input, contexts)
+}
+
+/// A task rewritten as a project, for the user to edit before it is filed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TaskBreakdown {
+    pub project: String,
+    #[serde(default)]
+    pub outcome: String,
+    pub steps: Vec<BreakdownStep>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct BreakdownStep {
+    pub title: String,
+    #[serde(default)]
+    pub esc: Option<u32>,
+    #[serde(default)]
+    pub context: Option<String>,
+}
+
+/// The nearest value on the ESC scale.
+pub fn snap_esc(esc: u32) -> u32 {
+    *ESC_SCALE.iter().min_by_key(|v| v.abs_diff(esc)).unwrap_or(&esc)
+}
+
+fn breakdown_system_prompt(contexts: &[String], project_names: &[String], today: &str) -> String {
+    format!(
+        r#"You turn a task that is too big to start into a GTD project. Answer with a JSON object only:
+{{"project": "...", "outcome": "...", "steps": [{{"title": "...", "esc": 10, "context": "@phone"}}]}}
+
+- project: a short name for the project. It must not be one of: {}
+- outcome: one sentence describing what done looks like.
+- steps: {} to {} concrete, physical actions in the order they would be done. Each title starts with a verb.
+- esc: the energy each step costs, one of {}. A whole day's budget is about 50.
+- context: where or with what the step is done, one of: {}; or null.
+- Today is {}."#,
+        if project_names.is_empty() { "(none)".to_string() } else { project_names.join(", ") },
+        BREAKDOWN_STEPS.start(),
+        BREAKDOWN_STEPS.end(),
+        ESC_SCALE.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", "),
+        if contexts.is_empty() { "(none)".to_string() } else { contexts.join(", ") },
+        today,
+    )
+}
+
+fn breakdown_user_message(task: &Task) -> String {
+    let mut msg = format!("{}\nTask: {}\n", BREAKDOWN_HEADER, task.title);
+    if let Some(ref project) = task.project {
+        msg.push_str(&format!("Part of project: {}\n", project));
+    }
+    if let Some(deadline) = task.deadline {
+        msg.push_str(&format!("Deadline: {}\n", deadline.format("%Y-%m-%d")));
+    }
+    if let Some(esc) = task.esc {
+        msg.push_str(&format!("Estimated energy: {}\n", esc));
+    }
+    if !task.notes.trim().is_empty() {
+        let capped: String = task.notes.trim().chars().take(2000).collect();
+        msg.push_str(&format!("Notes:\n{}\n", capped));
+    }
+    msg
+}
+
+/// Validate a breakdown: a new project name and 3-10 titled steps. ESC is
+/// snapped to the scale, and contexts Lamp doesn't know are dropped.
+pub fn check_breakdown(
+    mut breakdown: TaskBreakdown,
+    contexts: &[String],
+    project_names: &[String],
+) -> Result<TaskBreakdown, String> {
+    breakdown.project = breakdown.project.trim().to_string();
+    if breakdown.project.is_empty() {
+        return Err("the project has no name".to_string());
+    }
+    if project_names.iter().any(|p| p.eq_ignore_ascii_case(&breakdown.project)) {
+        return Err(format!("project \"{}\" already exists", breakdown.project));
+    }
+    if !BREAKDOWN_STEPS.contains(&breakdown.steps.len()) {
+        return Err(format!(
+            "{} steps, not {} to {}",
+            breakdown.steps.len(),
+            BREAKDOWN_STEPS.start(),
+            BREAKDOWN_STEPS.end()
+        ));
+    }
+    breakdown.outcome = breakdown.outcome.trim().to_string();
+    for (i, step) in breakdown.steps.iter_mut().enumerate() {
+        step.title = step.title.trim().to_string();
+        if step.title.is_empty() {
+            return Err(format!("step {} has no title", i + 1));
+        }
+        step.esc = step.esc.map(snap_esc);
+        step.context = step.context.take().and_then(|c| known_context(&c, contexts));
+    }
+    Ok(breakdown)
+}
+
+/// Ask the model to turn `task` into a project with a few concrete steps.
+/// An unusable answer is asked about once more, with the reason it was
+/// rejected.
+pub async fn break_down_task(
+    provider: &impl LlmProvider,
+    task: &Task,
+    contexts: &[String],
+    project_names: &[String],
+    today: &str,
+) -> Result<TaskBreakdown, String> {
+    let mut request = LlmRequest {
+        system: breakdown_system_prompt(contexts, project_names, today),
+        user: breakdown_user_message(task),
+        max_tokens: 1024,
+    };
+    let check = |text: &str| {
+        parse_json_reply::<TaskBreakdown>(text)
+            .map_err(|e| format!("reply was not a breakdown object ({})", e))
+            .and_then(|b| check_breakdown(b, contexts, project_names))
+    };
+    let reason = match check(&provider.complete(&request).await?) {
+        Ok(breakdown) => return Ok(breakdown),
+        Err(reason) => reason,
+    };
+    request.user.push_str(&format!(
+        "Your previous answer was rejected: {}. Answer again.\n",
+        reason
+    ));
+    check(&provider.complete(&request).await?).map_err(|e| format!("No usable breakdown: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::llm::MockProvider;
+    use futures::executor::block_on;
+
+    #[test]
+    fn review_suggestions_are_keyed_and_validated() {
+        let mut stuck = Project::new("Kitchen remodel");
+        stuck.outcome = "New cabinets installed".to_string();
+        let mut waiting = Task::new("Cabinet delivery");
+        waiting.state = TaskState::Waiting;
+        waiting.created -= chrono::Duration::days(3);
+        waiting.project = Some(stuck.name.clone());
+        stuck.tasks.push(waiting.clone());
+
+        let vague = Task::new("Taxes");
+        let mut spanish = Task::new("Learn Spanish");
+        spanish.state = TaskState::Someday;
+        let tasks = vec![waiting, vague.clone(), spanish.clone()];
+        let contexts = vec!["@phone".to_string()];
+
+        // The offline mock proposes a next action for each stuck project
+        let suggestions = block_on(suggest_review(
+            &MockProvider::default(),
+            std::slice::from_ref(&stuck),
+            &tasks,
+            &contexts,
+            "2026-03-02",
+        ))
+        .unwrap();
+        assert!(matches!(
+            suggestions.as_slice(),
+            [ReviewSuggestion::NextAction { project, .. }] if project == "Kitchen remodel"
+        ));
+
+        // Keys are resolved (newest task first); unknown keys, repeats and
+        // no-op rewrites are dropped
+        let reply = r#"```json
+            {"next_actions": [{"project": "p1", "title": "Call the fitter", "contexts": ["Phone", "@gym"]},
+                              {"project": "P1", "title": "Again"},
+                              {"project": "P9", "title": "Nowhere"}],
+             "rewrites": [{"task": "T2", "title": "cabinet delivery"}, {"task": "T1", "title": "Gather receipts for the tax return"}],
+             "activate": [{"task": "S1", "reason": "Trip in May"}, {"task": "T2"}]}
+            ```"#;
+        let provider = MockProvider { reply: Some(reply.to_string()) };
+        let suggestions = block_on(suggest_review(
+            &provider,
+            std::slice::from_ref(&stuck),
+            &tasks,
+            &contexts,
+            "2026-03-02",
+        ))
+        .unwrap();
+        assert_eq!(
+            suggestions,
+            vec![
+                ReviewSuggestion::NextAction {
+                    project: "Kitchen remodel".to_string(),
+                    title: "Call the fitter".to_string(),
+                    contexts: vec!["@phone".to_string()],
+                },
+                ReviewSuggestion::Rewrite {
+                    task: vague.id,
+                    old: "Taxes".to_string(),
+                    title: "Gather receipts for the tax return".to_string(),
+                },
+                ReviewSuggestion::Activate {
+                    task: spanish.id,
+                    title: "Learn Spanish".to_string(),
+                    reason: "Trip in May".to_string(),
+                },
+            ]
+        );
+
+        let broken = MockProvider { reply: Some("Looks fine to me".to_string()) };
+        assert!(block_on(suggest_review(&broken, &[stuck], &tasks, &contexts, "")).is_err());
+    }
+
+    #[test]
+    fn breakdowns_are_checked_and_retried() {
+        let contexts = vec!["@computer".to_string()];
+        let mut task = Task::new("Sort out the garage");
+        task.notes = "Bikes, paint, old boxes".to_string();
+
+        // A name that is taken still is after the retry
+        let reply = r#"{"project": "House", "steps": []}"#;
+        let provider = MockProvider { reply: Some(reply.to_string()) };
+        let err = block_on(break_down_task(&provider, &task, &contexts, &["house".to_string()], "")).unwrap_err();
+        assert!(err.contains("project \"House\" already exists"), "{}", err);
+
+        let breakdown = check_breakdown(
+            TaskBreakdown {
+                project: " Garage clear-out ".to_string(),
+                outcome: "Car fits in the garage".to_string(),
+                steps: vec![
+                    BreakdownStep { title: "List what goes".to_string(), esc: Some(12), context: None },
+                    BreakdownStep { title: "Book the tip run".to_string(), esc: Some(400), context: Some("Computer".to_string()) },
+                    BreakdownStep { title: "Sell the bikes".to_string(), esc: None, context: Some("@garden".to_string()) },
+                ],
+            },
+            &contexts,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(breakdown.project, "Garage clear-out");
+        assert_eq!(breakdown.steps[0].esc, Some(10));
+        assert_eq!(breakdown.steps[1].esc, Some(100));
+        assert_eq!(breakdown.steps[1].context.as_deref(), Some("@computer"));
+        assert_eq!(breakdown.steps[2].context, None);
+
+        let too_short = TaskBreakdown { steps: breakdown.steps[..2].to_vec(), ..breakdown.clone() };
+        assert_eq!(check_breakdown(too_short, &contexts, &[]), Err("2 steps, not 3 to 10".to_string()));
+
+        // The offline mock gives a usable breakdown
+        let mocked = block_on(break_down_task(&MockProvider::default(), &task, &contexts, &[], "")).unwrap();
+        assert_eq!(mocked.project, "Sort out the garage");
+        assert!(BREAKDOWN_STEPS.contains(&mocked.steps.len()));
+    }
+}
@@ -1,6 +1,9 @@
 use reqwest::header::CONTENT_TYPE;
+use chrono::NaiveDateTime;
 use reqwest::{Client, Method, StatusCode};
 
+use super::freebusy::{free_busy_query, parse_vfreebusy, BusyPeriod};
+
 /// Info about a remote calendar discovered via PROPFIND.
 #[derive(Debug, Clone)]
 pub struct CalendarInfo {
@@ -147,6 +150,37 @@ impl CalDavClient {
         parse_multistatus_vtodos(&text)
     }
 
+    /// Busy time in a calendar between two UTC times, via a `free-busy-query`
+    /// REPORT. Works on calendars shared with us that only grant free/busy access.
+    pub async fn free_busy(
+        &self,
+        calendar_href: &str,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Result<Vec<BusyPeriod>, String> {
+        let url = self.resolve_href(calendar_href);
+        let resp = self
+            .request(Method::from_bytes(b"REPORT").unwrap(), &url)
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .header("Depth", "1")
+            .body(free_busy_query(from, until))
+            .send()
+            .await
+            .map_err(|e| format!("free-busy-query REPORT failed: {}", e))?;
+
+        let status = resp.status();
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read free-busy response: {}", e))?;
+
+        if !status.is_success() {
+            return Err(format!("free-busy-query failed with status {}: {}", status, text));
+        }
+
+        Ok(parse_vfreebusy(&text))
+    }
+
     /// Get a single VTODO by href. Returns (etag, ical_body).
     pub async fn get_vtodo(&self, href: &str) -> Result<(String, String), String> {
         let url = self.resolve_href(href);
@@ -302,6 +336,115 @@ impl CalDavClient {
         parse_sync_response(&text)
     }
 
+    /// The calendar-home-set, where new calendars are created.
+    pub async fn calendar_home_set(&self) -> Result<String, String> {
+        let principal_url = self.find_principal().await?;
+        self.find_calendar_home_set(&principal_url).await
+    }
+
+    /// The principal's scheduling inbox (RFC 6638), or `None` if the server
+    /// doesn't do CalDAV scheduling.
+    pub async fn schedule_inbox(&self) -> Result<Option<String>, String> {
+        let principal_url = self.find_principal().await?;
+        let url = self.resolve_href(&principal_url);
+
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <c:schedule-inbox-URL/>
+  </d:prop>
+</d:propfind>"#;
+
+        let resp = self
+            .request(Method::from_bytes(b"PROPFIND").unwrap(), &url)
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .header("Depth", "0")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| format!("PROPFIND for schedule-inbox-URL at {} failed: {}", url, e))?;
+
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read schedule-inbox response: {}", e))?;
+
+        Ok(extract_href_from_xml(&text, "schedule-inbox-URL"))
+    }
+
+    /// Create a task list with MKCALENDAR (RFC 4791). A collection that
+    /// already exists at `href` is treated as success.
+    pub async fn make_calendar(&self, href: &str, display_name: &str) -> Result<(), String> {
+        let url = self.resolve_href(href);
+        let body = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<c:mkcalendar xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:set>
+    <d:prop>
+      <d:displayname>{}</d:displayname>
+      <c:supported-calendar-component-set>
+        <c:comp name="VTODO"/>
+      </c:supported-calendar-component-set>
+    </d:prop>
+  </d:set>
+</c:mkcalendar>"#,
+            xml_escape(display_name)
+        );
+
+        let resp = self
+            .request(Method::from_bytes(b"MKCALENDAR").unwrap(), &url)
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("MKCALENDAR failed: {}", e))?;
+
+        let status = resp.status();
+        if status == StatusCode::METHOD_NOT_ALLOWED {
+            log::info!("Calendar {} already exists", href);
+            return Ok(());
+        }
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(format!("MKCALENDAR {} failed ({}): {}", href, status, text));
+        }
+        Ok(())
+    }
+
+    /// Rename a calendar with PROPPATCH on its displayname.
+    pub async fn set_display_name(&self, href: &str, display_name: &str) -> Result<(), String> {
+        let url = self.resolve_href(href);
+        let body = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<d:propertyupdate xmlns:d="DAV:">
+  <d:set>
+    <d:prop>
+      <d:displayname>{}</d:displayname>
+    </d:prop>
+  </d:set>
+</d:propertyupdate>"#,
+            xml_escape(display_name)
+        );
+
+        let resp = self
+            .request(Method::from_bytes(b"PROPPATCH").unwrap(), &url)
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("PROPPATCH failed: {}", e))?;
+
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        // A 207 can still carry a per-property failure
+        if (!status.is_success() && status != StatusCode::MULTI_STATUS)
+            || (status == StatusCode::MULTI_STATUS && !text.contains(" 200 "))
+        {
+            return Err(format!("PROPPATCH {} failed ({}): {}", href, status, text));
+        }
+        Ok(())
+    }
+
     // --- Private helpers ---
 
     fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
@@ -674,6 +817,13 @@ fn parse_sync_response(
     Ok((changes, new_token))
 }
 
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 /// Extract UID from raw iCalendar text (simple line scan).
 fn extract_uid_from_ical(ical: &str) -> Option<String> {
     for line in ical.lines() {
@@ -703,6 +853,12 @@ pub fn vtodo_href(calendar_href: &str, task_uid: &uuid::Uuid) -> String {
     format!("{}/{}.ics", base, task_uid)
 }
 
+/// Generate the href of a project's own task list under the calendar home.
+pub fn project_list_href(home_set: &str, project_id: &uuid::Uuid) -> String {
+    let base = home_set.trim_end_matches('/');
+    format!("{}/lamp-{}/", base, project_id)
+}
+
 /// Generate a CalDAV href for a new VEVENT.
 pub fn vevent_href(calendar_href: &str, event_uid: &uuid::Uuid) -> String {
     let base = calendar_href.trim_end_matches('/');
@@ -32,6 +32,8 @@ pub struct Contact {
     pub last_contacted: Option<NaiveDate>,
     /// Server href for this vCard resource (used for DELETE).
     pub sync_href: Option<String>,
+    /// Id of the contact account the vCard was fetched from.
+    pub sync_account: Option<String>,
 }
 
 impl Contact {
@@ -47,6 +49,7 @@ impl Contact {
             category: ContactCategory::Personal,
             last_contacted: None,
             sync_href: None,
+            sync_account: None,
         }
     }
 }
@@ -378,6 +381,7 @@ fn parse_vcard(vcard: &str) -> Option<Contact> {
         category,
         last_contacted: None, // vCard doesn't carry this
         sync_href: None, // set by fetch_addressbook_contacts after parsing
+        sync_account: None,
     })
 }
 
@@ -527,6 +531,7 @@ pub fn merge_contacts(local: &mut Vec<Contact>, remote: Vec<Contact>) {
             }
             // preserve last_contacted (local only)
             lc.sync_href = rc.sync_href.or(lc.sync_href.clone());
+            lc.sync_account = rc.sync_account.or(lc.sync_account.clone());
         } else {
             local.push(rc);
         }
@@ -606,6 +611,7 @@ mod tests {
                 category: ContactCategory::Personal,
                 last_contacted: Some(NaiveDate::from_ymd_opt(2025, 2, 20).unwrap()),
                 sync_href: Some("/dav/addr/john.vcf".to_string()),
+                sync_account: None,
             },
             Contact {
                 id: jane_id,
@@ -618,6 +624,7 @@ mod tests {
                 category: ContactCategory::Service,
                 last_contacted: None,
                 sync_href: None,
+                sync_account: None,
             },
         ];
         let org = write_contacts_org(&contacts);
@@ -657,6 +664,7 @@ mod tests {
             category: ContactCategory::Personal,
             last_contacted: Some(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()),
             sync_href: None,
+            sync_account: None,
         }];
         let remote = vec![
             Contact::new("Alice".to_string()),
@@ -0,0 +1,94 @@
+//! CalDAV free/busy (RFC 4791 `free-busy-query`) and VFREEBUSY parsing.
+
+use chrono::NaiveDateTime;
+
+use super::ical::{format_utc, ical_param, parse_ical_datetime, parse_ical_line, unfold_lines};
+use crate::core::alarm::parse_duration;
+
+/// How a busy period is marked (FBTYPE). FREE periods aren't kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusyType {
+    Busy,
+    Tentative,
+    Unavailable,
+}
+
+/// A busy stretch of time, in UTC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusyPeriod {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub kind: BusyType,
+}
+
+/// REPORT body asking for free/busy between two UTC times.
+pub fn free_busy_query(from: NaiveDateTime, until: NaiveDateTime) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<c:free-busy-query xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <c:time-range start="{}" end="{}"/>
+</c:free-busy-query>"#,
+        format_utc(from),
+        format_utc(until)
+    )
+}
+
+/// Busy periods of every FREEBUSY property in a VFREEBUSY response. Periods
+/// are `start/end` or `start/duration`, several to a line.
+pub fn parse_vfreebusy(ical: &str) -> Vec<BusyPeriod> {
+    let mut periods = Vec::new();
+    for line in unfold_lines(ical).lines() {
+        let line = line.trim_end();
+        let Some(("FREEBUSY", value)) = parse_ical_line(line) else {
+            continue;
+        };
+        let kind = match ical_param(line, "FBTYPE").map(str::to_uppercase).as_deref() {
+            Some("FREE") => continue,
+            Some("BUSY-TENTATIVE") => BusyType::Tentative,
+            Some("BUSY-UNAVAILABLE") => BusyType::Unavailable,
+            _ => BusyType::Busy,
+        };
+        for period in value.split(',') {
+            let Some((start, end)) = period.trim().split_once('/') else {
+                continue;
+            };
+            let Some(start) = parse_ical_datetime(start) else {
+                continue;
+            };
+            let end = match parse_duration(end) {
+                Some(minutes) => Some(start + chrono::Duration::minutes(minutes)),
+                None => parse_ical_datetime(end),
+            };
+            if let Some(end) = end.filter(|&end| end > start) {
+                periods.push(BusyPeriod { start, end, kind });
+            }
+        }
+    }
+    periods
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_periods_and_skips_free_time() {
+        let ical = "BEGIN:VCALENDAR\r\nBEGIN:VFREEBUSY\r\nDTSTART:20260302T000000Z\r\nDTEND:20260309T000000Z\r\n\
+            FREEBUSY:20260302T090000Z/20260302T100000Z,20260302T130000Z/PT1H30M\r\n\
+            FREEBUSY;FBTYPE=BUSY-TENTATIVE:20260303T080000Z/20260303T083000Z\r\n\
+            FREEBUSY;FBTYPE=FREE:20260304T080000Z/20260304T120000Z\r\n\
+            END:VFREEBUSY\r\nEND:VCALENDAR\r\n";
+        let at = |d: u32, h: u32, m: u32| {
+            chrono::NaiveDate::from_ymd_opt(2026, 3, d).unwrap().and_hms_opt(h, m, 0).unwrap()
+        };
+
+        let periods = parse_vfreebusy(ical);
+        assert_eq!(periods.len(), 3);
+        assert_eq!((periods[0].start, periods[0].end), (at(2, 9, 0), at(2, 10, 0)));
+        assert_eq!((periods[1].start, periods[1].end), (at(2, 13, 0), at(2, 14, 30)));
+        assert_eq!(periods[2].kind, BusyType::Tentative);
+
+        let body = free_busy_query(at(2, 0, 0), at(9, 0, 0));
+        assert!(body.contains(r#"start="20260302T000000Z" end="20260309T000000Z""#));
+    }
+}
@@ -1,6 +1,13 @@
-use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use chrono::{Datelike, FixedOffset, NaiveDate, NaiveDateTime, Timelike};
 use uuid::Uuid;
 
+use crate::core::alarm::{format_duration, parse_duration, Alarm, AlarmAction, AlarmTrigger};
+use crate::core::rrule::{nth_weekday_of_month, weekday_code};
+use crate::core::timezone;
+
 /// UUID v5 namespace for converting non-UUID CalDAV UIDs into stable Uuids.
 pub const CALDAV_UUID_NAMESPACE: Uuid = Uuid::from_bytes([
     0x6b, 0xa7, 0xb8, 0x10, 0x9d, 0xad, 0x11, 0xd1, 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30,
@@ -15,6 +22,170 @@ pub fn format_datetime(dt: NaiveDateTime) -> String {
     dt.format("%Y%m%dT%H%M%S").to_string()
 }
 
+/// Format a UTC time as an iCalendar UTC value ("20260224T143000Z").
+pub fn format_utc(dt: NaiveDateTime) -> String {
+    format!("{}Z", format_datetime(dt))
+}
+
+/// Format a DATE-TIME property in zone `tzid`, e.g. `DTSTART;TZID=Europe/Berlin:20260224T143000`.
+/// UTC times get a `Z` suffix and `None` writes a floating time.
+pub fn format_zoned_property(key: &str, dt: NaiveDateTime, tzid: Option<&str>) -> String {
+    match tzid {
+        Some(timezone::UTC) => format!("{}:{}", key, format_utc(dt)),
+        Some(tzid) => format!("{};TZID={}:{}", key, tzid, format_datetime(dt)),
+        None => format!("{}:{}", key, format_datetime(dt)),
+    }
+}
+
+/// Years after the event's own that a generated VTIMEZONE is checked against.
+const VTIMEZONE_YEARS: i32 = 10;
+
+/// A UTC offset change: (UTC instant, offset before, offset after) in seconds.
+type Transition = (NaiveDateTime, i32, i32);
+
+/// A VTIMEZONE observance: (component, local DTSTART, TZOFFSETFROM, TZOFFSETTO, RRULE).
+type Observance = (&'static str, NaiveDateTime, i32, i32, Option<String>);
+
+/// Observances already worked out, by zone name and first year. Finding them
+/// scans every day of eleven years, too slow to repeat for each event written.
+static OBSERVANCES: LazyLock<Mutex<ObservanceCache>> = LazyLock::new(Mutex::default);
+
+type ObservanceCache = HashMap<(&'static str, i32), Option<Vec<Observance>>>;
+
+/// VTIMEZONE for `tzid` with a STANDARD and, where the zone observes DST, a
+/// DAYLIGHT component whose yearly RRULEs match the zone's real transitions
+/// from the year before `at` on. Zones whose rules can't be written that way
+/// get no definition: the TZID alone names the IANA zone.
+pub fn vtimezone_lines(tzid: &str, at: NaiveDateTime) -> Vec<String> {
+    let Some(tz) = timezone::resolve(tzid) else {
+        return Vec::new();
+    };
+    let first = at.year() - 1;
+    let observances = {
+        let mut cache = OBSERVANCES.lock().unwrap_or_else(|e| e.into_inner());
+        cache.entry((tz.name(), first)).or_insert_with(|| tz_observances(tz, first)).clone()
+    };
+    let Some(observances) = observances else {
+        log::warn!("No yearly rules reproduce {}, writing the TZID only", tz.name());
+        return Vec::new();
+    };
+    let mut lines = vec![
+        "BEGIN:VTIMEZONE".to_string(),
+        format!("TZID:{}", tzid),
+        format!("X-LIC-LOCATION:{}", tz.name()),
+    ];
+    for (kind, start, from, to, rrule) in observances {
+        lines.push(format!("BEGIN:{}", kind));
+        lines.push(format!("DTSTART:{}", format_datetime(start)));
+        if let Some(rrule) = rrule {
+            lines.push(format!("RRULE:{}", rrule));
+        }
+        lines.push(format!("TZOFFSETFROM:{}", format_offset(from)));
+        lines.push(format!("TZOFFSETTO:{}", format_offset(to)));
+        lines.push(format!("END:{}", kind));
+    }
+    lines.push("END:VTIMEZONE".to_string());
+    lines
+}
+
+/// The observances of `tz` from `first` on. `None` when the transitions
+/// don't follow a fixed "nth weekday of the month" pattern every year.
+fn tz_observances(tz: chrono_tz::Tz, first: i32) -> Option<Vec<Observance>> {
+    use chrono::TimeZone;
+    use chrono_tz::OffsetComponents;
+
+    let is_dst = |utc: NaiveDateTime| !tz.offset_from_utc_datetime(&utc).dst_offset().is_zero();
+    let transitions = tz_transitions(tz, first);
+    if transitions.is_empty() {
+        // No changes in the first year: a fixed offset, if it stays that way
+        let start = NaiveDate::from_ymd_opt(first, 1, 1)?.and_hms_opt(0, 0, 0)?;
+        let offset = offset_at(tz, start);
+        let fixed = (first + 1..=first + VTIMEZONE_YEARS).all(|year| tz_transitions(tz, year).is_empty());
+        return fixed.then(|| vec![("STANDARD", start, offset, offset, None)]);
+    }
+
+    // Each transition becomes a yearly rule: the nth weekday of its month,
+    // or the last one when it falls in the month's final week
+    let rules: Vec<(u32, i32, chrono::Weekday, chrono::NaiveTime)> = transitions
+        .iter()
+        .map(|&(utc, from, _)| {
+            let local = utc + chrono::Duration::seconds(from as i64);
+            let date = local.date();
+            let last_week = (date + chrono::Duration::days(7)).month() != date.month();
+            let n = if last_week { -1 } else { (date.day() as i32 - 1) / 7 + 1 };
+            (date.month(), n, date.weekday(), local.time())
+        })
+        .collect();
+    for year in first + 1..=first + VTIMEZONE_YEARS {
+        let actual = tz_transitions(tz, year);
+        if actual.len() != rules.len() {
+            return None;
+        }
+        for ((&(utc, from, to), &(month, n, wd, time)), &(_, first_from, first_to)) in
+            actual.iter().zip(&rules).zip(&transitions)
+        {
+            let month_start = NaiveDate::from_ymd_opt(year, month, 1)?;
+            let expected = nth_weekday_of_month(month_start, n, wd)?.and_time(time);
+            if utc + chrono::Duration::seconds(from as i64) != expected || (from, to) != (first_from, first_to) {
+                return None;
+            }
+        }
+    }
+
+    Some(
+        transitions
+            .iter()
+            .zip(&rules)
+            .map(|(&(utc, from, to), &(month, n, wd, _))| {
+                let kind = if is_dst(utc) { "DAYLIGHT" } else { "STANDARD" };
+                let start = utc + chrono::Duration::seconds(from as i64);
+                let rrule = format!("FREQ=YEARLY;BYMONTH={};BYDAY={}{}", month, n, weekday_code(wd));
+                (kind, start, from, to, Some(rrule))
+            })
+            .collect(),
+    )
+}
+
+/// UTC offset changes of `tz` during `year`, in order, found to the minute.
+fn tz_transitions(tz: chrono_tz::Tz, year: i32) -> Vec<Transition> {
+    let Some(mut day) = NaiveDate::from_ymd_opt(year, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)) else {
+        return Vec::new();
+    };
+    let mut transitions = Vec::new();
+    while day.year() == year {
+        let next = day + chrono::Duration::days(1);
+        let (from, to) = (offset_at(tz, day), offset_at(tz, next));
+        if from != to {
+            // Narrow down to the first minute with the new offset
+            let (mut lo, mut hi) = (day, next);
+            while hi - lo > chrono::Duration::minutes(1) {
+                let mid = lo + (hi - lo) / 2;
+                let mid = mid - chrono::Duration::seconds(mid.second() as i64);
+                if offset_at(tz, mid) == from {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            transitions.push((hi, from, to));
+        }
+        day = next;
+    }
+    transitions
+}
+
+fn offset_at(tz: chrono_tz::Tz, utc: NaiveDateTime) -> i32 {
+    use chrono::{Offset, TimeZone};
+
+    tz.offset_from_utc_datetime(&utc).fix().local_minus_utc()
+}
+
+/// An offset in seconds as a TZOFFSETFROM/TO value, e.g. `-0400`.
+fn format_offset(offset: i32) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    format!("{}{:02}{:02}", sign, offset.abs() / 3600, offset.abs() % 3600 / 60)
+}
+
 pub fn parse_ical_date(s: &str) -> Option<NaiveDate> {
     // Handles both "20260224" and date-time formats "20260224T120000"
     let date_str = if s.len() >= 8 { &s[..8] } else { s };
@@ -27,6 +198,190 @@ pub fn parse_ical_datetime(s: &str) -> Option<NaiveDateTime> {
     NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%S").ok()
 }
 
+/// A VALARM component. DISPLAY and EMAIL alarms need a DESCRIPTION, so
+/// `fallback` (usually the summary) stands in for an empty one.
+pub fn valarm_lines(alarm: &Alarm, fallback: &str) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VALARM".to_string(),
+        format!("ACTION:{}", alarm.action.as_ical()),
+    ];
+    lines.push(match alarm.trigger {
+        AlarmTrigger::Relative { minutes, from_end: false } => format!("TRIGGER:{}", format_duration(minutes)),
+        AlarmTrigger::Relative { minutes, from_end: true } => {
+            format!("TRIGGER;RELATED=END:{}", format_duration(minutes))
+        }
+        AlarmTrigger::Absolute(at) => format!("TRIGGER;VALUE=DATE-TIME:{}", format_utc(at)),
+    });
+    if !matches!(alarm.action, AlarmAction::Other(_)) {
+        let description = if alarm.description.is_empty() { fallback } else { &alarm.description };
+        lines.push(fold_line(&format!("DESCRIPTION:{}", escape_text(description))));
+    }
+    lines.extend(alarm.extra.iter().map(|line| fold_line(line)));
+    lines.push("END:VALARM".to_string());
+    lines
+}
+
+/// Parse the unfolded lines between `BEGIN:VALARM` and `END:VALARM`.
+pub fn parse_valarm(lines: &[&str]) -> Option<Alarm> {
+    let mut trigger = None;
+    let mut action = AlarmAction::Display;
+    let mut description = String::new();
+    let mut extra = Vec::new();
+    for &line in lines {
+        match parse_ical_line(line) {
+            Some(("TRIGGER", value)) => {
+                trigger = if ical_param(line, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE-TIME")) {
+                    parse_ical_datetime(value).map(AlarmTrigger::Absolute)
+                } else {
+                    let from_end = ical_param(line, "RELATED").is_some_and(|v| v.eq_ignore_ascii_case("END"));
+                    parse_duration(value).map(|minutes| AlarmTrigger::Relative { minutes, from_end })
+                };
+            }
+            Some(("ACTION", value)) => action = AlarmAction::from_ical(value),
+            Some(("DESCRIPTION", value)) => description = unescape_text(value),
+            _ => extra.push(line.to_string()),
+        }
+    }
+    Some(Alarm {
+        trigger: trigger?,
+        action,
+        description,
+        extra,
+    })
+}
+
+/// Value of parameter `name` on a content line ("DTSTART;TZID=Europe/Berlin:..." -> "Europe/Berlin").
+pub fn ical_param<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let key_part = &line[..line.find(':')?];
+    key_part.split(';').skip(1).find_map(|param| {
+        let (k, v) = param.split_once('=')?;
+        k.eq_ignore_ascii_case(name).then(|| v.trim_matches('"'))
+    })
+}
+
+/// How a VTIMEZONE's TZID maps onto a zone we can compute with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ZoneDef {
+    Iana(chrono_tz::Tz),
+    /// No recognisable IANA name: fall back to the standard-time offset.
+    Offset(FixedOffset),
+}
+
+/// TZIDs declared by the VTIMEZONE components of a calendar object.
+#[derive(Debug, Clone, Default)]
+pub struct TimeZones {
+    zones: HashMap<String, ZoneDef>,
+}
+
+impl TimeZones {
+    /// Collect VTIMEZONEs from unfolded iCalendar text.
+    pub fn parse(unfolded: &str) -> Self {
+        let mut zones = HashMap::new();
+        let mut tzid: Option<String> = None;
+        let mut location: Option<String> = None;
+        let mut standard_offset: Option<FixedOffset> = None;
+        let mut any_offset: Option<FixedOffset> = None;
+        let mut in_standard = false;
+
+        for line in unfolded.lines() {
+            let line = line.trim_end();
+            match line {
+                "BEGIN:VTIMEZONE" => {
+                    tzid = None;
+                    location = None;
+                    standard_offset = None;
+                    any_offset = None;
+                }
+                "BEGIN:STANDARD" => in_standard = true,
+                "END:STANDARD" => in_standard = false,
+                "END:VTIMEZONE" => {
+                    if let Some(id) = tzid.take() {
+                        let def = location
+                            .as_deref()
+                            .and_then(timezone::resolve)
+                            .or_else(|| timezone::resolve(&id))
+                            .map(ZoneDef::Iana)
+                            .or_else(|| standard_offset.or(any_offset).map(ZoneDef::Offset));
+                        match def {
+                            Some(def) => {
+                                zones.insert(id, def);
+                            }
+                            None => log::warn!("Unresolvable VTIMEZONE '{}'", id),
+                        }
+                    }
+                }
+                _ => {
+                    if let Some((key, value)) = parse_ical_line(line) {
+                        match key {
+                            "TZID" => tzid = Some(value.to_string()),
+                            "X-LIC-LOCATION" => location = Some(value.to_string()),
+                            "TZOFFSETTO" => {
+                                let offset = parse_utc_offset(value);
+                                if in_standard {
+                                    standard_offset = offset;
+                                }
+                                any_offset = any_offset.or(offset);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+        Self { zones }
+    }
+
+    /// Read a DATE-TIME property value as wall-clock time plus the zone it's
+    /// in: an IANA name, `"UTC"`, or `None` for floating times. TZIDs that only
+    /// resolve to a fixed offset are converted to UTC.
+    pub fn parse_datetime(&self, line: &str, value: &str) -> Option<(NaiveDateTime, Option<String>)> {
+        let dt = parse_ical_datetime(value)?;
+        if value.ends_with('Z') {
+            return Some((dt, Some(timezone::UTC.to_string())));
+        }
+        let Some(tzid) = ical_param(line, "TZID") else {
+            return Some((dt, None));
+        };
+        match self.zones.get(tzid).copied() {
+            Some(ZoneDef::Iana(tz)) => Some((dt, Some(tz.name().to_string()))),
+            Some(ZoneDef::Offset(offset)) => {
+                Some((dt - offset, Some(timezone::UTC.to_string())))
+            }
+            None => match timezone::resolve(tzid) {
+                Some(tz) => Some((dt, Some(tz.name().to_string()))),
+                None => {
+                    log::warn!("Unknown TZID '{}', treating time as local", tzid);
+                    Some((dt, None))
+                }
+            },
+        }
+    }
+
+    /// Read a DATE-TIME property value as system-local time.
+    pub fn parse_local_datetime(&self, line: &str, value: &str) -> Option<NaiveDateTime> {
+        self.parse_datetime(line, value)
+            .map(|(dt, tzid)| timezone::to_local(dt, tzid.as_deref()))
+    }
+}
+
+/// Parse a UTC offset like "+0530" or "-0800".
+fn parse_utc_offset(s: &str) -> Option<FixedOffset> {
+    let s = s.trim();
+    let sign = match s.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = &s[1..];
+    if digits.len() < 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..4].parse().ok()?;
+    let seconds: i32 = digits.get(4..6).and_then(|s| s.parse().ok()).unwrap_or(0);
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
 pub fn escape_text(s: &str) -> String {
     s.replace('\\', "\\\\")
         .replace(';', "\\;")
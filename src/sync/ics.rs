@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDate};
+
+use super::ical::unfold_lines;
+use super::vevent::{event_to_vcalendar, vcalendar_to_events};
+use super::vtodo::{task_to_vcalendar, vcalendar_to_task};
+use crate::core::event::CalendarEvent;
+use crate::core::task::Task;
+
+/// Tasks and events read from an `.ics` file.
+#[derive(Debug, Default)]
+pub struct IcsImport {
+    pub tasks: Vec<Task>,
+    pub events: Vec<CalendarEvent>,
+    /// Components skipped because a task or event with the same UID exists.
+    pub duplicates: usize,
+}
+
+/// Read every VTODO and VEVENT in an iCalendar file. Tasks and events whose
+/// UID is in `known_uids` are counted as duplicates rather than returned.
+pub fn parse_ics(content: &str, known_uids: &HashSet<String>) -> IcsImport {
+    let mut import = IcsImport::default();
+    let mut seen: HashSet<String> = HashSet::new();
+
+    // vcalendar_to_task reads one VTODO, so give it each one with the file's time zones
+    let unfolded = unfold_lines(content);
+    let timezones = blocks(&unfolded, "VTIMEZONE").concat();
+    for vtodo in blocks(&unfolded, "VTODO") {
+        let single = format!("BEGIN:VCALENDAR\n{}{}END:VCALENDAR\n", timezones, vtodo);
+        let Some(task) = vcalendar_to_task(&single) else {
+            continue;
+        };
+        let uid = task_uid(&task);
+        if known_uids.contains(&uid) || !seen.insert(uid) {
+            import.duplicates += 1;
+        } else {
+            import.tasks.push(task);
+        }
+    }
+
+    for event in vcalendar_to_events(content) {
+        let uid = event_uid(&event);
+        if known_uids.contains(&uid) || !seen.insert(uid) {
+            import.duplicates += 1;
+        } else {
+            import.events.push(event);
+        }
+    }
+
+    import
+}
+
+/// `BEGIN:<name>` … `END:<name>` blocks of unfolded iCalendar text, newline-terminated.
+fn blocks(unfolded: &str, name: &str) -> Vec<String> {
+    let begin = format!("BEGIN:{}", name);
+    let end = format!("END:{}", name);
+    let mut out = Vec::new();
+    let mut current: Option<String> = None;
+    for line in unfolded.lines() {
+        let line = line.trim_end();
+        if line == begin {
+            current = Some(String::new());
+        }
+        if let Some(ref mut block) = current {
+            block.push_str(line);
+            block.push('\n');
+        }
+        if line == end {
+            out.extend(current.take());
+        }
+    }
+    out
+}
+
+/// UID a task is written with.
+pub fn task_uid(task: &Task) -> String {
+    task.sync_uid.clone().unwrap_or_else(|| task.id.to_string())
+}
+
+/// UID an event is written with.
+pub fn event_uid(event: &CalendarEvent) -> String {
+    event.sync_uid.clone().unwrap_or_else(|| event.id.to_string())
+}
+
+/// Which tasks and events go into an export. Unset fields don't filter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportFilter {
+    pub project: Option<String>,
+    pub calendar_href: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub tasks: bool,
+    pub events: bool,
+}
+
+impl ExportFilter {
+    fn in_range(&self, date: NaiveDate) -> bool {
+        self.from.is_none_or(|from| date >= from) && self.to.is_none_or(|to| date <= to)
+    }
+
+    /// Tasks match on project, and on their scheduled or deadline date when
+    /// a range is set.
+    pub fn matches_task(&self, task: &Task) -> bool {
+        if !self.tasks {
+            return false;
+        }
+        if let Some(ref project) = self.project {
+            if task.project.as_deref() != Some(project.as_str()) {
+                return false;
+            }
+        }
+        if self.from.is_some() || self.to.is_some() {
+            return [task.scheduled, task.deadline]
+                .into_iter()
+                .flatten()
+                .any(|date| self.in_range(date));
+        }
+        true
+    }
+
+    /// Events match on calendar, and when a range is set on having an
+    /// occurrence in it. Events aren't in projects, so a project filter drops them.
+    pub fn matches_event(&self, event: &CalendarEvent) -> bool {
+        if !self.events || self.project.is_some() {
+            return false;
+        }
+        if let Some(ref href) = self.calendar_href {
+            if event.calendar_href != *href {
+                return false;
+            }
+        }
+        if self.from.is_none() && self.to.is_none() {
+            return true;
+        }
+        // An open-ended range looks a few years past the series start
+        let from = self.from.unwrap_or_else(|| event.local_start().date());
+        let to = self.to.unwrap_or(from + Duration::days(5 * 365));
+        !event.occurrences(from, to).is_empty()
+    }
+}
+
+/// One VCALENDAR holding every task and event, with each time zone defined once.
+pub fn export_ics<'a>(
+    tasks: impl IntoIterator<Item = &'a Task>,
+    events: impl IntoIterator<Item = &'a CalendarEvent>,
+) -> String {
+    let mut timezones: Vec<String> = Vec::new();
+    let mut tzids: HashSet<String> = HashSet::new();
+    let mut components: Vec<String> = Vec::new();
+
+    let calendars = tasks
+        .into_iter()
+        .map(task_to_vcalendar)
+        .chain(events.into_iter().map(event_to_vcalendar));
+    for vcal in calendars {
+        let mut in_timezone = false;
+        let mut block: Vec<&str> = Vec::new();
+        for line in vcal.lines() {
+            match line {
+                "BEGIN:VCALENDAR" | "END:VCALENDAR" => continue,
+                _ if line.starts_with("VERSION:") || line.starts_with("PRODID:") => continue,
+                "BEGIN:VTIMEZONE" => in_timezone = true,
+                _ => {}
+            }
+            if in_timezone {
+                block.push(line);
+                if line == "END:VTIMEZONE" {
+                    in_timezone = false;
+                    let tzid = block.iter().find_map(|l| l.strip_prefix("TZID:")).unwrap_or_default();
+                    if tzids.insert(tzid.to_string()) {
+                        timezones.extend(block.iter().map(|l| l.to_string()));
+                    }
+                    block.clear();
+                }
+            } else {
+                components.push(line.to_string());
+            }
+        }
+    }
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Lamp GTD//EN".to_string(),
+    ];
+    lines.extend(timezones);
+    lines.extend(components);
+    lines.push("END:VCALENDAR".to_string());
+    lines.join("\r\n") + "\r\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> chrono::NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn export_then_import_roundtrips_and_skips_duplicates() {
+        let mut task = Task::new("Pack bags");
+        task.project = Some("Trip".to_string());
+        task.scheduled = NaiveDate::from_ymd_opt(2026, 5, 2);
+        let mut talk = CalendarEvent::new("Keynote".to_string(), at(2026, 5, 3, 9), at(2026, 5, 3, 10));
+        talk.tzid = Some("Europe/Berlin".to_string());
+        let mut lunch = CalendarEvent::new("Lunch".to_string(), at(2026, 5, 3, 12), at(2026, 5, 3, 13));
+        lunch.tzid = Some("Europe/Berlin".to_string());
+
+        let ics = export_ics([&task], [&talk, &lunch]);
+        assert_eq!(ics.matches("BEGIN:VCALENDAR").count(), 1);
+        assert_eq!(ics.matches("BEGIN:VTIMEZONE").count(), 1);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+
+        let import = parse_ics(&ics, &HashSet::new());
+        assert_eq!(import.tasks.len(), 1);
+        assert_eq!(import.tasks[0].id, task.id);
+        assert_eq!(import.tasks[0].project.as_deref(), Some("Trip"));
+        assert_eq!(import.events.len(), 2);
+        assert_eq!(import.duplicates, 0);
+
+        let known: HashSet<String> = [task_uid(&task), event_uid(&talk)].into_iter().collect();
+        let again = parse_ics(&ics, &known);
+        assert_eq!(again.tasks.len(), 0);
+        assert_eq!(again.events.len(), 1);
+        assert_eq!(again.events[0].title, "Lunch");
+        assert_eq!(again.duplicates, 2);
+    }
+
+    #[test]
+    fn filters_by_project_calendar_and_range() {
+        let mut task = Task::new("Pack bags");
+        task.project = Some("Trip".to_string());
+        task.scheduled = NaiveDate::from_ymd_opt(2026, 5, 2);
+        let mut event = CalendarEvent::new("Keynote".to_string(), at(2026, 5, 3, 9), at(2026, 5, 3, 10));
+        event.tzid = None;
+        event.calendar_href = "/cal/work/".to_string();
+
+        let all = ExportFilter { tasks: true, events: true, ..ExportFilter::default() };
+        assert!(all.matches_task(&task) && all.matches_event(&event));
+
+        let may_2 = ExportFilter {
+            from: NaiveDate::from_ymd_opt(2026, 5, 1),
+            to: NaiveDate::from_ymd_opt(2026, 5, 2),
+            ..all.clone()
+        };
+        assert!(may_2.matches_task(&task));
+        assert!(!may_2.matches_event(&event));
+
+        let other_project = ExportFilter { project: Some("Home".to_string()), ..all.clone() };
+        assert!(!other_project.matches_task(&task));
+        assert!(!other_project.matches_event(&event));
+
+        let personal = ExportFilter { calendar_href: Some("/cal/personal/".to_string()), ..all };
+        assert!(!personal.matches_event(&event));
+    }
+}
@@ -1,5 +1,14 @@
-use chrono::NaiveDateTime;
+use std::collections::HashSet;
+use std::path::Path;
+
+use async_imap::extensions::idle::IdleResponse;
+use chrono::{NaiveDate, NaiveDateTime};
 use futures::TryStreamExt;
+use mail_parser::MimeHeaders;
+use serde::{Deserialize, Serialize};
+
+use super::llm::BatchEmailSuggestion;
+use crate::config::TriageRule;
 
 type ImapSession = async_imap::Session<async_native_tls::TlsStream<async_std::net::TcpStream>>;
 
@@ -30,25 +39,137 @@ async fn resolve_folder(
     ))
 }
 
-/// An email fetched from an IMAP folder.
-#[derive(Debug, Clone)]
+/// An email fetched from an IMAP folder. Listing only reads the headers;
+/// the body is filled in by `fetch_bodies` when something needs it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImapEmail {
     pub uid: u32,
+    /// The `Message-ID` header, stable across folders and sessions.
+    pub message_id: Option<String>,
     pub subject: String,
     pub from: String,
+    /// The sender's address, for rules that match on it.
+    #[serde(default)]
+    pub from_address: String,
+    /// The `List-Id` header of mailing list mail.
+    #[serde(default)]
+    pub list_id: Option<String>,
     pub date: Option<NaiveDateTime>,
     pub body_preview: String,
     pub body_full: String,
+    /// A `text/calendar` part, e.g. a meeting invitation.
+    pub calendar: Option<String>,
+    /// Whether the body has been downloaded.
+    #[serde(default)]
+    pub body_loaded: bool,
+    /// Known only once the body is loaded.
+    #[serde(default)]
+    pub has_attachments: bool,
+}
+
+/// How many of the newest messages get their bodies loaded for triage and
+/// invitations; older mail stays listed by its headers.
+pub const BODY_WINDOW: usize = 50;
+
+/// UIDs among the `limit` newest `emails` whose bodies aren't loaded yet.
+pub fn newest_unloaded(emails: &[ImapEmail], limit: usize) -> Vec<u32> {
+    let mut newest: Vec<&ImapEmail> = emails.iter().collect();
+    newest.sort_by_key(|e| std::cmp::Reverse((e.date, e.uid)));
+    newest
+        .into_iter()
+        .take(limit)
+        .filter(|e| !e.body_loaded)
+        .map(|e| e.uid)
+        .collect()
+}
+
+/// The folder as last seen, persisted so a refresh only lists new messages.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MailCache {
+    pub folder: String,
+    pub uid_validity: Option<u32>,
+    /// Highest UID listed so far; anything above it is new.
+    pub last_uid: u32,
+    pub emails: Vec<ImapEmail>,
 }
 
-/// Connect to IMAP over TLS and return a logged-in session.
+impl MailCache {
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        match serde_json::to_string(self) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    log::error!("Failed to save mail cache: {}", e);
+                }
+            }
+            Err(e) => log::error!("Failed to serialize mail cache: {}", e),
+        }
+    }
+
+    /// Bring the cache in line with the folder's current UIDs and return the
+    /// new ones to list. A different folder or UIDVALIDITY means the old UIDs
+    /// no longer name the same messages, so everything is listed again.
+    pub fn reconcile(&mut self, folder: &str, uid_validity: Option<u32>, server_uids: &HashSet<u32>) -> Vec<u32> {
+        if self.folder != folder || uid_validity.is_none() || self.uid_validity != uid_validity {
+            *self = MailCache {
+                folder: folder.to_string(),
+                uid_validity,
+                ..Self::default()
+            };
+        }
+        self.emails.retain(|e| server_uids.contains(&e.uid));
+        let mut new: Vec<u32> = server_uids.iter().copied().filter(|uid| *uid > self.last_uid).collect();
+        new.sort_unstable();
+        new
+    }
+
+    /// Add newly listed emails, or replace listed ones with their loaded bodies.
+    pub fn merge(&mut self, emails: Vec<ImapEmail>) {
+        for email in emails {
+            self.last_uid = self.last_uid.max(email.uid);
+            match self.emails.iter_mut().find(|e| e.uid == email.uid) {
+                Some(existing) => *existing = email,
+                None => self.emails.push(email),
+            }
+        }
+        self.emails.sort_by_key(|e| e.uid);
+    }
+}
+
+/// `host` or `host:port`; IMAPS on 993 unless a port is given.
+fn host_and_port(host: &str) -> (&str, u16) {
+    match host.rsplit_once(':') {
+        Some((name, port)) => match port.parse() {
+            Ok(port) => (name, port),
+            Err(_) => (host, 993),
+        },
+        None => (host, 993),
+    }
+}
+
+/// Whether `host:port` is a local test server (a Dovecot or GreenMail
+/// container) whose self-signed certificate is accepted: localhost on any
+/// port but the standard 993, which always gets full certificate checks.
+fn is_test_server(host: &str, port: u16) -> bool {
+    matches!(host, "localhost" | "127.0.0.1" | "::1") && port != 993
+}
+
+/// Connect to IMAP over TLS and return a logged-in session. Certificates and
+/// hostnames are verified, except for local test servers (`is_test_server`).
 async fn connect_and_login(
     host: &str,
     username: &str,
     password: &str,
-) -> Result<async_imap::Session<async_native_tls::TlsStream<async_std::net::TcpStream>>, String> {
-    let tls = async_native_tls::TlsConnector::new();
-    let tcp = async_std::net::TcpStream::connect((host, 993))
+) -> Result<ImapSession, String> {
+    let (host, port) = host_and_port(host);
+    let tls = async_native_tls::TlsConnector::new().danger_accept_invalid_certs(is_test_server(host, port));
+    let tcp = async_std::net::TcpStream::connect((host, port))
         .await
         .map_err(|e| format!("TCP connect failed: {}", e))?;
     let tls_stream = tls
@@ -65,13 +186,80 @@ async fn connect_and_login(
     Ok(session)
 }
 
-/// Fetch all emails from the configured IMAP folder.
-pub async fn fetch_emails(
+/// A UID set for FETCH, with runs collapsed: `[1, 2, 3, 7]` is `1:3,7`.
+fn uid_set(uids: &[u32]) -> String {
+    let mut sorted = uids.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let mut parts: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < sorted.len() {
+        let start = sorted[i];
+        while i + 1 < sorted.len() && sorted[i + 1] == sorted[i] + 1 {
+            i += 1;
+        }
+        parts.push(if sorted[i] == start {
+            start.to_string()
+        } else {
+            format!("{}:{}", start, sorted[i])
+        });
+        i += 1;
+    }
+    parts.join(",")
+}
+
+/// Refresh `cache` from the folder: drop messages that left it and list the
+/// headers of new ones. Bodies aren't downloaded.
+pub async fn sync_folder(
+    host: &str,
+    username: &str,
+    password: &str,
+    folder: &str,
+    mut cache: MailCache,
+) -> Result<MailCache, String> {
+    let mut session = connect_and_login(host, username, password).await?;
+
+    let folder = resolve_folder(&mut session, folder).await?;
+    let mailbox = session
+        .select(&folder)
+        .await
+        .map_err(|e| format!("Failed to select folder '{}': {}", folder, e))?;
+    let server_uids = session
+        .uid_search("ALL")
+        .await
+        .map_err(|e| format!("IMAP search failed: {}", e))?;
+
+    let new = cache.reconcile(&folder, mailbox.uid_validity, &server_uids);
+    if !new.is_empty() {
+        let messages: Vec<_> = session
+            .uid_fetch(uid_set(&new), "(UID BODY.PEEK[HEADER])")
+            .await
+            .map_err(|e| format!("IMAP fetch failed: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("IMAP stream error: {}", e))?;
+        let listed = messages
+            .iter()
+            .filter_map(|msg| parse_email(msg.uid?, msg.header()?, false))
+            .collect();
+        cache.merge(listed);
+    }
+
+    session.logout().await.ok();
+    Ok(cache)
+}
+
+/// Download the full messages for `uids`.
+pub async fn fetch_bodies(
     host: &str,
     username: &str,
     password: &str,
     folder: &str,
+    uids: Vec<u32>,
 ) -> Result<Vec<ImapEmail>, String> {
+    if uids.is_empty() {
+        return Ok(Vec::new());
+    }
     let mut session = connect_and_login(host, username, password).await?;
 
     let folder = resolve_folder(&mut session, folder).await?;
@@ -80,75 +268,317 @@ pub async fn fetch_emails(
         .await
         .map_err(|e| format!("Failed to select folder '{}': {}", folder, e))?;
 
-    let messages_stream = session
-        .fetch("1:*", "(UID BODY.PEEK[])")
+    let messages: Vec<_> = session
+        .uid_fetch(uid_set(&uids), "(UID BODY.PEEK[])")
         .await
-        .map_err(|e| format!("IMAP fetch failed: {}", e))?;
-
-    let messages: Vec<_> = messages_stream
+        .map_err(|e| format!("IMAP fetch failed: {}", e))?
         .try_collect()
         .await
         .map_err(|e| format!("IMAP stream error: {}", e))?;
+    let emails = messages
+        .iter()
+        .filter_map(|msg| parse_email(msg.uid?, msg.body()?, true))
+        .collect();
 
-    let mut emails = Vec::new();
-    for msg in &messages {
-        let uid = msg.uid.unwrap_or(0);
-        let body = match msg.body() {
-            Some(b) => b,
-            None => continue,
-        };
+    session.logout().await.ok();
+    Ok(emails)
+}
 
-        let parsed = match mail_parser::MessageParser::default().parse(body) {
-            Some(p) => p,
-            None => continue,
-        };
+/// An email sent to Lamp to become a task, with its attachments.
+#[derive(Debug, Clone)]
+pub struct CapturedEmail {
+    pub email: ImapEmail,
+    /// File name and contents of each attachment.
+    pub attachments: Vec<(String, Vec<u8>)>,
+}
 
-        let subject = parsed
-            .subject()
-            .unwrap_or("(no subject)")
-            .to_string();
+/// IMAP SEARCH for capture mail: everything in a dedicated folder, or only
+/// mail addressed to the capture (e.g. plus) address.
+fn capture_search(address: &str) -> String {
+    let address = address.trim();
+    if address.is_empty() {
+        return "ALL".to_string();
+    }
+    let address = address.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("OR TO \"{0}\" HEADER Delivered-To \"{0}\"", address)
+}
 
-        let from = parsed
-            .from()
-            .and_then(|addrs| addrs.first())
-            .map(|a| {
-                if let Some(name) = a.name() {
-                    name.to_string()
-                } else {
-                    a.address().unwrap_or("unknown").to_string()
-                }
-            })
-            .unwrap_or_else(|| "unknown".to_string());
-
-        let date = parsed.date().and_then(|dt| {
-            NaiveDateTime::parse_from_str(
-                &format!(
-                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
-                    dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second
-                ),
-                "%Y-%m-%d %H:%M:%S",
-            )
-            .ok()
-        });
+/// Download every message waiting in the capture folder, or addressed to
+/// `address` when one is set.
+pub async fn fetch_captures(
+    host: &str,
+    username: &str,
+    password: &str,
+    folder: &str,
+    address: &str,
+) -> Result<Vec<CapturedEmail>, String> {
+    let mut session = connect_and_login(host, username, password).await?;
+
+    let folder = resolve_folder(&mut session, folder).await?;
+    session
+        .select(&folder)
+        .await
+        .map_err(|e| format!("Failed to select folder '{}': {}", folder, e))?;
+    let uids: Vec<u32> = session
+        .uid_search(capture_search(address))
+        .await
+        .map_err(|e| format!("IMAP search failed: {}", e))?
+        .into_iter()
+        .collect();
+
+    let mut captured = Vec::new();
+    if !uids.is_empty() {
+        let messages: Vec<_> = session
+            .uid_fetch(uid_set(&uids), "(UID BODY.PEEK[])")
+            .await
+            .map_err(|e| format!("IMAP fetch failed: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("IMAP stream error: {}", e))?;
+        captured = messages
+            .iter()
+            .filter_map(|msg| parse_capture(msg.uid?, msg.body()?))
+            .collect();
+    }
+
+    session.logout().await.ok();
+    Ok(captured)
+}
+
+/// Wait on the folder with IMAP IDLE until the server reports a change or
+/// `timeout` passes. `Ok(true)` means something changed.
+pub async fn idle(
+    host: &str,
+    username: &str,
+    password: &str,
+    folder: &str,
+    timeout: std::time::Duration,
+) -> Result<bool, String> {
+    let mut session = connect_and_login(host, username, password).await?;
+
+    let folder = resolve_folder(&mut session, folder).await?;
+    session
+        .select(&folder)
+        .await
+        .map_err(|e| format!("Failed to select folder '{}': {}", folder, e))?;
+
+    let mut handle = session.idle();
+    handle.init().await.map_err(|e| format!("IMAP IDLE failed: {}", e))?;
+    let response = {
+        // Dropping the stop source ends the wait, so it's held until then
+        let (wait, _stop) = handle.wait_with_timeout(timeout);
+        wait.await.map_err(|e| format!("IMAP IDLE failed: {}", e))?
+    };
+    let mut session = handle.done().await.map_err(|e| format!("IMAP IDLE failed: {}", e))?;
+
+    session.logout().await.ok();
+    Ok(matches!(response, IdleResponse::NewData(_)))
+}
+
+/// IMAP SEARCH for mail in the thread of `message_id` that arrived on or after `since`.
+fn reply_search(message_id: &str, since: NaiveDate) -> String {
+    let id = message_id.replace('\\', "\\\\").replace('"', "\\\"");
+    format!(
+        "SINCE {} OR HEADER In-Reply-To \"{}\" HEADER References \"{}\"",
+        since.format("%-d-%b-%Y"),
+        id,
+        id
+    )
+}
+
+/// Which of `threads` (Message-ID, waiting since) got a reply in `folder`.
+pub async fn find_replies(
+    host: &str,
+    username: &str,
+    password: &str,
+    folder: &str,
+    threads: Vec<(String, NaiveDate)>,
+) -> Result<Vec<String>, String> {
+    let mut session = connect_and_login(host, username, password).await?;
+
+    let folder = resolve_folder(&mut session, folder).await?;
+    session
+        .examine(&folder)
+        .await
+        .map_err(|e| format!("Failed to open folder '{}': {}", folder, e))?;
+
+    let mut replied = Vec::new();
+    for (message_id, since) in threads {
+        let found = session
+            .uid_search(reply_search(&message_id, since))
+            .await
+            .map_err(|e| format!("IMAP search failed: {}", e))?;
+        if !found.is_empty() {
+            replied.push(message_id);
+        }
+    }
+
+    session.logout().await.ok();
+    Ok(replied)
+}
+
+/// An email from a raw message, or just its header when `with_body` is false.
+fn parse_email(uid: u32, raw: &[u8], with_body: bool) -> Option<ImapEmail> {
+    let parsed = mail_parser::MessageParser::default().parse(raw)?;
+
+    let message_id = parsed.message_id().map(str::to_string);
+
+    let subject = parsed
+        .subject()
+        .unwrap_or("(no subject)")
+        .to_string();
 
+    let sender = parsed.from().and_then(|addrs| addrs.first());
+    let from_address = sender
+        .and_then(|a| a.address())
+        .unwrap_or_default()
+        .to_string();
+    let from = sender
+        .map(|a| {
+            if let Some(name) = a.name() {
+                name.to_string()
+            } else {
+                a.address().unwrap_or("unknown").to_string()
+            }
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let date = parsed.date().and_then(|dt| {
+        NaiveDateTime::parse_from_str(
+            &format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second
+            ),
+            "%Y-%m-%d %H:%M:%S",
+        )
+        .ok()
+    });
+
+    let list_id = parsed
+        .header_raw("List-Id")
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
+    let (body_text, calendar) = if with_body {
         let body_text = parsed
             .body_text(0)
             .unwrap_or_default()
             .to_string();
-        let body_preview: String = body_text.chars().take(200).collect();
-
-        emails.push(ImapEmail {
-            uid,
-            subject,
-            from,
-            date,
-            body_preview,
-            body_full: body_text,
-        });
+        let calendar = parsed
+            .parts
+            .iter()
+            .filter(|part| part.is_content_type("text", "calendar"))
+            .find_map(|part| part.text_contents())
+            .map(str::to_string);
+        (body_text, calendar)
+    } else {
+        (String::new(), None)
+    };
+    let body_preview: String = body_text.chars().take(200).collect();
+
+    Some(ImapEmail {
+        uid,
+        message_id,
+        subject,
+        from,
+        from_address,
+        list_id,
+        date,
+        body_preview,
+        body_full: body_text,
+        calendar,
+        body_loaded: with_body,
+        has_attachments: with_body && parsed.attachment_count() > 0,
+    })
+}
+
+/// Whether `needle` (trimmed) occurs in `haystack`, ignoring case.
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.trim().to_lowercase())
+}
+
+/// Whether every condition the rule sets holds for the email. An invalid
+/// subject pattern never matches.
+pub fn rule_matches(rule: &TriageRule, email: &ImapEmail) -> bool {
+    if !rule.has_conditions() {
+        return false;
+    }
+    if !rule.from.trim().is_empty()
+        && !contains_ignore_case(&email.from, &rule.from)
+        && !contains_ignore_case(&email.from_address, &rule.from)
+    {
+        return false;
     }
+    if !rule.subject.trim().is_empty() {
+        match regex::RegexBuilder::new(rule.subject.trim())
+            .case_insensitive(true)
+            .build()
+        {
+            Ok(re) if re.is_match(&email.subject) => {}
+            _ => return false,
+        }
+    }
+    if !rule.list_id.trim().is_empty()
+        && !email
+            .list_id
+            .as_deref()
+            .is_some_and(|id| contains_ignore_case(id, &rule.list_id))
+    {
+        return false;
+    }
+    if let Some(wanted) = rule.has_attachment {
+        if email.has_attachments != wanted {
+            return false;
+        }
+    }
+    true
+}
 
-    session.logout().await.ok();
-    Ok(emails)
+/// The first rule matching the email, and the suggestion it makes.
+pub fn triage<'a>(
+    rules: &'a [TriageRule],
+    email: &ImapEmail,
+) -> Option<(&'a TriageRule, BatchEmailSuggestion)> {
+    let rule = rules.iter().find(|r| rule_matches(r, email))?;
+    let title = if rule.title.trim().is_empty() {
+        email.subject.clone()
+    } else {
+        rule.title
+            .replace("{subject}", &email.subject)
+            .replace("{from}", &email.from)
+    };
+    let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+    let suggestion = BatchEmailSuggestion {
+        action_needed: rule.create_task,
+        title: Some(title),
+        priority: non_empty(&rule.priority),
+        contexts: Some(rule.contexts.clone()).filter(|c| !c.is_empty()),
+        deadline: None,
+        scheduled: None,
+        project: non_empty(&rule.project),
+        is_duplicate: None,
+        duplicate_of: None,
+        esc: rule.esc,
+        rule: Some(rule.name.clone()),
+    };
+    Some((rule, suggestion))
+}
+
+fn parse_capture(uid: u32, raw: &[u8]) -> Option<CapturedEmail> {
+    let email = parse_email(uid, raw, true)?;
+    let parsed = mail_parser::MessageParser::default().parse(raw)?;
+    let attachments = parsed
+        .attachments()
+        .enumerate()
+        .map(|(i, part)| {
+            let name = part
+                .attachment_name()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("attachment-{}", i + 1));
+            (name, part.contents().to_vec())
+        })
+        .collect();
+    Some(CapturedEmail { email, attachments })
 }
 
 /// Archive an email by moving it to the Archive folder.
@@ -200,3 +630,113 @@ pub async fn test_connection(
     session.logout().await.ok();
     Ok(format!("Connected ({} folders)", count))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &[u8] = b"Message-ID: <q1@example.com>\r\nFrom: Sam Lee <sam@example.com>\r\n\
+        Subject: Roof quote\r\nDate: Mon, 2 Mar 2026 09:30:00 +0000\r\n\r\nCan you approve by Friday?\r\n";
+
+    #[test]
+    fn headers_first_then_bodies() {
+        let header_end = MESSAGE.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        let listed = parse_email(7, &MESSAGE[..header_end], false).unwrap();
+        assert_eq!(listed.subject, "Roof quote");
+        assert_eq!(listed.from, "Sam Lee");
+        assert_eq!(listed.message_id.as_deref(), Some("q1@example.com"));
+        assert!(!listed.body_loaded && listed.body_full.is_empty());
+
+        let mut cache = MailCache::default();
+        let server: HashSet<u32> = [3, 7].into();
+        assert_eq!(cache.reconcile("flup", Some(42), &server), vec![3, 7]);
+        cache.merge(vec![listed, parse_email(3, MESSAGE, false).unwrap()]);
+        assert_eq!(cache.last_uid, 7);
+        assert_eq!(cache.emails[0].uid, 3);
+
+        // Only mail above the high-water mark is new; mail gone from the folder is dropped
+        let server: HashSet<u32> = [7, 8, 9, 11].into();
+        assert_eq!(cache.reconcile("flup", Some(42), &server), vec![8, 9, 11]);
+        assert_eq!(cache.emails.len(), 1);
+        assert_eq!(uid_set(&[11, 8, 9]), "8:9,11");
+
+        let loaded = parse_email(7, MESSAGE, true).unwrap();
+        assert_eq!(loaded.body_full.trim(), "Can you approve by Friday?");
+        cache.merge(vec![loaded]);
+        assert!(cache.emails[0].body_loaded);
+
+        // Only the newest mail is worth downloading in full
+        let mut older = parse_email(8, MESSAGE, false).unwrap();
+        older.date = older.date.map(|d| d - chrono::Duration::days(30));
+        cache.merge(vec![older, parse_email(9, MESSAGE, false).unwrap()]);
+        assert_eq!(newest_unloaded(&cache.emails, 2), vec![9]);
+        assert_eq!(newest_unloaded(&cache.emails, 3), vec![9, 8]);
+
+        // A new UIDVALIDITY invalidates every UID we know
+        assert_eq!(cache.reconcile("flup", Some(43), &server), vec![7, 8, 9, 11]);
+        assert!(cache.emails.is_empty());
+        assert_eq!(host_and_port("localhost:3993"), ("localhost", 3993));
+        assert_eq!(host_and_port("imap.example.com"), ("imap.example.com", 993));
+        assert!(is_test_server("localhost", 3993));
+        assert!(!is_test_server("localhost", 993));
+        assert!(!is_test_server("imap.example.com", 3993));
+
+        let since = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        assert_eq!(
+            reply_search("q1@example.com", since),
+            "SINCE 2-Mar-2026 OR HEADER In-Reply-To \"q1@example.com\" HEADER References \"q1@example.com\""
+        );
+        assert_eq!(capture_search(" "), "ALL");
+        assert_eq!(
+            capture_search("me+lamp@example.com"),
+            "OR TO \"me+lamp@example.com\" HEADER Delivered-To \"me+lamp@example.com\""
+        );
+    }
+
+    #[test]
+    fn rules_match_and_fill_the_suggestion() {
+        let mut email = parse_email(7, MESSAGE, true).unwrap();
+        assert_eq!(email.from_address, "sam@example.com");
+        assert!(email.list_id.is_none() && !email.has_attachments);
+
+        let invoice = TriageRule {
+            name: "Invoices".to_string(),
+            subject: "^(invoice|receipt)".to_string(),
+            has_attachment: Some(true),
+            archive: true,
+            ..TriageRule::default()
+        };
+        let quotes = TriageRule {
+            name: "Quotes".to_string(),
+            from: "SAM@example".to_string(),
+            subject: "quote".to_string(),
+            title: "Reply to {from}: {subject}".to_string(),
+            contexts: vec!["@email".to_string()],
+            priority: "A".to_string(),
+            esc: Some(2),
+            ..TriageRule::default()
+        };
+        let rules = vec![TriageRule::default(), invoice, quotes];
+
+        let (rule, suggestion) = triage(&rules, &email).unwrap();
+        assert_eq!(rule.name, "Quotes");
+        assert!(suggestion.action_needed);
+        assert_eq!(suggestion.title.as_deref(), Some("Reply to Sam Lee: Roof quote"));
+        assert_eq!(suggestion.contexts, Some(vec!["@email".to_string()]));
+        assert_eq!(suggestion.priority.as_deref(), Some("A"));
+        assert_eq!(suggestion.project, None);
+        assert_eq!(suggestion.esc, Some(2));
+
+        email.subject = "Invoice 2026-03".to_string();
+        assert!(triage(&rules, &email).is_none());
+        email.has_attachments = true;
+        assert_eq!(triage(&rules, &email).unwrap().0.name, "Invoices");
+
+        let broken = TriageRule { subject: "(".to_string(), ..TriageRule::default() };
+        assert!(!rule_matches(&broken, &email));
+        let list = TriageRule { list_id: "announce.example.org".to_string(), ..TriageRule::default() };
+        assert!(!rule_matches(&list, &email));
+        email.list_id = Some("Announcements <announce.example.org>".to_string());
+        assert!(rule_matches(&list, &email));
+    }
+}
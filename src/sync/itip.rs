@@ -0,0 +1,179 @@
+//! iTIP (RFC 5546) invitations and replies for meeting events.
+
+use std::path::Path;
+
+use super::ical::{format_utc, parse_ical_line, unfold_lines};
+use super::vevent::{event_to_vcalendar, person_line, vcalendar_to_events};
+use crate::core::event::{Attendee, CalendarEvent, PartStat};
+use crate::core::timezone;
+
+/// Where an invitation arrived, which decides how the reply goes back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvitationSource {
+    /// Already in one of our CalDAV calendars; the server sends the REPLY
+    /// when the answered event syncs.
+    Calendar,
+    /// A scheduling message in the CalDAV inbox (RFC 6638), removed once answered.
+    CalDavInbox { href: String },
+    /// A `text/calendar` part of a mail in the IMAP folder.
+    Email { uid: u32 },
+}
+
+/// A meeting request waiting for an answer.
+#[derive(Debug, Clone)]
+pub struct Invitation {
+    pub event: CalendarEvent,
+    pub source: InvitationSource,
+}
+
+/// An iTIP REPLY to send to the organizer by mail.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ItipReply {
+    pub to: String,
+    pub subject: String,
+    /// `text/calendar; method=REPLY` body.
+    pub ical: String,
+}
+
+/// The METHOD of an iCalendar object, upper-cased; inbox items may leave it out.
+pub fn method(ical: &str) -> Option<String> {
+    unfold_lines(ical).lines().find_map(|line| match parse_ical_line(line.trim_end()) {
+        Some(("METHOD", value)) => Some(value.trim().to_uppercase()),
+        _ => None,
+    })
+}
+
+/// Events of a meeting request we're invited to. Replies, cancellations and
+/// objects that don't list any of `addresses` as an attendee are ignored.
+pub fn parse_request(ical: &str, addresses: &[String]) -> Vec<CalendarEvent> {
+    if method(ical).is_some_and(|m| m != "REQUEST") {
+        return Vec::new();
+    }
+    vcalendar_to_events(ical)
+        .into_iter()
+        .filter(|e| e.organizer.is_some() && e.attendee_for(addresses).is_some())
+        .collect()
+}
+
+/// A REPLY telling the organizer `attendee`'s answer to `event`. `None` if
+/// the event has no organizer to reply to.
+pub fn reply(event: &CalendarEvent, attendee: &Attendee) -> Option<ItipReply> {
+    let organizer = event.organizer.as_ref()?;
+
+    // Reuse the event's own serialization for times and zones, keeping only
+    // what a REPLY carries: UID, times, organizer and the one attendee.
+    let full = event_to_vcalendar(&CalendarEvent {
+        overrides: Vec::new(),
+        attendees: Vec::new(),
+        alarms: Vec::new(),
+        extra_properties: Vec::new(),
+        ..event.clone()
+    });
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_event = false;
+    let mut keep = false;
+    for line in full.lines() {
+        match line {
+            "BEGIN:VCALENDAR" | "VERSION:2.0" | "END:VCALENDAR" => continue,
+            _ if line.starts_with("PRODID:") => continue,
+            "BEGIN:VEVENT" => in_event = true,
+            "END:VEVENT" => {
+                lines.push(person_line("ATTENDEE", attendee, true));
+                lines.push(format!("DTSTAMP:{}", format_utc(timezone::now_utc())));
+                in_event = false;
+            }
+            _ => {}
+        }
+        // Folded continuation lines go with the property they continue
+        if !line.starts_with(' ') {
+            keep = !in_event
+                || ["BEGIN:", "UID:", "DTSTART", "DTEND", "RECURRENCE-ID", "SUMMARY:", "ORGANIZER"]
+                    .iter()
+                    .any(|prefix| line.starts_with(prefix));
+        }
+        if keep {
+            lines.push(line.to_string());
+        }
+    }
+
+    let mut ical = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Lamp GTD//EN".to_string(),
+        "METHOD:REPLY".to_string(),
+    ];
+    ical.extend(lines);
+    ical.push("END:VCALENDAR".to_string());
+
+    let verb = match attendee.partstat {
+        PartStat::Accepted => "Accepted",
+        PartStat::Tentative => "Tentatively accepted",
+        PartStat::Declined => "Declined",
+        PartStat::NeedsAction | PartStat::Delegated => "Updated",
+    };
+    Some(ItipReply {
+        to: organizer.email.clone(),
+        subject: format!("{}: {}", verb, event.title),
+        ical: ical.join("\r\n") + "\r\n",
+    })
+}
+
+pub fn load_replies(path: &Path) -> Vec<ItipReply> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub fn save_replies(path: &Path, replies: &[ItipReply]) {
+    match serde_json::to_string_pretty(replies) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(path, json) {
+                log::error!("Failed to save replies: {}", e);
+            }
+        }
+        Err(e) => log::error!("Failed to serialize replies: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INVITE: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nMETHOD:REQUEST\r\n\
+        BEGIN:VEVENT\r\nUID:plan-42@example.com\r\n\
+        DTSTART:20260512T130000Z\r\nDTEND:20260512T140000Z\r\nSUMMARY:Sprint planning\r\n\
+        ORGANIZER;CN=Sam Lee:mailto:sam@example.com\r\n\
+        ATTENDEE;CN=Alex;PARTSTAT=NEEDS-ACTION;RSVP=TRUE:mailto:alex@example.com\r\n\
+        ATTENDEE;PARTSTAT=ACCEPTED:mailto:kim@example.com\r\n\
+        END:VEVENT\r\nEND:VCALENDAR\r\n";
+
+    #[test]
+    fn parses_request_and_builds_reply() {
+        let me = vec!["Alex@Example.com".to_string()];
+        let mut events = parse_request(INVITE, &me);
+        assert_eq!(events.len(), 1);
+        let event = &mut events[0];
+        assert_eq!(event.organizer.as_ref().map(|o| o.label()), Some("Sam Lee"));
+        assert_eq!(event.attendees.len(), 2);
+        assert_eq!(event.attendee_for(&me).map(|a| a.partstat), Some(PartStat::NeedsAction));
+        assert!(event.attendee_for(&me).is_some_and(|a| a.rsvp));
+
+        // Not invited, or not a request: nothing to answer
+        assert!(parse_request(INVITE, &["someone@else.org".to_string()]).is_empty());
+        assert!(parse_request(&INVITE.replace("METHOD:REQUEST", "METHOD:CANCEL"), &me).is_empty());
+
+        let attendee = event.set_partstat(&me, PartStat::Tentative).unwrap();
+        let reply = reply(event, &attendee).unwrap();
+        assert_eq!(reply.to, "sam@example.com");
+        assert_eq!(reply.subject, "Tentatively accepted: Sprint planning");
+        assert_eq!(method(&reply.ical).as_deref(), Some("REPLY"));
+        assert!(reply.ical.contains("UID:plan-42@example.com"));
+        assert!(reply.ical.contains("ATTENDEE;CN=\"Alex\";PARTSTAT=TENTATIVE:mailto:alex@example.com"));
+        assert!(!reply.ical.contains("kim@example.com"));
+
+        // The answer survives a round trip through the event's own VEVENT
+        let stored = vcalendar_to_events(&event_to_vcalendar(event));
+        assert_eq!(stored[0].attendee_for(&me).map(|a| a.partstat), Some(PartStat::Tentative));
+    }
+}
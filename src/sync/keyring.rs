@@ -1,59 +1,232 @@
+//! Passwords and API keys. They go to the Secret Service when one is
+//! running, otherwise to an encrypted vault file unlocked with a passphrase.
+//! `LAMP_SECRET_<NAME>` environment variables override single secrets for
+//! CI and scripts, e.g. `LAMP_SECRET_IMAP_MAIL_EXAMPLE_COM=me:hunter2`.
+
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::SecretBackend;
 
 pub(crate) const SERVICE_NAME: &str = "lamp-sync";
 
-/// Store CalDAV credentials in the system keyring via Secret Service.
+/// Forces a backend (`secret-service` or `vault`) over the configured one.
+pub const BACKEND_ENV: &str = "LAMP_SECRET_BACKEND";
+/// Unlocks the vault without asking.
+pub const PASSPHRASE_ENV: &str = "LAMP_VAULT_PASSPHRASE";
+
+static BACKEND: Mutex<SecretBackend> = Mutex::new(SecretBackend::Auto);
+/// The vault passphrase entered this session; never written anywhere.
+static VAULT_PASSPHRASE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Use the configured backend from now on.
+pub fn set_backend(backend: SecretBackend) {
+    *BACKEND.lock().unwrap() = backend;
+}
+
+/// Remember (or forget) the vault passphrase for this session.
+pub fn set_vault_passphrase(passphrase: Option<String>) {
+    *VAULT_PASSPHRASE.lock().unwrap() = passphrase.filter(|p| !p.is_empty());
+}
+
+fn backend() -> SecretBackend {
+    match std::env::var(BACKEND_ENV).as_deref() {
+        Ok("secret-service") => SecretBackend::SecretService,
+        Ok("vault") => SecretBackend::Vault,
+        _ => *BACKEND.lock().unwrap(),
+    }
+}
+
+fn vault_passphrase() -> Option<String> {
+    std::env::var(PASSPHRASE_ENV)
+        .ok()
+        .filter(|p| !p.is_empty())
+        .or_else(|| VAULT_PASSPHRASE.lock().unwrap().clone())
+}
+
+/// The vault file in Lamp's config directory, if there's a home to put it in.
+pub fn vault_path() -> Option<PathBuf> {
+    let config = dirs::config_dir().or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(config.join("lamp").join("secrets.keyring"))
+}
+
+/// Whether a vault has been created yet; a new one takes its passphrase twice.
+pub fn vault_exists() -> bool {
+    vault_path().is_some_and(|path| path.exists())
+}
+
+/// The variable that overrides the secret for `server`: upper-cased, with
+/// everything but letters and digits turned into `_`.
+pub fn env_name(server: &str) -> String {
+    let mut name = String::from("LAMP_SECRET_");
+    let mut gap = false;
+    for c in server.chars() {
+        if c.is_ascii_alphanumeric() {
+            if gap && !name.ends_with('_') {
+                name.push('_');
+            }
+            name.push(c.to_ascii_uppercase());
+            gap = false;
+        } else {
+            gap = true;
+        }
+    }
+    name
+}
+
+fn env_secret(server: &str) -> Option<String> {
+    std::env::var(env_name(server)).ok().filter(|s| !s.is_empty())
+}
+
+/// One stored secret with what's needed to recreate it elsewhere.
+struct StoredItem {
+    label: String,
+    attributes: HashMap<String, String>,
+    secret: Vec<u8>,
+}
+
+/// An open secret store.
+enum Store {
+    Service(oo7::Keyring),
+    Vault(oo7::file::Keyring),
+}
+
+impl Store {
+    /// Open `backend`; `Auto` tries the Secret Service first.
+    async fn open(backend: SecretBackend) -> Result<Self, String> {
+        match backend {
+            SecretBackend::SecretService => oo7::Keyring::new()
+                .await
+                .map(Store::Service)
+                .map_err(|e| format!("Failed to connect to keyring: {}", e)),
+            SecretBackend::Vault => Self::open_default_vault().await,
+            SecretBackend::Auto => match oo7::Keyring::new().await {
+                Ok(keyring) => Ok(Store::Service(keyring)),
+                Err(e) => {
+                    log::debug!("No Secret Service ({}), using the vault", e);
+                    Self::open_default_vault().await
+                }
+            },
+        }
+    }
+
+    async fn open_default_vault() -> Result<Self, String> {
+        let passphrase = vault_passphrase()
+            .ok_or_else(|| "The secret vault is locked; enter its passphrase in Settings".to_string())?;
+        let path = vault_path().ok_or_else(|| "No home directory to keep the secret vault in".to_string())?;
+        Self::open_vault(&path, &passphrase).await
+    }
+
+    async fn open_vault(path: &Path, passphrase: &str) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        match oo7::file::Keyring::load(path, oo7::Secret::text(passphrase)).await {
+            Ok(vault) => Ok(Store::Vault(vault)),
+            Err(oo7::file::Error::IncorrectSecret) => Err("Wrong vault passphrase".to_string()),
+            Err(e) => Err(format!("Failed to open the secret vault: {}", e)),
+        }
+    }
+
+    async fn create(&self, label: &str, attrs: &HashMap<&str, &str>, secret: &[u8]) -> Result<(), String> {
+        let result = match self {
+            Store::Service(keyring) => keyring.create_item(label, attrs, secret, true).await.map(|_| ()),
+            Store::Vault(vault) => vault
+                .create_item(label, attrs, secret, true)
+                .await
+                .map(|_| ())
+                .map_err(oo7::Error::File),
+        };
+        result.map_err(|e| format!("Failed to store secret: {}", e))
+    }
+
+    async fn search(&self, attrs: &HashMap<&str, &str>) -> Result<Vec<StoredItem>, String> {
+        let failed = |e: oo7::Error| format!("Failed to search keyring: {}", e);
+        let mut found = Vec::new();
+        match self {
+            Store::Service(keyring) => {
+                for item in keyring.search_items(attrs).await.map_err(failed)? {
+                    let secret = item
+                        .secret()
+                        .await
+                        .map_err(|e| format!("Failed to read secret: {}", e))?;
+                    found.push(StoredItem {
+                        label: item.label().await.map_err(failed)?,
+                        attributes: item.attributes().await.map_err(failed)?,
+                        secret: secret.to_vec(),
+                    });
+                }
+            }
+            Store::Vault(vault) => {
+                let items = vault
+                    .search_items(attrs)
+                    .await
+                    .map_err(|e| failed(oo7::Error::File(e)))?;
+                for item in items {
+                    found.push(StoredItem {
+                        label: item.label().to_string(),
+                        attributes: item
+                            .attributes()
+                            .iter()
+                            .map(|(k, v)| (k.clone(), v.to_string()))
+                            .collect(),
+                        secret: item.secret().to_vec(),
+                    });
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    async fn delete(&self, attrs: &HashMap<&str, &str>) -> Result<(), String> {
+        let result = match self {
+            Store::Service(keyring) => keyring.delete(attrs).await,
+            Store::Vault(vault) => vault.delete(attrs).await.map_err(oo7::Error::File),
+        };
+        result.map_err(|e| format!("Failed to delete credential: {}", e))
+    }
+}
+
+fn utf8(secret: Vec<u8>) -> Result<String, String> {
+    String::from_utf8(secret).map_err(|e| format!("Invalid UTF-8 in secret: {}", e))
+}
+
+/// Store CalDAV credentials in the secret store.
 pub async fn store_credentials(
     server: &str,
     username: &str,
     password: &str,
 ) -> Result<(), String> {
-    let keyring = oo7::Keyring::new()
-        .await
-        .map_err(|e| format!("Failed to connect to keyring: {}", e))?;
-
     let mut attrs = HashMap::new();
     attrs.insert("service", SERVICE_NAME);
     attrs.insert("server", server);
+    attrs.insert("username", username);
 
     let secret = format!("{}:{}", username, password);
-
-    keyring
-        .create_item(
-            &format!("Lamp CalDAV ({})", server),
-            &attrs,
-            secret.as_bytes(),
-            true, // replace existing
-        )
+    Store::open(backend())
+        .await?
+        .create(&format!("Lamp CalDAV ({})", server), &attrs, secret.as_bytes())
         .await
-        .map_err(|e| format!("Failed to store credentials: {}", e))?;
-
-    Ok(())
 }
 
-/// Load CalDAV credentials from the system keyring.
+/// Load CalDAV credentials from the environment or the secret store.
 /// Returns (username, password) if found.
 pub async fn load_credentials(server: &str) -> Result<Option<(String, String)>, String> {
-    let keyring = oo7::Keyring::new()
-        .await
-        .map_err(|e| format!("Failed to connect to keyring: {}", e))?;
+    if let Some(secret) = env_secret(server) {
+        return Ok(secret
+            .split_once(':')
+            .map(|(username, password)| (username.to_string(), password.to_string())));
+    }
 
     let mut attrs = HashMap::new();
     attrs.insert("service", SERVICE_NAME);
     attrs.insert("server", server);
 
-    let items = keyring
-        .search_items(&attrs)
-        .await
-        .map_err(|e| format!("Failed to search keyring: {}", e))?;
-
-    if let Some(item) = items.first() {
-        let secret_bytes = item
-            .secret()
-            .await
-            .map_err(|e| format!("Failed to read secret: {}", e))?;
-        let secret = String::from_utf8(secret_bytes.to_vec())
-            .map_err(|e| format!("Invalid UTF-8 in secret: {}", e))?;
+    let items = Store::open(backend()).await?.search(&attrs).await?;
+    if let Some(item) = items.into_iter().next() {
+        let secret = utf8(item.secret)?;
         if let Some((username, password)) = secret.split_once(':') {
             return Ok(Some((username.to_string(), password.to_string())));
         }
@@ -62,26 +235,155 @@ pub async fn load_credentials(server: &str) -> Result<Option<(String, String)>,
     Ok(None)
 }
 
-/// Delete CalDAV credentials from the system keyring.
+/// Load the password for a specific account on a server, so several
+/// accounts can share one server. Falls back to a credential stored before
+/// usernames were recorded, if its username matches.
+pub async fn load_account_credentials(
+    server: &str,
+    username: &str,
+) -> Result<Option<(String, String)>, String> {
+    if let Some(secret) = env_secret(server) {
+        return Ok(secret
+            .split_once(':')
+            .filter(|(user, _)| *user == username)
+            .map(|(user, password)| (user.to_string(), password.to_string())));
+    }
+
+    let mut attrs = HashMap::new();
+    attrs.insert("service", SERVICE_NAME);
+    attrs.insert("server", server);
+
+    for item in Store::open(backend()).await?.search(&attrs).await? {
+        let secret = utf8(item.secret)?;
+        if let Some((user, password)) = secret.split_once(':') {
+            if user == username {
+                return Ok(Some((user.to_string(), password.to_string())));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Delete CalDAV credentials from the secret store.
 pub async fn delete_credentials(server: &str) -> Result<(), String> {
-    let keyring = oo7::Keyring::new()
-        .await
-        .map_err(|e| format!("Failed to connect to keyring: {}", e))?;
+    let mut attrs = HashMap::new();
+    attrs.insert("service", SERVICE_NAME);
+    attrs.insert("server", server);
+
+    Store::open(backend()).await?.delete(&attrs).await
+}
 
+/// Store a secret that isn't a username and password, such as an API key.
+pub async fn store_secret(server: &str, label: &str, secret: &str) -> Result<(), String> {
     let mut attrs = HashMap::new();
     attrs.insert("service", SERVICE_NAME);
     attrs.insert("server", server);
 
-    let items = keyring
-        .search_items(&attrs)
-        .await
-        .map_err(|e| format!("Failed to search keyring: {}", e))?;
+    Store::open(backend()).await?.create(label, &attrs, secret.as_bytes()).await
+}
 
-    for item in items {
-        item.delete()
-            .await
-            .map_err(|e| format!("Failed to delete credential: {}", e))?;
+/// Load a secret stored with [`store_secret`], or its environment override.
+pub async fn load_secret(server: &str) -> Result<Option<String>, String> {
+    if let Some(secret) = env_secret(server) {
+        return Ok(Some(secret));
     }
 
-    Ok(())
+    let mut attrs = HashMap::new();
+    attrs.insert("service", SERVICE_NAME);
+    attrs.insert("server", server);
+
+    let items = Store::open(backend()).await?.search(&attrs).await?;
+    match items.into_iter().next() {
+        Some(item) => Ok(Some(utf8(item.secret)?).filter(|s| !s.is_empty())),
+        None => Ok(None),
+    }
+}
+
+/// Copy every Lamp secret from one store to another, then remove them from
+/// the first. Returns how many were moved.
+async fn move_items(from: &Store, to: &Store) -> Result<usize, String> {
+    let mut attrs = HashMap::new();
+    attrs.insert("service", SERVICE_NAME);
+
+    let items = from.search(&attrs).await?;
+    for item in &items {
+        let item_attrs: HashMap<&str, &str> = item
+            .attributes
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        to.create(&item.label, &item_attrs, &item.secret).await?;
+    }
+    // Only once everything has arrived
+    from.delete(&attrs).await?;
+    Ok(items.len())
+}
+
+/// Move all secrets between the Secret Service and the vault. The vault
+/// must be unlocked (or have its passphrase in the environment).
+pub async fn migrate(from: SecretBackend, to: SecretBackend) -> Result<usize, String> {
+    if from == to {
+        return Ok(0);
+    }
+    let source = Store::open(from).await?;
+    let target = Store::open(to).await?;
+    move_items(&source, &target).await
+}
+
+/// Check the chosen backend opens, for the settings page.
+pub async fn check() -> Result<String, String> {
+    match Store::open(backend()).await? {
+        Store::Service(_) => Ok("Using the Secret Service".to_string()),
+        Store::Vault(_) => Ok(format!(
+            "Using the vault at {}",
+            vault_path().unwrap_or_default().display()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_names_are_shell_friendly() {
+        assert_eq!(env_name("imap://mail.example.com"), "LAMP_SECRET_IMAP_MAIL_EXAMPLE_COM");
+        assert_eq!(
+            env_name("https://dav.example.com/remote.php/dav/"),
+            "LAMP_SECRET_HTTPS_DAV_EXAMPLE_COM_REMOTE_PHP_DAV"
+        );
+        assert_eq!(env_name("anthropic-api"), "LAMP_SECRET_ANTHROPIC_API");
+    }
+
+    #[test]
+    fn vault_keeps_secrets_and_moves_them() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let dir = std::env::temp_dir().join(format!("lamp-vault-{}", uuid::Uuid::new_v4()));
+            let first = Store::open_vault(&dir.join("a.keyring"), "correct horse").await.unwrap();
+            let mut attrs = HashMap::new();
+            attrs.insert("service", SERVICE_NAME);
+            attrs.insert("server", "imap://mail.example.com");
+            first.create("Lamp IMAP", &attrs, b"me:hunter2").await.unwrap();
+            first.create("Lamp IMAP", &attrs, b"me:hunter3").await.unwrap();
+
+            // Replaced, not duplicated, and readable after reopening
+            let reopened = Store::open_vault(&dir.join("a.keyring"), "correct horse").await.unwrap();
+            let items = reopened.search(&attrs).await.unwrap();
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].secret, b"me:hunter3");
+            assert_eq!(items[0].attributes.get("server").map(String::as_str), Some("imap://mail.example.com"));
+            assert!(matches!(
+                Store::open_vault(&dir.join("a.keyring"), "wrong horse").await,
+                Err(e) if e == "Wrong vault passphrase"
+            ));
+
+            let second = Store::open_vault(&dir.join("b.keyring"), "battery staple").await.unwrap();
+            assert_eq!(move_items(&reopened, &second).await, Ok(1));
+            assert!(reopened.search(&attrs).await.unwrap().is_empty());
+            assert_eq!(second.search(&attrs).await.unwrap()[0].label, "Lamp IMAP");
+            std::fs::remove_dir_all(&dir).ok();
+        });
+    }
 }
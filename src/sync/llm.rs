@@ -0,0 +1,747 @@
+//! Task suggestions from email through a configurable language model.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use super::anthropic::AnthropicProvider;
+use crate::config::{LlmBackend, LlmConfig};
+
+/// Structured data extracted from an email by the AI model.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtractedTaskData {
+    pub title: String,
+    pub priority: Option<String>,
+    pub contexts: Option<Vec<String>>,
+    pub deadline: Option<String>,
+    pub scheduled: Option<String>,
+    pub project: Option<String>,
+    pub is_duplicate: Option<bool>,
+    pub duplicate_of: Option<String>,
+}
+
+/// A suggestion produced by batch AI analysis of multiple emails at once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchEmailSuggestion {
+    pub action_needed: bool,
+    pub title: Option<String>,
+    pub priority: Option<String>,
+    pub contexts: Option<Vec<String>>,
+    pub deadline: Option<String>,
+    pub scheduled: Option<String>,
+    pub project: Option<String>,
+    pub is_duplicate: Option<bool>,
+    pub duplicate_of: Option<String>,
+    #[serde(default)]
+    pub esc: Option<u32>,
+    /// Name of the email rule that made this suggestion, if no model did.
+    #[serde(default)]
+    pub rule: Option<String>,
+}
+
+/// One prompt: instructions, the content to work on, and a cap on the reply.
+#[derive(Debug, Clone)]
+pub struct LlmRequest {
+    pub system: String,
+    pub user: String,
+    pub max_tokens: usize,
+}
+
+/// A model that answers a prompt with text.
+pub trait LlmProvider {
+    fn complete(&self, request: &LlmRequest) -> impl Future<Output = Result<String, String>> + Send;
+}
+
+/// An OpenAI-compatible chat completions API (OpenAI, llama.cpp's server, vLLM...).
+pub struct OpenAiProvider {
+    pub endpoint: String,
+    pub model: String,
+    /// Local servers usually don't want one.
+    pub api_key: Option<String>,
+}
+
+impl LlmProvider for OpenAiProvider {
+    async fn complete(&self, request: &LlmRequest) -> Result<String, String> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "max_tokens": request.max_tokens,
+            "messages": [
+                { "role": "system", "content": request.system },
+                { "role": "user", "content": request.user }
+            ]
+        });
+        let mut req = reqwest::Client::new().post(&self.endpoint);
+        if let Some(ref key) = self.api_key {
+            req = req.bearer_auth(key);
+        }
+        let resp = post_json(req, &body).await?;
+        resp["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "No text in API response".to_string())
+    }
+}
+
+/// A local Ollama server's chat API.
+pub struct OllamaProvider {
+    pub endpoint: String,
+    pub model: String,
+}
+
+impl LlmProvider for OllamaProvider {
+    async fn complete(&self, request: &LlmRequest) -> Result<String, String> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "stream": false,
+            "options": { "num_predict": request.max_tokens },
+            "messages": [
+                { "role": "system", "content": request.system },
+                { "role": "user", "content": request.user }
+            ]
+        });
+        let resp = post_json(reqwest::Client::new().post(&self.endpoint), &body).await?;
+        resp["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "No text in Ollama response".to_string())
+    }
+}
+
+/// Answers without a model. With a fixed `reply` it returns that; otherwise
+/// every email gets a "Follow up" task named after its subject, every stuck
+/// project in a weekly review a "Decide the next step" action, and a task to
+/// break down three generic steps.
+#[derive(Debug, Clone, Default)]
+pub struct MockProvider {
+    pub reply: Option<String>,
+}
+
+impl LlmProvider for MockProvider {
+    async fn complete(&self, request: &LlmRequest) -> Result<String, String> {
+        if let Some(ref reply) = self.reply {
+            return Ok(reply.clone());
+        }
+        if request.user.starts_with(super::assistant::REVIEW_HEADER) {
+            let next_actions: Vec<serde_json::Value> = request
+                .user
+                .lines()
+                .filter_map(|line| line.strip_prefix("[P")?.split_once("] "))
+                .map(|(n, name)| {
+                    serde_json::json!({
+                        "project": format!("P{}", n),
+                        "title": format!("Decide the next step for {}", name),
+                    })
+                })
+                .collect();
+            return Ok(serde_json::json!({ "next_actions": next_actions }).to_string());
+        }
+        if request.user.starts_with(super::assistant::BREAKDOWN_HEADER) {
+            let task = request
+                .user
+                .lines()
+                .find_map(|line| line.strip_prefix("Task: "))
+                .unwrap_or("Task");
+            let steps: Vec<serde_json::Value> = ["Gather what's needed for", "Start on", "Finish"]
+                .iter()
+                .map(|verb| serde_json::json!({ "title": format!("{} {}", verb, task), "esc": 10 }))
+                .collect();
+            return Ok(serde_json::json!({ "project": task, "steps": steps }).to_string());
+        }
+        // Each email's UID from its header, paired with the subject below it
+        let mut uid = None;
+        let mut emails: Vec<(Option<u32>, &str)> = Vec::new();
+        for line in request.user.lines() {
+            if let Some(header) = line.strip_prefix("--- Email UID ") {
+                uid = header.trim_end_matches(" ---").parse().ok();
+            } else if let Some(subject) = line.strip_prefix("Subject: ") {
+                emails.push((uid.take(), subject));
+            }
+        }
+        let suggestion = |uid: Option<u32>, subject: &str| {
+            serde_json::json!({
+                "uid": uid,
+                "action_needed": true,
+                "title": format!("Follow up: {}", subject.trim()),
+                "priority": null,
+                "contexts": null,
+                "deadline": null,
+                "scheduled": null,
+                "project": null,
+                "is_duplicate": false,
+                "duplicate_of": null
+            })
+        };
+        let reply = if request.user.starts_with("--- Email ") {
+            serde_json::Value::Array(emails.iter().map(|(uid, s)| suggestion(*uid, s)).collect())
+        } else {
+            let subject = emails.first().map(|(_, s)| *s).unwrap_or("email");
+            suggestion(None, subject)
+        };
+        Ok(reply.to_string())
+    }
+}
+
+/// The provider chosen in settings.
+pub enum Provider {
+    Anthropic(AnthropicProvider),
+    OpenAi(OpenAiProvider),
+    Ollama(OllamaProvider),
+    Mock(MockProvider),
+}
+
+impl Provider {
+    pub fn from_config(config: &LlmConfig, api_key: Option<String>) -> Result<Self, String> {
+        let endpoint = config.endpoint().to_string();
+        let model = config.model().to_string();
+        Ok(match config.backend {
+            LlmBackend::Anthropic => Provider::Anthropic(AnthropicProvider {
+                endpoint,
+                model,
+                api_key: api_key.ok_or_else(|| "No Anthropic API key configured".to_string())?,
+            }),
+            LlmBackend::OpenAi => Provider::OpenAi(OpenAiProvider { endpoint, model, api_key }),
+            LlmBackend::Ollama => Provider::Ollama(OllamaProvider { endpoint, model }),
+            LlmBackend::Mock => Provider::Mock(MockProvider::default()),
+        })
+    }
+
+    /// The configured provider, with its API key from the keyring.
+    pub async fn load(config: &LlmConfig) -> Result<Self, String> {
+        let api_key = if config.backend.needs_api_key() {
+            load_api_key(config.backend).await?
+        } else {
+            None
+        };
+        Self::from_config(config, api_key)
+    }
+}
+
+impl LlmProvider for Provider {
+    async fn complete(&self, request: &LlmRequest) -> Result<String, String> {
+        match self {
+            Provider::Anthropic(p) => p.complete(request).await,
+            Provider::OpenAi(p) => p.complete(request).await,
+            Provider::Ollama(p) => p.complete(request).await,
+            Provider::Mock(p) => p.complete(request).await,
+        }
+    }
+}
+
+/// POST a JSON body and return the JSON response.
+pub(super) async fn post_json(
+    req: reqwest::RequestBuilder,
+    body: &serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let resp = req
+        .header("content-type", "application/json")
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| format!("API request failed: {}", e))?;
+
+    let status = resp.status();
+    if status.as_u16() == 401 {
+        return Err("Invalid API key".to_string());
+    }
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        return Err(format!("API error {}: {}", status, text));
+    }
+    resp.json()
+        .await
+        .map_err(|e| format!("Failed to parse API response: {}", e))
+}
+
+/// Parse a JSON reply, which models like to wrap in markdown code fences.
+pub(super) fn parse_json_reply<T: DeserializeOwned>(text: &str) -> Result<T, serde_json::Error> {
+    let json_str = text
+        .trim()
+        .strip_prefix("```json")
+        .or_else(|| text.trim().strip_prefix("```"))
+        .unwrap_or(text.trim());
+    let json_str = json_str.strip_suffix("```").unwrap_or(json_str).trim();
+    serde_json::from_str(json_str)
+}
+
+/// Ask the model for a task from one email.
+pub async fn extract_task_from_email(
+    provider: &impl LlmProvider,
+    subject: &str,
+    from: &str,
+    date: Option<&str>,
+    body_full: &str,
+    user_hint: Option<&str>,
+    available_contexts: &[String],
+    project_names: &[String],
+    existing_task_titles: &[String],
+    today: &str,
+) -> Result<ExtractedTaskData, String> {
+    let system_prompt = build_system_prompt(
+        available_contexts,
+        project_names,
+        existing_task_titles,
+        today,
+    );
+
+    let mut user_msg = format!(
+        "Subject: {}\nFrom: {}\nDate: {}\n\n{}",
+        subject,
+        from,
+        date.unwrap_or("unknown"),
+        // Cap body to ~4000 chars to stay within token budget
+        &body_full.chars().take(4000).collect::<String>(),
+    );
+    if let Some(hint) = user_hint {
+        if !hint.is_empty() {
+            user_msg.push_str(&format!("\n\nUser context: {}", hint));
+        }
+    }
+
+    let request = LlmRequest {
+        system: system_prompt,
+        user: user_msg,
+        max_tokens: 400,
+    };
+    let text = provider.complete(&request).await?;
+
+    parse_json_reply::<ExtractedTaskData>(&text)
+        .map_err(|e| format!("Failed to parse extracted data: {} — raw: {}", e, text))
+}
+
+fn build_system_prompt(
+    available_contexts: &[String],
+    project_names: &[String],
+    existing_task_titles: &[String],
+    today: &str,
+) -> String {
+    let mut prompt = String::from(
+        "You extract actionable tasks from emails. Return ONLY a JSON object, no explanation.\n\n\
+         Rules:\n\
+         - \"title\": concise imperative action (e.g. \"Reply to vendor quote\", \"Schedule dentist appointment\")\n\
+         - \"priority\": \"A\" (urgent+important), \"B\" (important), \"C\" (low), or null\n\
+         - \"contexts\": array of applicable contexts from the allowed list, or null\n\
+         - \"deadline\": \"YYYY-MM-DD\" if a hard deadline is mentioned, else null\n\
+         - \"scheduled\": \"YYYY-MM-DD\" if a specific do-date is mentioned, else null\n\
+         - \"project\": name of an existing project this relates to, or null\n\
+         - \"is_duplicate\": true if an existing task already covers this email's action\n\
+         - \"duplicate_of\": title of the existing task it duplicates, or null\n\n",
+    );
+
+    prompt.push_str(&format!("Today's date: {}\n\n", today));
+
+    if !available_contexts.is_empty() {
+        prompt.push_str("Available contexts (pick from these only): ");
+        prompt.push_str(&available_contexts.join(", "));
+        prompt.push_str("\n\n");
+    }
+
+    if !project_names.is_empty() {
+        prompt.push_str("Existing projects: ");
+        prompt.push_str(&project_names.join(", "));
+        prompt.push_str("\n\n");
+    }
+
+    if !existing_task_titles.is_empty() {
+        // Limit to most recent 100 titles to avoid huge prompts
+        let titles: Vec<&str> = existing_task_titles
+            .iter()
+            .take(100)
+            .map(|s| s.as_str())
+            .collect();
+        prompt.push_str("Existing task titles (for duplicate detection):\n");
+        for t in titles {
+            prompt.push_str(&format!("- {}\n", t));
+        }
+        prompt.push('\n');
+    }
+
+    prompt
+}
+
+/// Ask the model for tasks from all emails in one request.
+///
+/// Every suggestion must name the UID of its email and pass
+/// `check_suggestion`. Emails whose suggestion is missing or invalid are
+/// asked about once more on their own, with the reason it was rejected;
+/// those that still fail are left out rather than failing the batch.
+pub async fn extract_tasks_from_emails_batch(
+    provider: &impl LlmProvider,
+    emails: Vec<(u32, String, String, Option<String>, String)>, // (uid, subject, from, date, body)
+    available_contexts: &[String],
+    project_names: &[String],
+    existing_task_titles: &[String],
+    today: &str,
+) -> Result<Vec<(u32, BatchEmailSuggestion)>, String> {
+    let system_prompt = build_batch_system_prompt(
+        available_contexts,
+        project_names,
+        existing_task_titles,
+        today,
+    );
+    let uids: Vec<u32> = emails.iter().map(|(uid, ..)| *uid).collect();
+
+    let request = LlmRequest {
+        system: system_prompt.clone(),
+        user: batch_user_message(&emails),
+        max_tokens: std::cmp::min(300 * emails.len(), 4096),
+    };
+    let text = provider.complete(&request).await?;
+    let (mut results, failures) =
+        parse_keyed_suggestions(&text, &uids, available_contexts, project_names);
+
+    for (uid, reason) in failures {
+        let Some(email) = emails.iter().find(|e| e.0 == uid) else {
+            continue;
+        };
+        let mut user = batch_user_message(std::slice::from_ref(email));
+        user.push_str(&format!(
+            "Your previous answer for this email was rejected: {}. Answer again.\n",
+            reason
+        ));
+        let retry = LlmRequest {
+            system: system_prompt.clone(),
+            user,
+            max_tokens: 300,
+        };
+        let outcome = match provider.complete(&retry).await {
+            Ok(text) => {
+                let (mut ok, mut failed) =
+                    parse_keyed_suggestions(&text, &[uid], available_contexts, project_names);
+                match ok.pop() {
+                    Some(result) => Ok(result),
+                    None => Err(failed.pop().map(|(_, reason)| reason).unwrap_or_default()),
+                }
+            }
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(result) => results.push(result),
+            Err(e) => log::warn!("No usable suggestion for email {}: {}", uid, e),
+        }
+    }
+
+    results.sort_by_key(|(uid, _)| uids.iter().position(|u| u == uid));
+    Ok(results)
+}
+
+fn batch_user_message(emails: &[(u32, String, String, Option<String>, String)]) -> String {
+    let mut user_msg = String::new();
+    for (uid, subject, from, date, body) in emails {
+        let capped_body: String = body.chars().take(2000).collect();
+        user_msg.push_str(&format!(
+            "--- Email UID {} ---\nSubject: {}\nFrom: {}\nDate: {}\n\n{}\n\n",
+            uid,
+            subject,
+            from,
+            date.as_deref().unwrap_or("unknown"),
+            capped_body,
+        ));
+    }
+    user_msg
+}
+
+/// Emails to ask about again, with the reason their answer was rejected.
+type Rejections = Vec<(u32, String)>;
+
+/// Split a batch reply into valid suggestions for the requested `uids` and
+/// the UIDs that need asking again, with why. Items for other UIDs and
+/// repeats are ignored.
+fn parse_keyed_suggestions(
+    text: &str,
+    uids: &[u32],
+    available_contexts: &[String],
+    project_names: &[String],
+) -> (Vec<(u32, BatchEmailSuggestion)>, Rejections) {
+    let items: Vec<serde_json::Value> = match parse_json_reply(text) {
+        Ok(items) => items,
+        Err(e) => {
+            let reason = format!("reply was not a JSON array ({})", e);
+            return (Vec::new(), uids.iter().map(|uid| (*uid, reason.clone())).collect());
+        }
+    };
+
+    let mut answers: HashMap<u32, Result<BatchEmailSuggestion, String>> = HashMap::new();
+    for item in items {
+        let uid = match &item["uid"] {
+            serde_json::Value::Number(n) => n.as_u64(),
+            serde_json::Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        };
+        let Some(uid) = uid.and_then(|uid| u32::try_from(uid).ok()) else {
+            continue;
+        };
+        if !uids.contains(&uid) || answers.contains_key(&uid) {
+            continue;
+        }
+        let answer = serde_json::from_value::<BatchEmailSuggestion>(item)
+            .map_err(|e| format!("malformed object ({})", e))
+            .and_then(|s| check_suggestion(s, available_contexts, project_names));
+        answers.insert(uid, answer);
+    }
+
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+    for &uid in uids {
+        match answers.remove(&uid) {
+            Some(Ok(suggestion)) => results.push((uid, suggestion)),
+            Some(Err(reason)) => failures.push((uid, reason)),
+            None => failures.push((uid, "no object with this uid".to_string())),
+        }
+    }
+    (results, failures)
+}
+
+/// Validate a suggestion against what Lamp knows: an action needs a title,
+/// priorities are A-C, dates are real `YYYY-MM-DD` dates, and contexts and
+/// projects exist. Contexts and projects are matched ignoring case (and a
+/// missing `@`) and rewritten to their configured spelling.
+pub fn check_suggestion(
+    mut suggestion: BatchEmailSuggestion,
+    available_contexts: &[String],
+    project_names: &[String],
+) -> Result<BatchEmailSuggestion, String> {
+    if suggestion.action_needed
+        && suggestion.title.as_deref().is_none_or(|t| t.trim().is_empty())
+    {
+        return Err("action_needed without a title".to_string());
+    }
+    if let Some(ref p) = suggestion.priority {
+        if !matches!(p.trim(), "A" | "B" | "C") {
+            return Err(format!("priority \"{}\" is not A, B or C", p));
+        }
+    }
+    for date in [&suggestion.deadline, &suggestion.scheduled].into_iter().flatten() {
+        if chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").is_err() {
+            return Err(format!("\"{}\" is not a YYYY-MM-DD date", date));
+        }
+    }
+    if let Some(ref mut contexts) = suggestion.contexts {
+        for ctx in contexts.iter_mut() {
+            let wanted = ctx.trim().trim_start_matches('@');
+            let known = available_contexts
+                .iter()
+                .find(|c| c.trim_start_matches('@').eq_ignore_ascii_case(wanted))
+                .ok_or_else(|| format!("unknown context \"{}\"", ctx))?;
+            *ctx = known.clone();
+        }
+    }
+    if let Some(ref mut project) = suggestion.project {
+        let known = project_names
+            .iter()
+            .find(|p| p.eq_ignore_ascii_case(project.trim()))
+            .ok_or_else(|| format!("unknown project \"{}\"", project))?;
+        *project = known.clone();
+    }
+    Ok(suggestion)
+}
+
+/// Suggestions already paid for, by email Message-ID.
+pub type SuggestionCache = HashMap<String, BatchEmailSuggestion>;
+
+pub fn load_suggestion_cache(path: &Path) -> SuggestionCache {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => SuggestionCache::new(),
+    }
+}
+
+pub fn save_suggestion_cache(path: &Path, cache: &SuggestionCache) {
+    match serde_json::to_string_pretty(cache) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(path, json) {
+                log::error!("Failed to save suggestion cache: {}", e);
+            }
+        }
+        Err(e) => log::error!("Failed to serialize suggestion cache: {}", e),
+    }
+}
+
+fn build_batch_system_prompt(
+    available_contexts: &[String],
+    project_names: &[String],
+    existing_task_titles: &[String],
+    today: &str,
+) -> String {
+    let mut prompt = String::from(
+        "You analyze emails and suggest tasks. Return ONLY a JSON array with one object per email. No explanation.\n\n\
+         Each object must have:\n\
+         - \"uid\": the number from the email's \"--- Email UID n ---\" header\n\
+         - \"action_needed\": boolean — true if the email requires the user to do something\n\
+         - \"title\": concise imperative action (e.g. \"Reply to vendor quote\"), or null if no action needed\n\
+         - \"priority\": \"A\" (urgent+important), \"B\" (important), \"C\" (low), or null\n\
+         - \"contexts\": array of applicable contexts from the allowed list, or null\n\
+         - \"deadline\": \"YYYY-MM-DD\" if a hard deadline is mentioned, else null\n\
+         - \"scheduled\": \"YYYY-MM-DD\" if a specific do-date is mentioned, else null\n\
+         - \"project\": name of an existing project this relates to, or null\n\
+         - \"is_duplicate\": true if an existing task already covers this email's action\n\
+         - \"duplicate_of\": title of the existing task it duplicates, or null\n\n",
+    );
+
+    prompt.push_str(&format!("Today's date: {}\n\n", today));
+
+    if !available_contexts.is_empty() {
+        prompt.push_str("Available contexts (pick from these only): ");
+        prompt.push_str(&available_contexts.join(", "));
+        prompt.push_str("\n\n");
+    }
+
+    if !project_names.is_empty() {
+        prompt.push_str("Existing projects: ");
+        prompt.push_str(&project_names.join(", "));
+        prompt.push_str("\n\n");
+    }
+
+    if !existing_task_titles.is_empty() {
+        let titles: Vec<&str> = existing_task_titles
+            .iter()
+            .take(100)
+            .map(|s| s.as_str())
+            .collect();
+        prompt.push_str("Existing task titles (for duplicate detection):\n");
+        for t in titles {
+            prompt.push_str(&format!("- {}\n", t));
+        }
+        prompt.push('\n');
+    }
+
+    prompt
+}
+
+/// Check the provider answers with a minimal request.
+pub async fn test_provider(provider: &impl LlmProvider) -> Result<String, String> {
+    let request = LlmRequest {
+        system: String::new(),
+        user: "Reply with OK".to_string(),
+        max_tokens: 4,
+    };
+    provider.complete(&request).await?;
+    Ok("Model responded".to_string())
+}
+
+/// Keyring server attribute and label for a backend's API key.
+fn keyring_entry(backend: LlmBackend) -> Option<(&'static str, &'static str)> {
+    match backend {
+        LlmBackend::Anthropic => Some(("anthropic-api", "Lamp Anthropic API Key")),
+        LlmBackend::OpenAi => Some(("openai-api", "Lamp OpenAI API Key")),
+        LlmBackend::Ollama | LlmBackend::Mock => None,
+    }
+}
+
+/// Store a backend's API key in the secret store.
+pub async fn store_api_key(backend: LlmBackend, key: &str) -> Result<(), String> {
+    match keyring_entry(backend) {
+        Some((server, label)) => super::keyring::store_secret(server, label, key).await,
+        None => Ok(()),
+    }
+}
+
+/// Load a backend's API key from the secret store.
+pub async fn load_api_key(backend: LlmBackend) -> Result<Option<String>, String> {
+    match keyring_entry(backend) {
+        Some((server, _)) => super::keyring::load_secret(server).await,
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn email(uid: u32, subject: &str) -> (u32, String, String, Option<String>, String) {
+        (uid, subject.to_string(), "sam@example.com".to_string(), None, "Hi".to_string())
+    }
+
+    #[test]
+    fn mock_provider_drives_the_extraction_pipeline() {
+        let mock = MockProvider::default();
+        let emails = vec![email(7, "Quote for the roof"), email(9, "Lunch on Friday?")];
+        let suggestions =
+            block_on(extract_tasks_from_emails_batch(&mock, emails, &[], &[], &[], "2026-03-02")).unwrap();
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].0, 7);
+        assert_eq!(suggestions[1].1.title.as_deref(), Some("Follow up: Lunch on Friday?"));
+
+        let fenced = MockProvider {
+            reply: Some("```json\n{\"title\": \"Pay invoice\", \"priority\": \"A\"}\n```".to_string()),
+        };
+        let task = block_on(extract_task_from_email(
+            &fenced, "Invoice", "billing@example.com", None, "Due Monday", None, &[], &[], &[], "2026-03-02",
+        ))
+        .unwrap();
+        assert_eq!(task.title, "Pay invoice");
+        assert_eq!(task.priority.as_deref(), Some("A"));
+
+        // An unusable reply costs the email its suggestion, not the whole batch an error
+        let broken = MockProvider { reply: Some("Sorry, I can't help".to_string()) };
+        let result = block_on(extract_tasks_from_emails_batch(&broken, vec![email(1, "x")], &[], &[], &[], ""));
+        assert_eq!(result, Ok(Vec::new()));
+    }
+
+    /// Answers with each scripted reply in turn, recording the prompts.
+    struct Scripted {
+        replies: std::sync::Mutex<Vec<&'static str>>,
+        prompts: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl LlmProvider for Scripted {
+        async fn complete(&self, request: &LlmRequest) -> Result<String, String> {
+            self.prompts.lock().unwrap().push(request.user.clone());
+            let mut replies = self.replies.lock().unwrap();
+            if replies.is_empty() {
+                return Err("out of replies".to_string());
+            }
+            Ok(replies.remove(0).to_string())
+        }
+    }
+
+    #[test]
+    fn batch_suggestions_are_keyed_validated_and_retried() {
+        let contexts = vec!["@home".to_string(), "@errands".to_string()];
+        let projects = vec!["Kitchen remodel".to_string()];
+        let provider = Scripted {
+            replies: std::sync::Mutex::new(vec![
+                // Out of order, one bad date, one missing, one for an email we didn't send
+                r#"[{"uid": 9, "action_needed": true, "title": "Buy milk", "contexts": ["Errands"], "project": "kitchen remodel"},
+                    {"uid": 7, "action_needed": true, "title": "Approve quote", "deadline": "next week"},
+                    {"uid": 99, "action_needed": false}]"#,
+                r#"[{"uid": 7, "action_needed": true, "title": "Approve quote", "deadline": "2026-03-06"}]"#,
+                r#"[{"uid": 11, "action_needed": true, "title": "Book venue", "project": "Wedding"}]"#,
+            ]),
+            prompts: std::sync::Mutex::new(Vec::new()),
+        };
+        let emails = vec![email(7, "Roof quote"), email(9, "Groceries"), email(11, "Party")];
+
+        let results = block_on(extract_tasks_from_emails_batch(
+            &provider, emails, &contexts, &projects, &[], "2026-03-02",
+        ))
+        .unwrap();
+        let uids: Vec<u32> = results.iter().map(|(uid, _)| *uid).collect();
+        assert_eq!(uids, vec![7, 9]);
+        assert_eq!(results[0].1.deadline.as_deref(), Some("2026-03-06"));
+        assert_eq!(results[1].1.contexts, Some(vec!["@errands".to_string()]));
+        assert_eq!(results[1].1.project.as_deref(), Some("Kitchen remodel"));
+
+        // One retry each for the rejected and the missing email, saying why
+        let prompts = provider.prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 3);
+        assert!(prompts[1].starts_with("--- Email UID 7 ---"));
+        assert!(prompts[1].contains("\"next week\" is not a YYYY-MM-DD date"));
+        assert!(prompts[2].contains("no object with this uid"));
+    }
+
+    #[test]
+    fn config_falls_back_to_backend_defaults() {
+        let mut config = LlmConfig { backend: LlmBackend::Ollama, ..LlmConfig::default() };
+        assert_eq!(config.endpoint(), "http://localhost:11434/api/chat");
+        config.model = "qwen2.5:7b".to_string();
+        assert_eq!(config.model(), "qwen2.5:7b");
+
+        config.backend = LlmBackend::Anthropic;
+        assert!(Provider::from_config(&config, None).is_err());
+        assert!(matches!(Provider::from_config(&config, Some("k".into())), Ok(Provider::Anthropic(_))));
+    }
+}
@@ -70,6 +70,17 @@ pub fn merge_tasks(local: &Task, remote: &Task, base: &Task) -> Task {
     // Waiting for
     merge_option_string(&mut merged.waiting_for, &local.waiting_for, &remote.waiting_for, &base.waiting_for);
 
+    // Waiting for contact
+    if local.waiting_for_contact != base.waiting_for_contact
+        && remote.waiting_for_contact != base.waiting_for_contact
+    {
+        if local.waiting_for_contact != remote.waiting_for_contact {
+            merged.waiting_for_contact = remote.waiting_for_contact;
+        }
+    } else if remote.waiting_for_contact != base.waiting_for_contact {
+        merged.waiting_for_contact = remote.waiting_for_contact;
+    }
+
     // ESC
     if local.esc != base.esc && remote.esc != base.esc {
         if local.esc != remote.esc {
@@ -106,6 +117,23 @@ pub fn merge_tasks(local: &Task, remote: &Task, base: &Task) -> Task {
         merged.recurrence = remote.recurrence.clone();
     }
 
+    // Alarms
+    if local.alarms != base.alarms && remote.alarms != base.alarms {
+        if local.alarms != remote.alarms {
+            merged.alarms = remote.alarms.clone();
+        }
+    } else if remote.alarms != base.alarms {
+        merged.alarms = remote.alarms.clone();
+    }
+
+    // Source email
+    if remote.email != base.email {
+        merged.email = remote.email.clone();
+    }
+    if remote.delegation_id != base.delegation_id {
+        merged.delegation_id = remote.delegation_id.clone();
+    }
+
     merged
 }
 
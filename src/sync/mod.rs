@@ -1,20 +1,31 @@
 pub mod anthropic;
+pub mod assistant;
 pub mod caldav;
 pub mod carddav;
+pub mod freebusy;
 pub mod ical;
+pub mod ics;
+pub mod itip;
 pub mod imap;
 pub mod keyring;
+pub mod llm;
 pub mod merge;
+pub mod outbox;
+pub mod projects;
+pub mod smtp;
+pub mod subscription;
 pub mod vevent;
 pub mod vtodo;
 pub mod webdav;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use crate::config::ProjectLink;
 use crate::core::event::CalendarEvent;
 use crate::core::task::Task;
 use caldav::{CalDavClient, PutCondition, SyncChange};
+use outbox::{OutboxEntry, OutboxOp};
 use vevent::{event_content_hash, event_to_vcalendar, vcalendar_to_events};
 use vtodo::{task_content_hash, task_to_vcalendar, vcalendar_to_task};
 
@@ -29,7 +40,7 @@ pub enum SyncConflict {
         remote_state: String,
     },
     RemoteOnly {
-        task: Task,
+        task: Box<Task>,
         href: String,
     },
     LocalOnly {
@@ -78,12 +89,26 @@ pub struct SyncResult {
     pub errors: Vec<String>,
     /// Conflicts detected during sync that need user resolution.
     pub conflicts: Vec<SyncConflict>,
+    /// Outbox entries that reached the server and can be dropped.
+    pub outbox_sent: Vec<Uuid>,
+    /// Outbox entries that failed again: (entry id, error).
+    pub outbox_failed: Vec<(Uuid, String)>,
+    /// Writes that failed during this sync, to be queued in the outbox: (write, error).
+    pub queued: Vec<(OutboxOp, String)>,
+    /// Project lists and parent tasks created or renamed during this sync.
+    pub project_links: Vec<ProjectLink>,
+    /// Scheduling messages in the CalDAV inbox: (href, iCalendar body).
+    pub inbox: Vec<(String, String)>,
 }
 
 /// Performs bidirectional sync between local tasks/events and a CalDAV server.
 pub struct SyncEngine {
     client: CalDavClient,
     calendar_href: String,
+    /// Hrefs with a write still waiting in the outbox; left alone when pushing.
+    held_hrefs: HashSet<String>,
+    /// Unsynced tasks routed to this calendar; `None` creates every unsynced task here.
+    routed_tasks: Option<HashSet<Uuid>>,
 }
 
 impl SyncEngine {
@@ -91,6 +116,28 @@ impl SyncEngine {
         Self {
             client,
             calendar_href,
+            held_hrefs: HashSet::new(),
+            routed_tasks: None,
+        }
+    }
+
+    pub fn with_held_hrefs(mut self, held_hrefs: HashSet<String>) -> Self {
+        self.held_hrefs = held_hrefs;
+        self
+    }
+
+    pub fn with_routed_tasks(mut self, routed_tasks: HashSet<Uuid>) -> Self {
+        self.routed_tasks = Some(routed_tasks);
+        self
+    }
+
+    /// Whether this engine should push a task: synced tasks belong to the
+    /// calendar holding their href, unsynced ones to the calendar they're routed to.
+    fn owns_task(&self, task: &Task) -> bool {
+        match (&task.sync_href, &self.routed_tasks) {
+            (_, None) => true,
+            (Some(href), Some(_)) => href.starts_with(&self.calendar_href),
+            (None, Some(routed)) => routed.contains(&task.id),
         }
     }
 
@@ -273,13 +320,16 @@ impl SyncEngine {
 
         // Push local tasks that aren't on remote
         for task in tasks {
-            if task.state.is_done() {
+            if task.state.is_done() || !self.owns_task(task) {
                 continue;
             }
             if !matched_local.contains(&task.id) {
                 let href = task.sync_href.clone().unwrap_or_else(|| {
                     caldav::vtodo_href(&self.calendar_href, &task.id)
                 });
+                if self.held_hrefs.contains(&href) {
+                    continue;
+                }
                 let ical = task_to_vcalendar(task);
                 log::info!("Pushing to remote: {} -> {}", task.title, href);
                 match self.client.put_vtodo(&href, PutCondition::Unconditional, &ical).await {
@@ -294,6 +344,7 @@ impl SyncEngine {
                         result
                             .errors
                             .push(format!("Failed to push {}: {}", task.title, e));
+                        result.queued.push((queued_put(task, href, ical), e));
                     }
                 }
             }
@@ -351,26 +402,41 @@ impl SyncEngine {
                     std::collections::HashSet::new();
 
                 for remote in &remote_vevents {
-                    let remote_instances = vcalendar_to_events(&remote.ical_body);
-                    if remote_instances.is_empty() {
-                        continue;
-                    }
-
-                    for mut remote_event in remote_instances {
+                    // One series per resource; occurrences are expanded for display only
+                    for mut remote_event in vcalendar_to_events(&remote.ical_body) {
                         remote_event.calendar_href = cal_href.clone();
-                        if remote_event.sync_href.is_none() {
-                            // This is an expanded recurrence instance — no sync_href
-                        } else {
-                            remote_event.sync_href = Some(remote.href.clone());
-                        }
-                        remote_event.sync_hash = Some(event_content_hash(&remote_event));
+                        remote_event.sync_href = Some(remote.href.clone());
+                        let remote_hash = event_content_hash(&remote_event);
+                        remote_event.sync_hash = Some(remote_hash);
 
                         matched_local.insert(remote_event.id);
 
                         if let Some(&local) = local_by_id.get(&remote_event.id) {
                             let local_hash = event_content_hash(local);
-                            let remote_hash = event_content_hash(&remote_event);
-                            if local.sync_hash.is_none() || local_hash != remote_hash {
+                            let local_changed = local.sync_hash.is_some_and(|h| h != local_hash);
+                            let remote_changed = local.sync_hash != Some(remote_hash);
+                            if local_changed && !remote_changed {
+                                // Edited here since the last sync: the server copy is stale
+                                let ical = event_to_vcalendar(local);
+                                match self.client.put_vtodo(&remote.href, PutCondition::Unconditional, &ical).await {
+                                    Ok(_) => {
+                                        let mut updated = (*local).clone();
+                                        updated.sync_href = Some(remote.href.clone());
+                                        updated.sync_hash = Some(local_hash);
+                                        result.pulled_events.push(updated);
+                                        result.pushed_events += 1;
+                                    }
+                                    Err(e) => {
+                                        result.errors.push(format!(
+                                            "Failed to push event {}: {}",
+                                            local.title, e
+                                        ));
+                                    }
+                                }
+                            } else if local.sync_hash.is_none()
+                                || local.sync_href.is_none()
+                                || local_hash != remote_hash
+                            {
                                 remote_event.calendar_name = local.calendar_name.clone();
                                 result.pulled_events.push(remote_event);
                             }
@@ -385,6 +451,11 @@ impl SyncEngine {
                     if matched_local.contains(&local_event.id) {
                         continue;
                     }
+                    // Occurrences cached one by one before series were stored whole
+                    if local_event.sync_href.is_none() && local_event.sync_hash.is_some() {
+                        result.deleted_events.push(local_event.id);
+                        continue;
+                    }
                     let local_hash = event_content_hash(local_event);
                     let changed = local_event.sync_hash.is_some_and(|h| h != local_hash);
 
@@ -438,13 +509,17 @@ impl SyncEngine {
         result: &mut SyncResult,
     ) {
         for task in tasks {
+            if !self.owns_task(task) {
+                continue;
+            }
             if task.state.is_done() {
                 if let Some(ref href) = task.sync_href {
-                    if !seen_hrefs.contains(href) {
+                    if !seen_hrefs.contains(href) && !self.held_hrefs.contains(href) {
                         if let Err(e) = self.client.delete_vtodo(href, "").await {
                             result
                                 .errors
                                 .push(format!("Failed to delete done task: {}", e));
+                            result.queued.push((OutboxOp::Delete { href: href.clone() }, e));
                         }
                     }
                 }
@@ -452,7 +527,7 @@ impl SyncEngine {
             }
 
             if let Some(ref href) = task.sync_href {
-                if !seen_hrefs.contains(href) {
+                if !seen_hrefs.contains(href) && !self.held_hrefs.contains(href) {
                     let local_hash = task_content_hash(task);
                     let changed = task
                         .sync_hash
@@ -472,12 +547,22 @@ impl SyncEngine {
                                 result
                                     .errors
                                     .push(format!("Failed to push {}: {}", task.title, e));
+                                let op = OutboxOp::Update {
+                                    task_id: task.id,
+                                    href: href.clone(),
+                                    ical,
+                                    hash: local_hash,
+                                };
+                                result.queued.push((op, e));
                             }
                         }
                     }
                 }
             } else {
                 let href = caldav::vtodo_href(&self.calendar_href, &task.id);
+                if self.held_hrefs.contains(&href) {
+                    continue;
+                }
                 let ical = task_to_vcalendar(task);
                 log::info!("Creating remote: {} -> {}", task.title, href);
                 match self.client.put_vtodo(&href, PutCondition::CreateOnly, &ical).await {
@@ -502,6 +587,7 @@ impl SyncEngine {
                                 result
                                     .errors
                                     .push(format!("Failed to update {}: {}", task.title, e));
+                                result.queued.push((queued_put(task, href, ical), e));
                             }
                         }
                     }
@@ -509,6 +595,7 @@ impl SyncEngine {
                         result
                             .errors
                             .push(format!("Failed to create {}: {}", task.title, e));
+                        result.queued.push((queued_put(task, href, ical), e));
                     }
                 }
             }
@@ -516,9 +603,11 @@ impl SyncEngine {
     }
 }
 
-/// Flat sync orchestrator — single CalDAV account.
+/// Sync orchestrator for one CalDAV account.
 /// Creates one CalDavClient, syncs tasks from Task calendars
 /// and events from Event calendars, merging results.
+/// `task_targets` maps each task to the task calendar it belongs in, so
+/// unsynced tasks are created in exactly one calendar.
 pub async fn sync_all(
     caldav_url: &str,
     username: &str,
@@ -528,47 +617,66 @@ pub async fn sync_all(
     task_cals: &[String],
     event_cals: &[String],
     sync_tokens: &[(String, String)],
-    pending_completions: &[(String, String)],
-    pending_deletions: &[String],
+    task_targets: &HashMap<Uuid, String>,
+    outbox: &[OutboxEntry],
 ) -> Result<SyncResult, String> {
     let mut merged_result = new_sync_result();
 
     let client = CalDavClient::new(caldav_url, username, password)?;
 
-    // Push pending completions (tasks completed locally since last sync)
-    for (href, ical) in pending_completions {
-        log::info!("Pushing pending completion: {}", href);
-        match client
-            .put_vtodo(href, PutCondition::Unconditional, ical)
-            .await
-        {
-            Ok(_) => {
-                log::info!("Completion pushed successfully: {}", href);
-                merged_result.pushed += 1;
+    // Replay the outbox: writes queued offline or that failed on an earlier sync
+    let now = chrono::Utc::now();
+    let mut tasks: Vec<Task> = tasks.to_vec();
+    for entry in outbox.iter().filter(|e| e.is_due(now)) {
+        let href = entry.op.href();
+        log::info!("Replaying outbox {}: {}", entry.op.label(), href);
+        let outcome = match &entry.op {
+            OutboxOp::Create { ical, .. } => {
+                match client.put_vtodo(href, PutCondition::CreateOnly, ical).await {
+                    Err(ref e) if e.contains("412") || e.contains("403") => client
+                        .put_vtodo(href, PutCondition::Unconditional, ical)
+                        .await
+                        .map(|_| ()),
+                    r => r.map(|_| ()),
+                }
             }
-            Err(e) => {
-                log::error!("Failed to push completion {}: {}", href, e);
-                merged_result.errors.push(format!(
-                    "Failed to push completion {}: {}",
-                    href, e
-                ));
+            OutboxOp::Update { ical, .. } | OutboxOp::Complete { ical, .. } => client
+                .put_vtodo(href, PutCondition::Unconditional, ical)
+                .await
+                .map(|_| ()),
+            OutboxOp::Delete { .. } => client.delete_vtodo(href, "").await,
+        };
+        match outcome {
+            Ok(()) => {
+                merged_result.pushed += 1;
+                merged_result.outbox_sent.push(entry.id);
+                if let OutboxOp::Create { task_id, hash, .. }
+                | OutboxOp::Update { task_id, hash, .. } = &entry.op
+                {
+                    if let Some(task) = tasks.iter_mut().find(|t| t.id == *task_id) {
+                        task.sync_href = Some(href.to_string());
+                        task.sync_hash = Some(*hash);
+                        merged_result.pulled.push(task.clone());
+                    }
+                }
             }
-        }
-    }
-
-    // Process pending deletions (remote-only conflicts the user chose to delete)
-    for href in pending_deletions {
-        log::info!("Deleting remote VTODO: {}", href);
-        match client.delete_vtodo(href, "").await {
-            Ok(_) => log::info!("Deleted successfully: {}", href),
             Err(e) => {
+                log::error!("Outbox {} {} failed: {}", entry.op.label(), href, e);
                 merged_result
                     .errors
-                    .push(format!("Failed to delete {}: {}", href, e));
+                    .push(format!("Failed to replay {}: {}", href, e));
+                merged_result.outbox_failed.push((entry.id, e));
             }
         }
     }
 
+    // Anything still queued is left to the outbox rather than pushed again below
+    let held_hrefs: HashSet<String> = outbox
+        .iter()
+        .filter(|e| !merged_result.outbox_sent.contains(&e.id))
+        .map(|e| e.op.href().to_string())
+        .collect();
+
     // Sync tasks from each task calendar
     for cal_href in task_cals {
         let token = sync_tokens
@@ -576,8 +684,15 @@ pub async fn sync_all(
             .find(|(h, _)| h == cal_href)
             .map(|(_, t)| t.as_str());
 
-        let engine = SyncEngine::new(client.clone(), cal_href.clone());
-        match engine.sync_tasks(tasks, token).await {
+        let routed: HashSet<Uuid> = task_targets
+            .iter()
+            .filter(|(_, target)| *target == cal_href)
+            .map(|(id, _)| *id)
+            .collect();
+        let engine = SyncEngine::new(client.clone(), cal_href.clone())
+            .with_held_hrefs(held_hrefs.clone())
+            .with_routed_tasks(routed);
+        match engine.sync_tasks(&tasks, token).await {
             Ok(res) => {
                 merged_result.pulled.extend(res.pulled);
                 merged_result.pushed += res.pushed;
@@ -585,6 +700,7 @@ pub async fn sync_all(
                 merged_result.merged += res.merged;
                 merged_result.new_sync_tokens.extend(res.new_sync_tokens);
                 merged_result.errors.extend(res.errors);
+                merged_result.queued.extend(res.queued);
             }
             Err(e) => {
                 merged_result
@@ -658,7 +774,7 @@ pub async fn sync_all(
                 let mut task = remote_task;
                 task.sync_href = Some(remote_vtodo.href.clone());
                 merged_result.conflicts.push(SyncConflict::RemoteOnly {
-                    task,
+                    task: Box::new(task),
                     href: remote_vtodo.href.clone(),
                 });
             }
@@ -716,6 +832,20 @@ pub async fn sync_all(
         }
     }
 
+    // Meeting requests waiting in the scheduling inbox, for accounts with event calendars
+    if !event_cals.is_empty() {
+        match client.schedule_inbox().await {
+            Ok(Some(inbox)) => match client.list_vevents(&inbox).await {
+                Ok(items) => {
+                    merged_result.inbox = items.into_iter().map(|i| (i.href, i.ical_body)).collect();
+                }
+                Err(e) => log::debug!("Failed to list scheduling inbox {}: {}", inbox, e),
+            },
+            Ok(None) => {}
+            Err(e) => log::debug!("No scheduling inbox: {}", e),
+        }
+    }
+
     log::info!(
         "Sync complete: {} pulled tasks, {} pushed, {} pulled events, {} errors",
         merged_result.pulled.len(),
@@ -727,6 +857,40 @@ pub async fn sync_all(
     Ok(merged_result)
 }
 
+/// Cheap check for remote task/event changes: runs sync-collection against each
+/// calendar's stored token without touching local state. Calendars without a
+/// token, or whose token has expired, count as changed.
+pub async fn poll_changes(
+    caldav_url: &str,
+    username: &str,
+    password: &str,
+    calendars: &[String],
+    sync_tokens: &[(String, String)],
+) -> Result<bool, String> {
+    let client = CalDavClient::new(caldav_url, username, password)?;
+
+    for cal_href in calendars {
+        let Some(token) = sync_tokens
+            .iter()
+            .find(|(h, _)| h == cal_href)
+            .map(|(_, t)| t.as_str())
+        else {
+            return Ok(true);
+        };
+        match client.sync_collection(cal_href, Some(token)).await {
+            Ok((changes, _)) if !changes.is_empty() => {
+                log::debug!("Poll: {} changes in {}", changes.len(), cal_href);
+                return Ok(true);
+            }
+            Ok(_) => {}
+            Err(e) if e == "sync-token-expired" => return Ok(true),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(false)
+}
+
 fn new_sync_result() -> SyncResult {
     SyncResult {
         pulled: Vec::new(),
@@ -739,5 +903,22 @@ fn new_sync_result() -> SyncResult {
         new_sync_tokens: Vec::new(),
         errors: Vec::new(),
         conflicts: Vec::new(),
+        outbox_sent: Vec::new(),
+        outbox_failed: Vec::new(),
+        queued: Vec::new(),
+        project_links: Vec::new(),
+        inbox: Vec::new(),
+    }
+}
+
+/// Outbox entry for a task PUT that failed: an update if the task is already
+/// on the server, otherwise a create.
+fn queued_put(task: &Task, href: String, ical: String) -> OutboxOp {
+    let task_id = task.id;
+    let hash = task_content_hash(task);
+    if task.sync_href.is_some() {
+        OutboxOp::Update { task_id, href, ical, hash }
+    } else {
+        OutboxOp::Create { task_id, href, ical, hash }
     }
 }
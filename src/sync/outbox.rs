@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Delay before the first retry of a failed write; doubles with each attempt.
+const BASE_BACKOFF_SECS: i64 = 30;
+/// Upper bound on the retry delay.
+const MAX_BACKOFF_SECS: i64 = 60 * 60;
+
+/// A CalDAV write that hasn't reached the server yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutboxOp {
+    /// New task. `hash` is the content hash of `ical`, recorded on the task once pushed.
+    Create {
+        task_id: Uuid,
+        href: String,
+        ical: String,
+        hash: u64,
+    },
+    /// Local edit to a task already on the server.
+    Update {
+        task_id: Uuid,
+        href: String,
+        ical: String,
+        hash: u64,
+    },
+    /// Task completed locally. It's archived, so there's no local copy to update afterwards.
+    Complete { href: String, ical: String },
+    /// Remove a VTODO from the server.
+    Delete { href: String },
+}
+
+impl OutboxOp {
+    pub fn href(&self) -> &str {
+        match self {
+            Self::Create { href, .. }
+            | Self::Update { href, .. }
+            | Self::Complete { href, .. }
+            | Self::Delete { href } => href,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Create { .. } => "Create",
+            Self::Update { .. } => "Update",
+            Self::Complete { .. } => "Complete",
+            Self::Delete { .. } => "Delete",
+        }
+    }
+}
+
+/// A queued write plus its retry state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub id: Uuid,
+    pub op: OutboxOp,
+    pub queued: DateTime<Utc>,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub next_attempt: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+impl OutboxEntry {
+    /// Whether the entry's backoff has elapsed.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_attempt.is_none_or(|t| t <= now)
+    }
+}
+
+/// Retry delay after `attempts` failures: 30s, 1m, 2m, ... capped at an hour.
+pub fn backoff(attempts: u32) -> Duration {
+    let exp = attempts.saturating_sub(1).min(16);
+    Duration::seconds((BASE_BACKOFF_SECS << exp).min(MAX_BACKOFF_SECS))
+}
+
+/// Persistent journal of CalDAV writes, replayed at the start of each sync.
+#[derive(Debug, Clone, Default)]
+pub struct Outbox {
+    pub entries: Vec<OutboxEntry>,
+}
+
+impl Outbox {
+    pub fn load(path: &Path) -> Self {
+        let entries = match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!("Failed to parse outbox {}: {}", path.display(), e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self { entries }
+    }
+
+    pub fn save(&self, path: &Path) {
+        if self.entries.is_empty() {
+            if path.exists() {
+                if let Err(e) = std::fs::remove_file(path) {
+                    log::error!("Failed to remove empty outbox: {}", e);
+                }
+            }
+            return;
+        }
+        match serde_json::to_string_pretty(&self.entries) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    log::error!("Failed to save outbox: {}", e);
+                }
+            }
+            Err(e) => log::error!("Failed to serialize outbox: {}", e),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Queue a write, returning its entry id.
+    ///
+    /// A queued write for the same href is superseded, since only the latest
+    /// state matters — except that an update to a task whose create hasn't gone
+    /// out yet stays a create.
+    pub fn push(&mut self, op: OutboxOp, now: DateTime<Utc>) -> Uuid {
+        let op = match self.entries.iter().position(|e| e.op.href() == op.href()) {
+            Some(pos) => {
+                let old = self.entries.remove(pos);
+                match (old.op, op) {
+                    (
+                        OutboxOp::Create { .. },
+                        OutboxOp::Update {
+                            task_id,
+                            href,
+                            ical,
+                            hash,
+                        },
+                    ) => OutboxOp::Create {
+                        task_id,
+                        href,
+                        ical,
+                        hash,
+                    },
+                    (_, op) => op,
+                }
+            }
+            None => op,
+        };
+        let id = Uuid::new_v4();
+        self.entries.push(OutboxEntry {
+            id,
+            op,
+            queued: now,
+            attempts: 0,
+            next_attempt: None,
+            last_error: None,
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: Uuid) {
+        self.entries.retain(|e| e.id != id);
+    }
+
+    /// Record a failed attempt and push the entry's next attempt back.
+    pub fn record_failure(&mut self, id: Uuid, error: &str, now: DateTime<Utc>) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) {
+            entry.attempts += 1;
+            entry.next_attempt = Some(now + backoff(entry.attempts));
+            entry.last_error = Some(error.to_string());
+        }
+    }
+
+    /// Make every entry due again, for when the user asks to retry now.
+    pub fn retry_now(&mut self) {
+        for entry in &mut self.entries {
+            entry.next_attempt = None;
+        }
+    }
+
+    /// When the next backed-off entry becomes due, if any are waiting.
+    pub fn next_retry(&self) -> Option<DateTime<Utc>> {
+        self.entries.iter().filter_map(|e| e.next_attempt).min()
+    }
+
+    /// Hrefs with a queued write; the sync engine leaves these to the outbox.
+    pub fn hrefs(&self) -> HashSet<String> {
+        self.entries.iter().map(|e| e.op.href().to_string()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(task_id: Uuid, href: &str, hash: u64) -> OutboxOp {
+        OutboxOp::Update {
+            task_id,
+            href: href.to_string(),
+            ical: format!("BEGIN:VCALENDAR {}", hash),
+            hash,
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        assert_eq!(backoff(1), Duration::seconds(30));
+        assert_eq!(backoff(2), Duration::seconds(60));
+        assert_eq!(backoff(3), Duration::seconds(120));
+        assert_eq!(backoff(10), Duration::seconds(MAX_BACKOFF_SECS));
+        assert_eq!(backoff(u32::MAX), Duration::seconds(MAX_BACKOFF_SECS));
+    }
+
+    #[test]
+    fn newer_write_supersedes_queued_one() {
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        let mut outbox = Outbox::default();
+        outbox.push(update(id, "/cal/a.ics", 1), now);
+        outbox.push(update(id, "/cal/b.ics", 2), now);
+        outbox.push(update(id, "/cal/a.ics", 3), now);
+        assert_eq!(outbox.len(), 2);
+        assert!(matches!(outbox.entries[1].op, OutboxOp::Update { hash: 3, .. }));
+
+        outbox.push(OutboxOp::Delete { href: "/cal/a.ics".to_string() }, now);
+        assert_eq!(outbox.len(), 2);
+        assert_eq!(outbox.entries[1].op.label(), "Delete");
+    }
+
+    #[test]
+    fn update_after_unsent_create_stays_create() {
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        let mut outbox = Outbox::default();
+        outbox.push(
+            OutboxOp::Create {
+                task_id: id,
+                href: "/cal/a.ics".to_string(),
+                ical: String::new(),
+                hash: 1,
+            },
+            now,
+        );
+        outbox.push(update(id, "/cal/a.ics", 2), now);
+        assert_eq!(outbox.len(), 1);
+        assert!(matches!(outbox.entries[0].op, OutboxOp::Create { hash: 2, .. }));
+    }
+
+    #[test]
+    fn failures_back_off_until_due() {
+        let now = Utc::now();
+        let mut outbox = Outbox::default();
+        let id = outbox.push(OutboxOp::Delete { href: "/cal/a.ics".to_string() }, now);
+        assert!(outbox.entries[0].is_due(now));
+
+        outbox.record_failure(id, "connection refused", now);
+        outbox.record_failure(id, "connection refused", now);
+        let entry = &outbox.entries[0];
+        assert_eq!(entry.attempts, 2);
+        assert_eq!(entry.last_error.as_deref(), Some("connection refused"));
+        assert!(!entry.is_due(now + Duration::seconds(59)));
+        assert!(entry.is_due(now + Duration::seconds(60)));
+        assert_eq!(outbox.next_retry(), Some(now + Duration::seconds(60)));
+
+        outbox.retry_now();
+        assert!(outbox.entries[0].is_due(now));
+        assert_eq!(outbox.entries[0].attempts, 2);
+        assert_eq!(outbox.next_retry(), None);
+    }
+
+    #[test]
+    fn entries_roundtrip_through_json() {
+        let now = Utc::now();
+        let mut outbox = Outbox::default();
+        let id = outbox.push(update(Uuid::new_v4(), "/cal/a.ics", 7), now);
+        outbox.push(
+            OutboxOp::Complete {
+                href: "/cal/b.ics".to_string(),
+                ical: "BEGIN:VCALENDAR".to_string(),
+            },
+            now,
+        );
+        outbox.record_failure(id, "503 Service Unavailable", now);
+
+        let json = serde_json::to_string(&outbox.entries).unwrap();
+        let loaded: Vec<OutboxEntry> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, outbox.entries);
+    }
+}
@@ -0,0 +1,244 @@
+use uuid::Uuid;
+
+use super::caldav::{self, CalDavClient, PutCondition};
+use super::vtodo::project_to_vcalendar;
+use crate::config::{LampConfig, ProjectLink, ProjectSyncMode};
+use crate::core::project::Project;
+
+/// A server-side change needed to make a project visible to other clients.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectAction {
+    /// Create the project's own task list.
+    CreateList { project: Uuid, name: String },
+    /// Push a rename to the project's list.
+    RenameList {
+        project: Uuid,
+        calendar_href: String,
+        name: String,
+    },
+    /// Create or update the parent VTODO in `calendar_href`.
+    PutParent {
+        project: Uuid,
+        name: String,
+        outcome: String,
+        calendar_href: String,
+    },
+}
+
+/// Work out what one account needs to do for the configured project mode.
+/// A project belongs to the account holding its link, or for new projects
+/// the account of `LampConfig::project_home`.
+pub fn plan_project_actions(
+    config: &LampConfig,
+    account: &str,
+    projects: &[Project],
+) -> Vec<ProjectAction> {
+    let mut actions = Vec::new();
+    for project in projects {
+        let link = config
+            .project_links
+            .iter()
+            .find(|l| l.project == project.id);
+        match (config.project_sync_mode, link) {
+            (ProjectSyncMode::Property, _) => {}
+            (_, Some(link)) if link.account != account || link.synced_name == project.name => {}
+            (ProjectSyncMode::Collections, Some(link)) => {
+                actions.push(ProjectAction::RenameList {
+                    project: project.id,
+                    calendar_href: link.calendar_href.clone(),
+                    name: project.name.clone(),
+                });
+            }
+            (ProjectSyncMode::RelatedTo, Some(link)) => {
+                actions.push(ProjectAction::PutParent {
+                    project: project.id,
+                    name: project.name.clone(),
+                    outcome: project.outcome.clone(),
+                    calendar_href: link.calendar_href.clone(),
+                });
+            }
+            (mode, None) => {
+                let Some((home_account, home_calendar)) = config.project_home(&project.name) else {
+                    continue;
+                };
+                if home_account != account {
+                    continue;
+                }
+                actions.push(match mode {
+                    ProjectSyncMode::Collections => ProjectAction::CreateList {
+                        project: project.id,
+                        name: project.name.clone(),
+                    },
+                    _ => ProjectAction::PutParent {
+                        project: project.id,
+                        name: project.name.clone(),
+                        outcome: project.outcome.clone(),
+                        calendar_href: home_calendar.to_string(),
+                    },
+                });
+            }
+        }
+    }
+    actions
+}
+
+/// Carry out planned actions. Returns the links that now exist on the
+/// server, plus errors for actions that failed and will be retried next sync.
+pub async fn apply_project_actions(
+    client: &CalDavClient,
+    account: &str,
+    actions: &[ProjectAction],
+) -> (Vec<ProjectLink>, Vec<String>) {
+    let mut links = Vec::new();
+    let mut errors = Vec::new();
+    let mut home_set: Option<String> = None;
+
+    for action in actions {
+        match action {
+            ProjectAction::CreateList { project, name } => {
+                if home_set.is_none() {
+                    match client.calendar_home_set().await {
+                        Ok(home) => home_set = Some(home),
+                        Err(e) => {
+                            errors.push(format!("Project list for '{}': {}", name, e));
+                            continue;
+                        }
+                    }
+                }
+                let href = caldav::project_list_href(home_set.as_deref().unwrap_or_default(), project);
+                match client.make_calendar(&href, name).await {
+                    Ok(()) => {
+                        log::info!("Created task list for project '{}': {}", name, href);
+                        links.push(ProjectLink {
+                            project: *project,
+                            account: account.to_string(),
+                            calendar_href: href,
+                            parent_href: None,
+                            name: name.clone(),
+                            synced_name: name.clone(),
+                        });
+                    }
+                    Err(e) => errors.push(format!("Project list for '{}': {}", name, e)),
+                }
+            }
+            ProjectAction::RenameList {
+                project,
+                calendar_href,
+                name,
+            } => match client.set_display_name(calendar_href, name).await {
+                Ok(()) => links.push(ProjectLink {
+                    project: *project,
+                    account: account.to_string(),
+                    calendar_href: calendar_href.clone(),
+                    parent_href: None,
+                    name: name.clone(),
+                    synced_name: name.clone(),
+                }),
+                Err(e) => errors.push(format!("Renaming list for '{}': {}", name, e)),
+            },
+            ProjectAction::PutParent {
+                project,
+                name,
+                outcome,
+                calendar_href,
+            } => {
+                let mut parent = Project::new(name.clone());
+                parent.id = *project;
+                parent.outcome = outcome.clone();
+                let href = caldav::vtodo_href(calendar_href, project);
+                let ical = project_to_vcalendar(&parent);
+                match client.put_vtodo(&href, PutCondition::Unconditional, &ical).await {
+                    Ok(_) => links.push(ProjectLink {
+                        project: *project,
+                        account: account.to_string(),
+                        calendar_href: calendar_href.clone(),
+                        parent_href: Some(href),
+                        name: name.clone(),
+                        synced_name: name.clone(),
+                    }),
+                    Err(e) => errors.push(format!("Parent task for '{}': {}", name, e)),
+                }
+            }
+        }
+    }
+
+    (links, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CalendarAssignment, CalendarPurpose};
+
+    fn config(mode: ProjectSyncMode) -> LampConfig {
+        LampConfig {
+            project_sync_mode: mode,
+            calendar_assignments: vec![CalendarAssignment {
+                account: "acc".to_string(),
+                calendar_href: "/cal/tasks/".to_string(),
+                purpose: CalendarPurpose::Tasks,
+                sync_token: None,
+                spoons_per_hour: None,
+            }],
+            ..LampConfig::default()
+        }
+    }
+
+    #[test]
+    fn property_mode_does_nothing() {
+        let projects = vec![Project::new("Garden")];
+        let config = config(ProjectSyncMode::Property);
+        assert!(plan_project_actions(&config, "acc", &projects).is_empty());
+    }
+
+    #[test]
+    fn new_projects_get_a_list_or_parent_on_their_home_account() {
+        let projects = vec![Project::new("Garden")];
+
+        let collections = config(ProjectSyncMode::Collections);
+        assert_eq!(
+            plan_project_actions(&collections, "acc", &projects),
+            vec![ProjectAction::CreateList {
+                project: projects[0].id,
+                name: "Garden".to_string(),
+            }]
+        );
+        assert!(plan_project_actions(&collections, "other", &projects).is_empty());
+
+        let related = config(ProjectSyncMode::RelatedTo);
+        assert!(matches!(
+            plan_project_actions(&related, "acc", &projects).as_slice(),
+            [ProjectAction::PutParent { calendar_href, .. }] if calendar_href == "/cal/tasks/"
+        ));
+    }
+
+    #[test]
+    fn renames_are_pushed_once() {
+        let mut project = Project::new("Garden");
+        let mut config = config(ProjectSyncMode::Collections);
+        config.set_project_link(ProjectLink {
+            project: project.id,
+            account: "acc".to_string(),
+            calendar_href: "/cal/lamp-garden/".to_string(),
+            parent_href: None,
+            name: "Garden".to_string(),
+            synced_name: "Garden".to_string(),
+        });
+        assert!(plan_project_actions(&config, "acc", std::slice::from_ref(&project)).is_empty());
+
+        project.name = "Allotment".to_string();
+        assert!(config.refresh_project_links(std::slice::from_ref(&project)));
+        let actions = plan_project_actions(&config, "acc", std::slice::from_ref(&project));
+        assert!(matches!(
+            actions.as_slice(),
+            [ProjectAction::RenameList { name, .. }] if name == "Allotment"
+        ));
+
+        // Tasks of the renamed project still land in its list
+        let mut task = crate::core::task::Task::new("Dig beds");
+        task.project = Some("Allotment".to_string());
+        assert_eq!(config.task_target(&task), Some(("acc", "/cal/lamp-garden/")));
+        assert!(config.is_project_list("/cal/lamp-garden/"));
+        assert_eq!(config.project_for_remote("/cal/lamp-garden/x.ics", None), Some("Allotment"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Sending mail: delegation requests, follow-ups and meeting replies.
+
+use lettre::message::header::ContentType;
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+
+/// A plain-text email, optionally with a `text/calendar` part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingEmail {
+    pub to_name: Option<String>,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    /// `Message-ID` (without angle brackets) this answers, to stay in its thread.
+    pub in_reply_to: Option<String>,
+    /// An iTIP object and its METHOD, e.g. `REPLY`.
+    pub calendar: Option<(String, String)>,
+}
+
+impl OutgoingEmail {
+    pub fn new(to: &str, subject: &str, body: &str) -> Self {
+        Self {
+            to_name: None,
+            to: to.to_string(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+            in_reply_to: None,
+            calendar: None,
+        }
+    }
+}
+
+/// Split `host[:port]`; 587 (submission with STARTTLS) when no port is given.
+fn host_and_port(host: &str) -> (&str, u16) {
+    match host.rsplit_once(':') {
+        Some((name, port)) => match port.parse() {
+            Ok(port) => (name, port),
+            Err(_) => (host, 587),
+        },
+        None => (host, 587),
+    }
+}
+
+/// A fresh `Message-ID` (without angle brackets) on the sender's domain.
+pub fn new_message_id(from: &str) -> String {
+    let domain = from
+        .rsplit_once('@')
+        .map(|(_, d)| d.trim_end_matches('>'))
+        .filter(|d| !d.is_empty())
+        .unwrap_or("lamp.invalid");
+    format!("{}@{}", uuid::Uuid::new_v4(), domain)
+}
+
+/// `address` may be bare, a `mailto:` URI or already in `Name <address>` form.
+fn mailbox(name: Option<&str>, address: &str) -> Result<Mailbox, String> {
+    let address = address.trim();
+    let address = address.strip_prefix("mailto:").unwrap_or(address);
+    let invalid = |e: lettre::address::AddressError| format!("Invalid email address '{}': {}", address, e);
+    match name.filter(|n| !n.is_empty()) {
+        Some(name) => Ok(Mailbox::new(Some(name.to_string()), address.parse().map_err(invalid)?)),
+        None => address.parse().map_err(invalid),
+    }
+}
+
+/// The message as it goes on the wire, with the given `Message-ID`.
+pub fn build_message(from: &str, email: &OutgoingEmail, message_id: &str) -> Result<lettre::Message, String> {
+    let mut builder = lettre::Message::builder()
+        .from(mailbox(None, from)?)
+        .to(mailbox(email.to_name.as_deref(), &email.to)?)
+        .subject(email.subject.clone())
+        .message_id(Some(format!("<{}>", message_id)));
+    if let Some(ref parent) = email.in_reply_to {
+        builder = builder
+            .in_reply_to(format!("<{}>", parent))
+            .references(format!("<{}>", parent));
+    }
+    let built = match email.calendar {
+        None => builder.singlepart(SinglePart::plain(email.body.clone())),
+        Some((ref ical, ref method)) => {
+            let content_type = ContentType::parse(&format!("text/calendar; method={}; charset=utf-8", method))
+                .map_err(|e| format!("Invalid calendar method '{}': {}", method, e))?;
+            builder.multipart(
+                MultiPart::alternative()
+                    .singlepart(SinglePart::plain(email.body.clone()))
+                    .singlepart(SinglePart::builder().header(content_type).body(ical.clone())),
+            )
+        }
+    };
+    built.map_err(|e| format!("Failed to build email: {}", e))
+}
+
+/// STARTTLS on 587, implicit TLS on 465, and plain SMTP to a local test sink
+/// (MailHog, GreenMail) on any other port of localhost.
+fn transport(host: &str, username: &str, password: &str) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+    let (name, port) = host_and_port(host.trim());
+    let local = matches!(name, "localhost" | "127.0.0.1" | "::1");
+    let builder = if port == 465 {
+        AsyncSmtpTransport::<Tokio1Executor>::relay(name)
+    } else if local && port != 587 {
+        Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(name))
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(name)
+    }
+    .map_err(|e| format!("SMTP setup failed: {}", e))?
+    .port(port);
+    let builder = if username.is_empty() {
+        builder
+    } else {
+        builder.credentials(Credentials::new(username.to_string(), password.to_string()))
+    };
+    Ok(builder.build())
+}
+
+/// Send one email; returns its `Message-ID` so replies can be matched to it.
+pub async fn send(
+    host: &str,
+    username: &str,
+    password: &str,
+    from: &str,
+    email: &OutgoingEmail,
+) -> Result<String, String> {
+    let message_id = new_message_id(from);
+    let message = build_message(from, email, &message_id)?;
+    transport(host, username, password)?
+        .send(message)
+        .await
+        .map_err(|e| format!("Failed to send email: {}", e))?;
+    Ok(message_id)
+}
+
+/// Connect and authenticate without sending anything.
+pub async fn test_connection(host: &str, username: &str, password: &str) -> Result<String, String> {
+    match transport(host, username, password)?.test_connection().await {
+        Ok(true) => Ok("Connected".to_string()),
+        Ok(false) => Err("Server refused the connection".to_string()),
+        Err(e) => Err(format!("SMTP connection failed: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_threaded_and_calendar_mail() {
+        assert_eq!(host_and_port("localhost:1025"), ("localhost", 1025));
+        assert_eq!(host_and_port("smtp.example.com"), ("smtp.example.com", 587));
+        assert!(new_message_id("Me <me@example.com>").ends_with("@example.com"));
+
+        let mut email = OutgoingEmail::new("sam@example.com", "Roof quote", "Could you take this on?");
+        email.to_name = Some("Sam Lee".to_string());
+        email.in_reply_to = Some("q1@example.com".to_string());
+        let raw = String::from_utf8(build_message("me@example.com", &email, "d1@example.com").unwrap().formatted())
+            .unwrap();
+        assert!(raw.contains("Message-ID: <d1@example.com>"));
+        assert!(raw.contains("In-Reply-To: <q1@example.com>"));
+        assert!(raw.contains("To: \"Sam Lee\" <sam@example.com>"));
+        assert!(raw.contains("Could you take this on?"));
+
+        let mut reply = OutgoingEmail::new("mailto:boss@example.com", "Accepted: Standup", "Accepted");
+        reply.calendar = Some(("BEGIN:VCALENDAR\r\nMETHOD:REPLY\r\nEND:VCALENDAR\r\n".to_string(), "REPLY".to_string()));
+        let raw = String::from_utf8(build_message("me@example.com", &reply, "r1@example.com").unwrap().formatted())
+            .unwrap();
+        assert!(raw.contains("To: boss@example.com"));
+        assert!(raw.contains("text/calendar; method=REPLY"));
+
+        assert!(build_message("me@example.com", &OutgoingEmail::new("nobody", "x", "y"), "x@y").is_err());
+    }
+}
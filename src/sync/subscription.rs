@@ -0,0 +1,125 @@
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, StatusCode};
+
+use super::vevent::vcalendar_to_events;
+use crate::config::IcsSubscription;
+use crate::core::event::CalendarEvent;
+
+/// Outcome of refreshing one subscription.
+#[derive(Debug, Clone)]
+pub enum FeedFetch {
+    /// The feed is unchanged since the stored validators.
+    NotModified,
+    Updated {
+        events: Vec<CalendarEvent>,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+/// Fetch a feed, skipping the download when it hasn't changed.
+///
+/// HTTP feeds are sent `If-None-Match`/`If-Modified-Since`; `file://` feeds
+/// compare the file's modification time instead.
+pub async fn fetch_feed(sub: &IcsSubscription) -> Result<FeedFetch, String> {
+    let url = sub.url.trim();
+    if let Some(path) = url.strip_prefix("file://") {
+        let modified = tokio::fs::metadata(path)
+            .await
+            .and_then(|m| m.modified())
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let stamp = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs().to_string())
+            .ok();
+        if stamp.is_some() && stamp == sub.last_modified {
+            return Ok(FeedFetch::NotModified);
+        }
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        return Ok(FeedFetch::Updated {
+            events: feed_events(sub, &content),
+            etag: None,
+            last_modified: stamp,
+        });
+    }
+
+    let http = Client::builder()
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let mut req = http.get(http_url(url));
+    if let Some(ref etag) = sub.etag {
+        req = req.header(IF_NONE_MATCH, etag);
+    }
+    if let Some(ref modified) = sub.last_modified {
+        req = req.header(IF_MODIFIED_SINCE, modified);
+    }
+    let resp = req.send().await.map_err(|e| format!("GET {} failed: {}", url, e))?;
+
+    match resp.status() {
+        StatusCode::NOT_MODIFIED => Ok(FeedFetch::NotModified),
+        s if s.is_success() => {
+            let header = |name| {
+                resp.headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+            };
+            let etag = header(ETAG);
+            let last_modified = header(LAST_MODIFIED);
+            let content = resp
+                .text()
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", url, e))?;
+            Ok(FeedFetch::Updated {
+                events: feed_events(sub, &content),
+                etag,
+                last_modified,
+            })
+        }
+        s => Err(format!("GET {} returned {}", url, s)),
+    }
+}
+
+/// `webcal://` is plain HTTPS under another name.
+fn http_url(url: &str) -> String {
+    match url.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => url.to_string(),
+    }
+}
+
+/// The feed's events, labelled with the subscription so they render read-only.
+pub fn feed_events(sub: &IcsSubscription, content: &str) -> Vec<CalendarEvent> {
+    let mut events = vcalendar_to_events(content);
+    for event in &mut events {
+        event.calendar_href = sub.calendar_href();
+        event.calendar_name = sub.name.clone();
+        event.sync_href = None;
+        event.sync_hash = None;
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feed_events_are_marked_read_only() {
+        let mut sub = IcsSubscription::new();
+        sub.name = "Holidays".to_string();
+        let content = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:xmas-2026@example.com\r\n\
+            DTSTART;VALUE=DATE:20261225\r\nDTEND;VALUE=DATE:20261226\r\nSUMMARY:Christmas Day\r\n\
+            END:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let events = feed_events(&sub, content);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].title, "Christmas Day");
+        assert!(events[0].all_day);
+        assert!(events[0].is_subscribed());
+        assert_eq!(events[0].calendar_name, "Holidays");
+        assert_eq!(http_url("webcal://example.com/h.ics"), "https://example.com/h.ics");
+    }
+}
@@ -1,36 +1,83 @@
-use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
 use uuid::Uuid;
 
 use super::ical::*;
-use crate::core::event::{CalendarEvent, EventStatus};
+use crate::core::alarm::Alarm;
+use crate::core::event::{Attendee, CalendarEvent, EventStatus, PartStat};
+use crate::core::timezone;
 
-/// Generate a full VCALENDAR string containing a VEVENT for the given event.
+/// Generate a full VCALENDAR string for the given event: the VEVENT itself
+/// plus, for a recurring series, one VEVENT per overridden occurrence.
 pub fn event_to_vcalendar(event: &CalendarEvent) -> String {
     let mut lines = Vec::new();
     lines.push("BEGIN:VCALENDAR".to_string());
     lines.push("VERSION:2.0".to_string());
     lines.push("PRODID:-//Lamp GTD//EN".to_string());
+
+    // Floating events go out in the system zone when it's known
+    let tzid = if event.all_day {
+        None
+    } else {
+        event.tzid.clone().or_else(timezone::local_zone_name)
+    };
+    if let Some(ref tzid) = tzid {
+        if tzid != timezone::UTC {
+            lines.extend(vtimezone_lines(tzid, timezone::to_utc(event.start, Some(tzid))));
+        }
+    }
+
+    let uid = event.sync_uid.clone().unwrap_or_else(|| event.id.to_string());
+    vevent_lines(&mut lines, event, &uid, event.all_day, tzid.as_deref());
+    for over in &event.overrides {
+        vevent_lines(&mut lines, over, &uid, event.all_day, tzid.as_deref());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.join("\r\n") + "\r\n"
+}
+
+/// A DTSTART-style property: a date for all-day events, otherwise a time in `tzid`.
+fn time_property(key: &str, dt: NaiveDateTime, all_day: bool, tzid: Option<&str>) -> String {
+    if all_day {
+        format!("{};VALUE=DATE:{}", key, format_date(dt.date()))
+    } else {
+        format_zoned_property(key, dt, tzid)
+    }
+}
+
+/// One VEVENT. `series_all_day` and `tzid` come from the series, since
+/// RECURRENCE-ID and EXDATE must match the master's DTSTART.
+fn vevent_lines(
+    lines: &mut Vec<String>,
+    event: &CalendarEvent,
+    uid: &str,
+    series_all_day: bool,
+    tzid: Option<&str>,
+) {
     lines.push("BEGIN:VEVENT".to_string());
 
-    lines.push(format!("UID:{}", event.id));
+    lines.push(format!("UID:{}", uid));
+
+    if let Some(rid) = event.recurrence_id {
+        lines.push(time_property("RECURRENCE-ID", rid, series_all_day, tzid));
+    }
 
     lines.push(format!(
         "SUMMARY:{}",
         fold_line(&escape_text(&event.title))
     ));
 
-    if event.all_day {
-        lines.push(format!(
-            "DTSTART;VALUE=DATE:{}",
-            format_date(event.start.date())
-        ));
-        lines.push(format!(
-            "DTEND;VALUE=DATE:{}",
-            format_date(event.end.date())
-        ));
-    } else {
-        lines.push(format!("DTSTART:{}", format_datetime(event.start)));
-        lines.push(format!("DTEND:{}", format_datetime(event.end)));
+    lines.push(time_property("DTSTART", event.start, event.all_day, tzid));
+    lines.push(time_property("DTEND", event.end, event.all_day, tzid));
+
+    if let Some(ref rule) = event.rrule {
+        lines.push(format!("RRULE:{}", rule));
+        for &ex in &event.exdates {
+            lines.push(time_property("EXDATE", ex, series_all_day, tzid));
+        }
     }
 
     if !event.location.is_empty() {
@@ -47,6 +94,23 @@ pub fn event_to_vcalendar(event: &CalendarEvent) -> String {
         ));
     }
 
+    if let Some(esc) = event.esc {
+        lines.push(format!("X-LAMP-ESC:{}", esc));
+    }
+
+    if let Some(task_id) = event.task_id {
+        lines.push(format!("X-LAMP-TASK:{}", task_id));
+    }
+
+    if let Some(ref organizer) = event.organizer {
+        lines.push(person_line("ORGANIZER", organizer, false));
+    }
+    for attendee in &event.attendees {
+        lines.push(person_line("ATTENDEE", attendee, true));
+    }
+
+    lines.extend(event.extra_properties.iter().map(|line| fold_line(line)));
+
     let status = match event.status {
         EventStatus::Confirmed => "CONFIRMED",
         EventStatus::Tentative => "TENTATIVE",
@@ -54,81 +118,202 @@ pub fn event_to_vcalendar(event: &CalendarEvent) -> String {
     };
     lines.push(format!("STATUS:{}", status));
 
-    lines.push(format!(
-        "LAST-MODIFIED:{}",
-        format_datetime(chrono::Local::now().naive_local())
-    ));
+    lines.push(format!("LAST-MODIFIED:{}", format_utc(timezone::now_utc())));
+
+    for alarm in &event.alarms {
+        lines.extend(valarm_lines(alarm, &event.title));
+    }
 
     lines.push("END:VEVENT".to_string());
-    lines.push("END:VCALENDAR".to_string());
+}
 
-    lines.join("\r\n") + "\r\n"
+/// An ORGANIZER or ATTENDEE property; attendees carry PARTSTAT and RSVP.
+pub fn person_line(key: &str, person: &Attendee, with_partstat: bool) -> String {
+    let mut line = key.to_string();
+    if !person.name.is_empty() {
+        line.push_str(&format!(";CN=\"{}\"", person.name.replace('"', "'")));
+    }
+    if with_partstat {
+        line.push_str(&format!(";PARTSTAT={}", person.partstat.as_ical()));
+        if person.rsvp {
+            line.push_str(";RSVP=TRUE");
+        }
+    }
+    line.push_str(&format!(":mailto:{}", person.email));
+    fold_line(&line)
 }
 
-/// Parse a VCALENDAR string and extract CalendarEvent(s) from the VEVENT component.
-/// Recurring events (RRULE) are expanded into individual instances.
+/// Parse an ORGANIZER or ATTENDEE line. Non-mailto addresses are kept as given.
+pub fn parse_person(line: &str, value: &str) -> Attendee {
+    let email = value
+        .strip_prefix("mailto:")
+        .or_else(|| value.strip_prefix("MAILTO:"))
+        .unwrap_or(value)
+        .trim()
+        .to_string();
+    Attendee {
+        email,
+        name: ical_param(line, "CN").unwrap_or_default().to_string(),
+        partstat: ical_param(line, "PARTSTAT").map(PartStat::from_ical).unwrap_or_default(),
+        rsvp: ical_param(line, "RSVP").is_some_and(|v| v.eq_ignore_ascii_case("TRUE")),
+    }
+}
+
+/// A parsed VEVENT before overrides are attached to their series.
+struct Component {
+    uid: String,
+    event: CalendarEvent,
+    recurrence_id: Option<(NaiveDateTime, Option<String>)>,
+    exdates: Vec<(NaiveDateTime, Option<String>)>,
+}
+
+/// Parse a VCALENDAR string into events, one per UID. Recurring series keep
+/// their RRULE, EXDATEs and RECURRENCE-ID overrides; expand them with
+/// `CalendarEvent::occurrences`.
 pub fn vcalendar_to_events(ical: &str) -> Vec<CalendarEvent> {
     let unfolded = unfold_lines(ical);
+    let zones = TimeZones::parse(&unfolded);
 
-    let mut in_vevent = false;
-    let mut uid: Option<Uuid> = None;
+    let mut components = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+    for line in unfolded.lines() {
+        let line = line.trim_end();
+        match line {
+            "BEGIN:VEVENT" => current = Some(Vec::new()),
+            "END:VEVENT" => {
+                if let Some(component) = current.take().and_then(|c| parse_vevent(&c, &zones)) {
+                    components.push(component);
+                }
+            }
+            _ => {
+                if let Some(ref mut c) = current {
+                    c.push(line);
+                }
+            }
+        }
+    }
+
+    let (overrides, masters): (Vec<Component>, Vec<Component>) =
+        components.into_iter().partition(|c| c.recurrence_id.is_some());
+
+    let mut events: Vec<CalendarEvent> = Vec::new();
+    let mut by_uid: HashMap<String, usize> = HashMap::new();
+    for Component { uid, mut event, exdates, .. } in masters {
+        // EXDATEs are matched against occurrences in the series' zone
+        let zone = if event.all_day { None } else { event.tzid.clone() };
+        event.exdates = exdates
+            .into_iter()
+            .map(|(dt, tzid)| timezone::convert(dt, tzid.as_deref(), zone.as_deref()))
+            .collect();
+        if event.title.is_empty() {
+            continue;
+        }
+        by_uid.insert(uid, events.len());
+        events.push(event);
+    }
+
+    for Component { uid, mut event, recurrence_id, .. } in overrides {
+        let Some((rid, rid_tzid)) = recurrence_id else {
+            continue;
+        };
+        let Some(master) = by_uid.get(&uid).map(|&i| &mut events[i]) else {
+            // An occurrence without its series (e.g. a single invitation): show it on its own
+            log::debug!("RECURRENCE-ID component without master: {}", uid);
+            if !event.title.is_empty() {
+                event.id = Uuid::new_v5(&CALDAV_UUID_NAMESPACE, format!("{}:{}", uid, rid).as_bytes());
+                events.push(event);
+            }
+            continue;
+        };
+        // Store the override in the series' zone so it lines up with generated occurrences
+        let zone = if master.all_day { None } else { master.tzid.clone() };
+        if !event.all_day {
+            event.start = timezone::convert(event.start, event.tzid.as_deref(), zone.as_deref());
+            event.end = timezone::convert(event.end, event.tzid.as_deref(), zone.as_deref());
+        }
+        event.tzid = master.tzid.clone();
+        event.id = master.id;
+        event.sync_uid = None;
+        event.recurrence_id = Some(timezone::convert(rid, rid_tzid.as_deref(), zone.as_deref()));
+        if event.title.is_empty() {
+            event.title = master.title.clone();
+        }
+        master.overrides.push(event);
+    }
+
+    events
+}
+
+/// Parse the first event (or series) in a VCALENDAR string.
+pub fn vcalendar_to_event(ical: &str) -> Option<CalendarEvent> {
+    vcalendar_to_events(ical).into_iter().next()
+}
+
+fn parse_vevent(lines: &[&str], zones: &TimeZones) -> Option<Component> {
     let mut uid_raw = String::new();
     let mut summary = String::new();
-    let mut dtstart: Option<NaiveDateTime> = None;
-    let mut dtend: Option<NaiveDateTime> = None;
+    let mut dtstart: Option<(NaiveDateTime, Option<String>)> = None;
+    let mut dtend: Option<(NaiveDateTime, Option<String>)> = None;
     let mut all_day = false;
     let mut location = String::new();
     let mut description = String::new();
     let mut status = EventStatus::Confirmed;
     let mut rrule: Option<String> = None;
-    let mut exdates: Vec<NaiveDate> = Vec::new();
-
-    for line in unfolded.lines() {
-        let line = line.trim_end();
-        if line == "BEGIN:VEVENT" {
-            in_vevent = true;
+    let mut recurrence_id: Option<(NaiveDateTime, Option<String>)> = None;
+    let mut exdates: Vec<(NaiveDateTime, Option<String>)> = Vec::new();
+    let mut esc: Option<u32> = None;
+    let mut task_id: Option<Uuid> = None;
+    let mut organizer: Option<Attendee> = None;
+    let mut attendees: Vec<Attendee> = Vec::new();
+    let mut alarms: Vec<Alarm> = Vec::new();
+    let mut extra_properties: Vec<String> = Vec::new();
+    // Lines of a nested component (VALARM or something unknown) being read
+    let mut nested: Option<(&str, Vec<&str>)> = None;
+
+    for &line in lines {
+        if let Some((name, ref mut inner)) = nested {
+            if line.strip_prefix("END:") == Some(name) {
+                if name == "VALARM" {
+                    alarms.extend(parse_valarm(inner));
+                } else {
+                    extra_properties.push(line.to_string());
+                }
+                nested = None;
+            } else if name == "VALARM" {
+                inner.push(line);
+            } else {
+                extra_properties.push(line.to_string());
+            }
             continue;
         }
-        if line == "END:VEVENT" {
-            break;
-        }
-        if !in_vevent {
+        if let Some(name) = line.strip_prefix("BEGIN:") {
+            if name != "VALARM" {
+                extra_properties.push(line.to_string());
+            }
+            nested = Some((name, Vec::new()));
             continue;
         }
 
         // Check for VALUE=DATE parameter before parsing
         let is_date_only = line.contains("VALUE=DATE") && !line.contains("VALUE=DATE-TIME");
+        let date_time = |value: &str| {
+            if is_date_only {
+                parse_ical_date(value).map(|d| (d.and_time(chrono::NaiveTime::MIN), None))
+            } else {
+                zones.parse_datetime(line, value)
+            }
+        };
 
         if let Some((key, value)) = parse_ical_line(line) {
             match key {
-                "UID" => {
-                    uid_raw = value.to_string();
-                    uid = Some(
-                        Uuid::parse_str(value).unwrap_or_else(|_| {
-                            Uuid::new_v5(&CALDAV_UUID_NAMESPACE, value.as_bytes())
-                        }),
-                    );
-                }
+                "UID" => uid_raw = value.to_string(),
                 "SUMMARY" => summary = unescape_text(value),
                 "DTSTART" => {
-                    if is_date_only {
-                        all_day = true;
-                        if let Some(d) = parse_ical_date(value) {
-                            dtstart = Some(d.and_hms_opt(0, 0, 0).unwrap());
-                        }
-                    } else {
-                        dtstart = parse_ical_datetime(value);
-                    }
-                }
-                "DTEND" => {
-                    if is_date_only {
-                        if let Some(d) = parse_ical_date(value) {
-                            dtend = Some(d.and_hms_opt(0, 0, 0).unwrap());
-                        }
-                    } else {
-                        dtend = parse_ical_datetime(value);
-                    }
+                    all_day = is_date_only;
+                    dtstart = date_time(value);
                 }
+                "DTEND" => dtend = date_time(value),
+                "RECURRENCE-ID" => recurrence_id = date_time(value),
                 "LOCATION" => location = unescape_text(value),
                 "DESCRIPTION" => description = unescape_text(value),
                 "STATUS" => {
@@ -143,273 +328,66 @@ pub fn vcalendar_to_events(ical: &str) -> Vec<CalendarEvent> {
                 }
                 "EXDATE" => {
                     // EXDATE can be a comma-separated list of dates
-                    for part in value.split(',') {
-                        let part = part.trim();
-                        if let Some(d) = parse_ical_date(part) {
-                            exdates.push(d);
-                        } else if let Some(dt) = parse_ical_datetime(part) {
-                            exdates.push(dt.date());
-                        }
-                    }
+                    exdates.extend(value.split(',').filter_map(|part| date_time(part.trim())));
                 }
-                _ => {}
+                "X-LAMP-ESC" => esc = value.trim().parse().ok(),
+                "X-LAMP-TASK" => task_id = Uuid::parse_str(value.trim()).ok(),
+                "ORGANIZER" => organizer = Some(parse_person(line, value)),
+                "ATTENDEE" => attendees.push(parse_person(line, value)),
+                // Rewritten on every save
+                "LAST-MODIFIED" => {}
+                _ => extra_properties.push(line.to_string()),
             }
         }
     }
 
-    // Skip events with empty SUMMARY
-    if summary.is_empty() {
-        return Vec::new();
-    }
-
-    let base_id = uid.unwrap_or_else(Uuid::new_v4);
-    let start = match dtstart {
-        Some(s) => s,
-        None => return Vec::new(),
+    let (start, tzid) = dtstart?;
+    // DTEND may be given in a different zone; express it in DTSTART's
+    let end = match dtend {
+        Some((end, end_tzid)) => timezone::convert(end, end_tzid.as_deref(), tzid.as_deref()),
+        None => start,
     };
-    let end = dtend.unwrap_or(start);
-    let duration = end - start;
-
-    let base = CalendarEvent {
-        id: base_id,
-        title: summary,
-        start,
-        end,
-        all_day,
-        location,
-        description,
-        status,
-        calendar_href: String::new(),
-        calendar_name: String::new(),
-        sync_href: None,
-        sync_hash: None,
-    };
-
-    match rrule {
-        None => vec![base],
-        Some(rule) => expand_rrule(&base, &uid_raw, &rule, &exdates, duration),
-    }
-}
-
-/// Backwards-compatible wrapper that returns only the first event (or base instance).
-pub fn vcalendar_to_event(ical: &str) -> Option<CalendarEvent> {
-    vcalendar_to_events(ical).into_iter().next()
-}
-
-/// Expand an RRULE into concrete event instances.
-/// Supports FREQ=DAILY, WEEKLY, MONTHLY, YEARLY with COUNT and UNTIL.
-/// WEEKLY supports BYDAY. Generates up to 1 year ahead from today.
-fn expand_rrule(
-    base: &CalendarEvent,
-    uid_raw: &str,
-    rule: &str,
-    exdates: &[NaiveDate],
-    duration: Duration,
-) -> Vec<CalendarEvent> {
-    let params = parse_rrule_params(rule);
-
-    let freq = match params.get("FREQ").map(|s| s.as_str()) {
-        Some("DAILY") => RruleFreq::Daily,
-        Some("WEEKLY") => RruleFreq::Weekly,
-        Some("MONTHLY") => RruleFreq::Monthly,
-        Some("YEARLY") => RruleFreq::Yearly,
-        _ => return vec![base.clone()],
-    };
-
-    let interval: u32 = params
-        .get("INTERVAL")
-        .and_then(|v| v.parse().ok())
-        .unwrap_or(1);
-
-    let count: Option<u32> = params.get("COUNT").and_then(|v| v.parse().ok());
-
-    let until: Option<NaiveDate> = params.get("UNTIL").and_then(|v| {
-        parse_ical_date(v).or_else(|| parse_ical_datetime(v).map(|dt| dt.date()))
-    });
-
-    let byday: Vec<Weekday> = params
-        .get("BYDAY")
-        .map(|v| {
-            v.split(',')
-                .filter_map(|d| match d.trim() {
-                    "MO" => Some(Weekday::Mon),
-                    "TU" => Some(Weekday::Tue),
-                    "WE" => Some(Weekday::Wed),
-                    "TH" => Some(Weekday::Thu),
-                    "FR" => Some(Weekday::Fri),
-                    "SA" => Some(Weekday::Sat),
-                    "SU" => Some(Weekday::Sun),
-                    _ => None,
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-
-    let today = chrono::Local::now().date_naive();
-    // Generate instances from the base date up to 1 year from today
-    let horizon = today + Duration::days(365);
-    let max_count = count.unwrap_or(1000).min(1000) as usize;
-
-    let mut events = Vec::new();
-    let base_date = base.start.date();
-    let base_time = base.start.time();
-
-    match freq {
-        RruleFreq::Daily => {
-            let mut current = base_date;
-            let step = Duration::days(interval as i64);
-            let mut n = 0;
-            while current <= horizon && n < max_count {
-                if let Some(u) = until {
-                    if current > u {
-                        break;
-                    }
-                }
-                if !exdates.contains(&current) {
-                    let start = current.and_time(base_time);
-                    events.push(make_instance(base, uid_raw, start, duration, n));
-                }
-                current += step;
-                n += 1;
-            }
-        }
-        RruleFreq::Weekly => {
-            let days: Vec<Weekday> = if byday.is_empty() {
-                vec![base_date.weekday()]
-            } else {
-                byday
-            };
-            // Start from the week of the base date
-            let step_weeks = Duration::weeks(interval as i64);
-            let mut week_start = base_date - Duration::days(base_date.weekday().num_days_from_monday() as i64);
-            let mut n = 0;
-            while week_start <= horizon && n < max_count {
-                for &wd in &days {
-                    let current = week_start + Duration::days(wd.num_days_from_monday() as i64);
-                    if current < base_date {
-                        continue;
-                    }
-                    if current > horizon {
-                        break;
-                    }
-                    if let Some(u) = until {
-                        if current > u {
-                            break;
-                        }
-                    }
-                    if !exdates.contains(&current) {
-                        let start = current.and_time(base_time);
-                        events.push(make_instance(base, uid_raw, start, duration, n));
-                    }
-                    n += 1;
-                    if n >= max_count {
-                        break;
-                    }
-                }
-                week_start += step_weeks;
-            }
-        }
-        RruleFreq::Monthly => {
-            let mut current = base_date;
-            let mut n = 0;
-            while current <= horizon && n < max_count {
-                if let Some(u) = until {
-                    if current > u {
-                        break;
-                    }
-                }
-                if !exdates.contains(&current) {
-                    let start = current.and_time(base_time);
-                    events.push(make_instance(base, uid_raw, start, duration, n));
-                }
-                // Advance by interval months
-                let mut month = current.month() + interval;
-                let mut year = current.year();
-                while month > 12 {
-                    month -= 12;
-                    year += 1;
-                }
-                current = NaiveDate::from_ymd_opt(year, month, current.day().min(28))
-                    .or_else(|| NaiveDate::from_ymd_opt(year, month, 28))
-                    .unwrap_or(current);
-                n += 1;
-            }
-        }
-        RruleFreq::Yearly => {
-            let mut current = base_date;
-            let mut n = 0;
-            while current <= horizon && n < max_count {
-                if let Some(u) = until {
-                    if current > u {
-                        break;
-                    }
-                }
-                if !exdates.contains(&current) {
-                    let start = current.and_time(base_time);
-                    events.push(make_instance(base, uid_raw, start, duration, n));
-                }
-                let year = current.year() + interval as i32;
-                current = NaiveDate::from_ymd_opt(year, current.month(), current.day().min(28))
-                    .or_else(|| NaiveDate::from_ymd_opt(year, current.month(), 28))
-                    .unwrap_or(current);
-                n += 1;
-            }
-        }
-    }
-
-    events
-}
 
-fn make_instance(
-    base: &CalendarEvent,
-    uid_raw: &str,
-    start: NaiveDateTime,
-    duration: Duration,
-    index: usize,
-) -> CalendarEvent {
-    let instance_id = if index == 0 {
-        base.id
+    let id = if uid_raw.is_empty() {
+        Uuid::new_v4()
     } else {
-        // Stable ID per recurrence instance: hash UID + date
-        let key = format!("{}:{}", uid_raw, start.date());
-        Uuid::new_v5(&CALDAV_UUID_NAMESPACE, key.as_bytes())
+        Uuid::parse_str(&uid_raw)
+            .unwrap_or_else(|_| Uuid::new_v5(&CALDAV_UUID_NAMESPACE, uid_raw.as_bytes()))
     };
-    CalendarEvent {
-        id: instance_id,
-        title: base.title.clone(),
-        start,
-        end: start + duration,
-        all_day: base.all_day,
-        location: base.location.clone(),
-        description: base.description.clone(),
-        status: base.status.clone(),
-        calendar_href: base.calendar_href.clone(),
-        calendar_name: base.calendar_name.clone(),
-        // Only the base instance owns the sync_href
-        sync_href: if index == 0 {
-            base.sync_href.clone()
-        } else {
-            None
+    // Keep UIDs that don't survive the round trip through a Uuid
+    let sync_uid = (!uid_raw.is_empty() && id.to_string() != uid_raw).then(|| uid_raw.clone());
+
+    Some(Component {
+        uid: uid_raw,
+        event: CalendarEvent {
+            id,
+            title: summary,
+            start,
+            end,
+            tzid,
+            all_day,
+            location,
+            description,
+            status,
+            calendar_href: String::new(),
+            calendar_name: String::new(),
+            rrule,
+            exdates: Vec::new(),
+            overrides: Vec::new(),
+            recurrence_id: None,
+            esc,
+            task_id,
+            organizer,
+            attendees,
+            alarms,
+            extra_properties,
+            sync_href: None,
+            sync_hash: None,
+            sync_uid,
         },
-        sync_hash: base.sync_hash,
-    }
-}
-
-enum RruleFreq {
-    Daily,
-    Weekly,
-    Monthly,
-    Yearly,
-}
-
-fn parse_rrule_params(rule: &str) -> std::collections::HashMap<String, String> {
-    let mut map = std::collections::HashMap::new();
-    for part in rule.split(';') {
-        if let Some((k, v)) = part.split_once('=') {
-            map.insert(k.to_uppercase(), v.to_string());
-        }
-    }
-    map
+        recurrence_id,
+        exdates,
+    })
 }
 
 /// Compute a content hash for an event (for change detection).
@@ -421,17 +399,49 @@ pub fn event_content_hash(event: &CalendarEvent) -> u64 {
     event.title.hash(&mut hasher);
     event.start.to_string().hash(&mut hasher);
     event.end.to_string().hash(&mut hasher);
+    if let Some(ref tzid) = event.tzid {
+        tzid.hash(&mut hasher);
+    }
     event.all_day.hash(&mut hasher);
     event.location.hash(&mut hasher);
     event.description.hash(&mut hasher);
     format!("{:?}", event.status).hash(&mut hasher);
+    if let Some(ref rule) = event.rrule {
+        rule.hash(&mut hasher);
+        event.exdates.hash(&mut hasher);
+    }
+    if let Some(rid) = event.recurrence_id {
+        rid.hash(&mut hasher);
+    }
+    if let Some(esc) = event.esc {
+        esc.hash(&mut hasher);
+    }
+    if let Some(task_id) = event.task_id {
+        task_id.hash(&mut hasher);
+    }
+    if let Some(ref organizer) = event.organizer {
+        organizer.email.hash(&mut hasher);
+    }
+    for attendee in &event.attendees {
+        attendee.email.hash(&mut hasher);
+        attendee.partstat.as_ical().hash(&mut hasher);
+    }
+    for alarm in &event.alarms {
+        alarm.to_org().hash(&mut hasher);
+    }
+    if !event.extra_properties.is_empty() {
+        event.extra_properties.hash(&mut hasher);
+    }
+    for over in &event.overrides {
+        event_content_hash(over).hash(&mut hasher);
+    }
     hasher.finish()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::NaiveDate;
+    use chrono::{Datelike, Duration, NaiveDate, Weekday};
 
     #[test]
     fn roundtrip_timed_event() {
@@ -445,6 +455,8 @@ mod tests {
             .unwrap();
         let mut event = CalendarEvent::new("Meeting with Alice".to_string(), start, end);
         event.location = "Conference Room B".to_string();
+        event.task_id = Some(Uuid::new_v4());
+        event.esc = Some(6);
 
         let id = event.id;
         let ical = event_to_vcalendar(&event);
@@ -455,6 +467,8 @@ mod tests {
         assert_eq!(parsed.end, end);
         assert!(!parsed.all_day);
         assert_eq!(parsed.location, "Conference Room B");
+        assert_eq!(parsed.task_id, event.task_id);
+        assert_eq!(parsed.esc, Some(6));
     }
 
     #[test]
@@ -491,7 +505,11 @@ mod tests {
     #[test]
     fn expand_weekly_rrule() {
         let ical = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:weekly-test-123\r\nSUMMARY:Acting Class\r\nDTSTART:20260204T180000\r\nDTEND:20260204T200000\r\nRRULE:FREQ=WEEKLY;BYDAY=WE\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
-        let events = vcalendar_to_events(ical);
+        // The series is stored once and expanded for a display window
+        let series = vcalendar_to_events(ical);
+        assert_eq!(series.len(), 1);
+        let from = NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+        let events = series[0].occurrences(from, from + Duration::days(365));
         // Should have many instances (weekly for ~1 year)
         assert!(events.len() > 40, "Expected many weekly instances, got {}", events.len());
 
@@ -509,16 +527,43 @@ mod tests {
             assert_eq!(e.end - e.start, Duration::hours(2));
         }
 
-        // Each instance should have a unique ID
-        let ids: std::collections::HashSet<_> = events.iter().map(|e| e.id).collect();
+        // Each instance is identified by its series and original start
+        let ids: std::collections::HashSet<_> = events.iter().map(|e| (e.id, e.recurrence_id)).collect();
         assert_eq!(ids.len(), events.len(), "Instance IDs should be unique");
     }
 
     #[test]
     fn rrule_with_count() {
         let ical = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:count-test\r\nSUMMARY:Limited\r\nDTSTART:20260301T100000\r\nDTEND:20260301T110000\r\nRRULE:FREQ=DAILY;COUNT=5\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let series = vcalendar_to_event(ical).unwrap();
+        let from = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        assert_eq!(series.occurrences(from, from + Duration::days(365)).len(), 5);
+    }
+
+    #[test]
+    fn overrides_and_exdates_roundtrip() {
+        let ical = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:series-1\r\nSUMMARY:Standup\r\nDTSTART;TZID=Europe/Berlin:20260302T090000\r\nDTEND;TZID=Europe/Berlin:20260302T091500\r\nRRULE:FREQ=DAILY;COUNT=5\r\nEXDATE:20260303T080000Z\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nUID:series-1\r\nRECURRENCE-ID;TZID=Europe/Berlin:20260304T090000\r\nSUMMARY:Standup (moved)\r\nDTSTART:20260304T130000Z\r\nDTEND:20260304T131500Z\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let at = |d, h| NaiveDate::from_ymd_opt(2026, 3, d).unwrap().and_hms_opt(h, 0, 0).unwrap();
         let events = vcalendar_to_events(ical);
-        assert_eq!(events.len(), 5);
+        assert_eq!(events.len(), 1);
+        let series = &events[0];
+        assert_eq!(series.exdates, vec![at(3, 9)]);
+        assert_eq!(series.overrides.len(), 1);
+        assert_eq!(series.overrides[0].recurrence_id, Some(at(4, 9)));
+        assert_eq!(series.overrides[0].start, at(4, 14));
+
+        let shown = series.occurrences(at(1, 0).date(), at(31, 0).date());
+        assert_eq!(shown.len(), 4);
+        assert!(shown.iter().any(|e| e.title == "Standup (moved)"));
+
+        // Written back with the original UID, RRULE, EXDATE and override
+        let out = event_to_vcalendar(series);
+        assert_eq!(out.matches("UID:series-1\r\n").count(), 2);
+        assert!(out.contains("RRULE:FREQ=DAILY;COUNT=5\r\n"));
+        assert!(out.contains("EXDATE;TZID=Europe/Berlin:20260303T090000\r\n"));
+        assert!(out.contains("RECURRENCE-ID;TZID=Europe/Berlin:20260304T090000\r\n"));
+        let reparsed = vcalendar_to_event(&out).unwrap();
+        assert_eq!(event_content_hash(&reparsed), event_content_hash(series));
     }
 
     #[test]
@@ -528,6 +573,99 @@ mod tests {
         assert_eq!(events.len(), 1);
     }
 
+    #[test]
+    fn roundtrip_keeps_event_zone() {
+        let start = NaiveDate::from_ymd_opt(2026, 7, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let mut event = CalendarEvent::new("Standup".to_string(), start, start + Duration::minutes(15));
+        event.tzid = Some("America/New_York".to_string());
+
+        let ical = event_to_vcalendar(&event);
+        assert!(ical.contains("DTSTART;TZID=America/New_York:20260701T090000\r\n"));
+        assert!(ical.contains("TZID:America/New_York\r\n"));
+        assert!(ical.contains(
+            "BEGIN:DAYLIGHT\r\nDTSTART:20250309T020000\r\nRRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r\nTZOFFSETFROM:-0500\r\nTZOFFSETTO:-0400\r\nEND:DAYLIGHT\r\n"
+        ));
+        assert!(ical.contains(
+            "BEGIN:STANDARD\r\nDTSTART:20251102T020000\r\nRRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r\nTZOFFSETFROM:-0400\r\nTZOFFSETTO:-0500\r\nEND:STANDARD\r\n"
+        ));
+
+        let parsed = vcalendar_to_event(&ical).unwrap();
+        assert_eq!(parsed.start, start);
+        assert_eq!(parsed.tzid.as_deref(), Some("America/New_York"));
+        assert_eq!(event_content_hash(&parsed), event_content_hash(&event));
+    }
+
+    #[test]
+    fn vtimezones_follow_the_zone_rules() {
+        let at = NaiveDate::from_ymd_opt(2026, 7, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let berlin = vtimezone_lines("Europe/Berlin", at);
+        assert!(berlin.contains(&"RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU".to_string()));
+        assert!(berlin.contains(&"RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU".to_string()));
+        assert!(berlin.contains(&"DTSTART:20250330T020000".to_string()));
+
+        // No DST: one fixed STANDARD observance
+        let tokyo = vtimezone_lines("Asia/Tokyo", at);
+        assert_eq!(tokyo.iter().filter(|l| l.starts_with("BEGIN:")).count(), 2);
+        assert!(tokyo.contains(&"TZOFFSETTO:+0900".to_string()));
+        assert!(!tokyo.iter().any(|l| l.starts_with("RRULE") || l.contains("DAYLIGHT")));
+
+        // Transitions that move with the calendar have no yearly rule: TZID only
+        assert!(vtimezone_lines("Africa/Casablanca", at).is_empty());
+        assert!(vtimezone_lines("Nowhere/Special", at).is_empty());
+    }
+
+    #[test]
+    fn parses_utc_and_vtimezone_times() {
+        let at = |h| NaiveDate::from_ymd_opt(2026, 1, 15).unwrap().and_hms_opt(h, 0, 0).unwrap();
+
+        let utc = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:utc\r\nSUMMARY:Call\r\nDTSTART:20260115T140000Z\r\nDTEND:20260115T150000Z\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let event = vcalendar_to_event(utc).unwrap();
+        assert_eq!((event.start, event.tzid.as_deref()), (at(14), Some(timezone::UTC)));
+
+        // Outlook-style TZID resolved through X-LIC-LOCATION
+        let named = "BEGIN:VCALENDAR\r\nBEGIN:VTIMEZONE\r\nTZID:W. Europe Standard Time\r\nX-LIC-LOCATION:Europe/Berlin\r\nEND:VTIMEZONE\r\nBEGIN:VEVENT\r\nUID:named\r\nSUMMARY:Review\r\nDTSTART;TZID=W. Europe Standard Time:20260115T100000\r\nDTEND;TZID=W. Europe Standard Time:20260115T110000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let event = vcalendar_to_event(named).unwrap();
+        assert_eq!((event.start, event.tzid.as_deref()), (at(10), Some("Europe/Berlin")));
+
+        // No IANA name anywhere: fall back to the STANDARD offset, stored as UTC
+        let offset = "BEGIN:VCALENDAR\r\nBEGIN:VTIMEZONE\r\nTZID:Custom\r\nBEGIN:DAYLIGHT\r\nTZOFFSETTO:+0200\r\nEND:DAYLIGHT\r\nBEGIN:STANDARD\r\nTZOFFSETTO:+0100\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\nBEGIN:VEVENT\r\nUID:offset\r\nSUMMARY:Lunch\r\nDTSTART;TZID=Custom:20260115T120000\r\nDTEND;TZID=Custom:20260115T130000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let event = vcalendar_to_event(offset).unwrap();
+        assert_eq!((event.start, event.end), (at(11), at(12)));
+        assert_eq!(event.tzid.as_deref(), Some(timezone::UTC));
+    }
+
+    #[test]
+    fn keeps_alarms_and_unknown_properties() {
+        let ical = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:dentist@example.com\r\n\
+            SUMMARY:Dentist\r\nDTSTART:20260310T080000Z\r\nDTEND:20260310T090000Z\r\n\
+            DESCRIPTION:Bring the referral\r\nCLASS:PRIVATE\r\nSEQUENCE:3\r\nX-OTHER-APP-COLOR:teal\r\n\
+            BEGIN:VALARM\r\nACTION:DISPLAY\r\nTRIGGER:-PT30M\r\nDESCRIPTION:Leave now\r\nREPEAT:1\r\nDURATION:PT5M\r\nEND:VALARM\r\n\
+            BEGIN:VALARM\r\nACTION:EMAIL\r\nTRIGGER;VALUE=DATE-TIME:20260309T180000Z\r\n\
+            ATTENDEE:mailto:me@example.com\r\nSUMMARY:Tomorrow\r\nDESCRIPTION:Dentist tomorrow\r\nEND:VALARM\r\n\
+            BEGIN:X-WIDGET\r\nX-SIZE:2\r\nEND:X-WIDGET\r\n\
+            END:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let event = vcalendar_to_event(ical).unwrap();
+        // The alarm's DESCRIPTION doesn't replace the event's
+        assert_eq!(event.description, "Bring the referral");
+        assert_eq!(event.alarms.len(), 2);
+        assert_eq!(event.alarms[0].to_org(), "-PT30M DISPLAY");
+        assert_eq!(event.alarms[0].description, "Leave now");
+        assert_eq!(event.alarms[1].to_org(), "20260309T180000Z EMAIL");
+        assert_eq!(
+            event.extra_properties,
+            vec!["CLASS:PRIVATE", "SEQUENCE:3", "X-OTHER-APP-COLOR:teal", "BEGIN:X-WIDGET", "X-SIZE:2", "END:X-WIDGET"]
+        );
+
+        let written = event_to_vcalendar(&event);
+        assert!(written.contains("REPEAT:1\r\nDURATION:PT5M\r\n"));
+        assert!(written.contains("BEGIN:X-WIDGET\r\nX-SIZE:2\r\nEND:X-WIDGET\r\n"));
+        let again = vcalendar_to_event(&written).unwrap();
+        assert_eq!(again.alarms, event.alarms);
+        assert_eq!(again.extra_properties, event.extra_properties);
+        assert_eq!(event_content_hash(&again), event_content_hash(&event));
+    }
+
     #[test]
     fn skip_empty_summary() {
         let ical = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:abc-123\r\nSUMMARY:\r\nDTSTART:20260225T100000\r\nDTEND:20260225T110000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
@@ -2,8 +2,12 @@ use chrono::{NaiveDate, NaiveDateTime};
 use uuid::Uuid;
 
 use super::ical::*;
+use crate::core::alarm::Alarm;
+use crate::core::email_link::EmailLink;
+use crate::core::project::Project;
 use crate::core::recurrence::Recurrence;
 use crate::core::task::{Priority, Task, TaskState};
+use crate::core::timezone;
 
 /// Generate a full VCALENDAR string containing a VTODO for the given task.
 pub fn task_to_vcalendar(task: &Task) -> String {
@@ -73,18 +77,15 @@ pub fn task_to_vcalendar(task: &Task) -> String {
     }
 
     // CREATED
-    lines.push(format!("CREATED:{}", format_datetime(task.created)));
+    lines.push(format!("CREATED:{}", format_utc(timezone::to_utc(task.created, None))));
 
     // COMPLETED
     if let Some(completed) = task.completed {
-        lines.push(format!("COMPLETED:{}", format_datetime(completed)));
+        lines.push(format!("COMPLETED:{}", format_utc(timezone::to_utc(completed, None))));
     }
 
     // LAST-MODIFIED
-    lines.push(format!(
-        "LAST-MODIFIED:{}",
-        format_datetime(chrono::Local::now().naive_local())
-    ));
+    lines.push(format!("LAST-MODIFIED:{}", format_utc(timezone::now_utc())));
 
     // X-LAMP-STATE (GTD-specific state)
     lines.push(format!("X-LAMP-STATE:{}", task.state.as_keyword()));
@@ -94,16 +95,31 @@ pub fn task_to_vcalendar(task: &Task) -> String {
         lines.push(format!("X-LAMP-PROJECT:{}", escape_text(project)));
     }
 
+    // RELATED-TO (project parent task)
+    if let Some(ref parent) = task.sync_parent {
+        lines.push(format!("RELATED-TO;RELTYPE=PARENT:{}", parent));
+    }
+
     // X-LAMP-WAITING-FOR
     if let Some(ref wf) = task.waiting_for {
         lines.push(format!("X-LAMP-WAITING-FOR:{}", escape_text(wf)));
     }
 
+    // X-LAMP-WAITING-FOR-CONTACT
+    if let Some(contact_id) = task.waiting_for_contact {
+        lines.push(format!("X-LAMP-WAITING-FOR-CONTACT:{}", contact_id));
+    }
+
     // X-LAMP-ESC
     if let Some(esc) = task.esc {
         lines.push(format!("X-LAMP-ESC:{}", esc));
     }
 
+    // X-LAMP-ESTIMATE (minutes)
+    if let Some(minutes) = task.estimate {
+        lines.push(format!("X-LAMP-ESTIMATE:{}", minutes));
+    }
+
     // X-LAMP-DELEGATED
     if let Some(delegated) = task.delegated {
         lines.push(format!("X-LAMP-DELEGATED:{}", format_date(delegated)));
@@ -119,6 +135,44 @@ pub fn task_to_vcalendar(task: &Task) -> String {
         lines.push(format!("X-LAMP-RECURRENCE:{}", recurrence));
     }
 
+    // X-LAMP-EMAIL-ID
+    if let Some(ref email) = task.email {
+        lines.push(format!("X-LAMP-EMAIL-ID:{}", escape_text(&email.to_org())));
+    }
+    if let Some(ref delegation_id) = task.delegation_id {
+        lines.push(format!("X-LAMP-DELEGATION-ID:{}", escape_text(delegation_id)));
+    }
+
+    for alarm in &task.alarms {
+        lines.extend(valarm_lines(alarm, &task.title));
+    }
+
+    lines.push("END:VTODO".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.join("\r\n") + "\r\n"
+}
+
+/// Generate the parent VTODO that stands in for a project in other clients.
+/// Its UID is the project id, which project tasks reference via `RELATED-TO`.
+pub fn project_to_vcalendar(project: &Project) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Lamp GTD//EN".to_string(),
+        "BEGIN:VTODO".to_string(),
+        format!("UID:{}", project.id),
+        format!("SUMMARY:{}", fold_line(&escape_text(&project.name))),
+        "STATUS:NEEDS-ACTION".to_string(),
+    ];
+    if !project.outcome.is_empty() {
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            fold_line(&escape_text(&project.outcome))
+        ));
+    }
+    lines.push(format!("LAST-MODIFIED:{}", format_utc(timezone::now_utc())));
+    lines.push(format!("X-LAMP-PROJECT-ID:{}", project.id));
     lines.push("END:VTODO".to_string());
     lines.push("END:VCALENDAR".to_string());
 
@@ -126,9 +180,11 @@ pub fn task_to_vcalendar(task: &Task) -> String {
 }
 
 /// Parse a VCALENDAR string and extract a Task from the VTODO component.
+/// Project parent VTODOs (see `project_to_vcalendar`) yield `None`.
 pub fn vcalendar_to_task(ical: &str) -> Option<Task> {
     // Unfold lines (RFC 5545: lines starting with space/tab are continuations)
     let unfolded = unfold_lines(ical);
+    let zones = TimeZones::parse(&unfolded);
 
     let mut in_vtodo = false;
     let mut uid: Option<Uuid> = None;
@@ -142,15 +198,23 @@ pub fn vcalendar_to_task(ical: &str) -> Option<Task> {
     let mut description = String::new();
     let mut created: Option<NaiveDateTime> = None;
     let mut completed: Option<NaiveDateTime> = None;
+    let mut related_to: Option<String> = None;
 
     // X-LAMP properties
     let mut lamp_state: Option<String> = None;
     let mut lamp_project: Option<String> = None;
     let mut lamp_waiting_for: Option<String> = None;
+    let mut lamp_waiting_for_contact: Option<Uuid> = None;
     let mut lamp_esc: Option<u32> = None;
+    let mut lamp_estimate: Option<u32> = None;
     let mut lamp_delegated: Option<NaiveDate> = None;
     let mut lamp_follow_up: Option<NaiveDate> = None;
     let mut lamp_recurrence: Option<String> = None;
+    let mut lamp_email: Option<EmailLink> = None;
+    let mut lamp_delegation_id: Option<String> = None;
+    let mut alarms: Vec<Alarm> = Vec::new();
+    // Lines of the VALARM being read, kept apart so its DESCRIPTION isn't the task's
+    let mut alarm: Option<Vec<&str>> = None;
 
     for line in unfolded.lines() {
         let line = line.trim_end();
@@ -164,6 +228,19 @@ pub fn vcalendar_to_task(ical: &str) -> Option<Task> {
         if !in_vtodo {
             continue;
         }
+        if line == "BEGIN:VALARM" {
+            alarm = Some(Vec::new());
+            continue;
+        }
+        if let Some(ref mut alarm_lines) = alarm {
+            if line == "END:VALARM" {
+                alarms.extend(parse_valarm(alarm_lines));
+                alarm = None;
+            } else {
+                alarm_lines.push(line);
+            }
+            continue;
+        }
 
         if let Some((key, value)) = parse_ical_line(line) {
             match key {
@@ -183,15 +260,25 @@ pub fn vcalendar_to_task(ical: &str) -> Option<Task> {
                 "DTSTART" => dtstart = parse_ical_date(value),
                 "DUE" => due = parse_ical_date(value),
                 "DESCRIPTION" => description = unescape_text(value),
-                "CREATED" => created = parse_ical_datetime(value),
-                "COMPLETED" => completed = parse_ical_datetime(value),
+                "CREATED" => created = zones.parse_local_datetime(line, value),
+                "COMPLETED" => completed = zones.parse_local_datetime(line, value),
+                // Only the parent relation; CHILD and SIBLING links are ignored
+                "RELATED-TO" if !line.contains("RELTYPE=") || line.contains("RELTYPE=PARENT") => {
+                    related_to = Some(value.trim().to_string());
+                }
+                // Parent task standing in for a Lamp project, not a task itself
+                "X-LAMP-PROJECT-ID" => return None,
                 "X-LAMP-STATE" => lamp_state = Some(value.to_string()),
                 "X-LAMP-PROJECT" => lamp_project = Some(unescape_text(value)),
                 "X-LAMP-WAITING-FOR" => lamp_waiting_for = Some(unescape_text(value)),
+                "X-LAMP-WAITING-FOR-CONTACT" => lamp_waiting_for_contact = Uuid::parse_str(value).ok(),
                 "X-LAMP-ESC" => lamp_esc = value.parse().ok(),
+                "X-LAMP-ESTIMATE" => lamp_estimate = value.parse().ok(),
                 "X-LAMP-DELEGATED" => lamp_delegated = parse_ical_date(value),
                 "X-LAMP-FOLLOW-UP" => lamp_follow_up = parse_ical_date(value),
                 "X-LAMP-RECURRENCE" => lamp_recurrence = Some(value.to_string()),
+                "X-LAMP-EMAIL-ID" => lamp_email = EmailLink::from_org(&unescape_text(value)),
+                "X-LAMP-DELEGATION-ID" => lamp_delegation_id = Some(unescape_text(value)),
                 _ => {}
             }
         }
@@ -238,12 +325,18 @@ pub fn vcalendar_to_task(ical: &str) -> Option<Task> {
         completed,
         project: lamp_project.filter(|s| !s.is_empty()),
         waiting_for: lamp_waiting_for.filter(|s| !s.is_empty()),
+        waiting_for_contact: lamp_waiting_for_contact,
         esc: lamp_esc,
+        estimate: lamp_estimate,
         delegated: lamp_delegated,
         follow_up: lamp_follow_up,
         sync_href: None,
         sync_hash: None,
         sync_uid: uid_raw,
+        sync_parent: related_to.filter(|s| !s.is_empty()),
+        alarms,
+        email: lamp_email,
+        delegation_id: lamp_delegation_id,
     })
 }
 
@@ -263,10 +356,29 @@ pub fn task_content_hash(task: &Task) -> u64 {
     task.notes.hash(&mut hasher);
     task.project.hash(&mut hasher);
     task.waiting_for.hash(&mut hasher);
+    // Only hashed when set, so tasks synced before these fields existed keep their hash.
+    if let Some(contact_id) = task.waiting_for_contact {
+        contact_id.hash(&mut hasher);
+    }
+    if let Some(ref parent) = task.sync_parent {
+        parent.hash(&mut hasher);
+    }
+    if let Some(minutes) = task.estimate {
+        minutes.hash(&mut hasher);
+    }
     task.esc.hash(&mut hasher);
     task.delegated.map(|d| d.to_string()).hash(&mut hasher);
     task.follow_up.map(|d| d.to_string()).hash(&mut hasher);
     task.recurrence.as_ref().map(|r| r.to_string()).hash(&mut hasher);
+    for alarm in &task.alarms {
+        alarm.to_org().hash(&mut hasher);
+    }
+    if let Some(ref email) = task.email {
+        email.to_org().hash(&mut hasher);
+    }
+    if let Some(ref delegation_id) = task.delegation_id {
+        delegation_id.hash(&mut hasher);
+    }
     hasher.finish()
 }
 
@@ -287,6 +399,7 @@ mod tests {
             deadline: Some(NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()),
             notes: "Call the plumber first".to_string(),
             esc: Some(20),
+            alarms: vec![crate::core::alarm::Alarm::before_start(60)],
             ..Task::new("unused")
         };
         let id = task.id;
@@ -307,6 +420,7 @@ mod tests {
         );
         assert_eq!(parsed.notes, "Call the plumber first");
         assert_eq!(parsed.esc, Some(20));
+        assert_eq!(parsed.alarms[0].to_org(), "-PT1H DISPLAY");
     }
 
     #[test]
@@ -314,14 +428,21 @@ mod tests {
         let mut task = Task::new("Review PR");
         task.state = TaskState::Waiting;
         task.waiting_for = Some("Alice".to_string());
+        task.waiting_for_contact = Some(Uuid::new_v4());
         task.delegated = Some(NaiveDate::from_ymd_opt(2026, 2, 20).unwrap());
         task.follow_up = Some(NaiveDate::from_ymd_opt(2026, 2, 27).unwrap());
         task.project = Some("Launch v2".to_string());
+        task.email = Some(EmailLink {
+            message_id: "pr-88@git.example.com".to_string(),
+            folder: "flup".to_string(),
+        });
+        task.delegation_id = Some("req-1@lamp.example.com".to_string());
 
         let ical = task_to_vcalendar(&task);
         let parsed = vcalendar_to_task(&ical).unwrap();
         assert_eq!(parsed.state, TaskState::Waiting);
         assert_eq!(parsed.waiting_for, Some("Alice".to_string()));
+        assert_eq!(parsed.waiting_for_contact, task.waiting_for_contact);
         assert_eq!(
             parsed.delegated,
             Some(NaiveDate::from_ymd_opt(2026, 2, 20).unwrap())
@@ -331,6 +452,23 @@ mod tests {
             Some(NaiveDate::from_ymd_opt(2026, 2, 27).unwrap())
         );
         assert_eq!(parsed.project, Some("Launch v2".to_string()));
+        assert_eq!(parsed.email, task.email);
+        assert_eq!(parsed.delegation_id, task.delegation_id);
+    }
+
+    #[test]
+    fn project_parent_links() {
+        let project = Project::new("Launch v2");
+        assert!(vcalendar_to_task(&project_to_vcalendar(&project)).is_none());
+
+        let mut task = Task::new("Write changelog");
+        task.sync_parent = Some(project.id.to_string());
+        let parsed = vcalendar_to_task(&task_to_vcalendar(&task)).unwrap();
+        assert_eq!(parsed.sync_parent, task.sync_parent);
+
+        let child_link = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nUID:x\r\nSUMMARY:Parent\r\n\
+                          RELATED-TO;RELTYPE=CHILD:y\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        assert_eq!(vcalendar_to_task(child_link).unwrap().sync_parent, None);
     }
 
     #[test]